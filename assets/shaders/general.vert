@@ -23,7 +23,7 @@ uniform mat4 MODEL_MATRIX;
 // out vec3 v_bitangent;
 out vec3 v_color;
 out vec2 v_texcoord_0;
-// out vec2 v_texcoord_1;
+out vec2 v_texcoord_1;
 // out vec2 v_texcoord_2;
 // out vec2 v_texcoord_3;
 out vec3 v_world_position;
@@ -40,7 +40,7 @@ void main() {
 	// v_bitangent = BITANGENT;
 	v_color = COLOR;
 	v_texcoord_0 = TEXCOORD_0;
-	// v_texcoord_1 = TEXCOORD_1;
+	v_texcoord_1 = TEXCOORD_1;
 	// v_texcoord_2 = TEXCOORD_2;
 	// v_texcoord_3 = TEXCOORD_3;
 
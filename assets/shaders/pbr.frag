@@ -8,25 +8,102 @@
 
 precision highp int;
 
+#define MAX_POINT_LIGHTS 8
+#define MAX_DIRECTION_LIGHTS 4
+#define MAX_SPOT_LIGHTS 4
+#define MAX_CASCADES 4
+#define MAX_SPOT_SHADOWS 2
+
 struct PointLight {
 	vec3 position;  // meters
 	vec3 color;     // 
+	float range;    // meters, zero for unlimited
 };
 
-uniform bool USE_DIFFUSE_TEX;
-uniform sampler2D DIFFUSE_TEX;
+struct DirectionLight {
+	vec3 direction; // the way the light travels
+	vec3 color;
+	int shadow;     // 0 for the cascades, -1 for none
+};
 
-uniform bool USE_NORMAL_TEX;
-uniform sampler2D NORMAL_TEX;
+struct SpotLight {
+	vec3 position;
+	vec3 direction;
+	vec3 color;
+	float range;
+	// Map the cosine of the angle from direction to the cone's falloff.
+	float angle_scale;
+	float angle_offset;
+	int shadow;     // index into SPOT_SHADOWS, -1 for none
+};
+
+struct Cascade {
+	float far;      // the view depth the cascade reaches
+	mat4 matrix;    // world to the cascade's clip space
+};
 
+struct SpotShadow {
+	mat4 matrix;
+	float bias;
+};
+
+// Uploaded by LightManager, only the first *_COUNT lights are set.
+uniform int POINT_LIGHT_COUNT;
+uniform PointLight[MAX_POINT_LIGHTS] POINT_LIGHTS;
+uniform int DIRECTION_LIGHT_COUNT;
+uniform DirectionLight[MAX_DIRECTION_LIGHTS] DIRECTION_LIGHTS;
+uniform int SPOT_LIGHT_COUNT;
+uniform SpotLight[MAX_SPOT_LIGHTS] SPOT_LIGHTS;
+
+// Uploaded by ShadowPass, the counts are zero without shadows.
+uniform int CASCADE_COUNT;
+uniform Cascade[MAX_CASCADES] CASCADES;
+uniform float CASCADE_BIAS;
+uniform highp sampler2DShadow CASCADE_MAPS[MAX_CASCADES];
+uniform int SPOT_SHADOW_COUNT;
+uniform SpotShadow[MAX_SPOT_SHADOWS] SPOT_SHADOWS;
+uniform highp sampler2DShadow SPOT_SHADOW_MAPS[MAX_SPOT_SHADOWS];
+
+// glTF's metallic-roughness material, see src/model/material.rs. Each
+// texture is sampled with the TEXCOORD_n set its _TEX_COORD names.
+uniform vec4 BASE_COLOR_FACTOR;
+uniform bool USE_BASE_COLOR_TEX;
+uniform sampler2D BASE_COLOR_TEX;
+uniform int BASE_COLOR_TEX_COORD;
+
+uniform float METALLIC_FACTOR;
+uniform float ROUGHNESS_FACTOR;
 uniform bool USE_METALLIC_ROUGHNESS_TEX;
 uniform sampler2D METALLIC_ROUGHNESS_TEX;
-uniform float METALLIC;
-uniform float ROUGHNESS;
+uniform int METALLIC_ROUGHNESS_TEX_COORD;
+
+uniform float NORMAL_SCALE;
+uniform bool USE_NORMAL_TEX;
+uniform sampler2D NORMAL_TEX;
+uniform int NORMAL_TEX_COORD;
 
+uniform float OCCLUSION_STRENGTH;
 uniform bool USE_OCCLUSION_TEX;
 uniform sampler2D OCCLUSION_TEX;
-uniform float OCCLUSION;
+uniform int OCCLUSION_TEX_COORD;
+
+uniform vec3 EMISSIVE_FACTOR;
+uniform bool USE_EMISSIVE_TEX;
+uniform sampler2D EMISSIVE_TEX;
+uniform int EMISSIVE_TEX_COORD;
+
+// 0 opaque, 1 masked at ALPHA_CUTOFF, 2 blended.
+uniform int ALPHA_MODE;
+uniform float ALPHA_CUTOFF;
+uniform bool DOUBLE_SIDED;
+
+// Image based lighting, see src/environment.
+uniform bool USE_ENVIRONMENT;
+uniform samplerCube IRRADIANCE_MAP;
+uniform samplerCube PREFILTERED_MAP;
+uniform sampler2D BRDF_LUT;
+uniform float PREFILTERED_MAX_LOD;
+uniform float ENVIRONMENT_INTENSITY;
 
 uniform vec3 CAMERA_POS;
 
@@ -51,84 +128,119 @@ layout(location = 0) out lowp vec4 frag_color;
 
 const float PI = 3.14159265359;
 
+vec2 texcoord(int set);
 vec3 getNormalFromMap();
 float DistributionGGX(vec3 N, vec3 H, float roughness);
 float GeometrySchlickGGX(float NdotV, float roughness);
 float GeometrySmith(vec3 N, vec3 V, vec3 L, float roughness);
 vec3 fresnelSchlick(float cosTheta, vec3 F0);
+vec3 fresnelSchlickRoughness(float cosTheta, vec3 F0, float roughness);
+vec3 environment_light(vec3 N, vec3 V, vec3 albedo, float metallic,
+	float roughness, vec3 F0);
+vec3 reflectance(vec3 N, vec3 V, vec3 L, vec3 radiance, vec3 albedo,
+	float metallic, float roughness, vec3 F0);
+float range_attenuation(float distance, float range);
+float slope_bias(float bias, vec3 N, vec3 L);
+float cascade_shadow(float bias);
+float spot_shadow(int index, vec3 N, vec3 L);
 
 void main() {
-	PointLight light;
-	light.position = vec3(0.0, 8.0, 0.0);
-	light.color = vec3(5.0);
-
 	vec3 N = getNormalFromMap();
 	vec3 V = normalize(CAMERA_POS - v_world_position);
+	// Back faces are lit from their own side. Facing away from the viewer
+	// rather than gl_FrontFacing, as not every mesh is wound consistently.
+	if (DOUBLE_SIDED && dot(N, V) < 0.0) {
+		N = -N;
+	}
 
-	vec3 albedo = vec3(0.0);
-	if (USE_DIFFUSE_TEX) {
-		albedo = texture(DIFFUSE_TEX, v_texcoord_0).rgb;
-	} else {
-		albedo = v_color;
+	vec4 base_color = BASE_COLOR_FACTOR;
+	if (USE_BASE_COLOR_TEX) {
+		base_color *= texture(BASE_COLOR_TEX, texcoord(BASE_COLOR_TEX_COORD));
 	}
-	if (albedo == vec3(0.0)) {
-		albedo = vec3(1.0, 0.0, 1.0);
+	vec3 albedo = base_color.rgb;
+	float alpha = 1.0;
+	if (ALPHA_MODE == 1 && base_color.a < ALPHA_CUTOFF) {
+		discard;
+	} else if (ALPHA_MODE == 2) {
+		alpha = base_color.a;
 	}
 
-	float metallic = 0.0;
-	float roughness = 0.0;
+	// Roughness in green, metalness in blue.
+	float metallic = METALLIC_FACTOR;
+	float roughness = ROUGHNESS_FACTOR;
 	if (USE_METALLIC_ROUGHNESS_TEX) {
-		vec2 metallic_roughness = texture(METALLIC_ROUGHNESS_TEX, v_texcoord_0).rg;
-		metallic = metallic_roughness.r;
-		roughness = metallic_roughness.g;
-	} else {
-		metallic = METALLIC;
-		roughness = ROUGHNESS;
+		vec4 metallic_roughness = texture(METALLIC_ROUGHNESS_TEX,
+			texcoord(METALLIC_ROUGHNESS_TEX_COORD));
+		metallic *= metallic_roughness.b;
+		roughness *= metallic_roughness.g;
 	}
+	metallic = clamp(metallic, 0.0, 1.0);
+	roughness = clamp(roughness, 0.0, 1.0);
 
-	float ao = 0.0;
+	// Occlusion only darkens the indirect light.
+	float ao = 1.0;
 	if (USE_OCCLUSION_TEX) {
-		ao = texture(OCCLUSION_TEX, v_texcoord_0).r;
-	} else {
-		ao = OCCLUSION;
+		float occlusion = texture(OCCLUSION_TEX, texcoord(OCCLUSION_TEX_COORD)).r;
+		ao = 1.0 + OCCLUSION_STRENGTH * (occlusion - 1.0);
+	}
+
+	vec3 emissive = EMISSIVE_FACTOR;
+	if (USE_EMISSIVE_TEX) {
+		emissive *= texture(EMISSIVE_TEX, texcoord(EMISSIVE_TEX_COORD)).rgb;
 	}
 
 	vec3 F0 = vec3(0.04); 
 	F0 = mix(F0, albedo, metallic);
 
 	vec3 light_out = vec3(0.0);
-	{	
+	for (int i = 0; i < min(POINT_LIGHT_COUNT, MAX_POINT_LIGHTS); i++) {
+		PointLight light = POINT_LIGHTS[i];
 		vec3 L = normalize(light.position - v_world_position);
-		vec3 H = normalize(V + L);
 		float distance = length(light.position - v_world_position);
-		float attenuation = 1.0 / (distance * distance);
-		attenuation = 1.0;
+		vec3 radiance = light.color * range_attenuation(distance, light.range);
+		light_out += reflectance(N, V, L, radiance, albedo, metallic, roughness, F0);
+	}
+	for (int i = 0; i < min(DIRECTION_LIGHT_COUNT, MAX_DIRECTION_LIGHTS); i++) {
+		DirectionLight light = DIRECTION_LIGHTS[i];
+		vec3 L = -normalize(light.direction);
+		float shadow = 1.0;
+		if (light.shadow == 0) {
+			shadow = cascade_shadow(slope_bias(CASCADE_BIAS, N, L));
+		}
+		vec3 radiance = light.color * shadow;
+		light_out += reflectance(N, V, L, radiance, albedo, metallic, roughness, F0);
+	}
+	for (int i = 0; i < min(SPOT_LIGHT_COUNT, MAX_SPOT_LIGHTS); i++) {
+		SpotLight light = SPOT_LIGHTS[i];
+		vec3 L = normalize(light.position - v_world_position);
+		float distance = length(light.position - v_world_position);
+		float cone = clamp(
+			dot(normalize(light.direction), -L) * light.angle_scale + light.angle_offset,
+			0.0, 1.0);
+		float attenuation = range_attenuation(distance, light.range) * cone * cone
+			* spot_shadow(light.shadow, N, L);
 		vec3 radiance = light.color * attenuation;
-
-		float NDF = DistributionGGX(N, H, roughness);
-		float G = GeometrySmith(N, V, L, roughness);
-		vec3 F = fresnelSchlick(max(dot(H, V), 0.0), F0);
-
-		vec3 kS = F;
-		vec3 kD = vec3(1.0) - kS;
-		kD *= 1.0 - metallic;
-
-		vec3 numerator = NDF * G * F;
-		float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0);
-		vec3 specular = numerator / max(denominator, 0.0000001);  
-
-		float NdotL = max(dot(N, L), 0.0);
-		light_out = (kD * albedo / PI + specular) * radiance * NdotL;
+		light_out += reflectance(N, V, L, radiance, albedo, metallic, roughness, F0);
 	}
 
-	float ambient_strength = 0.03;
-	vec3 ambient = vec3(ambient_strength) * albedo.xyz * ao;
+	vec3 ambient = vec3(0.0);
+	if (USE_ENVIRONMENT) {
+		ambient = environment_light(N, V, albedo, metallic, roughness, F0) * ao;
+	} else {
+		float ambient_strength = 0.03;
+		ambient = vec3(ambient_strength) * albedo.xyz * ao;
+	}
 
-	vec3 color = ambient + light_out;
+	vec3 color = ambient + light_out + emissive;
 
-	frag_color = vec4(color, 1.0);
+	// Lit in linear space, as the sRGB textures decode to. The canvas is
+	// shown as sRGB so the result is encoded back.
+	frag_color = vec4(pow(color, vec3(1.0 / 2.2)), alpha);
 }
 
+vec2 texcoord(int set) {
+	return set == 1 ? v_texcoord_1 : v_texcoord_0;
+}
 vec3 getNormalFromMap() {
 	vec3 norm = vec3(0.0);
 
@@ -142,11 +254,13 @@ vec3 getNormalFromMap() {
 	}
 
 	if (USE_NORMAL_TEX) {
-		vec3 tangentNormal = texture(NORMAL_TEX, v_texcoord_0).xyz * 2.0 - 1.0;
+		vec2 uv = texcoord(NORMAL_TEX_COORD);
+		vec3 tangentNormal = texture(NORMAL_TEX, uv).xyz * 2.0 - 1.0;
+		tangentNormal.xy *= NORMAL_SCALE;
 		tangentNormal = normalize(tangentNormal);
 
-		vec2 st1 = dFdx(v_texcoord_0);
-		vec2 st2 = dFdy(v_texcoord_0);
+		vec2 st1 = dFdx(uv);
+		vec2 st2 = dFdy(uv);
 
 		mat3 TBN;
 		bool USE_TBN = false;
@@ -161,8 +275,6 @@ vec3 getNormalFromMap() {
 		norm = normalize(TBN * tangentNormal);
 	}
 
-	norm = v_world_normal;
-
 	return normalize(norm);
 }
 float DistributionGGX(vec3 N, vec3 H, float roughness) {
@@ -197,3 +309,109 @@ float GeometrySmith(vec3 N, vec3 V, vec3 L, float roughness) {
 vec3 fresnelSchlick(float cosTheta, vec3 F0) {
 	return F0 + (1.0 - F0) * pow(max(1.0 - cosTheta, 0.0), 5.0);
 }
+// Fresnel averaged over a rough surface's microfacets.
+vec3 fresnelSchlickRoughness(float cosTheta, vec3 F0, float roughness) {
+	vec3 F90 = max(vec3(1.0 - roughness), F0);
+	return F0 + (F90 - F0) * pow(max(1.0 - cosTheta, 0.0), 5.0);
+}
+// The environment's light leaving toward V, with the split sum
+// approximation. The irradiance map is already divided by pi.
+vec3 environment_light(vec3 N, vec3 V, vec3 albedo, float metallic,
+	float roughness, vec3 F0) {
+	float NdotV = max(dot(N, V), 0.0);
+	vec3 F = fresnelSchlickRoughness(NdotV, F0, roughness);
+	vec3 kD = (vec3(1.0) - F) * (1.0 - metallic);
+	vec3 diffuse = texture(IRRADIANCE_MAP, N).rgb * albedo;
+
+	vec3 R = reflect(-V, N);
+	vec3 prefiltered =
+		textureLod(PREFILTERED_MAP, R, roughness * PREFILTERED_MAX_LOD).rgb;
+	vec2 brdf = texture(BRDF_LUT, vec2(NdotV, roughness)).rg;
+	vec3 specular = prefiltered * (F * brdf.x + brdf.y);
+
+	return (kD * diffuse + specular) * ENVIRONMENT_INTENSITY;
+}
+// The light leaving toward V from radiance arriving along L.
+vec3 reflectance(vec3 N, vec3 V, vec3 L, vec3 radiance, vec3 albedo,
+	float metallic, float roughness, vec3 F0) {
+	vec3 H = normalize(V + L);
+
+	float NDF = DistributionGGX(N, H, roughness);
+	float G = GeometrySmith(N, V, L, roughness);
+	vec3 F = fresnelSchlick(max(dot(H, V), 0.0), F0);
+
+	vec3 kS = F;
+	vec3 kD = vec3(1.0) - kS;
+	kD *= 1.0 - metallic;
+
+	vec3 numerator = NDF * G * F;
+	float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0);
+	vec3 specular = numerator / max(denominator, 0.0000001);
+
+	float NdotL = max(dot(N, L), 0.0);
+	return (kD * albedo / PI + specular) * radiance * NdotL;
+}
+// Inverse square falloff reaching zero at range, as KHR_lights_punctual
+// recommends. Mirrored by lights::range_attenuation.
+float range_attenuation(float distance, float range) {
+	float inverse_square = 1.0 / max(distance * distance, 0.0001);
+	if (range <= 0.0) {
+		return inverse_square;
+	}
+	float ratio = distance / range;
+	return clamp(1.0 - pow(ratio, 4.0), 0.0, 1.0) * inverse_square;
+}
+
+// How much of a 3x3 texel neighbourhood around the fragment in shadow_map
+// is lit. Each comparison is itself filtered over four texels.
+float shadow_pcf(highp sampler2DShadow shadow_map, mat4 matrix, float bias) {
+	vec4 clip = matrix * vec4(v_world_position, 1.0);
+	vec3 coords = clip.xyz / clip.w * 0.5 + 0.5;
+	// Past the map's far plane nothing was rendered.
+	if (coords.z > 1.0) {
+		return 1.0;
+	}
+	vec2 texel = 1.0 / vec2(textureSize(shadow_map, 0));
+	float lit = 0.0;
+	for (int x = -1; x <= 1; x++) {
+		for (int y = -1; y <= 1; y++) {
+			vec2 offset = vec2(float(x), float(y)) * texel;
+			lit += textureLod(shadow_map, vec3(coords.xy + offset, coords.z - bias), 0.0);
+		}
+	}
+	return lit / 9.0;
+}
+// Surfaces at a grazing angle to the light need more bias against acne.
+float slope_bias(float bias, vec3 N, vec3 L) {
+	return bias * (1.0 + 4.0 * (1.0 - max(dot(N, L), 0.0)));
+}
+// Sampler arrays can only be indexed by constants, hence the unrolling.
+float cascade_shadow(float bias) {
+	float depth = -v_view_position.z;
+	int count = min(CASCADE_COUNT, MAX_CASCADES);
+	if (count > 0 && depth < CASCADES[0].far) {
+		return shadow_pcf(CASCADE_MAPS[0], CASCADES[0].matrix, bias);
+	}
+	if (count > 1 && depth < CASCADES[1].far) {
+		return shadow_pcf(CASCADE_MAPS[1], CASCADES[1].matrix, bias);
+	}
+	if (count > 2 && depth < CASCADES[2].far) {
+		return shadow_pcf(CASCADE_MAPS[2], CASCADES[2].matrix, bias);
+	}
+	if (count > 3 && depth < CASCADES[3].far) {
+		return shadow_pcf(CASCADE_MAPS[3], CASCADES[3].matrix, bias);
+	}
+	// Beyond the shadow distance.
+	return 1.0;
+}
+float spot_shadow(int index, vec3 N, vec3 L) {
+	if (index < 0 || index >= min(SPOT_SHADOW_COUNT, MAX_SPOT_SHADOWS)) {
+		return 1.0;
+	}
+	if (index == 0) {
+		float bias = slope_bias(SPOT_SHADOWS[0].bias, N, L);
+		return shadow_pcf(SPOT_SHADOW_MAPS[0], SPOT_SHADOWS[0].matrix, bias);
+	}
+	float bias = slope_bias(SPOT_SHADOWS[1].bias, N, L);
+	return shadow_pcf(SPOT_SHADOW_MAPS[1], SPOT_SHADOWS[1].matrix, bias);
+}
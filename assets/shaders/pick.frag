@@ -0,0 +1,13 @@
+#version 300 es
+
+precision highp float;
+precision highp int;
+
+// The drawn node and mesh, see picking/gpu.rs for the encoding.
+uniform highp uint PICK_ID;
+
+out uint id;
+
+void main() {
+	id = PICK_ID;
+}
@@ -0,0 +1,7 @@
+#version 300 es
+
+precision highp float;
+
+// Only depth is written, see shadow/pass.rs.
+void main() {
+}
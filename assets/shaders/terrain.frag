@@ -8,36 +8,53 @@
 
 precision highp int;
 
-#define POINT_LIGHT_COUNT 4
-#define DIRECTION_LIGHT_COUNT 1
+#define MAX_POINT_LIGHTS 8
+#define MAX_DIRECTION_LIGHTS 4
+#define MAX_SPOT_LIGHTS 4
 
 struct PointLight {
 	vec3 position;  // meters
 	vec3 color;     // 
+	float range;    // meters, zero for unlimited
 };
 
 struct DirectionLight {
+	vec3 direction; // the way the light travels
+	vec3 color;
+};
+
+struct SpotLight {
+	vec3 position;
 	vec3 direction;
 	vec3 color;
+	float range;
+	// Map the cosine of the angle from direction to the cone's falloff.
+	float angle_scale;
+	float angle_offset;
 };
 
-uniform PointLight[POINT_LIGHT_COUNT] POINT_LIGHTS;
-uniform DirectionLight[DIRECTION_LIGHT_COUNT] DIRECTION_LIGHTS;
+// Uploaded by LightManager, only the first *_COUNT lights are set.
+uniform int POINT_LIGHT_COUNT;
+uniform PointLight[MAX_POINT_LIGHTS] POINT_LIGHTS;
+uniform int DIRECTION_LIGHT_COUNT;
+uniform DirectionLight[MAX_DIRECTION_LIGHTS] DIRECTION_LIGHTS;
+uniform int SPOT_LIGHT_COUNT;
+uniform SpotLight[MAX_SPOT_LIGHTS] SPOT_LIGHTS;
 
-uniform bool USE_DIFFUSE_TEX;
-uniform sampler2D DIFFUSE_TEX;
+uniform bool USE_BASE_COLOR_TEX;
+uniform sampler2D BASE_COLOR_TEX;
 
 uniform bool USE_NORMAL_TEX;
 uniform sampler2D NORMAL_TEX;
 
 uniform bool USE_METALLIC_ROUGHNESS_TEX;
 uniform sampler2D METALLIC_ROUGHNESS_TEX;
-uniform float METALLIC;
-uniform float ROUGHNESS;
+uniform float METALLIC_FACTOR;
+uniform float ROUGHNESS_FACTOR;
 
 uniform bool USE_OCCLUSION_TEX;
 uniform sampler2D OCCLUSION_TEX;
-uniform float OCCLUSION;
+uniform float OCCLUSION_STRENGTH;
 
 uniform vec3 CAMERA_POS;
 
@@ -64,67 +81,62 @@ float DistributionGGX(vec3 N, vec3 H, float roughness);
 float GeometrySchlickGGX(float NdotV, float roughness);
 float GeometrySmith(vec3 N, vec3 V, vec3 L, float roughness);
 vec3 fresnelSchlick(float cosTheta, vec3 F0);
+vec3 reflectance(vec3 N, vec3 V, vec3 L, vec3 radiance, vec3 albedo,
+	float metallic, float roughness, vec3 F0);
+float range_attenuation(float distance, float range);
 
 void main() {
-	PointLight light;
-	light.position = vec3(200.0, 400.0, 0.0);
-	light.color = vec3(2.0, 2.0, 2.0);
-
 	vec3 N = getNormalFromMap();
 	vec3 V = normalize(CAMERA_POS - v_world_position);
 
 	vec3 albedo = vec3(0.0);
-	if (USE_DIFFUSE_TEX) {
-		albedo = texture(DIFFUSE_TEX, v_texcoord_0).rgb;
+	if (USE_BASE_COLOR_TEX) {
+		albedo = texture(BASE_COLOR_TEX, v_texcoord_0).rgb;
 	} else {
 		albedo = v_color;
 	}
 	albedo = vec3(1.0);
 
-	float metallic = 0.0;
-	float roughness = 0.0;
+	float metallic = METALLIC_FACTOR;
+	float roughness = ROUGHNESS_FACTOR;
 	if (USE_METALLIC_ROUGHNESS_TEX) {
-		vec2 metallic_roughness = texture(METALLIC_ROUGHNESS_TEX, v_texcoord_0).rg;
-		metallic = metallic_roughness.r;
-		roughness = metallic_roughness.g;
-	} else {
-		metallic = METALLIC;
-		roughness = ROUGHNESS;
+		vec4 metallic_roughness = texture(METALLIC_ROUGHNESS_TEX, v_texcoord_0);
+		metallic *= metallic_roughness.b;
+		roughness *= metallic_roughness.g;
 	}
 
-	float ao = 0.0;
+	float ao = 1.0;
 	if (USE_OCCLUSION_TEX) {
-		ao = texture(OCCLUSION_TEX, v_texcoord_0).r;
-	} else {
-		ao = OCCLUSION;
+		float occlusion = texture(OCCLUSION_TEX, v_texcoord_0).r;
+		ao = 1.0 + OCCLUSION_STRENGTH * (occlusion - 1.0);
 	}
 
 	vec3 F0 = vec3(0.04); 
 	F0 = mix(F0, albedo, metallic);
 
 	vec3 light_out = vec3(0.0);
-	{	
+	for (int i = 0; i < min(POINT_LIGHT_COUNT, MAX_POINT_LIGHTS); i++) {
+		PointLight light = POINT_LIGHTS[i];
 		vec3 L = normalize(light.position - v_world_position);
-		vec3 H = normalize(V + L);
 		float distance = length(light.position - v_world_position);
-		float attenuation = 1.0 / (distance * distance);
-		attenuation = 1.0;
+		vec3 radiance = light.color * range_attenuation(distance, light.range);
+		light_out += reflectance(N, V, L, radiance, albedo, metallic, roughness, F0);
+	}
+	for (int i = 0; i < min(DIRECTION_LIGHT_COUNT, MAX_DIRECTION_LIGHTS); i++) {
+		DirectionLight light = DIRECTION_LIGHTS[i];
+		vec3 L = -normalize(light.direction);
+		light_out += reflectance(N, V, L, light.color, albedo, metallic, roughness, F0);
+	}
+	for (int i = 0; i < min(SPOT_LIGHT_COUNT, MAX_SPOT_LIGHTS); i++) {
+		SpotLight light = SPOT_LIGHTS[i];
+		vec3 L = normalize(light.position - v_world_position);
+		float distance = length(light.position - v_world_position);
+		float cone = clamp(
+			dot(normalize(light.direction), -L) * light.angle_scale + light.angle_offset,
+			0.0, 1.0);
+		float attenuation = range_attenuation(distance, light.range) * cone * cone;
 		vec3 radiance = light.color * attenuation;
-
-		float NDF = DistributionGGX(N, H, roughness);
-		float G = GeometrySmith(N, V, L, roughness);
-		vec3 F = fresnelSchlick(max(dot(H, V), 0.0), F0);
-
-		vec3 kS = F;
-		vec3 kD = vec3(1.0) - kS;
-		kD *= 1.0 - metallic;
-
-		vec3 numerator = NDF * G * F;
-		float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0);
-		vec3 specular = numerator / max(denominator, 0.000001);  
-
-		float NdotL = max(dot(N, L), 0.0);
-		light_out = (kD * albedo / PI + specular) * radiance * NdotL;
+		light_out += reflectance(N, V, L, radiance, albedo, metallic, roughness, F0);
 	}
 
 	float ambient_strength = 0.03;
@@ -201,3 +213,33 @@ float GeometrySmith(vec3 N, vec3 V, vec3 L, float roughness) {
 vec3 fresnelSchlick(float cosTheta, vec3 F0) {
 	return F0 + (1.0 - F0) * pow(max(1.0 - cosTheta, 0.0), 5.0);
 }
+// The light leaving toward V from radiance arriving along L.
+vec3 reflectance(vec3 N, vec3 V, vec3 L, vec3 radiance, vec3 albedo,
+	float metallic, float roughness, vec3 F0) {
+	vec3 H = normalize(V + L);
+
+	float NDF = DistributionGGX(N, H, roughness);
+	float G = GeometrySmith(N, V, L, roughness);
+	vec3 F = fresnelSchlick(max(dot(H, V), 0.0), F0);
+
+	vec3 kS = F;
+	vec3 kD = vec3(1.0) - kS;
+	kD *= 1.0 - metallic;
+
+	vec3 numerator = NDF * G * F;
+	float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0);
+	vec3 specular = numerator / max(denominator, 0.000001);
+
+	float NdotL = max(dot(N, L), 0.0);
+	return (kD * albedo / PI + specular) * radiance * NdotL;
+}
+// Inverse square falloff reaching zero at range, as KHR_lights_punctual
+// recommends. Mirrored by lights::range_attenuation.
+float range_attenuation(float distance, float range) {
+	float inverse_square = 1.0 / max(distance * distance, 0.0001);
+	if (range <= 0.0) {
+		return inverse_square;
+	}
+	float ratio = distance / range;
+	return clamp(1.0 - pow(ratio, 4.0), 0.0, 1.0) * inverse_square;
+}
@@ -0,0 +1,246 @@
+// File: src/backend/mod.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// The subset of WebGL2 the renderer uses, abstracted so buffers, textures,
+// meshes and programs can be created and drawn without a browser.
+// Method names and arguments mirror web_sys so call sites read the same,
+// GL enums are still taken from WebGl2RenderingContext's constants.
+
+pub mod recording;
+pub mod software;
+pub mod webgl;
+
+use std::fmt::Debug;
+
+use web_sys::HtmlImageElement;
+
+pub trait GlBackend: Clone + Debug {
+	type Buffer: Clone + Debug;
+	type Texture: Clone + Debug;
+	type Shader: Clone + Debug;
+	type Program: Clone + Debug;
+	type VertexArray: Clone + Debug;
+	type UniformLocation: Clone + Debug;
+	type Framebuffer: Clone + Debug;
+	type Renderbuffer: Clone + Debug;
+	type Sampler: Clone + Debug;
+
+	fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32);
+	fn clear(&self, mask: u32);
+	fn enable(&self, cap: u32);
+	fn disable(&self, cap: u32);
+	fn front_face(&self, mode: u32);
+	fn cull_face(&self, mode: u32);
+	fn depth_func(&self, func: u32);
+	fn depth_mask(&self, flag: bool);
+	fn blend_func(&self, sfactor: u32, dfactor: u32);
+	fn blend_func_separate(
+		&self,
+		src_rgb: u32,
+		dst_rgb: u32,
+		src_alpha: u32,
+		dst_alpha: u32,
+	);
+	fn viewport(&self, x: i32, y: i32, width: i32, height: i32);
+
+	fn create_buffer(&self) -> Option<Self::Buffer>;
+	fn bind_buffer(&self, target: u32, buffer: Option<&Self::Buffer>);
+	fn buffer_data_with_u8_array(&self, target: u32, data: &[u8], usage: u32);
+	fn delete_buffer(&self, buffer: Option<&Self::Buffer>);
+
+	fn create_vertex_array(&self) -> Option<Self::VertexArray>;
+	fn bind_vertex_array(&self, vertex_array: Option<&Self::VertexArray>);
+	fn delete_vertex_array(&self, vertex_array: Option<&Self::VertexArray>);
+	fn vertex_attrib_pointer_with_i32(
+		&self,
+		index: u32,
+		size: i32,
+		type_: u32,
+		normalized: bool,
+		stride: i32,
+		offset: i32,
+	);
+	fn enable_vertex_attrib_array(&self, index: u32);
+
+	fn draw_arrays(&self, mode: u32, first: i32, count: i32);
+	fn draw_elements_with_i32(
+		&self,
+		mode: u32,
+		count: i32,
+		type_: u32,
+		offset: i32,
+	);
+
+	fn create_texture(&self) -> Option<Self::Texture>;
+	fn delete_texture(&self, texture: Option<&Self::Texture>);
+	fn active_texture(&self, texture: u32);
+	fn bind_texture(&self, target: u32, texture: Option<&Self::Texture>);
+	fn tex_parameteri(&self, target: u32, pname: u32, param: i32);
+	fn pixel_storei(&self, pname: u32, param: i32);
+	fn tex_image_2d_with_html_image_element(
+		&self,
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		format: u32,
+		type_: u32,
+		image: &HtmlImageElement,
+	) -> Result<(), &'static str>;
+	// Allocates the level, filled from `pixels` when given.
+	#[allow(clippy::too_many_arguments)]
+	fn tex_image_2d_with_opt_u8_array(
+		&self,
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		pixels: Option<&[u8]>,
+	) -> Result<(), &'static str>;
+	// Like tex_image_2d_with_opt_u8_array for FLOAT pixels.
+	#[allow(clippy::too_many_arguments)]
+	fn tex_image_2d_with_f32_array(
+		&self,
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		pixels: &[f32],
+	) -> Result<(), &'static str>;
+	fn generate_mipmap(&self, target: u32);
+
+	fn create_sampler(&self) -> Option<Self::Sampler>;
+	fn bind_sampler(&self, unit: u32, sampler: Option<&Self::Sampler>);
+	fn sampler_parameteri(
+		&self,
+		sampler: &Self::Sampler,
+		pname: u32,
+		param: i32,
+	);
+	fn sampler_parameterf(
+		&self,
+		sampler: &Self::Sampler,
+		pname: u32,
+		param: f32,
+	);
+	fn delete_sampler(&self, sampler: Option<&Self::Sampler>);
+
+	// Enables the extension, false when it isn't supported.
+	fn get_extension(&self, name: &str) -> bool;
+	fn get_parameter_f32(&self, pname: u32) -> Option<f32>;
+
+	fn create_framebuffer(&self) -> Option<Self::Framebuffer>;
+	fn bind_framebuffer(
+		&self,
+		target: u32,
+		framebuffer: Option<&Self::Framebuffer>,
+	);
+	fn delete_framebuffer(&self, framebuffer: Option<&Self::Framebuffer>);
+	fn framebuffer_renderbuffer(
+		&self,
+		target: u32,
+		attachment: u32,
+		renderbuffer_target: u32,
+		renderbuffer: Option<&Self::Renderbuffer>,
+	);
+	fn framebuffer_texture_2d(
+		&self,
+		target: u32,
+		attachment: u32,
+		texture_target: u32,
+		texture: Option<&Self::Texture>,
+		level: i32,
+	);
+	fn check_framebuffer_status(&self, target: u32) -> u32;
+	fn create_renderbuffer(&self) -> Option<Self::Renderbuffer>;
+	fn bind_renderbuffer(
+		&self,
+		target: u32,
+		renderbuffer: Option<&Self::Renderbuffer>,
+	);
+	fn renderbuffer_storage(
+		&self,
+		target: u32,
+		internal_format: u32,
+		width: i32,
+		height: i32,
+	);
+	fn delete_renderbuffer(&self, renderbuffer: Option<&Self::Renderbuffer>);
+	fn clear_bufferuiv_with_u32_array(
+		&self,
+		buffer: u32,
+		draw_buffer: i32,
+		values: &[u32],
+	);
+	// Reads from the bound framebuffer into `data`, which must hold
+	// every component of the requested rectangle.
+	#[allow(clippy::too_many_arguments)]
+	fn read_pixels_with_u32_array(
+		&self,
+		x: i32,
+		y: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		data: &mut [u32],
+	) -> Result<(), &'static str>;
+
+	fn create_shader(&self, shader_type: u32) -> Option<Self::Shader>;
+	fn shader_source(&self, shader: &Self::Shader, source: &str);
+	fn compile_shader(&self, shader: &Self::Shader);
+	fn delete_shader(&self, shader: Option<&Self::Shader>);
+	fn get_shader_info_log(&self, shader: &Self::Shader) -> Option<String>;
+
+	fn create_program(&self) -> Option<Self::Program>;
+	fn attach_shader(&self, program: &Self::Program, shader: &Self::Shader);
+	fn link_program(&self, program: &Self::Program);
+	fn validate_program(&self, program: &Self::Program);
+	fn get_program_parameter_bool(
+		&self,
+		program: &Self::Program,
+		pname: u32,
+	) -> bool;
+	fn get_program_info_log(&self, program: &Self::Program) -> Option<String>;
+	fn get_attrib_location(&self, program: &Self::Program, name: &str) -> i32;
+	fn get_uniform_location(
+		&self,
+		program: &Self::Program,
+		name: &str,
+	) -> Option<Self::UniformLocation>;
+	fn use_program(&self, program: Option<&Self::Program>);
+	fn delete_program(&self, program: Option<&Self::Program>);
+
+	fn uniform1i(&self, location: Option<&Self::UniformLocation>, x: i32);
+	fn uniform1ui(&self, location: Option<&Self::UniformLocation>, x: u32);
+	fn uniform1f(&self, location: Option<&Self::UniformLocation>, x: f32);
+	fn uniform3f(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		x: f32,
+		y: f32,
+		z: f32,
+	);
+	fn uniform4f(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		x: f32,
+		y: f32,
+		z: f32,
+		w: f32,
+	);
+	fn uniform_matrix4fv_with_f32_array(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		transpose: bool,
+		data: &[f32],
+	);
+}
@@ -0,0 +1,778 @@
+// File: src/backend/recording.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// A GlBackend that performs no rendering. Every call is appended to a log
+// and objects are fake numeric handles, which lets tests assert on what
+// resource creation and draw paths ask of GL.
+
+use std::{
+	cell::RefCell,
+	rc::Rc,
+};
+
+use web_sys::HtmlImageElement;
+
+use super::GlBackend;
+
+pub type Handle = u32;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GlCall {
+	ClearColor(f32, f32, f32, f32),
+	Clear(u32),
+	Enable(u32),
+	Disable(u32),
+	FrontFace(u32),
+	CullFace(u32),
+	DepthFunc(u32),
+	DepthMask(bool),
+	BlendFunc(u32, u32),
+	BlendFuncSeparate(u32, u32, u32, u32),
+	Viewport(i32, i32, i32, i32),
+
+	CreateBuffer(Handle),
+	BindBuffer(u32, Option<Handle>),
+	BufferData {
+		target: u32,
+		length: usize,
+		usage: u32,
+	},
+	DeleteBuffer(Option<Handle>),
+
+	CreateVertexArray(Handle),
+	BindVertexArray(Option<Handle>),
+	DeleteVertexArray(Option<Handle>),
+	VertexAttribPointer {
+		index: u32,
+		size: i32,
+		type_: u32,
+		normalized: bool,
+		stride: i32,
+		offset: i32,
+	},
+	EnableVertexAttribArray(u32),
+
+	DrawArrays {
+		mode: u32,
+		first: i32,
+		count: i32,
+	},
+	DrawElements {
+		mode: u32,
+		count: i32,
+		type_: u32,
+		offset: i32,
+	},
+
+	CreateTexture(Handle),
+	DeleteTexture(Option<Handle>),
+	ActiveTexture(u32),
+	BindTexture(u32, Option<Handle>),
+	TexParameteri(u32, u32, i32),
+	PixelStorei(u32, i32),
+	TexImage2D {
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		format: u32,
+		type_: u32,
+	},
+	// `length` is the byte count of the pixels, None when none were given.
+	TexImage2DPixels {
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		length: Option<usize>,
+	},
+	GenerateMipmap(u32),
+
+	CreateSampler(Handle),
+	BindSampler(u32, Option<Handle>),
+	SamplerParameteri(Handle, u32, i32),
+	SamplerParameterf(Handle, u32, f32),
+	DeleteSampler(Option<Handle>),
+
+	CreateFramebuffer(Handle),
+	BindFramebuffer(u32, Option<Handle>),
+	DeleteFramebuffer(Option<Handle>),
+	FramebufferRenderbuffer {
+		target: u32,
+		attachment: u32,
+		renderbuffer: Option<Handle>,
+	},
+	FramebufferTexture2D {
+		target: u32,
+		attachment: u32,
+		texture: Option<Handle>,
+		level: i32,
+	},
+	CreateRenderbuffer(Handle),
+	BindRenderbuffer(u32, Option<Handle>),
+	RenderbufferStorage {
+		internal_format: u32,
+		width: i32,
+		height: i32,
+	},
+	DeleteRenderbuffer(Option<Handle>),
+	ClearBufferuiv(u32, i32, Vec<u32>),
+	ReadPixels {
+		x: i32,
+		y: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+	},
+
+	CreateShader(u32, Handle),
+	ShaderSource(Handle),
+	CompileShader(Handle),
+	DeleteShader(Option<Handle>),
+
+	CreateProgram(Handle),
+	AttachShader(Handle, Handle),
+	LinkProgram(Handle),
+	ValidateProgram(Handle),
+	UseProgram(Option<Handle>),
+	DeleteProgram(Option<Handle>),
+
+	Uniform1i(Option<String>, i32),
+	Uniform1ui(Option<String>, u32),
+	Uniform1f(Option<String>, f32),
+	Uniform3f(Option<String>, f32, f32, f32),
+	Uniform4f(Option<String>, f32, f32, f32, f32),
+	UniformMatrix4fv(Option<String>, [f32; 16]),
+}
+
+#[derive(Debug, Default)]
+struct RecordingState {
+	calls: Vec<GlCall>,
+	next_handle: Handle,
+	// Supported extensions with the parameters they add.
+	extensions: Vec<(String, Vec<(u32, f32)>)>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RecordingBackend {
+	state: Rc<RefCell<RecordingState>>,
+}
+impl RecordingBackend {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	// Reports `name` as supported, with `parameters` as its values for
+	// get_parameter_f32.
+	pub fn add_extension(&self, name: &str, parameters: &[(u32, f32)]) {
+		self.state
+			.borrow_mut()
+			.extensions
+			.push((name.to_owned(), parameters.to_vec()));
+	}
+	pub fn calls(&self) -> Vec<GlCall> {
+		self.state.borrow().calls.clone()
+	}
+	pub fn clear_calls(&self) {
+		self.state.borrow_mut().calls.clear();
+	}
+	pub fn count(&self, predicate: impl Fn(&GlCall) -> bool) -> usize {
+		self.state
+			.borrow()
+			.calls
+			.iter()
+			.filter(|call| predicate(call))
+			.count()
+	}
+	fn record(&self, call: GlCall) {
+		self.state.borrow_mut().calls.push(call);
+	}
+	fn new_handle(&self) -> Handle {
+		let mut state = self.state.borrow_mut();
+		state.next_handle += 1;
+		state.next_handle
+	}
+}
+
+impl GlBackend for RecordingBackend {
+	type Buffer = Handle;
+	type Texture = Handle;
+	type Shader = Handle;
+	type Program = Handle;
+	type VertexArray = Handle;
+	// Locations are the uniform names, which keeps the log readable.
+	type UniformLocation = String;
+	type Framebuffer = Handle;
+	type Renderbuffer = Handle;
+	type Sampler = Handle;
+
+	fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+		self.record(GlCall::ClearColor(red, green, blue, alpha));
+	}
+	fn clear(&self, mask: u32) {
+		self.record(GlCall::Clear(mask));
+	}
+	fn enable(&self, cap: u32) {
+		self.record(GlCall::Enable(cap));
+	}
+	fn disable(&self, cap: u32) {
+		self.record(GlCall::Disable(cap));
+	}
+	fn front_face(&self, mode: u32) {
+		self.record(GlCall::FrontFace(mode));
+	}
+	fn cull_face(&self, mode: u32) {
+		self.record(GlCall::CullFace(mode));
+	}
+	fn depth_func(&self, func: u32) {
+		self.record(GlCall::DepthFunc(func));
+	}
+	fn depth_mask(&self, flag: bool) {
+		self.record(GlCall::DepthMask(flag));
+	}
+	fn blend_func(&self, sfactor: u32, dfactor: u32) {
+		self.record(GlCall::BlendFunc(sfactor, dfactor));
+	}
+	fn blend_func_separate(
+		&self,
+		src_rgb: u32,
+		dst_rgb: u32,
+		src_alpha: u32,
+		dst_alpha: u32,
+	) {
+		self.record(GlCall::BlendFuncSeparate(
+			src_rgb, dst_rgb, src_alpha, dst_alpha,
+		));
+	}
+	fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+		self.record(GlCall::Viewport(x, y, width, height));
+	}
+
+	fn create_buffer(&self) -> Option<Self::Buffer> {
+		let handle = self.new_handle();
+		self.record(GlCall::CreateBuffer(handle));
+		Some(handle)
+	}
+	fn bind_buffer(&self, target: u32, buffer: Option<&Self::Buffer>) {
+		self.record(GlCall::BindBuffer(target, buffer.copied()));
+	}
+	fn buffer_data_with_u8_array(&self, target: u32, data: &[u8], usage: u32) {
+		self.record(GlCall::BufferData {
+			target,
+			length: data.len(),
+			usage,
+		});
+	}
+	fn delete_buffer(&self, buffer: Option<&Self::Buffer>) {
+		self.record(GlCall::DeleteBuffer(buffer.copied()));
+	}
+
+	fn create_vertex_array(&self) -> Option<Self::VertexArray> {
+		let handle = self.new_handle();
+		self.record(GlCall::CreateVertexArray(handle));
+		Some(handle)
+	}
+	fn bind_vertex_array(&self, vertex_array: Option<&Self::VertexArray>) {
+		self.record(GlCall::BindVertexArray(vertex_array.copied()));
+	}
+	fn delete_vertex_array(&self, vertex_array: Option<&Self::VertexArray>) {
+		self.record(GlCall::DeleteVertexArray(vertex_array.copied()));
+	}
+	fn vertex_attrib_pointer_with_i32(
+		&self,
+		index: u32,
+		size: i32,
+		type_: u32,
+		normalized: bool,
+		stride: i32,
+		offset: i32,
+	) {
+		self.record(GlCall::VertexAttribPointer {
+			index,
+			size,
+			type_,
+			normalized,
+			stride,
+			offset,
+		});
+	}
+	fn enable_vertex_attrib_array(&self, index: u32) {
+		self.record(GlCall::EnableVertexAttribArray(index));
+	}
+
+	fn draw_arrays(&self, mode: u32, first: i32, count: i32) {
+		self.record(GlCall::DrawArrays { mode, first, count });
+	}
+	fn draw_elements_with_i32(
+		&self,
+		mode: u32,
+		count: i32,
+		type_: u32,
+		offset: i32,
+	) {
+		self.record(GlCall::DrawElements {
+			mode,
+			count,
+			type_,
+			offset,
+		});
+	}
+
+	fn create_texture(&self) -> Option<Self::Texture> {
+		let handle = self.new_handle();
+		self.record(GlCall::CreateTexture(handle));
+		Some(handle)
+	}
+	fn delete_texture(&self, texture: Option<&Self::Texture>) {
+		self.record(GlCall::DeleteTexture(texture.copied()));
+	}
+	fn active_texture(&self, texture: u32) {
+		self.record(GlCall::ActiveTexture(texture));
+	}
+	fn bind_texture(&self, target: u32, texture: Option<&Self::Texture>) {
+		self.record(GlCall::BindTexture(target, texture.copied()));
+	}
+	fn tex_parameteri(&self, target: u32, pname: u32, param: i32) {
+		self.record(GlCall::TexParameteri(target, pname, param));
+	}
+	fn pixel_storei(&self, pname: u32, param: i32) {
+		self.record(GlCall::PixelStorei(pname, param));
+	}
+	fn tex_image_2d_with_html_image_element(
+		&self,
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		format: u32,
+		type_: u32,
+		_image: &HtmlImageElement,
+	) -> Result<(), &'static str> {
+		self.record(GlCall::TexImage2D {
+			target,
+			level,
+			internal_format,
+			format,
+			type_,
+		});
+		Ok(())
+	}
+	fn tex_image_2d_with_opt_u8_array(
+		&self,
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		pixels: Option<&[u8]>,
+	) -> Result<(), &'static str> {
+		self.record(GlCall::TexImage2DPixels {
+			target,
+			level,
+			internal_format,
+			width,
+			height,
+			format,
+			type_,
+			length: pixels.map(<[u8]>::len),
+		});
+		Ok(())
+	}
+	fn tex_image_2d_with_f32_array(
+		&self,
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		pixels: &[f32],
+	) -> Result<(), &'static str> {
+		self.record(GlCall::TexImage2DPixels {
+			target,
+			level,
+			internal_format,
+			width,
+			height,
+			format,
+			type_,
+			length: Some(std::mem::size_of_val(pixels)),
+		});
+		Ok(())
+	}
+	fn generate_mipmap(&self, target: u32) {
+		self.record(GlCall::GenerateMipmap(target));
+	}
+
+	fn create_sampler(&self) -> Option<Self::Sampler> {
+		let handle = self.new_handle();
+		self.record(GlCall::CreateSampler(handle));
+		Some(handle)
+	}
+	fn bind_sampler(&self, unit: u32, sampler: Option<&Self::Sampler>) {
+		self.record(GlCall::BindSampler(unit, sampler.copied()));
+	}
+	fn sampler_parameteri(
+		&self,
+		sampler: &Self::Sampler,
+		pname: u32,
+		param: i32,
+	) {
+		self.record(GlCall::SamplerParameteri(*sampler, pname, param));
+	}
+	fn sampler_parameterf(
+		&self,
+		sampler: &Self::Sampler,
+		pname: u32,
+		param: f32,
+	) {
+		self.record(GlCall::SamplerParameterf(*sampler, pname, param));
+	}
+	fn delete_sampler(&self, sampler: Option<&Self::Sampler>) {
+		self.record(GlCall::DeleteSampler(sampler.copied()));
+	}
+
+	fn get_extension(&self, name: &str) -> bool {
+		self.state
+			.borrow()
+			.extensions
+			.iter()
+			.any(|(n, _)| n == name)
+	}
+	fn get_parameter_f32(&self, pname: u32) -> Option<f32> {
+		self.state
+			.borrow()
+			.extensions
+			.iter()
+			.flat_map(|(_, parameters)| parameters.iter())
+			.find(|(p, _)| *p == pname)
+			.map(|(_, value)| *value)
+	}
+
+	fn create_framebuffer(&self) -> Option<Self::Framebuffer> {
+		let handle = self.new_handle();
+		self.record(GlCall::CreateFramebuffer(handle));
+		Some(handle)
+	}
+	fn bind_framebuffer(
+		&self,
+		target: u32,
+		framebuffer: Option<&Self::Framebuffer>,
+	) {
+		self.record(GlCall::BindFramebuffer(target, framebuffer.copied()));
+	}
+	fn delete_framebuffer(&self, framebuffer: Option<&Self::Framebuffer>) {
+		self.record(GlCall::DeleteFramebuffer(framebuffer.copied()));
+	}
+	fn framebuffer_renderbuffer(
+		&self,
+		target: u32,
+		attachment: u32,
+		_renderbuffer_target: u32,
+		renderbuffer: Option<&Self::Renderbuffer>,
+	) {
+		self.record(GlCall::FramebufferRenderbuffer {
+			target,
+			attachment,
+			renderbuffer: renderbuffer.copied(),
+		});
+	}
+	fn framebuffer_texture_2d(
+		&self,
+		target: u32,
+		attachment: u32,
+		_texture_target: u32,
+		texture: Option<&Self::Texture>,
+		level: i32,
+	) {
+		self.record(GlCall::FramebufferTexture2D {
+			target,
+			attachment,
+			texture: texture.copied(),
+			level,
+		});
+	}
+	// Framebuffers are always complete.
+	fn check_framebuffer_status(&self, _target: u32) -> u32 {
+		web_sys::WebGl2RenderingContext::FRAMEBUFFER_COMPLETE
+	}
+	fn create_renderbuffer(&self) -> Option<Self::Renderbuffer> {
+		let handle = self.new_handle();
+		self.record(GlCall::CreateRenderbuffer(handle));
+		Some(handle)
+	}
+	fn bind_renderbuffer(
+		&self,
+		target: u32,
+		renderbuffer: Option<&Self::Renderbuffer>,
+	) {
+		self.record(GlCall::BindRenderbuffer(target, renderbuffer.copied()));
+	}
+	fn renderbuffer_storage(
+		&self,
+		_target: u32,
+		internal_format: u32,
+		width: i32,
+		height: i32,
+	) {
+		self.record(GlCall::RenderbufferStorage {
+			internal_format,
+			width,
+			height,
+		});
+	}
+	fn delete_renderbuffer(&self, renderbuffer: Option<&Self::Renderbuffer>) {
+		self.record(GlCall::DeleteRenderbuffer(renderbuffer.copied()));
+	}
+	fn clear_bufferuiv_with_u32_array(
+		&self,
+		buffer: u32,
+		draw_buffer: i32,
+		values: &[u32],
+	) {
+		self.record(GlCall::ClearBufferuiv(
+			buffer,
+			draw_buffer,
+			values.to_vec(),
+		));
+	}
+	// Nothing is drawn, so the pixels read back are zero.
+	fn read_pixels_with_u32_array(
+		&self,
+		x: i32,
+		y: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		data: &mut [u32],
+	) -> Result<(), &'static str> {
+		self.record(GlCall::ReadPixels {
+			x,
+			y,
+			width,
+			height,
+			format,
+			type_,
+		});
+		data.iter_mut().for_each(|value| *value = 0);
+		Ok(())
+	}
+
+	fn create_shader(&self, shader_type: u32) -> Option<Self::Shader> {
+		let handle = self.new_handle();
+		self.record(GlCall::CreateShader(shader_type, handle));
+		Some(handle)
+	}
+	fn shader_source(&self, shader: &Self::Shader, _source: &str) {
+		self.record(GlCall::ShaderSource(*shader));
+	}
+	fn compile_shader(&self, shader: &Self::Shader) {
+		self.record(GlCall::CompileShader(*shader));
+	}
+	fn delete_shader(&self, shader: Option<&Self::Shader>) {
+		self.record(GlCall::DeleteShader(shader.copied()));
+	}
+	fn get_shader_info_log(&self, _shader: &Self::Shader) -> Option<String> {
+		Some(String::new())
+	}
+
+	fn create_program(&self) -> Option<Self::Program> {
+		let handle = self.new_handle();
+		self.record(GlCall::CreateProgram(handle));
+		Some(handle)
+	}
+	fn attach_shader(&self, program: &Self::Program, shader: &Self::Shader) {
+		self.record(GlCall::AttachShader(*program, *shader));
+	}
+	fn link_program(&self, program: &Self::Program) {
+		self.record(GlCall::LinkProgram(*program));
+	}
+	fn validate_program(&self, program: &Self::Program) {
+		self.record(GlCall::ValidateProgram(*program));
+	}
+	fn get_program_parameter_bool(
+		&self,
+		_program: &Self::Program,
+		_pname: u32,
+	) -> bool {
+		true
+	}
+	fn get_program_info_log(&self, _program: &Self::Program) -> Option<String> {
+		Some(String::new())
+	}
+	fn get_attrib_location(
+		&self,
+		_program: &Self::Program,
+		_name: &str,
+	) -> i32 {
+		-1
+	}
+	fn get_uniform_location(
+		&self,
+		_program: &Self::Program,
+		name: &str,
+	) -> Option<Self::UniformLocation> {
+		Some(name.to_owned())
+	}
+	fn use_program(&self, program: Option<&Self::Program>) {
+		self.record(GlCall::UseProgram(program.copied()));
+	}
+	fn delete_program(&self, program: Option<&Self::Program>) {
+		self.record(GlCall::DeleteProgram(program.copied()));
+	}
+
+	fn uniform1i(&self, location: Option<&Self::UniformLocation>, x: i32) {
+		self.record(GlCall::Uniform1i(location.cloned(), x));
+	}
+	fn uniform1ui(&self, location: Option<&Self::UniformLocation>, x: u32) {
+		self.record(GlCall::Uniform1ui(location.cloned(), x));
+	}
+	fn uniform1f(&self, location: Option<&Self::UniformLocation>, x: f32) {
+		self.record(GlCall::Uniform1f(location.cloned(), x));
+	}
+	fn uniform3f(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		x: f32,
+		y: f32,
+		z: f32,
+	) {
+		self.record(GlCall::Uniform3f(location.cloned(), x, y, z));
+	}
+	fn uniform4f(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		x: f32,
+		y: f32,
+		z: f32,
+		w: f32,
+	) {
+		self.record(GlCall::Uniform4f(location.cloned(), x, y, z, w));
+	}
+	fn uniform_matrix4fv_with_f32_array(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		_transpose: bool,
+		data: &[f32],
+	) {
+		let mut matrix = [0.0; 16];
+		matrix.copy_from_slice(&data[..16]);
+		self.record(GlCall::UniformMatrix4fv(location.cloned(), matrix));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::rc::Rc;
+
+	use web_sys::WebGl2RenderingContext as Gl;
+
+	use super::*;
+	use crate::config::scene_config::UniformConfig;
+	use crate::model::{
+		buffer::Buffer,
+		mesh::generator::cube::generate_cube,
+		Drawable,
+	};
+	use crate::program::Program;
+	use crate::resources::{
+		traits::NewResourceT,
+		Resources,
+	};
+	use crate::shader::Shader;
+
+	fn program(gl: &RecordingBackend) -> Program<RecordingBackend> {
+		let uniforms = [
+			UniformConfig::new("USE_BASE_COLOR_TEX"),
+			UniformConfig::new("BASE_COLOR_FACTOR"),
+		];
+		let vert =
+			Shader::new(gl, Gl::VERTEX_SHADER, "", &[], &uniforms).unwrap();
+		let frag = Shader::new(gl, Gl::FRAGMENT_SHADER, "", &[], &[]).unwrap();
+		Program::new(gl, &vert, &frag).unwrap().0
+	}
+
+	#[test]
+	fn buffer_is_created_bound_and_filled() {
+		let gl = RecordingBackend::new();
+		let buffer = Buffer::new(&gl, Gl::ARRAY_BUFFER, &[0; 12]).unwrap();
+		assert_eq!(
+			gl.calls(),
+			vec![
+				GlCall::CreateBuffer(buffer.handle),
+				GlCall::BindBuffer(Gl::ARRAY_BUFFER, Some(buffer.handle)),
+				GlCall::BufferData {
+					target: Gl::ARRAY_BUFFER,
+					length: 12,
+					usage: Gl::STATIC_DRAW,
+				},
+			]
+		);
+	}
+	#[test]
+	fn program_links_and_finds_uniforms() {
+		let gl = RecordingBackend::new();
+		let program = program(&gl);
+		assert_eq!(gl.count(|c| matches!(c, GlCall::AttachShader(..))), 2);
+		assert_eq!(gl.count(|c| matches!(c, GlCall::LinkProgram(_))), 1);
+		assert!(program.uniform_locations.contains_key("USE_BASE_COLOR_TEX"));
+	}
+	#[test]
+	fn cube_draws_36_indices() {
+		let gl = RecordingBackend::new();
+		let program = program(&gl);
+		let cube = generate_cube(&gl);
+		assert_eq!(gl.count(|c| matches!(c, GlCall::CreateBuffer(_))), 2);
+
+		gl.clear_calls();
+		cube.draw(&gl, &program);
+		let draws: Vec<GlCall> = gl
+			.calls()
+			.into_iter()
+			.filter(|c| {
+				matches!(
+					c,
+					GlCall::DrawElements { .. } | GlCall::DrawArrays { .. }
+				)
+			})
+			.collect();
+		assert_eq!(
+			draws,
+			vec![GlCall::DrawElements {
+				mode: Gl::TRIANGLES,
+				count: 36,
+				type_: Gl::UNSIGNED_INT,
+				offset: 0,
+			}]
+		);
+		assert!(gl.calls().contains(&GlCall::Uniform1i(
+			Some(String::from("USE_BASE_COLOR_TEX")),
+			0
+		)));
+		assert!(gl.calls().contains(&GlCall::Uniform4f(
+			Some(String::from("BASE_COLOR_FACTOR")),
+			1.0,
+			1.0,
+			1.0,
+			1.0
+		)));
+	}
+	#[test]
+	fn resources_store_created_buffers() {
+		let gl = Rc::new(RecordingBackend::new());
+		let mut resources = Resources::new(Rc::clone(&gl));
+		let handle = resources
+			.new_buffer(Gl::ELEMENT_ARRAY_BUFFER, &[0; 6])
+			.unwrap();
+		assert!(resources.buffers.contains(handle));
+		assert_eq!(gl.count(|c| matches!(c, GlCall::CreateBuffer(_))), 1);
+	}
+}
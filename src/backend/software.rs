@@ -0,0 +1,1241 @@
+// File: src/backend/software.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// A GlBackend that rasterizes triangles on the CPU into an RGBA buffer.
+// The vertex stage mirrors general.vert (PROJECTION_MATRIX * VIEW_MATRIX *
+// MODEL_MATRIX * POSITION). The fragment stage replaces pbr.frag with
+// Lambert shading from a single directional light, using the interpolated
+// NORMAL attribute or the face normal when a mesh has none. Albedo is the
+// COLOR attribute, white when absent, times the BASE_COLOR_FACTOR uniform
+// Material::bind_to_uniforms sets. Textures are accepted but not
+// sampled and blending is not applied. Depth testing, depth writes, face
+// culling and the viewport follow the same GL state the WebGL2 backend
+// uses, which makes it suitable for golden-image tests. Run the tests with
+// BLESS=1 to write the goldens under tests/golden after a rendering change.
+
+use std::{
+	cell::RefCell,
+	collections::{
+		HashMap,
+		HashSet,
+	},
+	convert::TryInto,
+	rc::Rc,
+};
+
+use cgmath::{
+	ElementWise,
+	InnerSpace,
+	Matrix3,
+	Matrix4,
+	SquareMatrix,
+	Vector3,
+	Vector4,
+};
+use web_sys::{
+	HtmlImageElement,
+	WebGl2RenderingContext as Gl,
+};
+
+use super::GlBackend;
+use crate::program::attribute_locations;
+
+pub type Handle = u32;
+
+const AMBIENT: f32 = 0.1;
+// The default vertex array, used while no vertex array object is bound.
+const DEFAULT_VERTEX_ARRAY: Handle = 0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Uniform {
+	Int(i32),
+	Float(f32),
+	Vec3([f32; 3]),
+	Vec4([f32; 4]),
+	Mat4([f32; 16]),
+}
+
+#[derive(Copy, Clone, Debug)]
+struct VertexAttribute {
+	buffer: Handle,
+	size: i32,
+	type_: u32,
+	normalized: bool,
+	stride: i32,
+	offset: i32,
+}
+
+#[derive(Clone, Debug, Default)]
+struct VertexArray {
+	attributes: HashMap<u32, VertexAttribute>,
+	enabled: HashSet<u32>,
+	element_buffer: Option<Handle>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct ClipVertex {
+	position: Vector4<f32>,
+	world_position: Vector3<f32>,
+	normal: Vector3<f32>,
+	color: Vector3<f32>,
+}
+impl ClipVertex {
+	fn lerp(&self, other: &Self, t: f32) -> Self {
+		Self {
+			position: self.position + (other.position - self.position) * t,
+			world_position: self.world_position
+				+ (other.world_position - self.world_position) * t,
+			normal: self.normal + (other.normal - self.normal) * t,
+			color: self.color + (other.color - self.color) * t,
+		}
+	}
+}
+
+#[derive(Debug)]
+struct SoftwareState {
+	width: usize,
+	height: usize,
+	color: Vec<u8>,
+	depth: Vec<f32>,
+
+	clear_color: [f32; 4],
+	viewport: [i32; 4],
+	depth_test: bool,
+	depth_func: u32,
+	depth_mask: bool,
+	cull_face_enabled: bool,
+	cull_face: u32,
+	front_face: u32,
+	light_direction: Vector3<f32>,
+
+	next_handle: Handle,
+	buffers: HashMap<Handle, Vec<u8>>,
+	array_buffer: Option<Handle>,
+	vertex_arrays: HashMap<Handle, VertexArray>,
+	vertex_array: Handle,
+	programs: HashMap<Handle, HashMap<String, Uniform>>,
+	program: Option<Handle>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SoftwareBackend {
+	state: Rc<RefCell<SoftwareState>>,
+}
+impl SoftwareBackend {
+	pub fn new(width: usize, height: usize) -> Self {
+		let mut vertex_arrays = HashMap::new();
+		vertex_arrays.insert(DEFAULT_VERTEX_ARRAY, VertexArray::default());
+		let state = SoftwareState {
+			width,
+			height,
+			color: vec![0; width * height * 4],
+			depth: vec![1.0; width * height],
+
+			clear_color: [0.0; 4],
+			viewport: [0, 0, width as i32, height as i32],
+			depth_test: false,
+			depth_func: Gl::LESS,
+			depth_mask: true,
+			cull_face_enabled: false,
+			cull_face: Gl::BACK,
+			front_face: Gl::CCW,
+			light_direction: Vector3::new(0.4, 1.0, 0.6).normalize(),
+
+			next_handle: DEFAULT_VERTEX_ARRAY,
+			buffers: HashMap::new(),
+			array_buffer: None,
+			vertex_arrays,
+			vertex_array: DEFAULT_VERTEX_ARRAY,
+			programs: HashMap::new(),
+			program: None,
+		};
+		Self {
+			state: Rc::new(RefCell::new(state)),
+		}
+	}
+	pub fn width(&self) -> usize {
+		self.state.borrow().width
+	}
+	pub fn height(&self) -> usize {
+		self.state.borrow().height
+	}
+	// Direction towards the light in world space.
+	pub fn set_light_direction(&self, direction: Vector3<f32>) {
+		self.state.borrow_mut().light_direction = direction.normalize();
+	}
+	// RGBA of the pixel at (x, y) with the origin in the bottom left corner,
+	// matching glReadPixels.
+	pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+		let state = self.state.borrow();
+		let i = (y * state.width + x) * 4;
+		state.color[i..i + 4].try_into().unwrap()
+	}
+	// RGBA rows from the bottom of the image up, matching glReadPixels.
+	pub fn read_pixels(&self) -> Vec<u8> {
+		self.state.borrow().color.clone()
+	}
+	// Binary PPM (P6) with rows from the top of the image down.
+	pub fn to_ppm(&self) -> Vec<u8> {
+		let state = self.state.borrow();
+		let mut ppm =
+			format!("P6\n{} {}\n255\n", state.width, state.height).into_bytes();
+		for row in state.color.chunks(state.width * 4).rev() {
+			for pixel in row.chunks(4) {
+				ppm.extend_from_slice(&pixel[..3]);
+			}
+		}
+		ppm
+	}
+	fn new_handle(&self) -> Handle {
+		let mut state = self.state.borrow_mut();
+		state.next_handle += 1;
+		state.next_handle
+	}
+	fn set_uniform(&self, location: Option<&String>, value: Uniform) {
+		let mut state = self.state.borrow_mut();
+		if let (Some(name), Some(program)) = (location, state.program) {
+			if let Some(uniforms) = state.programs.get_mut(&program) {
+				uniforms.insert(name.clone(), value);
+			}
+		}
+	}
+}
+
+impl SoftwareState {
+	fn vertex_array(&self) -> &VertexArray {
+		&self.vertex_arrays[&self.vertex_array]
+	}
+	fn uniform_matrix(&self, name: &str) -> Matrix4<f32> {
+		let uniform = self
+			.program
+			.and_then(|program| self.programs.get(&program))
+			.and_then(|uniforms| uniforms.get(name));
+		match uniform {
+			Some(Uniform::Mat4(m)) => Matrix4::new(
+				m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7], m[8], m[9],
+				m[10], m[11], m[12], m[13], m[14], m[15],
+			),
+			_ => Matrix4::identity(),
+		}
+	}
+	fn uniform_vec4(&self, name: &str, default: Vector4<f32>) -> Vector4<f32> {
+		let uniform = self
+			.program
+			.and_then(|program| self.programs.get(&program))
+			.and_then(|uniforms| uniforms.get(name));
+		match uniform {
+			Some(Uniform::Vec4(v)) => Vector4::from(*v),
+			_ => default,
+		}
+	}
+	fn read_attribute(&self, location: u32, vertex: usize) -> Option<[f32; 4]> {
+		let vertex_array = self.vertex_array();
+		if !vertex_array.enabled.contains(&location) {
+			return None;
+		}
+		let attribute = vertex_array.attributes.get(&location)?;
+		let data = self.buffers.get(&attribute.buffer)?;
+
+		let component_size = component_size(attribute.type_);
+		let stride = match attribute.stride {
+			0 => attribute.size as usize * component_size,
+			stride => stride as usize,
+		};
+		let start = attribute.offset as usize + vertex * stride;
+		let mut out = [0.0, 0.0, 0.0, 1.0];
+		for (i, value) in
+			out.iter_mut().take(attribute.size as usize).enumerate()
+		{
+			let offset = start + i * component_size;
+			let bytes = data.get(offset..offset + component_size)?;
+			*value =
+				read_component(bytes, attribute.type_, attribute.normalized);
+		}
+		Some(out)
+	}
+	fn read_index(&self, type_: u32, offset: usize, i: usize) -> Option<usize> {
+		let buffer = self.vertex_array().element_buffer?;
+		let data = self.buffers.get(&buffer)?;
+		let size = component_size(type_);
+		let start = offset + i * size;
+		let bytes = data.get(start..start + size)?;
+		Some(read_component(bytes, type_, false) as usize)
+	}
+	fn clip_vertex(
+		&self,
+		vertex: usize,
+		model: &Matrix4<f32>,
+		view_projection: &Matrix4<f32>,
+	) -> Option<ClipVertex> {
+		let p = self
+			.read_attribute(attribute_locations::POSITION_LOCATION, vertex)?;
+		let world_position = model * Vector4::new(p[0], p[1], p[2], 1.0);
+		let normal = self
+			.read_attribute(attribute_locations::NORMAL_LOCATION, vertex)
+			.map(|n| {
+				Matrix3::from_cols(
+					model.x.truncate(),
+					model.y.truncate(),
+					model.z.truncate(),
+				) * Vector3::new(n[0], n[1], n[2])
+			})
+			.unwrap_or_else(|| Vector3::new(0.0, 0.0, 0.0));
+		let color = self
+			.read_attribute(attribute_locations::COLOR_LOCATION, vertex)
+			.map(|c| Vector3::new(c[0], c[1], c[2]))
+			.unwrap_or_else(|| Vector3::new(1.0, 1.0, 1.0));
+		Some(ClipVertex {
+			position: view_projection * world_position,
+			world_position: world_position.truncate(),
+			normal,
+			color,
+		})
+	}
+	fn draw(&mut self, mode: u32, vertices: &[usize]) {
+		let triangles = assemble_triangles(mode, vertices.len());
+		if triangles.is_empty() {
+			return;
+		}
+
+		let model = self.uniform_matrix("MODEL_MATRIX");
+		let view_projection = self.uniform_matrix("PROJECTION_MATRIX")
+			* self.uniform_matrix("VIEW_MATRIX");
+		let base_color = self
+			.uniform_vec4("BASE_COLOR_FACTOR", Vector4::new(1.0, 1.0, 1.0, 1.0))
+			.truncate();
+		let has_normals = self
+			.vertex_array()
+			.enabled
+			.contains(&attribute_locations::NORMAL_LOCATION);
+
+		let clip_vertices: Vec<Option<ClipVertex>> = vertices
+			.iter()
+			.map(|v| self.clip_vertex(*v, &model, &view_projection))
+			.collect();
+
+		for [a, b, c] in triangles {
+			let triangle =
+				match (clip_vertices[a], clip_vertices[b], clip_vertices[c]) {
+					(Some(a), Some(b), Some(c)) => [a, b, c],
+					_ => continue,
+				};
+			let face_normal = if has_normals {
+				None
+			} else {
+				let n = (triangle[1].world_position
+					- triangle[0].world_position)
+					.cross(
+						triangle[2].world_position - triangle[0].world_position,
+					);
+				Some(if self.front_face == Gl::CW { -n } else { n })
+			};
+			let polygon = clip_near(&triangle);
+			for i in 1..polygon.len().saturating_sub(1) {
+				self.rasterize(
+					&[polygon[0], polygon[i], polygon[i + 1]],
+					face_normal,
+					base_color,
+				);
+			}
+		}
+	}
+	fn rasterize(
+		&mut self,
+		triangle: &[ClipVertex; 3],
+		face_normal: Option<Vector3<f32>>,
+		base_color: Vector3<f32>,
+	) {
+		let [vx, vy, vw, vh] = self.viewport;
+		let window: Vec<Vector3<f32>> = triangle
+			.iter()
+			.map(|v| {
+				let ndc = v.position.truncate() / v.position.w;
+				Vector3::new(
+					vx as f32 + (ndc.x + 1.0) * 0.5 * vw as f32,
+					vy as f32 + (ndc.y + 1.0) * 0.5 * vh as f32,
+					(ndc.z + 1.0) * 0.5,
+				)
+			})
+			.collect();
+
+		let area = edge(&window[0], &window[1], window[2].x, window[2].y);
+		if area == 0.0 || !area.is_finite() {
+			return;
+		}
+		let is_front = (area > 0.0) == (self.front_face == Gl::CCW);
+		if self.cull_face_enabled {
+			let culled = match self.cull_face {
+				Gl::FRONT => is_front,
+				Gl::FRONT_AND_BACK => true,
+				_ => !is_front,
+			};
+			if culled {
+				return;
+			}
+		}
+
+		let min_x = window.iter().fold(f32::MAX, |m, v| m.min(v.x));
+		let max_x = window.iter().fold(f32::MIN, |m, v| m.max(v.x));
+		let min_y = window.iter().fold(f32::MAX, |m, v| m.min(v.y));
+		let max_y = window.iter().fold(f32::MIN, |m, v| m.max(v.y));
+		let x_start = (min_x.floor() as i32).max(vx).max(0);
+		let x_end = (max_x.ceil() as i32).min(vx + vw).min(self.width as i32);
+		let y_start = (min_y.floor() as i32).max(vy).max(0);
+		let y_end = (max_y.ceil() as i32).min(vy + vh).min(self.height as i32);
+
+		let inverse_w: Vec<f32> =
+			triangle.iter().map(|v| 1.0 / v.position.w).collect();
+
+		for y in y_start..y_end {
+			for x in x_start..x_end {
+				let px = x as f32 + 0.5;
+				let py = y as f32 + 0.5;
+				let b = [
+					edge(&window[1], &window[2], px, py) / area,
+					edge(&window[2], &window[0], px, py) / area,
+					edge(&window[0], &window[1], px, py) / area,
+				];
+				if b.iter().any(|b| *b < 0.0) {
+					continue;
+				}
+				let depth = b[0] * window[0].z
+					+ b[1] * window[1].z
+					+ b[2] * window[2].z;
+				if !(0.0..=1.0).contains(&depth) {
+					continue;
+				}
+				let i = y as usize * self.width + x as usize;
+				if self.depth_test
+					&& !depth_passes(self.depth_func, depth, self.depth[i])
+				{
+					continue;
+				}
+
+				// Perspective correct weights for the varyings.
+				let weights = [
+					b[0] * inverse_w[0],
+					b[1] * inverse_w[1],
+					b[2] * inverse_w[2],
+				];
+				let sum = weights[0] + weights[1] + weights[2];
+				let varying = |f: fn(&ClipVertex) -> Vector3<f32>| {
+					(f(&triangle[0]) * weights[0]
+						+ f(&triangle[1]) * weights[1]
+						+ f(&triangle[2]) * weights[2])
+						/ sum
+				};
+				let normal =
+					face_normal.unwrap_or_else(|| varying(|v| v.normal));
+				let albedo = varying(|v| v.color).mul_element_wise(base_color);
+				let color = self.shade(normal, albedo);
+
+				if self.depth_mask {
+					self.depth[i] = depth;
+				}
+				self.color[i * 4..i * 4 + 4].copy_from_slice(&color);
+			}
+		}
+	}
+	fn shade(&self, normal: Vector3<f32>, albedo: Vector3<f32>) -> [u8; 4] {
+		let lambert = if normal.magnitude2() > 0.0 {
+			normal.normalize().dot(self.light_direction).max(0.0)
+		} else {
+			0.0
+		};
+		let intensity = AMBIENT + (1.0 - AMBIENT) * lambert;
+		[
+			to_u8(albedo.x * intensity),
+			to_u8(albedo.y * intensity),
+			to_u8(albedo.z * intensity),
+			255,
+		]
+	}
+}
+
+fn to_u8(value: f32) -> u8 {
+	(value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+fn edge(a: &Vector3<f32>, b: &Vector3<f32>, x: f32, y: f32) -> f32 {
+	(b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+fn depth_passes(func: u32, depth: f32, stored: f32) -> bool {
+	match func {
+		Gl::NEVER => false,
+		Gl::LESS => depth < stored,
+		Gl::EQUAL => depth == stored,
+		Gl::LEQUAL => depth <= stored,
+		Gl::GREATER => depth > stored,
+		Gl::NOTEQUAL => depth != stored,
+		Gl::GEQUAL => depth >= stored,
+		_ => true,
+	}
+}
+fn component_size(type_: u32) -> usize {
+	match type_ {
+		Gl::BYTE | Gl::UNSIGNED_BYTE => 1,
+		Gl::SHORT | Gl::UNSIGNED_SHORT => 2,
+		_ => 4,
+	}
+}
+fn read_component(bytes: &[u8], type_: u32, normalized: bool) -> f32 {
+	let (value, max) = match type_ {
+		Gl::BYTE => (bytes[0] as i8 as f32, i8::MAX as f32),
+		Gl::UNSIGNED_BYTE => (bytes[0] as f32, u8::MAX as f32),
+		Gl::SHORT => (
+			i16::from_le_bytes(bytes.try_into().unwrap()) as f32,
+			i16::MAX as f32,
+		),
+		Gl::UNSIGNED_SHORT => (
+			u16::from_le_bytes(bytes.try_into().unwrap()) as f32,
+			u16::MAX as f32,
+		),
+		Gl::UNSIGNED_INT => (
+			u32::from_le_bytes(bytes.try_into().unwrap()) as f32,
+			u32::MAX as f32,
+		),
+		_ => return f32::from_le_bytes(bytes.try_into().unwrap()),
+	};
+	if normalized {
+		(value / max).max(-1.0)
+	} else {
+		value
+	}
+}
+// Indices into the drawn vertices for each triangle. Point and line modes
+// are not rasterized.
+fn assemble_triangles(mode: u32, count: usize) -> Vec<[usize; 3]> {
+	match mode {
+		Gl::TRIANGLES => (0..count / 3)
+			.map(|t| [3 * t, 3 * t + 1, 3 * t + 2])
+			.collect(),
+		Gl::TRIANGLE_STRIP => (0..count.saturating_sub(2))
+			.map(|t| {
+				if t % 2 == 0 {
+					[t, t + 1, t + 2]
+				} else {
+					[t + 1, t, t + 2]
+				}
+			})
+			.collect(),
+		Gl::TRIANGLE_FAN => (1..count.saturating_sub(1))
+			.map(|t| [0, t, t + 1])
+			.collect(),
+		_ => Vec::new(),
+	}
+}
+// Sutherland-Hodgman against the near plane (z >= -w). The other planes are
+// handled by the viewport bounds and the depth range check.
+fn clip_near(triangle: &[ClipVertex; 3]) -> Vec<ClipVertex> {
+	let distance = |v: &ClipVertex| v.position.z + v.position.w;
+	let mut polygon = Vec::with_capacity(4);
+	for i in 0..3 {
+		let current = &triangle[i];
+		let next = &triangle[(i + 1) % 3];
+		let (d0, d1) = (distance(current), distance(next));
+		if d0 >= 0.0 {
+			polygon.push(*current);
+		}
+		if (d0 >= 0.0) != (d1 >= 0.0) {
+			polygon.push(current.lerp(next, d0 / (d0 - d1)));
+		}
+	}
+	polygon
+}
+
+impl GlBackend for SoftwareBackend {
+	type Buffer = Handle;
+	type Texture = Handle;
+	type Shader = Handle;
+	type Program = Handle;
+	type VertexArray = Handle;
+	type UniformLocation = String;
+	type Framebuffer = Handle;
+	type Renderbuffer = Handle;
+	type Sampler = Handle;
+
+	fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+		self.state.borrow_mut().clear_color = [red, green, blue, alpha];
+	}
+	fn clear(&self, mask: u32) {
+		let mut state = self.state.borrow_mut();
+		if mask & Gl::COLOR_BUFFER_BIT != 0 {
+			let [r, g, b, a] = state.clear_color;
+			let color = [to_u8(r), to_u8(g), to_u8(b), to_u8(a)];
+			state
+				.color
+				.chunks_mut(4)
+				.for_each(|pixel| pixel.copy_from_slice(&color));
+		}
+		if mask & Gl::DEPTH_BUFFER_BIT != 0 {
+			state.depth.iter_mut().for_each(|depth| *depth = 1.0);
+		}
+	}
+	fn enable(&self, cap: u32) {
+		let mut state = self.state.borrow_mut();
+		match cap {
+			Gl::DEPTH_TEST => state.depth_test = true,
+			Gl::CULL_FACE => state.cull_face_enabled = true,
+			_ => (),
+		}
+	}
+	fn disable(&self, cap: u32) {
+		let mut state = self.state.borrow_mut();
+		match cap {
+			Gl::DEPTH_TEST => state.depth_test = false,
+			Gl::CULL_FACE => state.cull_face_enabled = false,
+			_ => (),
+		}
+	}
+	fn front_face(&self, mode: u32) {
+		self.state.borrow_mut().front_face = mode;
+	}
+	fn cull_face(&self, mode: u32) {
+		self.state.borrow_mut().cull_face = mode;
+	}
+	fn depth_func(&self, func: u32) {
+		self.state.borrow_mut().depth_func = func;
+	}
+	fn depth_mask(&self, flag: bool) {
+		self.state.borrow_mut().depth_mask = flag;
+	}
+	fn blend_func(&self, _sfactor: u32, _dfactor: u32) {}
+	fn blend_func_separate(
+		&self,
+		_src_rgb: u32,
+		_dst_rgb: u32,
+		_src_alpha: u32,
+		_dst_alpha: u32,
+	) {
+	}
+	fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+		self.state.borrow_mut().viewport = [x, y, width, height];
+	}
+
+	fn create_buffer(&self) -> Option<Self::Buffer> {
+		let handle = self.new_handle();
+		self.state.borrow_mut().buffers.insert(handle, Vec::new());
+		Some(handle)
+	}
+	fn bind_buffer(&self, target: u32, buffer: Option<&Self::Buffer>) {
+		let mut state = self.state.borrow_mut();
+		match target {
+			Gl::ARRAY_BUFFER => state.array_buffer = buffer.copied(),
+			Gl::ELEMENT_ARRAY_BUFFER => {
+				let current = state.vertex_array;
+				if let Some(vertex_array) =
+					state.vertex_arrays.get_mut(&current)
+				{
+					vertex_array.element_buffer = buffer.copied();
+				}
+			}
+			_ => (),
+		}
+	}
+	fn buffer_data_with_u8_array(&self, target: u32, data: &[u8], _usage: u32) {
+		let mut state = self.state.borrow_mut();
+		let bound = match target {
+			Gl::ARRAY_BUFFER => state.array_buffer,
+			Gl::ELEMENT_ARRAY_BUFFER => state.vertex_array().element_buffer,
+			_ => None,
+		};
+		if let Some(buffer) = bound.and_then(|b| state.buffers.get_mut(&b)) {
+			*buffer = data.to_vec();
+		}
+	}
+	fn delete_buffer(&self, buffer: Option<&Self::Buffer>) {
+		if let Some(buffer) = buffer {
+			self.state.borrow_mut().buffers.remove(buffer);
+		}
+	}
+
+	fn create_vertex_array(&self) -> Option<Self::VertexArray> {
+		let handle = self.new_handle();
+		self.state
+			.borrow_mut()
+			.vertex_arrays
+			.insert(handle, VertexArray::default());
+		Some(handle)
+	}
+	fn bind_vertex_array(&self, vertex_array: Option<&Self::VertexArray>) {
+		self.state.borrow_mut().vertex_array =
+			vertex_array.copied().unwrap_or(DEFAULT_VERTEX_ARRAY);
+	}
+	fn delete_vertex_array(&self, vertex_array: Option<&Self::VertexArray>) {
+		if let Some(vertex_array) = vertex_array {
+			let mut state = self.state.borrow_mut();
+			state.vertex_arrays.remove(vertex_array);
+			if state.vertex_array == *vertex_array {
+				state.vertex_array = DEFAULT_VERTEX_ARRAY;
+			}
+		}
+	}
+	fn vertex_attrib_pointer_with_i32(
+		&self,
+		index: u32,
+		size: i32,
+		type_: u32,
+		normalized: bool,
+		stride: i32,
+		offset: i32,
+	) {
+		let mut state = self.state.borrow_mut();
+		let buffer = match state.array_buffer {
+			Some(buffer) => buffer,
+			None => return,
+		};
+		let current = state.vertex_array;
+		if let Some(vertex_array) = state.vertex_arrays.get_mut(&current) {
+			vertex_array.attributes.insert(
+				index,
+				VertexAttribute {
+					buffer,
+					size,
+					type_,
+					normalized,
+					stride,
+					offset,
+				},
+			);
+		}
+	}
+	fn enable_vertex_attrib_array(&self, index: u32) {
+		let mut state = self.state.borrow_mut();
+		let current = state.vertex_array;
+		if let Some(vertex_array) = state.vertex_arrays.get_mut(&current) {
+			vertex_array.enabled.insert(index);
+		}
+	}
+
+	fn draw_arrays(&self, mode: u32, first: i32, count: i32) {
+		let vertices: Vec<usize> =
+			(first.max(0) as usize..(first + count).max(0) as usize).collect();
+		self.state.borrow_mut().draw(mode, &vertices);
+	}
+	fn draw_elements_with_i32(
+		&self,
+		mode: u32,
+		count: i32,
+		type_: u32,
+		offset: i32,
+	) {
+		let mut state = self.state.borrow_mut();
+		let vertices: Option<Vec<usize>> = (0..count.max(0) as usize)
+			.map(|i| state.read_index(type_, offset.max(0) as usize, i))
+			.collect();
+		if let Some(vertices) = vertices {
+			state.draw(mode, &vertices);
+		}
+	}
+
+	fn create_texture(&self) -> Option<Self::Texture> {
+		Some(self.new_handle())
+	}
+	fn delete_texture(&self, _texture: Option<&Self::Texture>) {}
+	fn active_texture(&self, _texture: u32) {}
+	fn bind_texture(&self, _target: u32, _texture: Option<&Self::Texture>) {}
+	fn tex_parameteri(&self, _target: u32, _pname: u32, _param: i32) {}
+	fn pixel_storei(&self, _pname: u32, _param: i32) {}
+	fn tex_image_2d_with_html_image_element(
+		&self,
+		_target: u32,
+		_level: i32,
+		_internal_format: i32,
+		_format: u32,
+		_type_: u32,
+		_image: &HtmlImageElement,
+	) -> Result<(), &'static str> {
+		Ok(())
+	}
+	fn tex_image_2d_with_opt_u8_array(
+		&self,
+		_target: u32,
+		_level: i32,
+		_internal_format: i32,
+		_width: i32,
+		_height: i32,
+		_format: u32,
+		_type_: u32,
+		_pixels: Option<&[u8]>,
+	) -> Result<(), &'static str> {
+		Ok(())
+	}
+	fn tex_image_2d_with_f32_array(
+		&self,
+		_target: u32,
+		_level: i32,
+		_internal_format: i32,
+		_width: i32,
+		_height: i32,
+		_format: u32,
+		_type_: u32,
+		_pixels: &[f32],
+	) -> Result<(), &'static str> {
+		Ok(())
+	}
+	fn generate_mipmap(&self, _target: u32) {}
+
+	fn create_sampler(&self) -> Option<Self::Sampler> {
+		Some(self.new_handle())
+	}
+	fn bind_sampler(&self, _unit: u32, _sampler: Option<&Self::Sampler>) {}
+	fn sampler_parameteri(
+		&self,
+		_sampler: &Self::Sampler,
+		_pname: u32,
+		_param: i32,
+	) {
+	}
+	fn sampler_parameterf(
+		&self,
+		_sampler: &Self::Sampler,
+		_pname: u32,
+		_param: f32,
+	) {
+	}
+	fn delete_sampler(&self, _sampler: Option<&Self::Sampler>) {}
+
+	fn get_extension(&self, _name: &str) -> bool {
+		false
+	}
+	fn get_parameter_f32(&self, _pname: u32) -> Option<f32> {
+		None
+	}
+
+	// Only the default framebuffer is drawn to, others are accepted and
+	// ignored.
+	fn create_framebuffer(&self) -> Option<Self::Framebuffer> {
+		Some(self.new_handle())
+	}
+	fn bind_framebuffer(
+		&self,
+		_target: u32,
+		_framebuffer: Option<&Self::Framebuffer>,
+	) {
+	}
+	fn delete_framebuffer(&self, _framebuffer: Option<&Self::Framebuffer>) {}
+	fn framebuffer_renderbuffer(
+		&self,
+		_target: u32,
+		_attachment: u32,
+		_renderbuffer_target: u32,
+		_renderbuffer: Option<&Self::Renderbuffer>,
+	) {
+	}
+	fn framebuffer_texture_2d(
+		&self,
+		_target: u32,
+		_attachment: u32,
+		_texture_target: u32,
+		_texture: Option<&Self::Texture>,
+		_level: i32,
+	) {
+	}
+	fn check_framebuffer_status(&self, _target: u32) -> u32 {
+		Gl::FRAMEBUFFER_COMPLETE
+	}
+	fn create_renderbuffer(&self) -> Option<Self::Renderbuffer> {
+		Some(self.new_handle())
+	}
+	fn bind_renderbuffer(
+		&self,
+		_target: u32,
+		_renderbuffer: Option<&Self::Renderbuffer>,
+	) {
+	}
+	fn renderbuffer_storage(
+		&self,
+		_target: u32,
+		_internal_format: u32,
+		_width: i32,
+		_height: i32,
+	) {
+	}
+	fn delete_renderbuffer(&self, _renderbuffer: Option<&Self::Renderbuffer>) {}
+	fn clear_bufferuiv_with_u32_array(
+		&self,
+		_buffer: u32,
+		_draw_buffer: i32,
+		_values: &[u32],
+	) {
+	}
+	fn read_pixels_with_u32_array(
+		&self,
+		_x: i32,
+		_y: i32,
+		_width: i32,
+		_height: i32,
+		_format: u32,
+		_type_: u32,
+		data: &mut [u32],
+	) -> Result<(), &'static str> {
+		data.iter_mut().for_each(|value| *value = 0);
+		Ok(())
+	}
+
+	fn create_shader(&self, _shader_type: u32) -> Option<Self::Shader> {
+		Some(self.new_handle())
+	}
+	fn shader_source(&self, _shader: &Self::Shader, _source: &str) {}
+	fn compile_shader(&self, _shader: &Self::Shader) {}
+	fn delete_shader(&self, _shader: Option<&Self::Shader>) {}
+	fn get_shader_info_log(&self, _shader: &Self::Shader) -> Option<String> {
+		Some(String::new())
+	}
+
+	fn create_program(&self) -> Option<Self::Program> {
+		let handle = self.new_handle();
+		self.state
+			.borrow_mut()
+			.programs
+			.insert(handle, HashMap::new());
+		Some(handle)
+	}
+	fn attach_shader(&self, _program: &Self::Program, _shader: &Self::Shader) {}
+	fn link_program(&self, _program: &Self::Program) {}
+	fn validate_program(&self, _program: &Self::Program) {}
+	fn get_program_parameter_bool(
+		&self,
+		_program: &Self::Program,
+		_pname: u32,
+	) -> bool {
+		true
+	}
+	fn get_program_info_log(&self, _program: &Self::Program) -> Option<String> {
+		Some(String::new())
+	}
+	fn get_attrib_location(&self, _program: &Self::Program, name: &str) -> i32 {
+		let location = match name {
+			"POSITION" => attribute_locations::POSITION_LOCATION,
+			"NORMAL" => attribute_locations::NORMAL_LOCATION,
+			"TANGENT" => attribute_locations::TANGENT_LOCATION,
+			"BITANGENT" => attribute_locations::BITANGENT_LOCATION,
+			"COLOR" => attribute_locations::COLOR_LOCATION,
+			"TEXCOORD_0" => attribute_locations::TEXCORD_0_LOCATION,
+			"TEXCOORD_1" => attribute_locations::TEXCORD_1_LOCATION,
+			"TEXCOORD_2" => attribute_locations::TEXCORD_2_LOCATION,
+			"TEXCOORD_3" => attribute_locations::TEXCORD_3_LOCATION,
+			_ => return -1,
+		};
+		location as i32
+	}
+	fn get_uniform_location(
+		&self,
+		_program: &Self::Program,
+		name: &str,
+	) -> Option<Self::UniformLocation> {
+		Some(name.to_owned())
+	}
+	fn use_program(&self, program: Option<&Self::Program>) {
+		self.state.borrow_mut().program = program.copied();
+	}
+	fn delete_program(&self, program: Option<&Self::Program>) {
+		if let Some(program) = program {
+			let mut state = self.state.borrow_mut();
+			state.programs.remove(program);
+			if state.program == Some(*program) {
+				state.program = None;
+			}
+		}
+	}
+
+	fn uniform1i(&self, location: Option<&Self::UniformLocation>, x: i32) {
+		self.set_uniform(location, Uniform::Int(x));
+	}
+	fn uniform1ui(&self, location: Option<&Self::UniformLocation>, x: u32) {
+		self.set_uniform(location, Uniform::Int(x as i32));
+	}
+	fn uniform1f(&self, location: Option<&Self::UniformLocation>, x: f32) {
+		self.set_uniform(location, Uniform::Float(x));
+	}
+	fn uniform3f(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		x: f32,
+		y: f32,
+		z: f32,
+	) {
+		self.set_uniform(location, Uniform::Vec3([x, y, z]));
+	}
+	fn uniform4f(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		x: f32,
+		y: f32,
+		z: f32,
+		w: f32,
+	) {
+		self.set_uniform(location, Uniform::Vec4([x, y, z, w]));
+	}
+	fn uniform_matrix4fv_with_f32_array(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		_transpose: bool,
+		data: &[f32],
+	) {
+		let mut matrix = [0.0; 16];
+		matrix.copy_from_slice(&data[..16]);
+		self.set_uniform(location, Uniform::Mat4(matrix));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		env,
+		fs,
+		path::PathBuf,
+	};
+
+	use cgmath::Matrix4;
+
+	use super::*;
+	use crate::camera::Camera;
+	use crate::config::scene_config::{
+		CameraConfig,
+		ProjectionConfig,
+		UniformConfig,
+	};
+	use crate::mat_4_to_array;
+	use crate::model::{
+		material::Material,
+		mesh::{
+			generator::{
+				cube::generate_cube,
+				uv_sphere::generate_uv_sphere,
+			},
+			Mesh,
+		},
+		Drawable,
+	};
+	use crate::program::Program;
+	use crate::shader::Shader;
+
+	const SIZE: usize = 64;
+	const UNIFORMS: [&str; 4] = [
+		"PROJECTION_MATRIX",
+		"VIEW_MATRIX",
+		"MODEL_MATRIX",
+		"BASE_COLOR_FACTOR",
+	];
+
+	fn setup() -> (SoftwareBackend, Program<SoftwareBackend>) {
+		let gl = SoftwareBackend::new(SIZE, SIZE);
+		gl.clear_color(0.2, 0.2, 0.2, 1.0);
+		gl.front_face(Gl::CCW);
+		gl.cull_face(Gl::BACK);
+		gl.depth_func(Gl::LESS);
+		gl.enable(Gl::DEPTH_TEST);
+		gl.clear(Gl::COLOR_BUFFER_BIT | Gl::DEPTH_BUFFER_BIT);
+
+		let uniforms: Vec<UniformConfig> = UNIFORMS
+			.iter()
+			.map(|name| UniformConfig::new(name))
+			.collect();
+		let vert =
+			Shader::new(&gl, Gl::VERTEX_SHADER, "", &[], &uniforms).unwrap();
+		let frag = Shader::new(&gl, Gl::FRAGMENT_SHADER, "", &[], &[]).unwrap();
+		let (program, _) = Program::new(&gl, &vert, &frag).unwrap();
+		gl.use_program(Some(&program.program));
+		(gl, program)
+	}
+	fn set_matrix(
+		gl: &SoftwareBackend,
+		program: &Program<SoftwareBackend>,
+		name: &str,
+		matrix: &Matrix4<f32>,
+	) {
+		gl.uniform_matrix4fv_with_f32_array(
+			program.uniform_locations.get(name),
+			false,
+			&mat_4_to_array(matrix),
+		);
+	}
+	fn set_camera(gl: &SoftwareBackend, program: &Program<SoftwareBackend>) {
+		let config = CameraConfig {
+			id: String::from("main"),
+			projection: ProjectionConfig::Perspective,
+			fov_y: 45.0,
+			z_near: 0.1,
+			z_far: 100.0,
+			eye: [1.6, 1.2, 2.4],
+			center: [0.0, 0.0, 0.0],
+			up: [0.0, 1.0, 0.0],
+		};
+		let camera = Camera::new(&config, SIZE as u32, SIZE as u32);
+		set_matrix(
+			gl,
+			program,
+			"PROJECTION_MATRIX",
+			&camera.projection_matrix(),
+		);
+		set_matrix(gl, program, "VIEW_MATRIX", &camera.view_matrix());
+		set_matrix(gl, program, "MODEL_MATRIX", &Matrix4::identity());
+	}
+	// Draws a triangle given in normalized device coordinates.
+	fn draw_triangle(gl: &SoftwareBackend, positions: [[f32; 3]; 3]) {
+		let data: Vec<u8> = positions
+			.iter()
+			.flatten()
+			.flat_map(|f| f.to_le_bytes())
+			.collect();
+		let buffer = gl.create_buffer().unwrap();
+		gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&buffer));
+		gl.buffer_data_with_u8_array(Gl::ARRAY_BUFFER, &data, Gl::STATIC_DRAW);
+		gl.vertex_attrib_pointer_with_i32(0, 3, Gl::FLOAT, false, 0, 0);
+		gl.enable_vertex_attrib_array(0);
+		gl.draw_arrays(Gl::TRIANGLES, 0, 3);
+	}
+	fn center(gl: &SoftwareBackend) -> [u8; 4] {
+		gl.pixel(SIZE / 2, SIZE / 2)
+	}
+
+	fn read_ppm(data: &[u8]) -> (usize, usize, &[u8]) {
+		// Header is "P6\n<width> <height>\n255\n".
+		let mut fields = Vec::new();
+		let mut start = 0;
+		for (i, byte) in data.iter().enumerate() {
+			if byte.is_ascii_whitespace() {
+				if i > start {
+					fields.push(std::str::from_utf8(&data[start..i]).unwrap());
+				}
+				start = i + 1;
+				if fields.len() == 4 {
+					break;
+				}
+			}
+		}
+		assert_eq!(fields[0], "P6");
+		let width = fields[1].parse().unwrap();
+		let height = fields[2].parse().unwrap();
+		(width, height, &data[start..])
+	}
+	// Compares against tests/golden/<name>.ppm. With BLESS=1 in the
+	// environment the file is written instead, a missing golden fails
+	// otherwise. Channels may differ by `tolerance` to absorb float
+	// differences between platforms.
+	fn assert_golden(gl: &SoftwareBackend, name: &str, tolerance: u8) {
+		let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+			.join("tests")
+			.join("golden")
+			.join(format!("{}.ppm", name));
+		let image = gl.to_ppm();
+		if env::var("BLESS").as_deref() == Ok("1") {
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(&path, &image).unwrap();
+			return;
+		}
+		let golden = fs::read(&path).unwrap_or_else(|_| {
+			panic!("{:?} is missing, run with BLESS=1 to write it", path)
+		});
+		let (width, height, expected) = read_ppm(&golden);
+		let (_, _, actual) = read_ppm(&image);
+		assert_eq!((width, height), (gl.width(), gl.height()), "{}", name);
+		let mismatched = expected
+			.iter()
+			.zip(actual.iter())
+			.filter(|(e, a)| (**e as i16 - **a as i16).abs() > tolerance as i16)
+			.count();
+		assert_eq!(mismatched, 0, "{} differs from {:?}", name, path);
+	}
+	fn render_golden(
+		name: &str,
+		mesh: impl Fn(&SoftwareBackend) -> Rc<Mesh<SoftwareBackend>>,
+	) {
+		let (gl, program) = setup();
+		set_camera(&gl, &program);
+		mesh(&gl).draw(&gl, &program);
+		assert_golden(&gl, name, 2);
+	}
+
+	#[test]
+	fn clear_fills_color() {
+		let (gl, _) = setup();
+		assert_eq!(center(&gl), [51, 51, 51, 255]);
+	}
+	#[test]
+	fn front_facing_triangle_is_drawn_and_back_facing_is_culled() {
+		let (gl, _) = setup();
+		gl.enable(Gl::CULL_FACE);
+		draw_triangle(
+			&gl,
+			[[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [0.0, 1.0, 0.0]],
+		);
+		assert_ne!(center(&gl), [51, 51, 51, 255]);
+
+		gl.clear(Gl::COLOR_BUFFER_BIT | Gl::DEPTH_BUFFER_BIT);
+		draw_triangle(
+			&gl,
+			[[-1.0, -1.0, 0.0], [0.0, 1.0, 0.0], [1.0, -1.0, 0.0]],
+		);
+		assert_eq!(center(&gl), [51, 51, 51, 255]);
+
+		gl.disable(Gl::CULL_FACE);
+		draw_triangle(
+			&gl,
+			[[-1.0, -1.0, 0.0], [0.0, 1.0, 0.0], [1.0, -1.0, 0.0]],
+		);
+		assert_ne!(center(&gl), [51, 51, 51, 255]);
+	}
+	#[test]
+	fn depth_test_keeps_nearest_fragment() {
+		let (gl, _) = setup();
+		let far = [[-1.0, -1.0, 0.5], [1.0, -1.0, 0.5], [0.0, 1.0, 0.5]];
+		let near = [[-1.0, -1.0, -0.5], [1.0, -1.0, -0.5], [0.0, 1.0, -0.5]];
+		// Color the near triangle differently by lighting it from behind.
+		draw_triangle(&gl, near);
+		let near_color = center(&gl);
+		gl.set_light_direction(Vector3::new(0.0, 0.0, -1.0));
+		draw_triangle(&gl, far);
+		assert_eq!(center(&gl), near_color);
+
+		gl.clear(Gl::COLOR_BUFFER_BIT | Gl::DEPTH_BUFFER_BIT);
+		draw_triangle(&gl, far);
+		let far_color = center(&gl);
+		assert_ne!(far_color, near_color);
+		gl.set_light_direction(Vector3::new(0.4, 1.0, 0.6));
+		draw_triangle(&gl, near);
+		assert_eq!(center(&gl), near_color);
+	}
+	#[test]
+	fn depth_writes_can_be_masked() {
+		let (gl, _) = setup();
+		let far = [[-1.0, -1.0, 0.5], [1.0, -1.0, 0.5], [0.0, 1.0, 0.5]];
+		let near = [[-1.0, -1.0, -0.5], [1.0, -1.0, -0.5], [0.0, 1.0, -0.5]];
+		draw_triangle(&gl, far);
+		let far_color = center(&gl);
+		gl.clear(Gl::COLOR_BUFFER_BIT | Gl::DEPTH_BUFFER_BIT);
+
+		gl.set_light_direction(Vector3::new(0.0, 0.0, -1.0));
+		gl.depth_mask(false);
+		draw_triangle(&gl, near);
+		assert_ne!(center(&gl), far_color);
+
+		// The near triangle was drawn but left no depth behind.
+		gl.depth_mask(true);
+		gl.set_light_direction(Vector3::new(0.4, 1.0, 0.6));
+		draw_triangle(&gl, far);
+		assert_eq!(center(&gl), far_color);
+	}
+	#[test]
+	fn cube_matches_golden() {
+		render_golden("cube", generate_cube);
+	}
+	#[test]
+	fn uv_sphere_matches_golden() {
+		render_golden("uv_sphere", |gl| generate_uv_sphere(gl, 0.8, 12, 16));
+	}
+	#[test]
+	fn material_color_matches_golden() {
+		render_golden("orange_cube", |gl| {
+			let mut cube = (*generate_cube(gl)).clone();
+			cube.material = Rc::new(Material {
+				base_color_factor: Vector4::new(1.0, 0.5, 0.1, 1.0),
+				..(*cube.material).clone()
+			});
+			Rc::new(cube)
+		});
+	}
+}
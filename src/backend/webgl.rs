@@ -0,0 +1,468 @@
+// File: src/backend/webgl.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// GlBackend for the browser's WebGL2 context. Every method forwards to the
+// web_sys method of the same name.
+
+use web_sys::{
+	HtmlImageElement,
+	WebGl2RenderingContext,
+	WebGlBuffer,
+	WebGlFramebuffer,
+	WebGlProgram,
+	WebGlRenderbuffer,
+	WebGlSampler,
+	WebGlShader,
+	WebGlTexture,
+	WebGlUniformLocation,
+	WebGlVertexArrayObject,
+};
+
+use super::GlBackend;
+
+type Gl = WebGl2RenderingContext;
+
+impl GlBackend for WebGl2RenderingContext {
+	type Buffer = WebGlBuffer;
+	type Texture = WebGlTexture;
+	type Shader = WebGlShader;
+	type Program = WebGlProgram;
+	type VertexArray = WebGlVertexArrayObject;
+	type UniformLocation = WebGlUniformLocation;
+	type Framebuffer = WebGlFramebuffer;
+	type Renderbuffer = WebGlRenderbuffer;
+	type Sampler = WebGlSampler;
+
+	fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+		Gl::clear_color(self, red, green, blue, alpha);
+	}
+	fn clear(&self, mask: u32) {
+		Gl::clear(self, mask);
+	}
+	fn enable(&self, cap: u32) {
+		Gl::enable(self, cap);
+	}
+	fn disable(&self, cap: u32) {
+		Gl::disable(self, cap);
+	}
+	fn front_face(&self, mode: u32) {
+		Gl::front_face(self, mode);
+	}
+	fn cull_face(&self, mode: u32) {
+		Gl::cull_face(self, mode);
+	}
+	fn depth_func(&self, func: u32) {
+		Gl::depth_func(self, func);
+	}
+	fn depth_mask(&self, flag: bool) {
+		Gl::depth_mask(self, flag);
+	}
+	fn blend_func(&self, sfactor: u32, dfactor: u32) {
+		Gl::blend_func(self, sfactor, dfactor);
+	}
+	fn blend_func_separate(
+		&self,
+		src_rgb: u32,
+		dst_rgb: u32,
+		src_alpha: u32,
+		dst_alpha: u32,
+	) {
+		Gl::blend_func_separate(self, src_rgb, dst_rgb, src_alpha, dst_alpha);
+	}
+	fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+		Gl::viewport(self, x, y, width, height);
+	}
+
+	fn create_buffer(&self) -> Option<Self::Buffer> {
+		Gl::create_buffer(self)
+	}
+	fn bind_buffer(&self, target: u32, buffer: Option<&Self::Buffer>) {
+		Gl::bind_buffer(self, target, buffer);
+	}
+	fn buffer_data_with_u8_array(&self, target: u32, data: &[u8], usage: u32) {
+		// The view is only alive for the duration of the call and no
+		// allocation happens in between, so wasm memory can not move.
+		unsafe {
+			let array = js_sys::Uint8Array::view(data);
+			Gl::buffer_data_with_array_buffer_view(self, target, &array, usage);
+		}
+	}
+	fn delete_buffer(&self, buffer: Option<&Self::Buffer>) {
+		Gl::delete_buffer(self, buffer);
+	}
+
+	fn create_vertex_array(&self) -> Option<Self::VertexArray> {
+		Gl::create_vertex_array(self)
+	}
+	fn bind_vertex_array(&self, vertex_array: Option<&Self::VertexArray>) {
+		Gl::bind_vertex_array(self, vertex_array);
+	}
+	fn delete_vertex_array(&self, vertex_array: Option<&Self::VertexArray>) {
+		Gl::delete_vertex_array(self, vertex_array);
+	}
+	fn vertex_attrib_pointer_with_i32(
+		&self,
+		index: u32,
+		size: i32,
+		type_: u32,
+		normalized: bool,
+		stride: i32,
+		offset: i32,
+	) {
+		Gl::vertex_attrib_pointer_with_i32(
+			self, index, size, type_, normalized, stride, offset,
+		);
+	}
+	fn enable_vertex_attrib_array(&self, index: u32) {
+		Gl::enable_vertex_attrib_array(self, index);
+	}
+
+	fn draw_arrays(&self, mode: u32, first: i32, count: i32) {
+		Gl::draw_arrays(self, mode, first, count);
+	}
+	fn draw_elements_with_i32(
+		&self,
+		mode: u32,
+		count: i32,
+		type_: u32,
+		offset: i32,
+	) {
+		Gl::draw_elements_with_i32(self, mode, count, type_, offset);
+	}
+
+	fn create_texture(&self) -> Option<Self::Texture> {
+		Gl::create_texture(self)
+	}
+	fn delete_texture(&self, texture: Option<&Self::Texture>) {
+		Gl::delete_texture(self, texture);
+	}
+	fn active_texture(&self, texture: u32) {
+		Gl::active_texture(self, texture);
+	}
+	fn bind_texture(&self, target: u32, texture: Option<&Self::Texture>) {
+		Gl::bind_texture(self, target, texture);
+	}
+	fn tex_parameteri(&self, target: u32, pname: u32, param: i32) {
+		Gl::tex_parameteri(self, target, pname, param);
+	}
+	fn pixel_storei(&self, pname: u32, param: i32) {
+		Gl::pixel_storei(self, pname, param);
+	}
+	fn tex_image_2d_with_html_image_element(
+		&self,
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		format: u32,
+		type_: u32,
+		image: &HtmlImageElement,
+	) -> Result<(), &'static str> {
+		Gl::tex_image_2d_with_u32_and_u32_and_html_image_element(
+			self,
+			target,
+			level,
+			internal_format,
+			format,
+			type_,
+			image,
+		)
+		.map_err(|_e| "failed to create glTexture from image")
+	}
+	fn tex_image_2d_with_opt_u8_array(
+		&self,
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		pixels: Option<&[u8]>,
+	) -> Result<(), &'static str> {
+		Gl::tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+			self,
+			target,
+			level,
+			internal_format,
+			width,
+			height,
+			0,
+			format,
+			type_,
+			pixels,
+		)
+		.map_err(|_e| "failed to create glTexture from pixels")
+	}
+	fn tex_image_2d_with_f32_array(
+		&self,
+		target: u32,
+		level: i32,
+		internal_format: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		pixels: &[f32],
+	) -> Result<(), &'static str> {
+		// As in buffer_data_with_u8_array, wasm memory can't move while
+		// the view is alive.
+		unsafe {
+			let array = js_sys::Float32Array::view(pixels);
+			Gl::tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+				self,
+				target,
+				level,
+				internal_format,
+				width,
+				height,
+				0,
+				format,
+				type_,
+				Some(&array),
+			)
+		}
+		.map_err(|_e| "failed to create glTexture from pixels")
+	}
+	fn generate_mipmap(&self, target: u32) {
+		Gl::generate_mipmap(self, target);
+	}
+
+	fn create_sampler(&self) -> Option<Self::Sampler> {
+		Gl::create_sampler(self)
+	}
+	fn bind_sampler(&self, unit: u32, sampler: Option<&Self::Sampler>) {
+		Gl::bind_sampler(self, unit, sampler);
+	}
+	fn sampler_parameteri(
+		&self,
+		sampler: &Self::Sampler,
+		pname: u32,
+		param: i32,
+	) {
+		Gl::sampler_parameteri(self, sampler, pname, param);
+	}
+	fn sampler_parameterf(
+		&self,
+		sampler: &Self::Sampler,
+		pname: u32,
+		param: f32,
+	) {
+		Gl::sampler_parameterf(self, sampler, pname, param);
+	}
+	fn delete_sampler(&self, sampler: Option<&Self::Sampler>) {
+		Gl::delete_sampler(self, sampler);
+	}
+
+	fn get_extension(&self, name: &str) -> bool {
+		matches!(Gl::get_extension(self, name), Ok(Some(_)))
+	}
+	fn get_parameter_f32(&self, pname: u32) -> Option<f32> {
+		Gl::get_parameter(self, pname)
+			.ok()?
+			.as_f64()
+			.map(|value| value as f32)
+	}
+
+	fn create_framebuffer(&self) -> Option<Self::Framebuffer> {
+		Gl::create_framebuffer(self)
+	}
+	fn bind_framebuffer(
+		&self,
+		target: u32,
+		framebuffer: Option<&Self::Framebuffer>,
+	) {
+		Gl::bind_framebuffer(self, target, framebuffer);
+	}
+	fn delete_framebuffer(&self, framebuffer: Option<&Self::Framebuffer>) {
+		Gl::delete_framebuffer(self, framebuffer);
+	}
+	fn framebuffer_renderbuffer(
+		&self,
+		target: u32,
+		attachment: u32,
+		renderbuffer_target: u32,
+		renderbuffer: Option<&Self::Renderbuffer>,
+	) {
+		Gl::framebuffer_renderbuffer(
+			self,
+			target,
+			attachment,
+			renderbuffer_target,
+			renderbuffer,
+		);
+	}
+	fn framebuffer_texture_2d(
+		&self,
+		target: u32,
+		attachment: u32,
+		texture_target: u32,
+		texture: Option<&Self::Texture>,
+		level: i32,
+	) {
+		Gl::framebuffer_texture_2d(
+			self,
+			target,
+			attachment,
+			texture_target,
+			texture,
+			level,
+		);
+	}
+	fn check_framebuffer_status(&self, target: u32) -> u32 {
+		Gl::check_framebuffer_status(self, target)
+	}
+	fn create_renderbuffer(&self) -> Option<Self::Renderbuffer> {
+		Gl::create_renderbuffer(self)
+	}
+	fn bind_renderbuffer(
+		&self,
+		target: u32,
+		renderbuffer: Option<&Self::Renderbuffer>,
+	) {
+		Gl::bind_renderbuffer(self, target, renderbuffer);
+	}
+	fn renderbuffer_storage(
+		&self,
+		target: u32,
+		internal_format: u32,
+		width: i32,
+		height: i32,
+	) {
+		Gl::renderbuffer_storage(self, target, internal_format, width, height);
+	}
+	fn delete_renderbuffer(&self, renderbuffer: Option<&Self::Renderbuffer>) {
+		Gl::delete_renderbuffer(self, renderbuffer);
+	}
+	fn clear_bufferuiv_with_u32_array(
+		&self,
+		buffer: u32,
+		draw_buffer: i32,
+		values: &[u32],
+	) {
+		Gl::clear_bufferuiv_with_u32_array(self, buffer, draw_buffer, values);
+	}
+	fn read_pixels_with_u32_array(
+		&self,
+		x: i32,
+		y: i32,
+		width: i32,
+		height: i32,
+		format: u32,
+		type_: u32,
+		data: &mut [u32],
+	) -> Result<(), &'static str> {
+		// WebGL wants a Uint32Array for UNSIGNED_INT, which is read into a
+		// JS owned array and copied out.
+		let array = js_sys::Uint32Array::new_with_length(data.len() as u32);
+		Gl::read_pixels_with_opt_array_buffer_view(
+			self,
+			x,
+			y,
+			width,
+			height,
+			format,
+			type_,
+			Some(&array),
+		)
+		.map_err(|_| "Failed to read pixels")?;
+		array.copy_to(data);
+		Ok(())
+	}
+
+	fn create_shader(&self, shader_type: u32) -> Option<Self::Shader> {
+		Gl::create_shader(self, shader_type)
+	}
+	fn shader_source(&self, shader: &Self::Shader, source: &str) {
+		Gl::shader_source(self, shader, source);
+	}
+	fn compile_shader(&self, shader: &Self::Shader) {
+		Gl::compile_shader(self, shader);
+	}
+	fn delete_shader(&self, shader: Option<&Self::Shader>) {
+		Gl::delete_shader(self, shader);
+	}
+	fn get_shader_info_log(&self, shader: &Self::Shader) -> Option<String> {
+		Gl::get_shader_info_log(self, shader)
+	}
+
+	fn create_program(&self) -> Option<Self::Program> {
+		Gl::create_program(self)
+	}
+	fn attach_shader(&self, program: &Self::Program, shader: &Self::Shader) {
+		Gl::attach_shader(self, program, shader);
+	}
+	fn link_program(&self, program: &Self::Program) {
+		Gl::link_program(self, program);
+	}
+	fn validate_program(&self, program: &Self::Program) {
+		Gl::validate_program(self, program);
+	}
+	fn get_program_parameter_bool(
+		&self,
+		program: &Self::Program,
+		pname: u32,
+	) -> bool {
+		Gl::get_program_parameter(self, program, pname)
+			.as_bool()
+			.unwrap_or(false)
+	}
+	fn get_program_info_log(&self, program: &Self::Program) -> Option<String> {
+		Gl::get_program_info_log(self, program)
+	}
+	fn get_attrib_location(&self, program: &Self::Program, name: &str) -> i32 {
+		Gl::get_attrib_location(self, program, name)
+	}
+	fn get_uniform_location(
+		&self,
+		program: &Self::Program,
+		name: &str,
+	) -> Option<Self::UniformLocation> {
+		Gl::get_uniform_location(self, program, name)
+	}
+	fn use_program(&self, program: Option<&Self::Program>) {
+		Gl::use_program(self, program);
+	}
+	fn delete_program(&self, program: Option<&Self::Program>) {
+		Gl::delete_program(self, program);
+	}
+
+	fn uniform1i(&self, location: Option<&Self::UniformLocation>, x: i32) {
+		Gl::uniform1i(self, location, x);
+	}
+	fn uniform1ui(&self, location: Option<&Self::UniformLocation>, x: u32) {
+		Gl::uniform1ui(self, location, x);
+	}
+	fn uniform1f(&self, location: Option<&Self::UniformLocation>, x: f32) {
+		Gl::uniform1f(self, location, x);
+	}
+	fn uniform3f(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		x: f32,
+		y: f32,
+		z: f32,
+	) {
+		Gl::uniform3f(self, location, x, y, z);
+	}
+	fn uniform4f(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		x: f32,
+		y: f32,
+		z: f32,
+		w: f32,
+	) {
+		Gl::uniform4f(self, location, x, y, z, w);
+	}
+	fn uniform_matrix4fv_with_f32_array(
+		&self,
+		location: Option<&Self::UniformLocation>,
+		transpose: bool,
+		data: &[f32],
+	) {
+		Gl::uniform_matrix4fv_with_f32_array(self, location, transpose, data);
+	}
+}
@@ -0,0 +1,216 @@
+// File: src/bounds.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Axis aligned bounding boxes and view frustums for skipping draws that
+// can't be seen.
+
+use cgmath::{
+	EuclideanSpace,
+	InnerSpace,
+	Matrix,
+	Matrix4,
+	Point3,
+	Vector3,
+	Vector4,
+};
+
+use crate::model::{
+	buffer_view::BufferView,
+	mesh::geometry::read_positions,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+	pub min: Point3<f32>,
+	pub max: Point3<f32>,
+}
+impl Aabb {
+	pub fn new(min: Point3<f32>, max: Point3<f32>) -> Self {
+		Self { min, max }
+	}
+	pub fn from_points(
+		points: impl IntoIterator<Item = Point3<f32>>,
+	) -> Option<Self> {
+		let mut points = points.into_iter();
+		let first = points.next()?;
+		Some(points.fold(Self::new(first, first), |aabb, point| {
+			aabb.union(&Self::new(point, point))
+		}))
+	}
+	// From a glTF accessor's min and max, which need at least x, y and z.
+	pub fn from_min_max(min: &[f32], max: &[f32]) -> Option<Self> {
+		match (min, max) {
+			([min_x, min_y, min_z, ..], [max_x, max_y, max_z, ..]) => {
+				Some(Self::new(
+					Point3::new(*min_x, *min_y, *min_z),
+					Point3::new(*max_x, *max_y, *max_z),
+				))
+			}
+			_ => None,
+		}
+	}
+	// Reads the positions `view` describes out of the buffer they were
+	// uploaded from.
+	pub fn from_position_data(data: &[u8], view: &BufferView) -> Option<Self> {
+		Self::from_points(read_positions(data, view)?)
+	}
+
+	pub fn union(&self, other: &Self) -> Self {
+		Self::new(
+			Point3::new(
+				self.min.x.min(other.min.x),
+				self.min.y.min(other.min.y),
+				self.min.z.min(other.min.z),
+			),
+			Point3::new(
+				self.max.x.max(other.max.x),
+				self.max.y.max(other.max.y),
+				self.max.z.max(other.max.z),
+			),
+		)
+	}
+	pub fn center(&self) -> Point3<f32> {
+		self.min.midpoint(self.max)
+	}
+	pub fn half_extents(&self) -> Vector3<f32> {
+		(self.max - self.min) * 0.5
+	}
+	// The box around this one after `matrix` is applied to it.
+	pub fn transformed(&self, matrix: &Matrix4<f32>) -> Self {
+		let center = matrix * self.center().to_homogeneous();
+		let center = Point3::from_homogeneous(center);
+		let half = self.half_extents();
+		let abs = |column: Vector4<f32>| {
+			Vector3::new(column.x.abs(), column.y.abs(), column.z.abs())
+		};
+		let half = abs(matrix.x) * half.x
+			+ abs(matrix.y) * half.y
+			+ abs(matrix.z) * half.z;
+		Self::new(center - half, center + half)
+	}
+}
+
+// Six planes facing inward, each as the a, b, c and d of ax + by + cz + d.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frustum {
+	pub planes: [Vector4<f32>; 6],
+}
+impl Frustum {
+	// Extracts the planes from a projection times view matrix, the world
+	// space frustum that matrix maps to clip space.
+	pub fn from_matrix(matrix: &Matrix4<f32>) -> Self {
+		let row = |i| matrix.row(i);
+		let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+		Self {
+			planes: [w + x, w - x, w + y, w - y, w + z, w - z],
+		}
+	}
+	// Conservative, a box near a corner of the frustum can pass while
+	// being just outside.
+	pub fn intersects(&self, aabb: &Aabb) -> bool {
+		self.planes.iter().all(|plane| {
+			// The corner furthest along the plane's normal.
+			let corner = Vector4::new(
+				if plane.x >= 0.0 {
+					aabb.max.x
+				} else {
+					aabb.min.x
+				},
+				if plane.y >= 0.0 {
+					aabb.max.y
+				} else {
+					aabb.min.y
+				},
+				if plane.z >= 0.0 {
+					aabb.max.z
+				} else {
+					aabb.min.z
+				},
+				1.0,
+			);
+			plane.dot(corner) >= 0.0
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::scene_config::BufferViewConfig;
+	use cgmath::{
+		assert_abs_diff_eq,
+		Deg,
+		Matrix4,
+		SquareMatrix,
+	};
+
+	fn unit_box() -> Aabb {
+		Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0))
+	}
+	fn frustum() -> Frustum {
+		let projection = cgmath::perspective(Deg(90.0), 1.0, 0.1, 100.0);
+		let view = Matrix4::look_at_rh(
+			Point3::new(0.0, 0.0, 10.0),
+			Point3::new(0.0, 0.0, 0.0),
+			Vector3::unit_y(),
+		);
+		Frustum::from_matrix(&(projection * view))
+	}
+	fn translated(x: f32, y: f32, z: f32) -> Aabb {
+		unit_box()
+			.transformed(&Matrix4::from_translation(Vector3::new(x, y, z)))
+	}
+
+	#[test]
+	fn reads_strided_positions() {
+		let mut data = vec![0u8; 4];
+		for position in [[1.0f32, -2.0, 3.0], [-4.0, 5.0, 0.5]] {
+			for value in position.iter() {
+				data.extend_from_slice(&value.to_le_bytes());
+			}
+			data.extend_from_slice(&[0u8; 4]);
+		}
+		let view = BufferView::new(&BufferViewConfig {
+			length: 32,
+			buffer_offset: Some(4),
+			stride: Some(16),
+			..BufferViewConfig::test_positions(2)
+		});
+		assert_eq!(
+			Aabb::from_position_data(&data, &view),
+			Some(Aabb::new(
+				Point3::new(-4.0, -2.0, 0.5),
+				Point3::new(1.0, 5.0, 3.0)
+			))
+		);
+		assert_eq!(Aabb::from_position_data(&data[..20], &view), None);
+	}
+	#[test]
+	fn transformed_box_contains_the_rotated_corners() {
+		let rotation = Matrix4::from_angle_z(Deg(45.0));
+		let aabb = unit_box().transformed(
+			&(Matrix4::from_translation(Vector3::new(5.0, 0.0, 0.0))
+				* rotation),
+		);
+		let reach = 2.0f32.sqrt();
+		assert_abs_diff_eq!(aabb.min, Point3::new(5.0 - reach, -reach, -1.0));
+		assert_abs_diff_eq!(aabb.max, Point3::new(5.0 + reach, reach, 1.0));
+		assert_eq!(unit_box().transformed(&Matrix4::identity()), unit_box());
+	}
+	#[test]
+	fn frustum_keeps_visible_boxes_and_culls_the_rest() {
+		let frustum = frustum();
+		assert!(frustum.intersects(&unit_box()));
+		// Straddling the left plane.
+		assert!(frustum.intersects(&translated(-10.5, 0.0, 0.0)));
+
+		assert!(!frustum.intersects(&translated(-13.0, 0.0, 0.0)));
+		assert!(!frustum.intersects(&translated(0.0, 13.0, 0.0)));
+		// Behind the camera and past the far plane.
+		assert!(!frustum.intersects(&translated(0.0, 0.0, 12.0)));
+		assert!(!frustum.intersects(&translated(0.0, 0.0, -95.0)));
+	}
+}
@@ -0,0 +1,264 @@
+// File: src/camera/fly.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// First person camera. WASD or the arrow keys move, E and Q rise and sink,
+// shift moves faster and dragging with the left button looks around. Only
+// the camera's position and orientation are driven, eye, center and up
+// follow from them.
+
+use std::f32::consts::FRAC_PI_2;
+
+use cgmath::{
+	InnerSpace,
+	Point3,
+	Quaternion,
+	Rad,
+	Rotation3,
+	Vector3,
+	Zero,
+};
+
+use super::Camera;
+use crate::input::{
+	keyboard::{
+		KeyState,
+		KeyboardEvent,
+		KeyboardEventSubscriber,
+	},
+	mouse::{
+		MouseEvent,
+		MouseEventSubscriber,
+	},
+};
+
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+const FORWARD_KEYS: &[&str] = &["KeyW", "ArrowUp"];
+const BACK_KEYS: &[&str] = &["KeyS", "ArrowDown"];
+const LEFT_KEYS: &[&str] = &["KeyA", "ArrowLeft"];
+const RIGHT_KEYS: &[&str] = &["KeyD", "ArrowRight"];
+const UP_KEYS: &[&str] = &["KeyE"];
+const DOWN_KEYS: &[&str] = &["KeyQ"];
+const FAST_KEYS: &[&str] = &["ShiftLeft", "ShiftRight"];
+
+#[derive(Clone, Debug)]
+pub struct FlyController {
+	pub position: Point3<f32>,
+	// Radians around the world's y axis, zero looks down -z.
+	pub yaw: f32,
+	// Radians above the xz plane.
+	pub pitch: f32,
+
+	// World units per second.
+	pub speed: f32,
+	pub fast_multiplier: f32,
+	// Radians per pixel dragged.
+	pub look_speed: f32,
+
+	keys: KeyState,
+	looking: bool,
+	last_position: (i32, i32),
+	changed: bool,
+}
+impl FlyController {
+	pub fn new(camera: &Camera) -> Self {
+		let mut controller = Self {
+			position: camera.position,
+			yaw: 0.0,
+			pitch: 0.0,
+			speed: 5.0,
+			fast_multiplier: 4.0,
+			look_speed: 0.005,
+			keys: KeyState::default(),
+			looking: false,
+			last_position: (0, 0),
+			changed: false,
+		};
+		controller.sync(camera);
+		controller
+	}
+	// Picks up a camera that was moved or switched to by something else.
+	pub fn sync(&mut self, camera: &Camera) {
+		let forward = camera.forward();
+		self.position = camera.position;
+		self.pitch = forward
+			.y
+			.clamp(-1.0, 1.0)
+			.asin()
+			.clamp(-MAX_PITCH, MAX_PITCH);
+		self.yaw = (-forward.x).atan2(-forward.z);
+		self.looking = false;
+		self.changed = false;
+	}
+
+	pub fn orientation(&self) -> Quaternion<f32> {
+		Quaternion::from_angle_y(Rad(self.yaw))
+			* Quaternion::from_angle_x(Rad(self.pitch))
+	}
+	pub fn look(&mut self, dx: f32, dy: f32) {
+		self.yaw -= dx * self.look_speed;
+		self.pitch =
+			(self.pitch - dy * self.look_speed).clamp(-MAX_PITCH, MAX_PITCH);
+		self.changed = true;
+	}
+	// Moves along the view direction, the camera's right and the world's
+	// up by the given amounts in world units.
+	pub fn translate(&mut self, forward: f32, right: f32, up: f32) {
+		let orientation = self.orientation();
+		self.position += orientation * -Vector3::unit_z() * forward
+			+ orientation * Vector3::unit_x() * right
+			+ Vector3::unit_y() * up;
+		self.changed = true;
+	}
+
+	// Applies held movement keys over `seconds` and writes the result into
+	// the camera if anything moved. Returns whether it did.
+	pub fn update(&mut self, camera: &mut Camera, seconds: f32) -> bool {
+		let keys = &self.keys;
+		let axis = |positive: &[&str], negative: &[&str]| {
+			keys.any_down(positive) as i32 as f32
+				- keys.any_down(negative) as i32 as f32
+		};
+		let direction = Vector3::new(
+			axis(RIGHT_KEYS, LEFT_KEYS),
+			axis(UP_KEYS, DOWN_KEYS),
+			axis(FORWARD_KEYS, BACK_KEYS),
+		);
+		if !direction.is_zero() {
+			let speed = if keys.any_down(FAST_KEYS) {
+				self.speed * self.fast_multiplier
+			} else {
+				self.speed
+			};
+			let step = direction.normalize() * speed * seconds;
+			self.translate(step.z, step.x, step.y);
+		}
+
+		if !self.changed {
+			return false;
+		}
+		camera.set_pose(self.position, self.orientation());
+		self.changed = false;
+		true
+	}
+}
+impl KeyboardEventSubscriber for FlyController {
+	fn notify_key_down(&mut self, event: &KeyboardEvent) {
+		self.keys.press(&event.code);
+	}
+	fn notify_key_up(&mut self, event: &KeyboardEvent) {
+		self.keys.release(&event.code);
+	}
+	fn bound_keys(&self) -> Vec<&'static str> {
+		[
+			FORWARD_KEYS,
+			BACK_KEYS,
+			LEFT_KEYS,
+			RIGHT_KEYS,
+			UP_KEYS,
+			DOWN_KEYS,
+			FAST_KEYS,
+		]
+		.concat()
+	}
+}
+impl MouseEventSubscriber for FlyController {
+	fn notify_down(&mut self, event: &MouseEvent) {
+		self.looking = event.left_btn;
+		self.last_position = (event.x, event.y);
+	}
+	fn notify_up(&mut self, event: &MouseEvent) {
+		self.looking = event.left_btn;
+	}
+	fn notify_move(&mut self, event: &MouseEvent) {
+		self.looking &= event.left_btn;
+		let dx = (event.x - self.last_position.0) as f32;
+		let dy = (event.y - self.last_position.1) as f32;
+		self.last_position = (event.x, event.y);
+		if self.looking {
+			self.look(dx, dy);
+		}
+	}
+	fn notify_leave(&mut self, _event: &MouseEvent) {
+		self.looking = false;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cgmath::assert_abs_diff_eq;
+
+	fn camera() -> Camera {
+		Camera::test_camera(800, 600)
+	}
+	fn key(code: &str) -> KeyboardEvent {
+		KeyboardEvent {
+			code: String::from(code),
+			..KeyboardEvent::default()
+		}
+	}
+
+	#[test]
+	fn camera_orientation_matches_its_look_at() {
+		let camera = camera();
+		assert_abs_diff_eq!(
+			camera.forward(),
+			Vector3::new(0.0, 0.0, -1.0),
+			epsilon = 1e-6
+		);
+		assert_abs_diff_eq!(
+			camera.right(),
+			Vector3::new(1.0, 0.0, 0.0),
+			epsilon = 1e-6
+		);
+	}
+	#[test]
+	fn held_keys_move_at_speed() {
+		let mut camera = camera();
+		let mut fly = FlyController::new(&camera);
+		assert!(!fly.update(&mut camera, 1.0));
+
+		fly.notify_key_down(&key("KeyW"));
+		assert!(fly.update(&mut camera, 0.5));
+		assert_abs_diff_eq!(
+			camera.position,
+			Point3::new(0.0, 0.0, 7.5),
+			epsilon = 1e-5
+		);
+
+		fly.notify_key_up(&key("KeyW"));
+		fly.notify_key_down(&key("KeyD"));
+		fly.notify_key_down(&key("ShiftLeft"));
+		fly.update(&mut camera, 0.1);
+		assert_abs_diff_eq!(camera.position.x, 2.0, epsilon = 1e-5);
+		assert_abs_diff_eq!(camera.eye, camera.position);
+	}
+	#[test]
+	fn dragging_turns_the_view() {
+		let mut camera = camera();
+		let mut fly = FlyController::new(&camera);
+		let drag = |x| MouseEvent {
+			x,
+			left_btn: true,
+			..MouseEvent::default()
+		};
+		fly.notify_down(&drag(0));
+		fly.notify_move(&drag(-314));
+
+		fly.update(&mut camera, 0.0);
+		assert_abs_diff_eq!(
+			camera.forward(),
+			Vector3::new(-1.0, 0.0, 0.0),
+			epsilon = 0.01
+		);
+		assert_abs_diff_eq!(
+			(camera.center - camera.eye).normalize(),
+			camera.forward(),
+			epsilon = 1e-5
+		);
+	}
+}
@@ -0,0 +1,254 @@
+// File: src/camera/mod.rs
+// Author: Jacob Guenther
+// Date created: March 2021
+// License: AGPLv3
+//
+// Description:
+
+pub mod fly;
+pub mod orbit;
+
+use crate::bounds::Frustum;
+use crate::config::scene_config::{
+	CameraConfig,
+	ProjectionConfig,
+};
+use crate::picking::Ray;
+use cgmath::{
+	InnerSpace,
+	Matrix3,
+	Matrix4,
+	One,
+	Point3,
+	Quaternion,
+	SquareMatrix,
+	Vector3,
+};
+// use cgmath::EuclideanSpace;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+	// fovy is in degrees.
+	Perspective { fovy: f32 },
+	// height is the world units visible vertically at a zoom of one.
+	Orthographic { height: f32, zoom: f32 },
+}
+impl Projection {
+	pub fn from_config(config: &CameraConfig) -> Self {
+		match config.projection {
+			ProjectionConfig::Perspective => {
+				Self::Perspective { fovy: config.fov_y }
+			}
+			ProjectionConfig::Orthographic { height, zoom } => {
+				Self::Orthographic { height, zoom }
+			}
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Camera {
+	pub projection: Projection,
+	pub aspect: f32,
+	pub znear: f32,
+	pub zfar: f32,
+
+	pub eye: Point3<f32>,
+	pub center: Point3<f32>,
+	pub up: Vector3<f32>,
+
+	// Rotates the camera's axes, -z forward and y up, into world space.
+	// Kept in sync with eye, center and up by look_at and set_pose.
+	pub orientation: Quaternion<f32>,
+	pub position: Point3<f32>,
+}
+impl Camera {
+	pub fn new(config: &CameraConfig, width: u32, height: u32) -> Self {
+		let mut camera = Self {
+			projection: Projection::from_config(config),
+			aspect: width as f32 / height as f32,
+			znear: config.z_near,
+			zfar: config.z_far,
+
+			eye: Point3::from(config.eye),
+			center: Point3::from(config.center),
+			up: Vector3::from(config.up),
+
+			orientation: Quaternion::one(),
+			position: Point3::from(config.eye),
+		};
+		camera.look_at(camera.eye, camera.center, camera.up);
+		camera
+	}
+	pub fn look_at(
+		&mut self,
+		eye: Point3<f32>,
+		center: Point3<f32>,
+		up: Vector3<f32>,
+	) {
+		let forward = (center - eye).normalize();
+		let right = forward.cross(up).normalize();
+		let up_ortho = right.cross(forward);
+		let basis = Matrix3::from_cols(right, up_ortho, -forward);
+
+		self.eye = eye;
+		self.center = center;
+		self.up = up;
+		self.orientation = Quaternion::from(basis).normalize();
+		self.position = eye;
+	}
+	// Places the camera from a position and orientation, keeping the
+	// distance to the center.
+	pub fn set_pose(
+		&mut self,
+		position: Point3<f32>,
+		orientation: Quaternion<f32>,
+	) {
+		let distance = (self.center - self.eye).magnitude().max(1.0);
+		self.position = position;
+		self.orientation = orientation;
+		self.eye = position;
+		self.center = position + self.forward() * distance;
+		self.up = orientation * Vector3::unit_y();
+	}
+	pub fn forward(&self) -> Vector3<f32> {
+		self.orientation * -Vector3::unit_z()
+	}
+	pub fn right(&self) -> Vector3<f32> {
+		self.orientation * Vector3::unit_x()
+	}
+	pub fn view_matrix(&self) -> Matrix4<f32> {
+		// let rotation = Matrix4::from(self.orientation);
+		// let pos: Vector3<f32> = self.position.to_vec();
+		// let translation = Matrix4::from_translation(pos);
+		// translation * rotation
+
+		Matrix4::look_at_rh(self.eye, self.center, self.up)
+	}
+	// The world space point at normalized device coordinates `ndc`, z of
+	// -1 is on the near plane and 1 on the far plane.
+	pub fn unproject(&self, ndc: Point3<f32>) -> Point3<f32> {
+		let inverse = (self.projection_matrix() * self.view_matrix())
+			.invert()
+			.unwrap_or_else(Matrix4::one);
+		Point3::from_homogeneous(inverse * ndc.to_homogeneous())
+	}
+	// The ray from the near plane through normalized device coordinates x
+	// and y, see CanvasSize::to_ndc for getting them from the cursor.
+	pub fn ray(&self, x: f32, y: f32) -> Ray {
+		let near = self.unproject(Point3::new(x, y, -1.0));
+		let far = self.unproject(Point3::new(x, y, 1.0));
+		Ray::new(near, far - near)
+	}
+	// What the camera can see, in world space.
+	pub fn frustum(&self) -> Frustum {
+		Frustum::from_matrix(&(self.projection_matrix() * self.view_matrix()))
+	}
+	pub fn projection_matrix(&self) -> Matrix4<f32> {
+		match self.projection {
+			Projection::Perspective { fovy } => cgmath::perspective(
+				cgmath::Deg(fovy),
+				self.aspect,
+				self.znear,
+				self.zfar,
+			),
+			Projection::Orthographic { height, zoom } => {
+				let top = height * 0.5 / zoom;
+				let right = top * self.aspect;
+				cgmath::ortho(-right, right, -top, top, self.znear, self.zfar)
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+impl Camera {
+	// A perspective camera at (0, 0, 10) looking at the origin.
+	pub(crate) fn test_camera(width: u32, height: u32) -> Self {
+		Self::new(
+			&CameraConfig {
+				id: String::from("main"),
+				projection: ProjectionConfig::Perspective,
+				fov_y: 45.0,
+				z_near: 0.1,
+				z_far: 100.0,
+				eye: [0.0, 0.0, 10.0],
+				center: [0.0, 0.0, 0.0],
+				up: [0.0, 1.0, 0.0],
+			},
+			width,
+			height,
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cgmath::{
+		assert_abs_diff_eq,
+		Vector4,
+	};
+
+	fn config(projection: ProjectionConfig) -> CameraConfig {
+		CameraConfig {
+			id: String::from("main"),
+			projection,
+			fov_y: 45.0,
+			z_near: 1.0,
+			z_far: 11.0,
+			eye: [0.0, 0.0, 0.0],
+			center: [0.0, 0.0, -1.0],
+			up: [0.0, 1.0, 0.0],
+		}
+	}
+
+	#[test]
+	fn orthographic_extents_follow_height_zoom_and_aspect() {
+		let projection = ProjectionConfig::Orthographic {
+			height: 4.0,
+			zoom: 2.0,
+		};
+		let camera = Camera::new(&config(projection), 800, 400);
+		let matrix = camera.projection_matrix();
+
+		// The corner of the near plane and the center of the far plane.
+		let corner = matrix * Vector4::new(2.0, 1.0, -1.0, 1.0);
+		let far = matrix * Vector4::new(0.0, 0.0, -11.0, 1.0);
+		assert_abs_diff_eq!(corner, Vector4::new(1.0, 1.0, -1.0, 1.0));
+		assert_abs_diff_eq!(far, Vector4::new(0.0, 0.0, 1.0, 1.0));
+	}
+	#[test]
+	fn rays_start_on_the_near_plane() {
+		let perspective = config(ProjectionConfig::Perspective);
+		let camera = Camera::new(&perspective, 800, 800);
+		let center = camera.ray(0.0, 0.0);
+		assert_abs_diff_eq!(center.origin, Point3::new(0.0, 0.0, -1.0));
+		assert_abs_diff_eq!(center.direction, Vector3::new(0.0, 0.0, -1.0));
+
+		let projection = ProjectionConfig::Orthographic {
+			height: 4.0,
+			zoom: 1.0,
+		};
+		let camera = Camera::new(&config(projection), 800, 800);
+		let corner = camera.ray(1.0, 1.0);
+		assert_abs_diff_eq!(corner.origin, Point3::new(2.0, 2.0, -1.0));
+		assert_abs_diff_eq!(corner.direction, Vector3::new(0.0, 0.0, -1.0));
+	}
+	#[test]
+	fn perspective_is_the_default() {
+		let config: CameraConfig = serde_json::from_str(
+			r#"{
+				"id": "main", "z_near": 0.1, "z_far": 100,
+				"eye": [0, 0, 5], "center": [0, 0, 0], "up": [0, 1, 0]
+			}"#,
+		)
+		.unwrap();
+		let camera = Camera::new(&config, 800, 600);
+		assert_eq!(camera.projection, Projection::Perspective { fovy: 45.0 });
+		assert_abs_diff_eq!(
+			camera.projection_matrix(),
+			cgmath::perspective(cgmath::Deg(45.0), 800.0 / 600.0, 0.1, 100.0)
+		);
+	}
+}
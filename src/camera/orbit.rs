@@ -0,0 +1,269 @@
+// File: src/camera/orbit.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Orbits a camera around its center. Dragging with the left button
+// rotates, dragging with the middle button (or shift and left) pans and
+// the wheel dollies toward or away from the center. The world's y axis is
+// kept up.
+
+use std::f32::consts::FRAC_PI_2;
+
+use cgmath::{
+	InnerSpace,
+	Point3,
+	Vector3,
+};
+
+use super::{
+	Camera,
+	Projection,
+};
+use crate::input::mouse::{
+	MouseEvent,
+	MouseEventSubscriber,
+	WheelEvent,
+};
+
+// Keeps the view direction from lining up with the up axis.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Drag {
+	Rotate,
+	Pan,
+}
+
+#[derive(Clone, Debug)]
+pub struct OrbitController {
+	pub center: Point3<f32>,
+	pub distance: f32,
+	// Radians around the y axis, zero looks down -z.
+	pub yaw: f32,
+	// Radians above the xz plane.
+	pub pitch: f32,
+
+	// Radians per pixel dragged.
+	pub rotate_speed: f32,
+	// Fraction of the distance moved per pixel dragged.
+	pub pan_speed: f32,
+	// Exponential dolly rate per pixel scrolled.
+	pub dolly_speed: f32,
+	pub min_distance: f32,
+	pub max_distance: f32,
+
+	drag: Option<Drag>,
+	last_position: (i32, i32),
+	changed: bool,
+}
+impl OrbitController {
+	pub fn new(camera: &Camera) -> Self {
+		let mut controller = Self {
+			center: camera.center,
+			distance: 1.0,
+			yaw: 0.0,
+			pitch: 0.0,
+			rotate_speed: 0.01,
+			pan_speed: 0.002,
+			dolly_speed: 0.001,
+			min_distance: camera.znear * 2.0,
+			max_distance: camera.zfar * 0.5,
+			drag: None,
+			last_position: (0, 0),
+			changed: false,
+		};
+		controller.sync(camera);
+		controller
+	}
+	// Picks up a camera that was moved or switched to by something else.
+	pub fn sync(&mut self, camera: &Camera) {
+		let offset = camera.eye - camera.center;
+		self.center = camera.center;
+		self.distance = offset.magnitude();
+		let offset = offset / self.distance;
+		self.pitch = offset.y.asin().clamp(-MAX_PITCH, MAX_PITCH);
+		self.yaw = offset.x.atan2(offset.z);
+		self.drag = None;
+		self.changed = false;
+	}
+
+	pub fn eye(&self) -> Point3<f32> {
+		let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+		let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+		let direction =
+			Vector3::new(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw);
+		self.center + direction * self.distance
+	}
+	pub fn rotate(&mut self, dx: f32, dy: f32) {
+		self.yaw -= dx * self.rotate_speed;
+		self.pitch =
+			(self.pitch + dy * self.rotate_speed).clamp(-MAX_PITCH, MAX_PITCH);
+		self.changed = true;
+	}
+	// Moves the center in the view plane so the scene follows the cursor.
+	pub fn pan(&mut self, dx: f32, dy: f32) {
+		let forward = (self.center - self.eye()).normalize();
+		let right = forward.cross(Vector3::unit_y()).normalize();
+		let up = right.cross(forward);
+		let scale = self.distance * self.pan_speed;
+		self.center += (-right * dx + up * dy) * scale;
+		self.changed = true;
+	}
+	pub fn dolly(&mut self, delta: f32) {
+		self.distance = (self.distance * (delta * self.dolly_speed).exp())
+			.clamp(self.min_distance, self.max_distance);
+		self.changed = true;
+	}
+
+	// Writes the orbit into the camera if it moved since the last update
+	// and returns whether it did. Moving closer does nothing for an
+	// orthographic camera so it's zoomed by the same amount as well.
+	pub fn update(&mut self, camera: &mut Camera) -> bool {
+		if !self.changed {
+			return false;
+		}
+		if let Projection::Orthographic { ref mut zoom, .. } = camera.projection
+		{
+			*zoom *= (camera.eye - camera.center).magnitude() / self.distance;
+		}
+		camera.look_at(self.eye(), self.center, Vector3::unit_y());
+		self.changed = false;
+		true
+	}
+
+	fn drag_kind(event: &MouseEvent) -> Option<Drag> {
+		if event.middle_btn || (event.left_btn && event.shift) {
+			Some(Drag::Pan)
+		} else if event.left_btn {
+			Some(Drag::Rotate)
+		} else {
+			None
+		}
+	}
+}
+impl MouseEventSubscriber for OrbitController {
+	fn notify_down(&mut self, event: &MouseEvent) {
+		self.drag = Self::drag_kind(event);
+		self.last_position = (event.x, event.y);
+	}
+	fn notify_up(&mut self, event: &MouseEvent) {
+		self.drag = Self::drag_kind(event);
+	}
+	fn notify_move(&mut self, event: &MouseEvent) {
+		// Buttons released outside the canvas never send an up event.
+		self.drag = self.drag.and(Self::drag_kind(event));
+		let dx = (event.x - self.last_position.0) as f32;
+		let dy = (event.y - self.last_position.1) as f32;
+		self.last_position = (event.x, event.y);
+		match self.drag {
+			Some(Drag::Rotate) => self.rotate(dx, dy),
+			Some(Drag::Pan) => self.pan(dx, dy),
+			None => (),
+		}
+	}
+	fn notify_leave(&mut self, _event: &MouseEvent) {
+		self.drag = None;
+	}
+	fn notify_wheel(&mut self, event: &WheelEvent) {
+		self.dolly(event.delta_y as f32);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cgmath::{
+		assert_abs_diff_eq,
+		EuclideanSpace,
+	};
+
+	fn camera() -> Camera {
+		Camera::test_camera(800, 600)
+	}
+	fn mouse(x: i32, y: i32, left_btn: bool, middle_btn: bool) -> MouseEvent {
+		MouseEvent {
+			x,
+			y,
+			left_btn,
+			middle_btn,
+			..MouseEvent::default()
+		}
+	}
+
+	#[test]
+	fn syncs_to_the_camera_without_moving_it() {
+		let mut camera = camera();
+		let mut orbit = OrbitController::new(&camera);
+		assert_abs_diff_eq!(orbit.eye(), camera.eye, epsilon = 1e-5);
+		assert!(!orbit.update(&mut camera));
+	}
+	#[test]
+	fn left_drag_orbits_at_a_fixed_distance() {
+		let mut camera = camera();
+		let mut orbit = OrbitController::new(&camera);
+		orbit.notify_down(&mouse(0, 0, true, false));
+		orbit.notify_move(&mouse(-157, 0, true, false));
+
+		assert!(orbit.update(&mut camera));
+		assert_abs_diff_eq!(
+			camera.eye,
+			Point3::new(10.0, 0.0, 0.0),
+			epsilon = 0.01
+		);
+		assert_abs_diff_eq!((camera.eye - camera.center).magnitude(), 10.0);
+
+		orbit.notify_move(&mouse(-157, 10_000, true, false));
+		assert!(orbit.pitch <= MAX_PITCH);
+	}
+	#[test]
+	fn middle_drag_pans_and_release_stops_dragging() {
+		let mut camera = camera();
+		let mut orbit = OrbitController::new(&camera);
+		orbit.notify_down(&mouse(0, 0, false, true));
+		orbit.notify_move(&mouse(100, 0, false, true));
+		orbit.update(&mut camera);
+		assert!(camera.center.x < 0.0);
+		assert_abs_diff_eq!(camera.eye.x, camera.center.x);
+
+		orbit.notify_up(&mouse(100, 0, false, false));
+		orbit.notify_move(&mouse(200, 50, false, false));
+		assert!(!orbit.update(&mut camera));
+	}
+	#[test]
+	fn wheel_dollies_within_limits() {
+		let mut camera = camera();
+		let mut orbit = OrbitController::new(&camera);
+		orbit.notify_wheel(&WheelEvent {
+			delta_y: -693.0,
+			..WheelEvent::default()
+		});
+		orbit.update(&mut camera);
+		assert_abs_diff_eq!(
+			camera.eye.to_vec().magnitude(),
+			5.0,
+			epsilon = 0.01
+		);
+
+		orbit.dolly(1.0e6);
+		assert_eq!(orbit.distance, orbit.max_distance);
+	}
+	#[test]
+	fn wheel_zooms_orthographic_cameras() {
+		let mut camera = camera();
+		camera.projection = Projection::Orthographic {
+			height: 10.0,
+			zoom: 1.0,
+		};
+		let mut orbit = OrbitController::new(&camera);
+		orbit.dolly(-(2.0f32.ln()) / orbit.dolly_speed);
+		orbit.update(&mut camera);
+		match camera.projection {
+			Projection::Orthographic { zoom, .. } => {
+				assert_abs_diff_eq!(zoom, 2.0, epsilon = 1e-4)
+			}
+			_ => panic!("expected an orthographic camera"),
+		}
+	}
+}
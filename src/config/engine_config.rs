@@ -7,9 +7,15 @@
 
 use serde::Deserialize;
 
+// `width` and `height` are the canvas's size on the page in CSS pixels,
+// applied unless the canvas is already sized by an inline style. With
+// `fixed_aspect` the scene is letterboxed to that width over height ratio
+// instead of stretching to fill the canvas.
 #[derive(Clone, Debug, Deserialize)]
 pub struct EngineConfig {
 	pub canvas_id: String,
 	pub width: u32,
 	pub height: u32,
+	#[serde(default)]
+	pub fixed_aspect: Option<f32>,
 }
@@ -7,6 +7,7 @@
 
 pub mod engine_config;
 pub mod scene_config;
+pub mod validation;
 
 use serde::Deserialize;
 
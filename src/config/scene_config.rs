@@ -6,6 +6,7 @@
 // Description:
 
 use serde::Deserialize;
+use web_sys::WebGl2RenderingContext;
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct SceneConfig {
@@ -14,11 +15,240 @@ pub struct SceneConfig {
 
 	pub shaders: Vec<ShaderConfig>,
 	pub programs: Vec<ProgramConfig>,
+
+	#[serde(default)]
+	pub models: Vec<ModelSourceConfig>,
+	#[serde(default)]
+	pub primitives: Vec<PrimitiveConfig>,
+	#[serde(default)]
+	pub lights: Vec<LightConfig>,
+	#[serde(default)]
+	pub nodes: Vec<NodeConfig>,
+	#[serde(default)]
+	pub environment: Option<EnvironmentConfig>,
+}
+
+// A .gltf or .glb file relative to the assets directory.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ModelSourceConfig {
+	pub id: String,
+	pub uri: String,
+}
+
+// An equirectangular Radiance .hdr relative to the assets directory that
+// lights the scene's materials. `size` is the width of the prefiltered
+// specular map, a power of two up to 128, and `intensity` scales the light.
+// The maps are prefiltered on the CPU while the environment loads, about a
+// quarter second at 64 in an optimized native build and over twice that at
+// 128, slower still in the browser.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EnvironmentConfig {
+	pub uri: String,
+	#[serde(default = "default_environment_size")]
+	pub size: u32,
+	#[serde(default = "default_intensity")]
+	pub intensity: f32,
+}
+fn default_environment_size() -> u32 {
+	64
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PrimitiveConfig {
+	Cube {
+		id: String,
+	},
+	UvSphere {
+		id: String,
+		#[serde(default = "default_radius")]
+		radius: f32,
+		#[serde(default = "default_vertical_subdivisions")]
+		vertical_subdivisions: usize,
+		#[serde(default = "default_horizontal_subdivisions")]
+		horizontal_subdivisions: usize,
+	},
+}
+impl PrimitiveConfig {
+	pub fn id(&self) -> &str {
+		match self {
+			PrimitiveConfig::Cube { id } => id,
+			PrimitiveConfig::UvSphere { id, .. } => id,
+		}
+	}
+}
+fn default_radius() -> f32 {
+	1.0
+}
+fn default_vertical_subdivisions() -> usize {
+	16
+}
+fn default_horizontal_subdivisions() -> usize {
+	32
+}
+
+// Positions and directions are in the space of the node the light is
+// attached to, or world space when no node references it. As in glTF's
+// KHR_lights_punctual, `intensity` scales `color`, point and spot lights
+// fade out by `range` and spot cone angles are radians from the direction.
+// Directional and spot lights cast shadows when they have a `shadow`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LightConfig {
+	Point {
+		id: String,
+		position: [f32; 3],
+		#[serde(default = "default_light_color")]
+		color: [f32; 3],
+		#[serde(default = "default_intensity")]
+		intensity: f32,
+		#[serde(default)]
+		range: Option<f32>,
+	},
+	Directional {
+		id: String,
+		direction: [f32; 3],
+		#[serde(default = "default_light_color")]
+		color: [f32; 3],
+		#[serde(default = "default_intensity")]
+		intensity: f32,
+		#[serde(default)]
+		shadow: Option<ShadowConfig>,
+	},
+	Spot {
+		id: String,
+		position: [f32; 3],
+		direction: [f32; 3],
+		#[serde(default = "default_light_color")]
+		color: [f32; 3],
+		#[serde(default = "default_intensity")]
+		intensity: f32,
+		#[serde(default)]
+		range: Option<f32>,
+		#[serde(default)]
+		inner_cone_angle: f32,
+		#[serde(default = "default_outer_cone_angle")]
+		outer_cone_angle: f32,
+		#[serde(default)]
+		shadow: Option<ShadowConfig>,
+	},
+}
+impl LightConfig {
+	pub fn id(&self) -> &str {
+		match self {
+			LightConfig::Point { id, .. } => id,
+			LightConfig::Directional { id, .. } => id,
+			LightConfig::Spot { id, .. } => id,
+		}
+	}
+	pub fn range(&self) -> Option<f32> {
+		match *self {
+			LightConfig::Point { range, .. } => range,
+			LightConfig::Directional { .. } => None,
+			LightConfig::Spot { range, .. } => range,
+		}
+	}
+	pub fn shadow(&self) -> Option<&ShadowConfig> {
+		match self {
+			LightConfig::Point { .. } => None,
+			LightConfig::Directional { shadow, .. } => shadow.as_ref(),
+			LightConfig::Spot { shadow, .. } => shadow.as_ref(),
+		}
+	}
+}
+fn default_light_color() -> [f32; 3] {
+	[1.0, 1.0, 1.0]
+}
+fn default_intensity() -> f32 {
+	1.0
+}
+fn default_outer_cone_angle() -> f32 {
+	std::f32::consts::FRAC_PI_4
+}
+
+// `resolution` is the width and height of each shadow map in texels and
+// `bias` is subtracted from depths before they're compared. Directional
+// lights split the camera's view up to `distance` into `cascades` maps,
+// `split_lambda` blends the splits from even (0) to logarithmic (1).
+// Spot lights use one map and the distance when they have no range.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+pub struct ShadowConfig {
+	#[serde(default = "default_shadow_resolution")]
+	pub resolution: u32,
+	#[serde(default = "default_shadow_bias")]
+	pub bias: f32,
+	#[serde(default = "default_cascades")]
+	pub cascades: usize,
+	#[serde(default = "default_split_lambda")]
+	pub split_lambda: f32,
+	#[serde(default = "default_shadow_distance")]
+	pub distance: f32,
+}
+impl Default for ShadowConfig {
+	fn default() -> Self {
+		Self {
+			resolution: default_shadow_resolution(),
+			bias: default_shadow_bias(),
+			cascades: default_cascades(),
+			split_lambda: default_split_lambda(),
+			distance: default_shadow_distance(),
+		}
+	}
+}
+fn default_shadow_resolution() -> u32 {
+	1024
+}
+fn default_shadow_bias() -> f32 {
+	0.002
+}
+fn default_cascades() -> usize {
+	3
+}
+fn default_split_lambda() -> f32 {
+	0.6
+}
+fn default_shadow_distance() -> f32 {
+	50.0
 }
 
+// `matrix` takes precedence over translation, rotation and scale. Rotation
+// is a unit quaternion ordered x, y, z, w as in glTF. `model` names a
+// model or primitive and `light` names a light.
+#[derive(Clone, Debug, Deserialize)]
+pub struct NodeConfig {
+	// Names the node for JS, e.g. in pick results.
+	#[serde(default)]
+	pub id: Option<String>,
+	#[serde(default)]
+	pub translation: [f32; 3],
+	#[serde(default = "default_rotation")]
+	pub rotation: [f32; 4],
+	#[serde(default = "default_scale")]
+	pub scale: [f32; 3],
+	#[serde(default)]
+	pub matrix: Option<[f32; 16]>,
+
+	#[serde(default)]
+	pub model: Option<String>,
+	#[serde(default)]
+	pub light: Option<String>,
+	#[serde(default)]
+	pub children: Vec<NodeConfig>,
+}
+fn default_rotation() -> [f32; 4] {
+	[0.0, 0.0, 0.0, 1.0]
+}
+fn default_scale() -> [f32; 3] {
+	[1.0, 1.0, 1.0]
+}
+
+// `fov_y` is in degrees and only used by perspective cameras.
 #[derive(Clone, Debug, Deserialize)]
 pub struct CameraConfig {
 	pub id: String,
+	#[serde(default)]
+	pub projection: ProjectionConfig,
+	#[serde(default = "default_fov_y")]
 	pub fov_y: f32,
 	pub z_near: f32,
 	pub z_far: f32,
@@ -28,6 +258,32 @@ pub struct CameraConfig {
 	pub up: [f32; 3],
 }
 
+fn default_fov_y() -> f32 {
+	45.0
+}
+
+// An orthographic camera sees `height` world units from the bottom of the
+// view to the top at a zoom of one, the width follows the aspect ratio.
+// Zooming in shrinks both.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProjectionConfig {
+	#[default]
+	Perspective,
+	Orthographic {
+		#[serde(default = "default_orthographic_height")]
+		height: f32,
+		#[serde(default = "default_zoom")]
+		zoom: f32,
+	},
+}
+fn default_orthographic_height() -> f32 {
+	10.0
+}
+fn default_zoom() -> f32 {
+	1.0
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct ShaderConfig {
 	pub id: String,
@@ -51,10 +307,46 @@ pub struct AttributeConfig {
 	// pub location: usize,
 }
 
+// A uniform with a `count` is an array, one with `fields` a struct, and
+// one with both an array of structs. Each element and field is looked up
+// on its own, e.g. LIGHTS[0].color.
 #[derive(Clone, Debug, Deserialize)]
 pub struct UniformConfig {
 	pub name: String,
 	// pub kind: String,
+	#[serde(default)]
+	pub count: Option<usize>,
+	#[serde(default)]
+	pub fields: Vec<String>,
+}
+impl UniformConfig {
+	pub fn new(name: &str) -> Self {
+		Self {
+			name: String::from(name),
+			count: None,
+			fields: Vec::new(),
+		}
+	}
+	// The names GL knows the uniform's parts by.
+	pub fn names(&self) -> Vec<String> {
+		let elements = match self.count {
+			Some(count) => (0..count)
+				.map(|i| format!("{}[{}]", self.name, i))
+				.collect(),
+			None => vec![self.name.clone()],
+		};
+		if self.fields.is_empty() {
+			return elements;
+		}
+		elements
+			.iter()
+			.flat_map(|element| {
+				self.fields
+					.iter()
+					.map(move |field| format!("{}.{}", element, field))
+			})
+			.collect()
+	}
 }
 
 #[derive(Copy, Clone, Debug, Deserialize)]
@@ -74,7 +366,12 @@ pub enum WebGLType {
 	mat4,
 }
 
-use crate::model::texture::Sampler;
+use crate::model::texture::{
+	Channels,
+	ColorSpace,
+	Sampler,
+	TextureFormat,
+};
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ModelConfig {
@@ -85,11 +382,17 @@ pub struct ModelConfig {
 	pub texture_wrapper_id: String,
 	pub samplers: Vec<Sampler>,
 	pub textures: Vec<Texture>,
+	// In the model's space, placed along with it.
+	#[serde(default)]
+	pub lights: Vec<LightConfig>,
 }
 #[derive(Clone, Debug, Deserialize)]
 pub struct Texture {
 	pub source: usize,
 	pub sampler: usize,
+	// Linear RGBA unless given, sRGB for the colors material slots show.
+	#[serde(default)]
+	pub format: TextureFormat,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -100,6 +403,13 @@ pub struct MeshConfig {
 	pub index_view: Option<BufferViewConfig>,
 	pub buffer_views: Vec<BufferViewConfig>,
 	pub material: Option<u32>,
+	// How the vertices are assembled, e.g. TRIANGLES. Only triangle modes
+	// can be picked.
+	#[serde(default = "default_primitive_mode")]
+	pub mode: u32,
+}
+fn default_primitive_mode() -> u32 {
+	WebGl2RenderingContext::TRIANGLES
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -113,13 +423,127 @@ pub struct BufferViewConfig {
 	pub component_size: i32,
 	pub component_count: i32,
 	pub component_type: u32,
+	// Integer components are mapped to 0..1, or -1..1 when signed.
+	#[serde(default)]
+	pub normalized: bool,
+	// Per component bounds of the data as in a glTF accessor. Used for the
+	// mesh bounds when given on the position view.
+	#[serde(default)]
+	pub min: Option<Vec<f32>>,
+	#[serde(default)]
+	pub max: Option<Vec<f32>>,
+}
+#[cfg(test)]
+impl BufferViewConfig {
+	// Tightly packed float positions at the start of buffer 0.
+	pub(crate) fn test_positions(vertex_count: usize) -> Self {
+		Self {
+			id: String::from("POSITION"),
+			buffer: 0,
+			length: vertex_count * 12,
+			buffer_offset: None,
+			offset: None,
+			stride: None,
+			component_size: 3,
+			component_count: vertex_count as i32,
+			component_type: WebGl2RenderingContext::FLOAT,
+			normalized: false,
+			min: None,
+			max: None,
+		}
+	}
 }
 
+// A texture index and the TEXCOORD_n set it's sampled with.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub struct MaterialTextureConfig {
+	pub index: u32,
+	#[serde(default)]
+	pub tex_coord: u32,
+}
+
+// glTF's metallic-roughness material, see Material.
 #[derive(Clone, Debug, Deserialize)]
 pub struct MaterialConfig {
 	pub id: String,
-	pub diffuse: Option<u32>,
-	pub normal: Option<u32>,
-	pub metallic_roughness: Option<u32>,
-	pub occlusion: Option<u32>,
+	#[serde(default = "default_base_color_factor")]
+	pub base_color_factor: [f32; 4],
+	#[serde(default)]
+	pub base_color: Option<MaterialTextureConfig>,
+	#[serde(default = "default_factor")]
+	pub metallic_factor: f32,
+	#[serde(default = "default_factor")]
+	pub roughness_factor: f32,
+	#[serde(default)]
+	pub metallic_roughness: Option<MaterialTextureConfig>,
+	#[serde(default)]
+	pub normal: Option<MaterialTextureConfig>,
+	#[serde(default = "default_factor")]
+	pub normal_scale: f32,
+	#[serde(default)]
+	pub occlusion: Option<MaterialTextureConfig>,
+	#[serde(default = "default_factor")]
+	pub occlusion_strength: f32,
+	#[serde(default)]
+	pub emissive: Option<MaterialTextureConfig>,
+	#[serde(default)]
+	pub emissive_factor: [f32; 3],
+	#[serde(default)]
+	pub alpha_mode: AlphaModeConfig,
+	#[serde(default = "default_alpha_cutoff")]
+	pub alpha_cutoff: f32,
+	#[serde(default)]
+	pub double_sided: bool,
+}
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlphaModeConfig {
+	#[default]
+	Opaque,
+	Mask,
+	Blend,
+}
+impl MaterialConfig {
+	// The material's texture slots by name.
+	pub fn textures(
+		&self,
+	) -> [(&'static str, Option<MaterialTextureConfig>); 5] {
+		[
+			("base_color", self.base_color),
+			("metallic_roughness", self.metallic_roughness),
+			("normal", self.normal),
+			("occlusion", self.occlusion),
+			("emissive", self.emissive),
+		]
+	}
+	pub fn textures_mut(
+		&mut self,
+	) -> [(&'static str, &mut Option<MaterialTextureConfig>); 5] {
+		[
+			("base_color", &mut self.base_color),
+			("metallic_roughness", &mut self.metallic_roughness),
+			("normal", &mut self.normal),
+			("occlusion", &mut self.occlusion),
+			("emissive", &mut self.emissive),
+		]
+	}
+	// The format a texture in `slot` of textures() is uploaded as. Base
+	// color and emissive are colors in sRGB, the rest linear data.
+	pub fn slot_format(slot: &str) -> TextureFormat {
+		match slot {
+			"base_color" | "emissive" => {
+				TextureFormat::new(Channels::Rgba, ColorSpace::Srgb)
+			}
+			_ => TextureFormat::new(Channels::Rgb, ColorSpace::Linear),
+		}
+	}
+}
+fn default_base_color_factor() -> [f32; 4] {
+	[1.0, 1.0, 1.0, 1.0]
+}
+fn default_factor() -> f32 {
+	1.0
+}
+fn default_alpha_cutoff() -> f32 {
+	0.5
 }
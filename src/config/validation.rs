@@ -0,0 +1,840 @@
+// File: src/config/validation.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Checks scene and model configs for problems that would otherwise show up
+// as a failed lookup or a panic while loading. Every problem is reported
+// with the JSON path of the offending value.
+
+use std::{
+	collections::HashMap,
+	fmt,
+};
+
+use wasm_bindgen::JsValue;
+
+use super::scene_config::{
+	BufferViewConfig,
+	EnvironmentConfig,
+	LightConfig,
+	ModelConfig,
+	NodeConfig,
+	PrimitiveConfig,
+	ProjectionConfig,
+	SceneConfig,
+	ShadowConfig,
+};
+use crate::environment::{
+	MAX_ENVIRONMENT_SIZE,
+	MIN_ENVIRONMENT_SIZE,
+};
+use crate::model::{
+	material::MAX_TEX_COORDS,
+	texture::ComponentType,
+};
+use crate::shadow::{
+	MAX_CASCADES,
+	MAX_SHADOW_RESOLUTION,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigErrorKind {
+	DanglingId {
+		kind: &'static str,
+		id: String,
+	},
+	DuplicateId {
+		kind: &'static str,
+		id: String,
+		first: String,
+	},
+	IndexOutOfRange {
+		kind: &'static str,
+		index: usize,
+		len: usize,
+	},
+	UnknownShaderKind(String),
+	ShaderKindMismatch {
+		id: String,
+		expected: &'static str,
+		found: String,
+	},
+	BufferViewOutOfRange {
+		end: i64,
+		buffer_length: usize,
+	},
+	NegativeOffset(i32),
+	TooFewSubdivisions {
+		minimum: usize,
+		found: usize,
+	},
+	NotPositive(f32),
+	ConeAngles {
+		inner: f32,
+		outer: f32,
+	},
+	ShadowResolution(u32),
+	CascadeCount(usize),
+	EnvironmentSize(u32),
+	TextureFormat(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigError {
+	pub path: String,
+	pub kind: ConfigErrorKind,
+}
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: ", self.path)?;
+		match self.kind {
+			ConfigErrorKind::DanglingId { kind, ref id } => {
+				write!(f, "no {} with id '{}'", kind, id)
+			}
+			ConfigErrorKind::DuplicateId {
+				kind,
+				ref id,
+				ref first,
+			} => write!(f, "{} id '{}' is already used at {}", kind, id, first),
+			ConfigErrorKind::IndexOutOfRange { kind, index, len } => write!(
+				f,
+				"{} index {} is out of range, there are {}",
+				kind, index, len
+			),
+			ConfigErrorKind::UnknownShaderKind(ref kind) => write!(
+				f,
+				"shader kind '{}' is not 'vertex' or 'fragment'",
+				kind
+			),
+			ConfigErrorKind::ShaderKindMismatch {
+				ref id,
+				expected,
+				ref found,
+			} => write!(
+				f,
+				"shader '{}' is a {} shader, expected a {} shader",
+				id, found, expected
+			),
+			ConfigErrorKind::BufferViewOutOfRange { end, buffer_length } => {
+				write!(
+					f,
+					"buffer view ends at byte {} but the buffer is {} bytes",
+					end, buffer_length
+				)
+			}
+			ConfigErrorKind::NegativeOffset(offset) => {
+				write!(f, "offset {} is negative", offset)
+			}
+			ConfigErrorKind::TooFewSubdivisions { minimum, found } => write!(
+				f,
+				"{} subdivisions is less than the minimum of {}",
+				found, minimum
+			),
+			ConfigErrorKind::NotPositive(value) => {
+				write!(f, "{} must be greater than zero", value)
+			}
+			ConfigErrorKind::ConeAngles { inner, outer } => write!(
+				f,
+				"cone angles {} and {} must satisfy 0 <= inner < outer <= pi/2",
+				inner, outer
+			),
+			ConfigErrorKind::ShadowResolution(resolution) => write!(
+				f,
+				"shadow resolution {} is not between 1 and {}",
+				resolution, MAX_SHADOW_RESOLUTION
+			),
+			ConfigErrorKind::CascadeCount(count) => write!(
+				f,
+				"{} cascades is not between 1 and {}",
+				count, MAX_CASCADES
+			),
+			ConfigErrorKind::EnvironmentSize(size) => write!(
+				f,
+				"environment size {} is not a power of two between {} and {}",
+				size, MIN_ENVIRONMENT_SIZE, MAX_ENVIRONMENT_SIZE
+			),
+			ConfigErrorKind::TextureFormat(reason) => write!(f, "{}", reason),
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationErrors(pub Vec<ConfigError>);
+impl fmt::Display for ValidationErrors {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (i, error) in self.0.iter().enumerate() {
+			if i > 0 {
+				writeln!(f)?;
+			}
+			write!(f, "{}", error)?;
+		}
+		Ok(())
+	}
+}
+impl From<ValidationErrors> for String {
+	fn from(errors: ValidationErrors) -> Self {
+		errors.to_string()
+	}
+}
+impl From<ValidationErrors> for JsValue {
+	fn from(errors: ValidationErrors) -> Self {
+		JsValue::from_str(&errors.to_string())
+	}
+}
+
+#[derive(Default)]
+struct Validator {
+	errors: Vec<ConfigError>,
+}
+impl Validator {
+	fn error(&mut self, path: String, kind: ConfigErrorKind) {
+		self.errors.push(ConfigError { path, kind });
+	}
+	fn finish(self) -> Result<(), ValidationErrors> {
+		if self.errors.is_empty() {
+			Ok(())
+		} else {
+			Err(ValidationErrors(self.errors))
+		}
+	}
+	// Maps each id to the path it was first declared at, reporting repeats.
+	fn collect_ids<'a>(
+		&mut self,
+		kind: &'static str,
+		ids: impl Iterator<Item = (&'a str, String)>,
+		seen: &mut HashMap<&'a str, String>,
+	) {
+		for (id, path) in ids {
+			match seen.get(id) {
+				Some(first) => {
+					let first = first.clone();
+					self.error(
+						path,
+						ConfigErrorKind::DuplicateId {
+							kind,
+							id: id.to_owned(),
+							first,
+						},
+					)
+				}
+				None => {
+					seen.insert(id, path);
+				}
+			}
+		}
+	}
+	fn check_index(
+		&mut self,
+		path: String,
+		kind: &'static str,
+		index: usize,
+		len: usize,
+	) -> bool {
+		if index >= len {
+			self.error(
+				path,
+				ConfigErrorKind::IndexOutOfRange { kind, index, len },
+			);
+			false
+		} else {
+			true
+		}
+	}
+	fn check_buffer_view(
+		&mut self,
+		path: &str,
+		view: &BufferViewConfig,
+		buffer_lengths: &[usize],
+	) {
+		let mut valid = true;
+		for (name, offset) in [
+			("buffer_offset", view.buffer_offset),
+			("offset", view.offset),
+		] {
+			if let Some(offset) = offset.filter(|offset| *offset < 0) {
+				self.error(
+					format!("{}.{}", path, name),
+					ConfigErrorKind::NegativeOffset(offset),
+				);
+				valid = false;
+			}
+		}
+		let buffer_path = format!("{}.buffer", path);
+		if !self.check_index(
+			buffer_path,
+			"buffer",
+			view.buffer,
+			buffer_lengths.len(),
+		) || !valid
+		{
+			return;
+		}
+		let end = view.buffer_offset.unwrap_or(0) as i64 + view.length as i64;
+		let buffer_length = buffer_lengths[view.buffer];
+		if end > buffer_length as i64 {
+			self.error(
+				format!("{}.length", path),
+				ConfigErrorKind::BufferViewOutOfRange { end, buffer_length },
+			);
+		}
+	}
+	fn check_lights(&mut self, path: &str, lights: &[LightConfig]) {
+		for (i, light) in lights.iter().enumerate() {
+			let path = format!("{}[{}]", path, i);
+			if let Some(range) = light.range().filter(|range| *range <= 0.0) {
+				self.error(
+					format!("{}.range", path),
+					ConfigErrorKind::NotPositive(range),
+				);
+			}
+			if let LightConfig::Spot {
+				inner_cone_angle: inner,
+				outer_cone_angle: outer,
+				..
+			} = *light
+			{
+				let right_angle = std::f32::consts::FRAC_PI_2;
+				if !(inner >= 0.0 && inner < outer && outer <= right_angle) {
+					self.error(
+						format!("{}.outer_cone_angle", path),
+						ConfigErrorKind::ConeAngles { inner, outer },
+					);
+				}
+			}
+			if let Some(shadow) = light.shadow() {
+				self.check_shadow(&format!("{}.shadow", path), shadow);
+			}
+		}
+	}
+	fn check_shadow(&mut self, path: &str, shadow: &ShadowConfig) {
+		if !(1..=MAX_SHADOW_RESOLUTION).contains(&shadow.resolution) {
+			self.error(
+				format!("{}.resolution", path),
+				ConfigErrorKind::ShadowResolution(shadow.resolution),
+			);
+		}
+		if !(1..=MAX_CASCADES).contains(&shadow.cascades) {
+			self.error(
+				format!("{}.cascades", path),
+				ConfigErrorKind::CascadeCount(shadow.cascades),
+			);
+		}
+		if shadow.distance <= 0.0 {
+			self.error(
+				format!("{}.distance", path),
+				ConfigErrorKind::NotPositive(shadow.distance),
+			);
+		}
+	}
+	fn check_environment(
+		&mut self,
+		path: &str,
+		environment: &EnvironmentConfig,
+	) {
+		let size = environment.size;
+		if !size.is_power_of_two()
+			|| !(MIN_ENVIRONMENT_SIZE..=MAX_ENVIRONMENT_SIZE).contains(&size)
+		{
+			self.error(
+				format!("{}.size", path),
+				ConfigErrorKind::EnvironmentSize(size),
+			);
+		}
+	}
+	fn check_nodes(
+		&mut self,
+		path: &str,
+		nodes: &[NodeConfig],
+		models: &HashMap<&str, String>,
+		lights: &HashMap<&str, String>,
+	) {
+		for (i, node) in nodes.iter().enumerate() {
+			let path = format!("{}[{}]", path, i);
+			if let Some(ref model) = node.model {
+				if !models.contains_key(model.as_str()) {
+					self.error(
+						format!("{}.model", path),
+						ConfigErrorKind::DanglingId {
+							kind: "model",
+							id: model.clone(),
+						},
+					);
+				}
+			}
+			if let Some(ref light) = node.light {
+				if !lights.contains_key(light.as_str()) {
+					self.error(
+						format!("{}.light", path),
+						ConfigErrorKind::DanglingId {
+							kind: "light",
+							id: light.clone(),
+						},
+					);
+				}
+			}
+			let children = format!("{}.children", path);
+			self.check_nodes(&children, &node.children, models, lights);
+		}
+	}
+}
+
+impl SceneConfig {
+	pub fn validate(&self) -> Result<(), ValidationErrors> {
+		let mut validator = Validator::default();
+
+		let mut cameras = HashMap::new();
+		validator.collect_ids(
+			"camera",
+			self.cameras
+				.iter()
+				.enumerate()
+				.map(|(i, c)| (c.id.as_str(), format!("$.cameras[{}].id", i))),
+			&mut cameras,
+		);
+		validator.check_index(
+			String::from("$.current_camera"),
+			"camera",
+			self.current_camera,
+			self.cameras.len(),
+		);
+		for (i, camera) in self.cameras.iter().enumerate() {
+			let values = match camera.projection {
+				ProjectionConfig::Perspective => vec![("fov_y", camera.fov_y)],
+				ProjectionConfig::Orthographic { height, zoom } => {
+					vec![
+						("projection.height", height),
+						("projection.zoom", zoom),
+					]
+				}
+			};
+			for (field, value) in values {
+				if value <= 0.0 {
+					validator.error(
+						format!("$.cameras[{}].{}", i, field),
+						ConfigErrorKind::NotPositive(value),
+					);
+				}
+			}
+		}
+
+		let mut shaders = HashMap::new();
+		validator.collect_ids(
+			"shader",
+			self.shaders
+				.iter()
+				.enumerate()
+				.map(|(i, s)| (s.id.as_str(), format!("$.shaders[{}].id", i))),
+			&mut shaders,
+		);
+		for (i, shader) in self.shaders.iter().enumerate() {
+			if shader.kind != "vertex" && shader.kind != "fragment" {
+				validator.error(
+					format!("$.shaders[{}].kind", i),
+					ConfigErrorKind::UnknownShaderKind(shader.kind.clone()),
+				);
+			}
+		}
+
+		let mut programs = HashMap::new();
+		validator.collect_ids(
+			"program",
+			self.programs
+				.iter()
+				.enumerate()
+				.map(|(i, p)| (p.id.as_str(), format!("$.programs[{}].id", i))),
+			&mut programs,
+		);
+		for (i, program) in self.programs.iter().enumerate() {
+			let stages = [
+				("vertex_id", &program.vertex_id, "vertex"),
+				("fragment_id", &program.fragment_id, "fragment"),
+			];
+			for (field, id, expected) in stages {
+				let path = format!("$.programs[{}].{}", i, field);
+				match self.shaders.iter().find(|s| s.id == *id) {
+					None => validator.error(
+						path,
+						ConfigErrorKind::DanglingId {
+							kind: "shader",
+							id: id.clone(),
+						},
+					),
+					Some(shader)
+						if shader.kind != expected
+							&& (shader.kind == "vertex"
+								|| shader.kind == "fragment") =>
+					{
+						validator.error(
+							path,
+							ConfigErrorKind::ShaderKindMismatch {
+								id: id.clone(),
+								expected,
+								found: shader.kind.clone(),
+							},
+						)
+					}
+					Some(_) => (),
+				}
+			}
+		}
+
+		// Loaded models and generated primitives share one namespace.
+		let mut models = HashMap::new();
+		validator.collect_ids(
+			"model",
+			self.models
+				.iter()
+				.enumerate()
+				.map(|(i, m)| (m.id.as_str(), format!("$.models[{}].id", i)))
+				.chain(
+					self.primitives.iter().enumerate().map(|(i, p)| {
+						(p.id(), format!("$.primitives[{}].id", i))
+					}),
+				),
+			&mut models,
+		);
+		for (i, primitive) in self.primitives.iter().enumerate() {
+			if let PrimitiveConfig::UvSphere {
+				vertical_subdivisions,
+				horizontal_subdivisions,
+				..
+			} = *primitive
+			{
+				let counts = [
+					("vertical_subdivisions", vertical_subdivisions, 2),
+					("horizontal_subdivisions", horizontal_subdivisions, 3),
+				];
+				for (field, found, minimum) in counts {
+					if found < minimum {
+						validator.error(
+							format!("$.primitives[{}].{}", i, field),
+							ConfigErrorKind::TooFewSubdivisions {
+								minimum,
+								found,
+							},
+						);
+					}
+				}
+			}
+		}
+
+		let mut lights = HashMap::new();
+		validator.collect_ids(
+			"light",
+			self.lights
+				.iter()
+				.enumerate()
+				.map(|(i, l)| (l.id(), format!("$.lights[{}].id", i))),
+			&mut lights,
+		);
+
+		validator.check_lights("$.lights", &self.lights);
+		if let Some(ref environment) = self.environment {
+			validator.check_environment("$.environment", environment);
+		}
+
+		validator.check_nodes("$.nodes", &self.nodes, &models, &lights);
+		validator.finish()
+	}
+}
+
+impl ModelConfig {
+	pub fn validate(&self) -> Result<(), ValidationErrors> {
+		let mut validator = Validator::default();
+		let buffer_lengths =
+			self.buffers.iter().map(|b| b.0.len()).collect::<Vec<_>>();
+
+		for (i, texture) in self.textures.iter().enumerate() {
+			validator.check_index(
+				format!("$.textures[{}].sampler", i),
+				"sampler",
+				texture.sampler,
+				self.samplers.len(),
+			);
+			// Checked as bytes, float pixels are only known when uploaded.
+			if let Err(reason) =
+				texture.format.gl_format(ComponentType::UnsignedByte)
+			{
+				validator.error(
+					format!("$.textures[{}].format", i),
+					ConfigErrorKind::TextureFormat(reason),
+				);
+			}
+		}
+		for (i, material) in self.materials.iter().enumerate() {
+			for (field, texture) in material.textures() {
+				if let Some(texture) = texture {
+					let path = format!("$.materials[{}].{}", i, field);
+					validator.check_index(
+						format!("{}.index", path),
+						"texture",
+						texture.index as usize,
+						self.textures.len(),
+					);
+					validator.check_index(
+						format!("{}.tex_coord", path),
+						"texture coordinate set",
+						texture.tex_coord as usize,
+						MAX_TEX_COORDS,
+					);
+				}
+			}
+		}
+		for (i, mesh) in self.meshes.iter().enumerate() {
+			if let Some(ref view) = mesh.index_view {
+				let path = format!("$.meshes[{}].index_view", i);
+				validator.check_buffer_view(&path, view, &buffer_lengths);
+			}
+			for (j, view) in mesh.buffer_views.iter().enumerate() {
+				let path = format!("$.meshes[{}].buffer_views[{}]", i, j);
+				validator.check_buffer_view(&path, view, &buffer_lengths);
+			}
+			if let Some(material) = mesh.material {
+				validator.check_index(
+					format!("$.meshes[{}].material", i),
+					"material",
+					material as usize,
+					self.materials.len(),
+				);
+			}
+		}
+		validator.check_lights("$.lights", &self.lights);
+		validator.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn scene(json: &str) -> SceneConfig {
+		serde_json::from_str(json).unwrap()
+	}
+	fn paths(errors: ValidationErrors) -> Vec<String> {
+		errors.0.into_iter().map(|e| e.path).collect()
+	}
+
+	#[test]
+	fn valid_scene_passes() {
+		let config = scene(include_str!("../../assets/scene_config.json"));
+		assert_eq!(config.validate(), Ok(()));
+	}
+	#[test]
+	fn scene_problems_are_reported_with_paths() {
+		let config = scene(
+			r#"{
+				"current_camera": 1,
+				"cameras": [{
+					"id": "main", "fov_y": 45, "z_near": 0.1, "z_far": 100,
+					"eye": [0, 0, 1], "center": [0, 0, 0], "up": [0, 1, 0]
+				}],
+				"shaders": [
+					{ "id": "vert", "kind": "vertex", "path": "a",
+					  "attributes": [], "uniforms": [] },
+					{ "id": "vert", "kind": "geometry", "path": "b",
+					  "attributes": [], "uniforms": [] }
+				],
+				"programs": [
+					{ "id": "pbr", "vertex_id": "vert", "fragment_id": "frag" }
+				],
+				"primitives": [{ "kind": "cube", "id": "box" }],
+				"models": [{ "id": "box", "uri": "box.glb" }],
+				"nodes": [{ "children": [{ "model": "ball", "light": "sun" }] }]
+			}"#,
+		);
+		let errors = config.validate().unwrap_err();
+		assert_eq!(
+			errors.0[0].to_string(),
+			"$.current_camera: camera index 1 is out of range, there are 1"
+		);
+		assert_eq!(
+			paths(errors),
+			vec![
+				"$.current_camera",
+				"$.shaders[1].id",
+				"$.shaders[1].kind",
+				"$.programs[0].fragment_id",
+				"$.primitives[0].id",
+				"$.nodes[0].children[0].model",
+				"$.nodes[0].children[0].light",
+			]
+		);
+	}
+	#[test]
+	fn orthographic_extents_must_be_positive() {
+		let config = scene(
+			r#"{
+				"current_camera": 0,
+				"cameras": [{
+					"id": "top", "z_near": 0.1, "z_far": 100,
+					"projection": {
+						"kind": "orthographic", "height": 0, "zoom": -1
+					},
+					"eye": [0, 1, 0], "center": [0, 0, 0], "up": [0, 0, -1]
+				}],
+				"shaders": [],
+				"programs": []
+			}"#,
+		);
+		let errors = config.validate().unwrap_err();
+		assert_eq!(
+			errors.0,
+			vec![
+				ConfigError {
+					path: String::from("$.cameras[0].projection.height"),
+					kind: ConfigErrorKind::NotPositive(0.0),
+				},
+				ConfigError {
+					path: String::from("$.cameras[0].projection.zoom"),
+					kind: ConfigErrorKind::NotPositive(-1.0),
+				},
+			]
+		);
+	}
+	#[test]
+	fn lights_shadows_and_environment_are_checked() {
+		let config = scene(
+			r#"{
+				"current_camera": 0,
+				"cameras": [],
+				"shaders": [],
+				"programs": [],
+				"environment": { "uri": "sky.hdr", "size": 48 },
+				"lights": [
+					{ "kind": "point", "id": "a", "position": [0, 0, 0],
+					  "range": 0 },
+					{ "kind": "spot", "id": "b", "position": [0, 0, 0],
+					  "direction": [0, -1, 0], "inner_cone_angle": 0.5,
+					  "outer_cone_angle": 0.4 },
+					{ "kind": "spot", "id": "c", "position": [0, 0, 0],
+					  "direction": [0, -1, 0], "range": 5 },
+					{ "kind": "directional", "id": "d",
+					  "direction": [0, -1, 0],
+					  "shadow": { "resolution": 0, "cascades": 5 } }
+				]
+			}"#,
+		);
+		let errors = config.validate().unwrap_err();
+		assert_eq!(
+			errors.0[2],
+			ConfigError {
+				path: String::from("$.lights[1].outer_cone_angle"),
+				kind: ConfigErrorKind::ConeAngles {
+					inner: 0.5,
+					outer: 0.4,
+				},
+			}
+		);
+		assert_eq!(
+			paths(errors),
+			vec![
+				"$.current_camera",
+				"$.lights[0].range",
+				"$.lights[1].outer_cone_angle",
+				"$.lights[3].shadow.resolution",
+				"$.lights[3].shadow.cascades",
+				"$.environment.size",
+			]
+		);
+	}
+	#[test]
+	fn environment_size_is_capped() {
+		let config = scene(
+			r#"{
+				"current_camera": 0,
+				"cameras": [],
+				"shaders": [],
+				"programs": [],
+				"environment": { "uri": "sky.hdr", "size": 256 }
+			}"#,
+		);
+		let errors = config.validate().unwrap_err();
+		assert_eq!(
+			errors.0[1].to_string(),
+			"$.environment.size: environment size 256 is not a power of two \
+			 between 4 and 128"
+		);
+	}
+	#[test]
+	fn program_stage_must_match_shader_kind() {
+		let config = scene(
+			r#"{
+				"current_camera": 0,
+				"cameras": [],
+				"shaders": [
+					{ "id": "frag", "kind": "fragment", "path": "a",
+					  "attributes": [], "uniforms": [] }
+				],
+				"programs": [
+					{ "id": "p", "vertex_id": "frag", "fragment_id": "frag" }
+				]
+			}"#,
+		);
+		let errors = config.validate().unwrap_err();
+		assert_eq!(
+			errors.0[1],
+			ConfigError {
+				path: String::from("$.programs[0].vertex_id"),
+				kind: ConfigErrorKind::ShaderKindMismatch {
+					id: String::from("frag"),
+					expected: "vertex",
+					found: String::from("fragment"),
+				},
+			}
+		);
+	}
+	#[test]
+	fn model_buffer_views_must_fit_their_buffers() {
+		let config: ModelConfig = serde_json::from_str(
+			r#"{
+				"id": "m",
+				"buffers": [[0, 0, 0, 0, 0, 0, 0, 0]],
+				"meshes": [{
+					"index_view": {
+						"id": "INDICES", "buffer": 0, "length": 8,
+						"buffer_offset": 4, "offset": null, "stride": null,
+						"component_size": 2, "component_count": 1,
+						"component_type": 5123
+					},
+					"buffer_views": [{
+						"id": "POSITION", "buffer": 1, "length": 4,
+						"buffer_offset": null, "offset": -4, "stride": null,
+						"component_size": 4, "component_count": 3,
+						"component_type": 5126
+					}],
+					"material": 1
+				}],
+				"materials": [{
+					"id": "m", "normal": { "index": 1, "tex_coord": 2 }
+				}],
+				"texture_wrapper_id": "",
+				"samplers": [],
+				"textures": [{
+					"source": 0, "sampler": 0,
+					"format": { "channels": "r", "color_space": "srgb" }
+				}]
+			}"#,
+		)
+		.unwrap();
+		let errors = config.validate().unwrap_err();
+		assert_eq!(
+			errors.to_string(),
+			"$.textures[0].sampler: sampler index 0 is out of range, there \
+			 are 0\n\
+			 $.textures[0].format: sRGB textures need RGB or RGBA channels\n\
+			 $.materials[0].normal.index: texture index 1 is out of range, \
+			 there are 1\n\
+			 $.materials[0].normal.tex_coord: texture coordinate set index 2 \
+			 is out of range, there are 2\n\
+			 $.meshes[0].index_view.length: buffer view ends at byte 12 but \
+			 the buffer is 8 bytes\n\
+			 $.meshes[0].buffer_views[0].offset: offset -4 is negative\n\
+			 $.meshes[0].buffer_views[0].buffer: buffer index 1 is out of \
+			 range, there are 1\n\
+			 $.meshes[0].material: material index 1 is out of range, there \
+			 are 1"
+		);
+	}
+}
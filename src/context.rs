@@ -5,6 +5,8 @@
 //
 // Description:
 
+use std::cell::Cell;
+
 use wasm_bindgen::JsCast;
 use web_sys::{
 	Document,
@@ -14,12 +16,16 @@ use web_sys::{
 };
 
 use crate::config::engine_config::EngineConfig;
+use crate::viewport::CanvasSize;
 
 pub struct Context {
 	pub window: Window,
 	pub document: Document,
 	pub canvas: HtmlCanvasElement,
 	pub gl: WebGl2RenderingContext,
+
+	fixed_aspect: Option<f32>,
+	size: Cell<CanvasSize>,
 }
 impl Context {
 	pub fn new(engine_config: &EngineConfig) -> Result<Self, &'static str> {
@@ -42,19 +48,69 @@ impl Context {
 			document,
 			canvas,
 			gl,
+			fixed_aspect: engine_config.fixed_aspect,
+			size: Cell::new(CanvasSize::default()),
 		};
-		context.set_size(engine_config.width, engine_config.height);
+		context.set_size(engine_config.width, engine_config.height)?;
 
 		Ok(context)
 	}
-	pub fn set_size(&self, width: u32, height: u32) {
-		self.canvas.set_width(width);
-		self.canvas.set_height(height);
-		let width = self.canvas.client_width();
-		let height = self.canvas.client_height();
-		self.canvas.set_width(width as u32);
-		self.canvas.set_height(height as u32);
-		self.gl.viewport(0, 0, width, height);
+	// Sets the canvas's size on the page in CSS pixels, unless an inline
+	// style already does, and resizes to match.
+	pub fn set_size(
+		&self,
+		width: u32,
+		height: u32,
+	) -> Result<CanvasSize, &'static str> {
+		let style = self.canvas.style();
+		let sized = |property| {
+			!style
+				.get_property_value(property)
+				.unwrap_or_default()
+				.is_empty()
+		};
+		if !sized("width") && !sized("height") {
+			style
+				.set_property("width", &format!("{}px", width))
+				.and_then(|_| {
+					style.set_property("height", &format!("{}px", height))
+				})
+				.map_err(|_err| "Failed to set the canvas size")?;
+		}
+		Ok(self.resize().unwrap_or_else(|| self.size.get()))
+	}
+	// Matches the canvas's drawing buffer to its size on the page in device
+	// pixels and updates the viewport. Cheap enough to call every frame,
+	// which also catches devicePixelRatio changing when the window is zoomed
+	// or moved to another screen. Returns the new size if it changed.
+	pub fn resize(&self) -> Option<CanvasSize> {
+		let size = CanvasSize::new(
+			self.canvas.client_width(),
+			self.canvas.client_height(),
+			self.window.device_pixel_ratio(),
+			self.fixed_aspect,
+		);
+		if size == self.size.get() {
+			return None;
+		}
+		self.size.set(size);
+		if self.canvas.width() != size.width {
+			self.canvas.set_width(size.width);
+		}
+		if self.canvas.height() != size.height {
+			self.canvas.set_height(size.height);
+		}
+		let viewport = size.viewport;
+		self.gl.viewport(
+			viewport.x,
+			viewport.y,
+			viewport.width,
+			viewport.height,
+		);
+		Some(size)
+	}
+	pub fn size(&self) -> CanvasSize {
+		self.size.get()
 	}
 	pub fn now(&self) -> Result<f64, &'static str> {
 		Ok(self
@@ -0,0 +1,132 @@
+// File: src/environment/brdf.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// GGX importance sampling shared by the specular prefilter and the BRDF
+// lookup table. The table holds the scale and bias to F0 of the split sum
+// approximation, indexed by N.V across and roughness up.
+
+use std::f32::consts::PI;
+
+use cgmath::{
+	InnerSpace,
+	Vector3,
+};
+
+// The `i`th of `count` points spread evenly over the unit square.
+pub fn hammersley(i: u32, count: u32) -> (f32, f32) {
+	let radical_inverse = i.reverse_bits() as f32 / 2f32.powi(32);
+	(i as f32 / count as f32, radical_inverse)
+}
+
+// A half vector around `n` distributed like GGX's normals for
+// `roughness`, from a point `xi` on the unit square.
+pub fn importance_sample_ggx(
+	xi: (f32, f32),
+	n: Vector3<f32>,
+	roughness: f32,
+) -> Vector3<f32> {
+	let a = roughness * roughness;
+	let phi = 2.0 * PI * xi.0;
+	let cos_theta = ((1.0 - xi.1) / (1.0 + (a * a - 1.0) * xi.1)).sqrt();
+	let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+	let (tangent, bitangent) = tangent_frame(n);
+	(tangent * (phi.cos() * sin_theta)
+		+ bitangent * (phi.sin() * sin_theta)
+		+ n * cos_theta)
+		.normalize()
+}
+
+// Two unit vectors perpendicular to `n` and each other.
+pub fn tangent_frame(n: Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+	let up = if n.z.abs() < 0.999 {
+		Vector3::unit_z()
+	} else {
+		Vector3::unit_x()
+	};
+	let tangent = up.cross(n).normalize();
+	(tangent, n.cross(tangent))
+}
+
+// GGX's normal distribution, as DistributionGGX in pbr.frag.
+pub fn ggx_distribution(n_dot_h: f32, roughness: f32) -> f32 {
+	let a = roughness * roughness;
+	let a2 = a * a;
+	let denom = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+	a2 / (PI * denom * denom).max(1e-7)
+}
+
+// Smith's geometry term with the k image based lighting uses.
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+	let k = roughness * roughness / 2.0;
+	let schlick = |n_dot_x: f32| n_dot_x / (n_dot_x * (1.0 - k) + k);
+	schlick(n_dot_v) * schlick(n_dot_l)
+}
+
+// The scale and bias to F0 for a surface at `n_dot_v` with `roughness`.
+pub fn integrate_brdf(n_dot_v: f32, roughness: f32, samples: u32) -> [f32; 2] {
+	let n_dot_v = n_dot_v.max(1e-4);
+	let v = Vector3::new((1.0 - n_dot_v * n_dot_v).sqrt(), 0.0, n_dot_v);
+	let n = Vector3::unit_z();
+	let (mut scale, mut bias) = (0.0, 0.0);
+	for i in 0..samples {
+		let h = importance_sample_ggx(hammersley(i, samples), n, roughness);
+		let l = h * (2.0 * v.dot(h)) - v;
+		let n_dot_l = l.z.max(0.0);
+		if n_dot_l <= 0.0 {
+			continue;
+		}
+		let n_dot_h = h.z.max(0.0);
+		let v_dot_h = v.dot(h).max(0.0);
+		let g = geometry_smith(n_dot_v, n_dot_l, roughness);
+		let visibility = g * v_dot_h / (n_dot_h * n_dot_v).max(1e-7);
+		let fresnel = (1.0 - v_dot_h).powi(5);
+		scale += (1.0 - fresnel) * visibility;
+		bias += fresnel * visibility;
+	}
+	[scale / samples as f32, bias / samples as f32]
+}
+
+// `size` by `size` texels, rows from roughness zero up to one.
+pub fn brdf_lut(size: usize, samples: u32) -> Vec<[f32; 2]> {
+	let center = |i: usize| (i as f32 + 0.5) / size as f32;
+	(0..size * size)
+		.map(|i| integrate_brdf(center(i % size), center(i / size), samples))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cgmath::assert_abs_diff_eq;
+
+	#[test]
+	fn ggx_samples_stay_around_the_normal() {
+		let n = Vector3::new(1.0, 1.0, 0.0).normalize();
+		for i in 0..16 {
+			let h = importance_sample_ggx(hammersley(i, 16), n, 0.5);
+			assert_abs_diff_eq!(h.magnitude(), 1.0, epsilon = 1e-5);
+			assert!(h.dot(n) > 0.0);
+		}
+		// Smooth surfaces only reflect along the normal.
+		let h = importance_sample_ggx(hammersley(3, 16), n, 0.0);
+		assert_abs_diff_eq!(h, n, epsilon = 1e-5);
+	}
+	#[test]
+	fn lut_scales_toward_full_reflection_for_smooth_surfaces() {
+		let lut = brdf_lut(8, 64);
+		assert_eq!(lut.len(), 64);
+		for texel in lut.iter() {
+			assert!(texel.iter().all(|x| (0.0..=1.0).contains(x)));
+		}
+		// Smooth and facing the viewer nearly all light is reflected and
+		// the scale carries it.
+		let [scale, bias] = lut[7];
+		assert!(scale + bias > 0.9 && scale > bias);
+		// Rough and grazing much is lost.
+		let [scale, bias] = lut[7 * 8];
+		assert!(scale + bias < 0.8);
+	}
+}
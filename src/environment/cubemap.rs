@@ -0,0 +1,306 @@
+// File: src/environment/cubemap.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Cube maps on the CPU, for turning an equirectangular image into the
+// diffuse irradiance and prefiltered specular maps pbr.frag samples.
+
+use std::f32::consts::PI;
+
+use cgmath::{
+	InnerSpace,
+	Vector3,
+};
+
+use super::{
+	brdf::{
+		ggx_distribution,
+		hammersley,
+		importance_sample_ggx,
+		tangent_frame,
+	},
+	hdr::HdrImage,
+	MAX_ENVIRONMENT_SIZE,
+};
+
+// The direction through `s` and `t` of `face`, in GL's face order +X, -X,
+// +Y, -Y, +Z, -Z. `s` and `t` run from zero to one across the face as
+// texture coordinates do.
+pub fn face_direction(face: usize, s: f32, t: f32) -> Vector3<f32> {
+	let (a, b) = (2.0 * s - 1.0, 2.0 * t - 1.0);
+	let dir = match face {
+		0 => Vector3::new(1.0, -b, -a),
+		1 => Vector3::new(-1.0, -b, a),
+		2 => Vector3::new(a, 1.0, b),
+		3 => Vector3::new(a, -1.0, -b),
+		4 => Vector3::new(a, -b, 1.0),
+		_ => Vector3::new(-a, -b, -1.0),
+	};
+	dir.normalize()
+}
+// The face `dir` points through and where, the inverse of face_direction.
+pub fn face_coordinates(dir: Vector3<f32>) -> (usize, f32, f32) {
+	let abs = dir.map(f32::abs);
+	let (face, sc, tc, major) = if abs.x >= abs.y && abs.x >= abs.z {
+		if dir.x > 0.0 {
+			(0, -dir.z, -dir.y, abs.x)
+		} else {
+			(1, dir.z, -dir.y, abs.x)
+		}
+	} else if abs.y >= abs.z {
+		if dir.y > 0.0 {
+			(2, dir.x, dir.z, abs.y)
+		} else {
+			(3, dir.x, -dir.z, abs.y)
+		}
+	} else if dir.z > 0.0 {
+		(4, dir.x, -dir.y, abs.z)
+	} else {
+		(5, -dir.x, -dir.y, abs.z)
+	};
+	(face, (sc / major + 1.0) / 2.0, (tc / major + 1.0) / 2.0)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubeMap {
+	pub size: usize,
+	// Six faces of `size` by `size` texels in face_direction's order,
+	// rows from t of zero.
+	pub faces: Vec<Vec<[f32; 3]>>,
+}
+impl CubeMap {
+	// Fills each texel with `f` of the direction through its center.
+	pub fn from_fn(size: usize, f: impl Fn(Vector3<f32>) -> [f32; 3]) -> Self {
+		let center = |i: usize| (i as f32 + 0.5) / size as f32;
+		let faces = (0..6)
+			.map(|face| {
+				(0..size * size)
+					.map(|i| {
+						f(face_direction(
+							face,
+							center(i % size),
+							center(i / size),
+						))
+					})
+					.collect()
+			})
+			.collect();
+		Self { size, faces }
+	}
+	// At least `size` wide, then box filtered down to it so small bright
+	// spots aren't missed. `size` must be a power of two.
+	pub fn from_equirect(image: &HdrImage, size: usize) -> Self {
+		let start = (image.width / 4)
+			.next_power_of_two()
+			.clamp(size, MAX_ENVIRONMENT_SIZE as usize);
+		let mut cube = Self::from_fn(start, |dir| image.sample(dir));
+		while cube.size > size {
+			cube = cube.downsampled();
+		}
+		cube
+	}
+
+	// Bilinear within the face `dir` points through.
+	pub fn sample(&self, dir: Vector3<f32>) -> [f32; 3] {
+		let (face, s, t) = face_coordinates(dir);
+		let texels = &self.faces[face];
+		let last = self.size - 1;
+		let x = (s * self.size as f32 - 0.5).clamp(0.0, last as f32);
+		let y = (t * self.size as f32 - 0.5).clamp(0.0, last as f32);
+		let (x0, y0) = (x as usize, y as usize);
+		let (x1, y1) = ((x0 + 1).min(last), (y0 + 1).min(last));
+		let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+		let texel = |x: usize, y: usize| texels[y * self.size + x];
+		let mut color = [0.0; 3];
+		for (c, color) in color.iter_mut().enumerate() {
+			let top = texel(x0, y0)[c] * (1.0 - fx) + texel(x1, y0)[c] * fx;
+			let bottom = texel(x0, y1)[c] * (1.0 - fx) + texel(x1, y1)[c] * fx;
+			*color = top * (1.0 - fy) + bottom * fy;
+		}
+		color
+	}
+	// Half the size, each texel the average of the four it covers.
+	pub fn downsampled(&self) -> Self {
+		let size = (self.size / 2).max(1);
+		let faces = self
+			.faces
+			.iter()
+			.map(|texels| {
+				let texel = |x: usize, y: usize| {
+					let x = x.min(self.size - 1);
+					let y = y.min(self.size - 1);
+					texels[y * self.size + x]
+				};
+				(0..size * size)
+					.map(|i| {
+						let (x, y) = (i % size * 2, i / size * 2);
+						let mut color = [0.0; 3];
+						for (c, color) in color.iter_mut().enumerate() {
+							*color = (texel(x, y)[c]
+								+ texel(x + 1, y)[c] + texel(x, y + 1)
+								[c] + texel(x + 1, y + 1)[c])
+								/ 4.0;
+						}
+						color
+					})
+					.collect()
+			})
+			.collect();
+		Self { size, faces }
+	}
+	// `face`'s texels as consecutive RGB floats, for uploading.
+	pub fn face_data(&self, face: usize) -> Vec<f32> {
+		self.faces[face].iter().flatten().copied().collect()
+	}
+}
+
+// The cosine weighted average of `source` over the hemisphere around each
+// texel's direction, so a diffuse surface's light is one lookup. Divided
+// by pi, pbr.frag multiplies it by albedo alone.
+pub fn irradiance(source: &CubeMap, size: usize) -> CubeMap {
+	const PHI_STEPS: usize = 32;
+	const THETA_STEPS: usize = 8;
+	CubeMap::from_fn(size, |n| {
+		let (tangent, bitangent) = tangent_frame(n);
+		let mut sum = [0.0; 3];
+		for i in 0..PHI_STEPS {
+			let phi = (i as f32 + 0.5) / PHI_STEPS as f32 * 2.0 * PI;
+			for j in 0..THETA_STEPS {
+				let theta = (j as f32 + 0.5) / THETA_STEPS as f32 * PI / 2.0;
+				let dir = (tangent * phi.cos() + bitangent * phi.sin())
+					* theta.sin() + n * theta.cos();
+				let weight = theta.cos() * theta.sin();
+				let radiance = source.sample(dir);
+				for (sum, radiance) in sum.iter_mut().zip(radiance.iter()) {
+					*sum += radiance * weight;
+				}
+			}
+		}
+		// The integral is the sum times the step areas, 2pi over the phi
+		// steps and pi / 2 over the theta steps, here divided by pi.
+		sum.map(|sum| sum * PI / (PHI_STEPS * THETA_STEPS) as f32)
+	})
+}
+
+// `levels` mips of `source` blurred for roughness from zero to one, with
+// GGX importance sampling. Samples come from a blurrier copy of `source`
+// the more of it they stand for, which keeps bright spots from speckling.
+pub fn prefiltered(
+	source: &CubeMap,
+	levels: usize,
+	samples: u32,
+) -> Vec<CubeMap> {
+	let mut chain = vec![source.clone()];
+	while chain.last().is_some_and(|cube| cube.size > 1) {
+		let next = chain.last().map(CubeMap::downsampled);
+		chain.extend(next);
+	}
+	let texel_solid_angle = 4.0 * PI / (6 * source.size * source.size) as f32;
+
+	(0..levels)
+		.map(|level| {
+			if level == 0 {
+				return source.clone();
+			}
+			let size = (source.size >> level).max(1);
+			let roughness = level as f32 / (levels - 1).max(1) as f32;
+			CubeMap::from_fn(size, |n| {
+				let mut sum = [0.0; 3];
+				let mut weight = 0.0;
+				for i in 0..samples {
+					let h = importance_sample_ggx(
+						hammersley(i, samples),
+						n,
+						roughness,
+					);
+					let l = h * (2.0 * n.dot(h)) - n;
+					let n_dot_l = n.dot(l);
+					if n_dot_l <= 0.0 {
+						continue;
+					}
+					// With the view along the normal the pdf is D / 4.
+					let n_dot_h = n.dot(h).max(0.0);
+					let pdf = ggx_distribution(n_dot_h, roughness) / 4.0 + 1e-4;
+					let sample_solid_angle = 1.0 / (samples as f32 * pdf);
+					let lod =
+						0.5 * (sample_solid_angle / texel_solid_angle).log2();
+					let lod =
+						(lod.max(0.0).round() as usize).min(chain.len() - 1);
+					let radiance = chain[lod].sample(l);
+					for (sum, radiance) in sum.iter_mut().zip(radiance.iter()) {
+						*sum += radiance * n_dot_l;
+					}
+					weight += n_dot_l;
+				}
+				sum.map(|sum| sum / weight.max(1e-4))
+			})
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cgmath::assert_abs_diff_eq;
+
+	#[test]
+	fn face_coordinates_invert_face_direction() {
+		for face in 0..6 {
+			for (s, t) in [(0.5, 0.5), (0.1, 0.8), (0.9, 0.3)] {
+				let dir = face_direction(face, s, t);
+				let (found, found_s, found_t) = face_coordinates(dir);
+				assert_eq!(found, face);
+				assert_abs_diff_eq!(found_s, s, epsilon = 1e-5);
+				assert_abs_diff_eq!(found_t, t, epsilon = 1e-5);
+			}
+		}
+		assert_eq!(face_coordinates(Vector3::unit_y()).0, 2);
+		assert_eq!(face_coordinates(-Vector3::unit_z()).0, 5);
+	}
+	#[test]
+	fn converts_and_filters_equirectangular_images() {
+		// Bright above the horizon, dark below.
+		let image = HdrImage {
+			width: 64,
+			height: 32,
+			pixels: (0..64 * 32)
+				.map(|i| if i / 64 < 16 { [2.0; 3] } else { [0.0; 3] })
+				.collect(),
+		};
+		let cube = CubeMap::from_equirect(&image, 8);
+		assert_eq!(cube.size, 8);
+		assert_eq!(cube.sample(Vector3::unit_y()), [2.0; 3]);
+		assert_eq!(cube.sample(-Vector3::unit_y()), [0.0; 3]);
+		assert_eq!(cube.face_data(2).len(), 8 * 8 * 3);
+
+		// A surface facing up sees only the bright half, one facing
+		// sideways half of it.
+		let irradiance = irradiance(&cube, 4);
+		assert_abs_diff_eq!(
+			irradiance.sample(Vector3::unit_y())[0],
+			2.0,
+			epsilon = 0.1
+		);
+		assert_abs_diff_eq!(
+			irradiance.sample(Vector3::unit_x())[0],
+			1.0,
+			epsilon = 0.1
+		);
+	}
+	#[test]
+	fn prefiltering_keeps_a_uniform_environment() {
+		let cube = CubeMap::from_fn(16, |_| [1.0, 0.5, 0.25]);
+		let levels = prefiltered(&cube, 4, 32);
+		assert_eq!(
+			levels.iter().map(|cube| cube.size).collect::<Vec<_>>(),
+			vec![16, 8, 4, 2]
+		);
+		for level in levels.iter() {
+			let color = level.sample(Vector3::new(0.3, -0.2, 0.9));
+			assert_abs_diff_eq!(color[0], 1.0, epsilon = 1e-4);
+			assert_abs_diff_eq!(color[2], 0.25, epsilon = 1e-4);
+		}
+	}
+}
@@ -0,0 +1,268 @@
+// File: src/environment/hdr.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Reads Radiance RGBE (.hdr) images, the usual format for equirectangular
+// environment maps. Flat and run length encoded scanlines are supported,
+// in the standard top to bottom, left to right orientation.
+
+use std::{
+	f32::consts::PI,
+	ops::Range,
+};
+
+use cgmath::{
+	InnerSpace,
+	Vector3,
+};
+
+const TRUNCATED: &str = "The .hdr image is truncated";
+// An 8K equirectangular map, about 400MB once decoded.
+const MAX_PIXELS: usize = 8192 * 4096;
+// Scanlines outside these widths can't be run length encoded.
+const RLE_WIDTHS: Range<usize> = 8..0x8000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HdrImage {
+	pub width: usize,
+	pub height: usize,
+	// Linear RGB, row by row from the top.
+	pub pixels: Vec<[f32; 3]>,
+}
+impl HdrImage {
+	pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+		let mut pos = 0;
+		let mut line = || -> Result<&str, &'static str> {
+			let rest = bytes.get(pos..).ok_or(TRUNCATED)?;
+			let end = rest.iter().position(|b| *b == b'\n').ok_or(TRUNCATED)?;
+			pos += end + 1;
+			std::str::from_utf8(&rest[..end])
+				.map_err(|_| "The .hdr header is not text")
+		};
+
+		if !line()?.starts_with("#?") {
+			return Err("Not a Radiance .hdr image");
+		}
+		loop {
+			let header = line()?;
+			if header.is_empty() {
+				break;
+			}
+			if let Some(format) = header.strip_prefix("FORMAT=") {
+				if format != "32-bit_rle_rgbe" {
+					return Err("Only RGBE .hdr images are supported");
+				}
+			}
+		}
+		let (width, height): (usize, usize) =
+			match line()?.split_whitespace().collect::<Vec<_>>()[..] {
+				["-Y", height, "+X", width] => (
+					width.parse().map_err(|_| "Bad .hdr image width")?,
+					height.parse().map_err(|_| "Bad .hdr image height")?,
+				),
+				_ => return Err("Unsupported .hdr image orientation"),
+			};
+		if width == 0 || height == 0 {
+			return Err("The .hdr image has no pixels");
+		}
+		let pixel_count = width
+			.checked_mul(height)
+			.filter(|count| *count <= MAX_PIXELS)
+			.ok_or("The .hdr image is too large")?;
+		// Checked before reserving the pixels so a short file can't claim
+		// hundreds of megabytes.
+		if bytes.len() - pos < min_scanline_length(width) * height {
+			return Err(TRUNCATED);
+		}
+
+		let mut pixels = Vec::with_capacity(pixel_count);
+		let mut scanline = vec![[0; 4]; width];
+		for _ in 0..height {
+			read_scanline(bytes, &mut pos, &mut scanline)?;
+			pixels.extend(scanline.iter().map(rgbe_to_rgb));
+		}
+		Ok(Self {
+			width,
+			height,
+			pixels,
+		})
+	}
+
+	// The radiance arriving from `dir`, with +Y up the middle of the image
+	// and -Z at its center.
+	pub fn sample(&self, dir: Vector3<f32>) -> [f32; 3] {
+		let dir = dir.normalize();
+		let u = 0.5 + dir.x.atan2(-dir.z) / (2.0 * PI);
+		let v = dir.y.clamp(-1.0, 1.0).acos() / PI;
+		// Straight behind gives a u of one, which wraps to the first column.
+		let x = (u * self.width as f32) as usize % self.width;
+		let y = ((v * self.height as f32) as usize).min(self.height - 1);
+		self.pixels[y * self.width + x]
+	}
+}
+
+pub fn rgbe_to_rgb(rgbe: &[u8; 4]) -> [f32; 3] {
+	if rgbe[3] == 0 {
+		return [0.0; 3];
+	}
+	let scale = 2f32.powi(rgbe[3] as i32 - (128 + 8));
+	[
+		rgbe[0] as f32 * scale,
+		rgbe[1] as f32 * scale,
+		rgbe[2] as f32 * scale,
+	]
+}
+
+fn next_byte(bytes: &[u8], pos: &mut usize) -> Result<u8, &'static str> {
+	let byte = *bytes.get(*pos).ok_or(TRUNCATED)?;
+	*pos += 1;
+	Ok(byte)
+}
+// Bytes in the smallest encoding of a scanline, with every channel in
+// runs of the longest length.
+fn min_scanline_length(width: usize) -> usize {
+	if RLE_WIDTHS.contains(&width) {
+		4 + 4 * 2 * width.div_ceil(127)
+	} else {
+		4 * width
+	}
+}
+fn read_scanline(
+	bytes: &[u8],
+	pos: &mut usize,
+	scanline: &mut [[u8; 4]],
+) -> Result<(), &'static str> {
+	let width = scanline.len();
+	let header = bytes.get(*pos..*pos + 4).ok_or(TRUNCATED)?;
+	let run_length_encoded = RLE_WIDTHS.contains(&width)
+		&& header[0] == 2
+		&& header[1] == 2
+		&& header[2] & 0x80 == 0;
+	if !run_length_encoded {
+		for pixel in scanline.iter_mut() {
+			*pixel = [
+				next_byte(bytes, pos)?,
+				next_byte(bytes, pos)?,
+				next_byte(bytes, pos)?,
+				next_byte(bytes, pos)?,
+			];
+		}
+		return Ok(());
+	}
+	if (header[2] as usize) << 8 | header[3] as usize != width {
+		return Err("An .hdr scanline's width doesn't match the image");
+	}
+	*pos += 4;
+
+	// Each channel is encoded on its own, as runs of one value or counts
+	// of literal values.
+	for channel in 0..4 {
+		let mut x = 0;
+		while x < width {
+			let count = next_byte(bytes, pos)? as usize;
+			let (count, run) = if count > 128 {
+				(count - 128, Some(next_byte(bytes, pos)?))
+			} else {
+				(count, None)
+			};
+			if count == 0 || x + count > width {
+				return Err("An .hdr scanline overruns the image");
+			}
+			for pixel in scanline[x..x + count].iter_mut() {
+				pixel[channel] = match run {
+					Some(value) => value,
+					None => next_byte(bytes, pos)?,
+				};
+			}
+			x += count;
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn header(width: usize, height: usize) -> Vec<u8> {
+		format!(
+			"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+			height, width
+		)
+		.into_bytes()
+	}
+
+	#[test]
+	fn reads_flat_scanlines() {
+		let mut bytes = header(2, 1);
+		bytes.extend_from_slice(&[128, 64, 32, 129, 0, 0, 0, 0]);
+		let image = HdrImage::parse(&bytes).unwrap();
+		assert_eq!((image.width, image.height), (2, 1));
+		assert_eq!(image.pixels, vec![[1.0, 0.5, 0.25], [0.0; 3]]);
+
+		assert!(HdrImage::parse(&bytes[..bytes.len() - 1]).is_err());
+		assert!(HdrImage::parse(b"P6\n").is_err());
+	}
+	#[test]
+	fn reads_run_length_encoded_scanlines() {
+		let mut bytes = header(8, 1);
+		bytes.extend_from_slice(&[2, 2, 0, 8]);
+		// Red is one run, green literals, blue a run and literals.
+		bytes.extend_from_slice(&[128 + 8, 64]);
+		bytes.extend_from_slice(&[8, 0, 16, 32, 48, 64, 80, 96, 112]);
+		bytes.extend_from_slice(&[128 + 6, 0, 2, 128, 255]);
+		bytes.extend_from_slice(&[128 + 8, 136]);
+		let image = HdrImage::parse(&bytes).unwrap();
+		assert_eq!(image.pixels[0], [64.0, 0.0, 0.0]);
+		assert_eq!(image.pixels[7], [64.0, 112.0, 255.0]);
+
+		// A run past the end of the scanline.
+		let end = bytes.len() - 2;
+		bytes[end] = 128 + 9;
+		assert!(HdrImage::parse(&bytes).is_err());
+	}
+	#[test]
+	fn rejects_empty_images() {
+		assert_eq!(
+			HdrImage::parse(&header(0, 1)),
+			Err("The .hdr image has no pixels")
+		);
+		assert_eq!(
+			HdrImage::parse(&header(1, 0)),
+			Err("The .hdr image has no pixels")
+		);
+	}
+	#[test]
+	fn rejects_oversized_images() {
+		assert_eq!(
+			HdrImage::parse(&header(16384, 8192)),
+			Err("The .hdr image is too large")
+		);
+		assert_eq!(
+			HdrImage::parse(&header(2, usize::MAX)),
+			Err("The .hdr image is too large")
+		);
+	}
+	#[test]
+	fn rejects_headers_larger_than_the_file() {
+		let mut bytes = header(8192, 4096);
+		bytes.extend_from_slice(&[2, 2, 32, 0]);
+		assert_eq!(HdrImage::parse(&bytes), Err(TRUNCATED));
+		assert_eq!(HdrImage::parse(&header(4, 1)), Err(TRUNCATED));
+	}
+	#[test]
+	fn samples_up_from_the_top_row() {
+		let image = HdrImage {
+			width: 4,
+			height: 2,
+			pixels: (0..8).map(|i| [i as f32; 3]).collect(),
+		};
+		assert!(image.sample(Vector3::unit_y())[0] < 4.0);
+		assert!(image.sample(-Vector3::unit_y())[0] >= 4.0);
+		// -Z is the middle column of the image.
+		assert_eq!(image.sample(Vector3::new(0.0, 0.1, -1.0))[0], 2.0);
+		assert_eq!(image.sample(Vector3::new(0.0, 0.1, 1.0))[0], 0.0);
+	}
+}
@@ -0,0 +1,258 @@
+// File: src/environment/mod.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Image based lighting. An equirectangular HDR image is turned into a
+// diffuse irradiance cube map, a specular cube map prefiltered for each
+// roughness across its mips and a BRDF lookup table, which pbr.frag
+// combines with the split sum approximation.
+
+pub mod brdf;
+pub mod cubemap;
+pub mod hdr;
+
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+	backend::GlBackend,
+	program::UniformLocations,
+};
+
+use cubemap::CubeMap;
+use hdr::HdrImage;
+
+type Gl = WebGl2RenderingContext;
+
+// The irradiance map's unit, the prefiltered map and BRDF lookup table
+// take the two after it. Clear of the units materials bind.
+pub const ENVIRONMENT_TEXTURE_UNIT: u32 = 5;
+// Smallest prefiltered mip, rougher surfaces would only blur further.
+const MIN_PREFILTERED_SIZE: usize = 4;
+// Bounds on the prefiltered map's width, the largest cube map an
+// equirectangular image is converted to. Prefiltering runs on the CPU and
+// each doubling costs about four times as much, 256 already takes seconds.
+pub const MIN_ENVIRONMENT_SIZE: u32 = MIN_PREFILTERED_SIZE as u32;
+pub const MAX_ENVIRONMENT_SIZE: u32 = 128;
+const IRRADIANCE_SIZE: usize = 16;
+const PREFILTER_SAMPLES: u32 = 64;
+const BRDF_LUT_SIZE: usize = 64;
+const BRDF_SAMPLES: u32 = 128;
+
+// The environment's maps on the CPU, see Environment for uploading them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvironmentMaps {
+	pub irradiance: CubeMap,
+	// Mip levels from smooth to rough.
+	pub prefiltered: Vec<CubeMap>,
+	pub brdf_lut: Vec<[f32; 2]>,
+}
+impl EnvironmentMaps {
+	// `size` is the prefiltered map's width, a power of two.
+	pub fn new(image: &HdrImage, size: usize) -> Self {
+		let source = CubeMap::from_equirect(image, size);
+		let mut small = source.clone();
+		while small.size > IRRADIANCE_SIZE {
+			small = small.downsampled();
+		}
+		let levels = (size / MIN_PREFILTERED_SIZE).max(1).ilog2() as usize + 1;
+		Self {
+			irradiance: cubemap::irradiance(&small, IRRADIANCE_SIZE),
+			prefiltered: cubemap::prefiltered(
+				&source,
+				levels,
+				PREFILTER_SAMPLES,
+			),
+			brdf_lut: brdf::brdf_lut(BRDF_LUT_SIZE, BRDF_SAMPLES),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Environment<G: GlBackend = WebGl2RenderingContext> {
+	irradiance: G::Texture,
+	prefiltered: G::Texture,
+	brdf_lut: G::Texture,
+	prefiltered_levels: usize,
+	// Scales the light the environment gives.
+	pub intensity: f32,
+}
+impl<G: GlBackend> Environment<G> {
+	pub fn new(
+		gl: &G,
+		maps: &EnvironmentMaps,
+		intensity: f32,
+	) -> Result<Self, &'static str> {
+		let create = || gl.create_texture().ok_or("Failed to create a texture");
+		let environment = Self {
+			irradiance: create()?,
+			prefiltered: create()?,
+			brdf_lut: create()?,
+			prefiltered_levels: maps.prefiltered.len(),
+			intensity,
+		};
+		let uploaded = environment.upload(gl, maps);
+		if uploaded.is_err() {
+			environment.delete(gl);
+		}
+		uploaded.map(|_| environment)
+	}
+	fn upload(
+		&self,
+		gl: &G,
+		maps: &EnvironmentMaps,
+	) -> Result<(), &'static str> {
+		upload_cube_map(gl, &self.irradiance, &[&maps.irradiance])?;
+		let levels = maps.prefiltered.iter().collect::<Vec<_>>();
+		upload_cube_map(gl, &self.prefiltered, &levels)?;
+
+		let data = maps.brdf_lut.iter().flatten().copied().collect::<Vec<_>>();
+		gl.bind_texture(Gl::TEXTURE_2D, Some(&self.brdf_lut));
+		set_filtering(gl, Gl::TEXTURE_2D, 1);
+		let uploaded = gl.tex_image_2d_with_f32_array(
+			Gl::TEXTURE_2D,
+			0,
+			Gl::RG16F as i32,
+			BRDF_LUT_SIZE as i32,
+			BRDF_LUT_SIZE as i32,
+			Gl::RG,
+			Gl::FLOAT,
+			&data,
+		);
+		gl.bind_texture(Gl::TEXTURE_2D, None);
+		uploaded
+	}
+	pub fn delete(&self, gl: &G) {
+		gl.delete_texture(Some(&self.irradiance));
+		gl.delete_texture(Some(&self.prefiltered));
+		gl.delete_texture(Some(&self.brdf_lut));
+	}
+}
+
+// Each of `levels` is a mip, from level zero.
+fn upload_cube_map<G: GlBackend>(
+	gl: &G,
+	texture: &G::Texture,
+	levels: &[&CubeMap],
+) -> Result<(), &'static str> {
+	gl.bind_texture(Gl::TEXTURE_CUBE_MAP, Some(texture));
+	set_filtering(gl, Gl::TEXTURE_CUBE_MAP, levels.len());
+	let mut uploaded = Ok(());
+	for (level, cube) in levels.iter().enumerate() {
+		for face in 0..6 {
+			uploaded = uploaded.and_then(|_| {
+				gl.tex_image_2d_with_f32_array(
+					Gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+					level as i32,
+					Gl::RGB16F as i32,
+					cube.size as i32,
+					cube.size as i32,
+					Gl::RGB,
+					Gl::FLOAT,
+					&cube.face_data(face),
+				)
+			});
+		}
+	}
+	gl.bind_texture(Gl::TEXTURE_CUBE_MAP, None);
+	uploaded
+}
+fn set_filtering<G: GlBackend>(gl: &G, target: u32, levels: usize) {
+	let min_filter = if levels > 1 {
+		Gl::LINEAR_MIPMAP_LINEAR
+	} else {
+		Gl::LINEAR
+	};
+	for (pname, param) in [
+		(Gl::TEXTURE_MIN_FILTER, min_filter as i32),
+		(Gl::TEXTURE_MAG_FILTER, Gl::LINEAR as i32),
+		(Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE as i32),
+		(Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE as i32),
+		(Gl::TEXTURE_MAX_LEVEL, levels as i32 - 1),
+	] {
+		gl.tex_parameteri(target, pname, param);
+	}
+}
+
+// Binds `environment`'s maps for pbr.frag, or turns environment lighting
+// off. The samplers get their units either way, a samplerCube left on a
+// material's unit would fail to draw.
+pub fn bind_environment<G: GlBackend>(
+	gl: &G,
+	uniform_locations: &UniformLocations<G>,
+	environment: Option<&Environment<G>>,
+) {
+	let units = [
+		("IRRADIANCE_MAP", Gl::TEXTURE_CUBE_MAP),
+		("PREFILTERED_MAP", Gl::TEXTURE_CUBE_MAP),
+		("BRDF_LUT", Gl::TEXTURE_2D),
+	];
+	for (i, (name, _)) in units.iter().enumerate() {
+		let unit = ENVIRONMENT_TEXTURE_UNIT + i as u32;
+		gl.uniform1i(uniform_locations.get(*name), unit as i32);
+	}
+	gl.uniform1i(
+		uniform_locations.get("USE_ENVIRONMENT"),
+		environment.is_some() as i32,
+	);
+	let environment = match environment {
+		Some(environment) => environment,
+		None => return,
+	};
+	let textures = [
+		&environment.irradiance,
+		&environment.prefiltered,
+		&environment.brdf_lut,
+	];
+	for (i, ((_, target), texture)) in units.iter().zip(textures).enumerate() {
+		gl.active_texture(Gl::TEXTURE0 + ENVIRONMENT_TEXTURE_UNIT + i as u32);
+		gl.bind_texture(*target, Some(texture));
+	}
+	gl.uniform1f(
+		uniform_locations.get("PREFILTERED_MAX_LOD"),
+		(environment.prefiltered_levels - 1) as f32,
+	);
+	gl.uniform1f(
+		uniform_locations.get("ENVIRONMENT_INTENSITY"),
+		environment.intensity,
+	);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::recording::{
+		GlCall,
+		RecordingBackend,
+	};
+
+	#[test]
+	fn uploads_every_face_of_every_mip() {
+		let image = HdrImage {
+			width: 16,
+			height: 8,
+			pixels: vec![[1.0; 3]; 16 * 8],
+		};
+		let maps = EnvironmentMaps::new(&image, 16);
+		// 16, 8 and 4 texels wide.
+		assert_eq!(maps.prefiltered.len(), 3);
+		assert_eq!(maps.irradiance.size, IRRADIANCE_SIZE);
+
+		let gl = RecordingBackend::new();
+		Environment::new(&gl, &maps, 1.0).unwrap();
+		let cube_faces = gl.count(|call| {
+			matches!(
+				call,
+				GlCall::TexImage2DPixels { internal_format, .. }
+					if *internal_format == Gl::RGB16F as i32
+			)
+		});
+		assert_eq!(cube_faces, 6 + 6 * 3);
+		assert!(gl.calls().contains(&GlCall::TexParameteri(
+			Gl::TEXTURE_CUBE_MAP,
+			Gl::TEXTURE_MAX_LEVEL,
+			2
+		)));
+	}
+}
@@ -0,0 +1,82 @@
+// File: src/gltf/data_uri.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Decoding for the base64 data URIs glTF uses to embed buffers and images.
+
+pub fn is_data_uri(uri: &str) -> bool {
+	uri.starts_with("data:")
+}
+
+/// Decodes `data:[<mediatype>];base64,<data>` into raw bytes.
+/// Returns None if the uri is not a base64 data uri or the payload is
+/// malformed.
+pub fn decode(uri: &str) -> Option<Vec<u8>> {
+	let rest = uri.strip_prefix("data:")?;
+	let comma = rest.find(',')?;
+	let (header, payload) = (&rest[..comma], &rest[comma + 1..]);
+	if !header.ends_with(";base64") {
+		return None;
+	}
+	decode_base64(payload)
+}
+
+pub fn decode_base64(input: &str) -> Option<Vec<u8>> {
+	let mut out = Vec::with_capacity(input.len() / 4 * 3);
+	let mut acc: u32 = 0;
+	let mut bits = 0;
+	let mut padding = 0;
+	for byte in input.bytes() {
+		let value = match byte {
+			b'A'..=b'Z' => byte - b'A',
+			b'a'..=b'z' => byte - b'a' + 26,
+			b'0'..=b'9' => byte - b'0' + 52,
+			b'+' | b'-' => 62,
+			b'/' | b'_' => 63,
+			b'=' => {
+				padding += 1;
+				continue;
+			}
+			b' ' | b'\n' | b'\r' | b'\t' => continue,
+			_ => return None,
+		};
+		if padding > 0 {
+			return None;
+		}
+		acc = (acc << 6) | value as u32;
+		bits += 6;
+		if bits >= 8 {
+			bits -= 8;
+			out.push((acc >> bits) as u8);
+			acc &= (1 << bits) - 1;
+		}
+	}
+	if padding > 2 {
+		return None;
+	}
+	Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn decodes_octet_stream_uri() {
+		let uri = "data:application/octet-stream;base64,AAABAAIA";
+		assert_eq!(decode(uri), Some(vec![0, 0, 1, 0, 2, 0]));
+	}
+	#[test]
+	fn decodes_padded_input() {
+		assert_eq!(decode_base64("TWE="), Some(b"Ma".to_vec()));
+		assert_eq!(decode_base64("TQ=="), Some(b"M".to_vec()));
+	}
+	#[test]
+	fn rejects_non_base64_uris() {
+		assert_eq!(decode("data:text/plain,hello"), None);
+		assert_eq!(decode("buffer.bin"), None);
+		assert_eq!(decode_base64("AB$D"), None);
+	}
+}
@@ -0,0 +1,290 @@
+// File: src/gltf/glb.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Binary glTF container. A 12 byte header is followed by a JSON chunk and
+// an optional BIN chunk holding the data of the document's first buffer.
+
+use std::{
+	convert::TryInto,
+	fmt,
+};
+
+pub const MAGIC: u32 = 0x4654_6C67; // "glTF"
+pub const VERSION: u32 = 2;
+pub const CHUNK_TYPE_JSON: u32 = 0x4E4F_534A; // "JSON"
+pub const CHUNK_TYPE_BIN: u32 = 0x004E_4942; // "BIN\0"
+
+const HEADER_LENGTH: usize = 12;
+const CHUNK_HEADER_LENGTH: usize = 8;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GlbError {
+	TooShort(usize),
+	BadMagic(u32),
+	UnsupportedVersion(u32),
+	LengthMismatch {
+		declared: usize,
+		actual: usize,
+	},
+	ChunkOutOfBounds {
+		chunk: usize,
+		end: usize,
+		length: usize,
+	},
+	MisalignedChunk {
+		chunk: usize,
+		length: usize,
+	},
+	MissingJsonChunk,
+	UnexpectedChunk {
+		chunk: usize,
+		chunk_type: u32,
+	},
+}
+impl fmt::Display for GlbError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			GlbError::TooShort(length) => {
+				write!(f, "GLB is {} bytes, too short for a header", length)
+			}
+			GlbError::BadMagic(magic) => {
+				write!(f, "GLB has bad magic {:#010x}", magic)
+			}
+			GlbError::UnsupportedVersion(version) => {
+				write!(f, "GLB version {} is not supported", version)
+			}
+			GlbError::LengthMismatch { declared, actual } => write!(
+				f,
+				"GLB header declares {} bytes but {} were given",
+				declared, actual
+			),
+			GlbError::ChunkOutOfBounds { chunk, end, length } => write!(
+				f,
+				"GLB chunk {} ends at byte {} past the end of the file ({} bytes)",
+				chunk, end, length
+			),
+			GlbError::MisalignedChunk { chunk, length } => write!(
+				f,
+				"GLB chunk {} is {} bytes, not a multiple of four",
+				chunk, length
+			),
+			GlbError::MissingJsonChunk => {
+				write!(f, "GLB does not start with a JSON chunk")
+			}
+			GlbError::UnexpectedChunk { chunk, chunk_type } => write!(
+				f,
+				"GLB chunk {} has unexpected type {:#010x}",
+				chunk, chunk_type
+			),
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Glb<'a> {
+	pub json: &'a [u8],
+	pub bin: Option<&'a [u8]>,
+}
+
+pub fn is_glb(data: &[u8]) -> bool {
+	data.len() >= 4 && read_u32(data, 0) == MAGIC
+}
+
+pub fn parse(data: &[u8]) -> Result<Glb<'_>, GlbError> {
+	if data.len() < HEADER_LENGTH {
+		return Err(GlbError::TooShort(data.len()));
+	}
+	let magic = read_u32(data, 0);
+	if magic != MAGIC {
+		return Err(GlbError::BadMagic(magic));
+	}
+	let version = read_u32(data, 4);
+	if version != VERSION {
+		return Err(GlbError::UnsupportedVersion(version));
+	}
+	let declared = read_u32(data, 8) as usize;
+	if declared != data.len() {
+		return Err(GlbError::LengthMismatch {
+			declared,
+			actual: data.len(),
+		});
+	}
+
+	let mut json = None;
+	let mut bin = None;
+	let mut offset = HEADER_LENGTH;
+	let mut chunk = 0;
+	while offset < data.len() {
+		let data_start = offset + CHUNK_HEADER_LENGTH;
+		if data_start > data.len() {
+			return Err(GlbError::ChunkOutOfBounds {
+				chunk,
+				end: data_start,
+				length: data.len(),
+			});
+		}
+		let chunk_length = read_u32(data, offset) as usize;
+		let chunk_type = read_u32(data, offset + 4);
+		if !chunk_length.is_multiple_of(4) {
+			return Err(GlbError::MisalignedChunk {
+				chunk,
+				length: chunk_length,
+			});
+		}
+		// A length near u32::MAX overflows where usize is 32 bits, the
+		// saturated end is still past the file.
+		let end = data_start.saturating_add(chunk_length);
+		if end > data.len() {
+			return Err(GlbError::ChunkOutOfBounds {
+				chunk,
+				end,
+				length: data.len(),
+			});
+		}
+		let chunk_data = &data[data_start..end];
+		match (chunk, chunk_type) {
+			(0, CHUNK_TYPE_JSON) => json = Some(chunk_data),
+			(0, _) => return Err(GlbError::MissingJsonChunk),
+			(1, CHUNK_TYPE_BIN) => bin = Some(chunk_data),
+			(_, CHUNK_TYPE_JSON) | (_, CHUNK_TYPE_BIN) => {
+				return Err(GlbError::UnexpectedChunk { chunk, chunk_type })
+			}
+			// Unknown chunk types must be ignored.
+			_ => (),
+		}
+		offset = end;
+		chunk += 1;
+	}
+
+	Ok(Glb {
+		json: json.ok_or(GlbError::MissingJsonChunk)?,
+		bin,
+	})
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+	u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn chunk(chunk_type: u32, data: &[u8]) -> Vec<u8> {
+		let mut out = Vec::new();
+		out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+		out.extend_from_slice(&chunk_type.to_le_bytes());
+		out.extend_from_slice(data);
+		out
+	}
+	fn glb(version: u32, chunks: &[Vec<u8>]) -> Vec<u8> {
+		let body = chunks.concat();
+		let mut out = Vec::new();
+		out.extend_from_slice(&MAGIC.to_le_bytes());
+		out.extend_from_slice(&version.to_le_bytes());
+		out.extend_from_slice(
+			&((HEADER_LENGTH + body.len()) as u32).to_le_bytes(),
+		);
+		out.extend_from_slice(&body);
+		out
+	}
+
+	#[test]
+	fn parses_json_and_bin_chunks() {
+		let data = glb(
+			2,
+			&[
+				chunk(CHUNK_TYPE_JSON, b"{}  "),
+				chunk(CHUNK_TYPE_BIN, &[1, 2, 3, 4]),
+			],
+		);
+		assert!(is_glb(&data));
+		let glb = parse(&data).unwrap();
+		assert_eq!(glb.json, b"{}  ");
+		assert_eq!(glb.bin, Some(&[1u8, 2, 3, 4][..]));
+	}
+	#[test]
+	fn bin_chunk_is_optional_and_unknown_chunks_are_skipped() {
+		let data = glb(
+			2,
+			&[chunk(CHUNK_TYPE_JSON, b"{}  "), chunk(0x1234_5678, &[0; 4])],
+		);
+		let glb = parse(&data).unwrap();
+		assert_eq!(glb.bin, None);
+	}
+	#[test]
+	fn rejects_bad_magic() {
+		let mut data = glb(2, &[chunk(CHUNK_TYPE_JSON, b"{}  ")]);
+		data[0] = b'x';
+		assert!(!is_glb(&data));
+		assert!(matches!(parse(&data), Err(GlbError::BadMagic(_))));
+	}
+	#[test]
+	fn rejects_unsupported_version() {
+		let data = glb(1, &[chunk(CHUNK_TYPE_JSON, b"{}  ")]);
+		assert_eq!(parse(&data), Err(GlbError::UnsupportedVersion(1)));
+	}
+	#[test]
+	fn rejects_short_data() {
+		assert_eq!(parse(&MAGIC.to_le_bytes()), Err(GlbError::TooShort(4)));
+	}
+	#[test]
+	fn rejects_mismatched_total_length() {
+		let mut data = glb(2, &[chunk(CHUNK_TYPE_JSON, b"{}  ")]);
+		data.extend_from_slice(&[0; 4]);
+		assert_eq!(
+			parse(&data),
+			Err(GlbError::LengthMismatch {
+				declared: 24,
+				actual: 28,
+			})
+		);
+	}
+	#[test]
+	fn rejects_chunk_longer_than_file() {
+		let mut json = chunk(CHUNK_TYPE_JSON, b"{}  ");
+		json[0] = 64;
+		let data = glb(2, &[json]);
+		assert_eq!(
+			parse(&data),
+			Err(GlbError::ChunkOutOfBounds {
+				chunk: 0,
+				end: 84,
+				length: 24,
+			})
+		);
+	}
+	#[test]
+	fn rejects_chunk_lengths_near_u32_max() {
+		let mut json = chunk(CHUNK_TYPE_JSON, b"{}  ");
+		json[..4].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+		let data = glb(2, &[json]);
+		assert!(matches!(
+			parse(&data),
+			Err(GlbError::ChunkOutOfBounds {
+				chunk: 0,
+				length: 24,
+				..
+			})
+		));
+	}
+	#[test]
+	fn rejects_unpadded_chunks() {
+		let data = glb(2, &[chunk(CHUNK_TYPE_JSON, b"{}")]);
+		assert_eq!(
+			parse(&data),
+			Err(GlbError::MisalignedChunk {
+				chunk: 0,
+				length: 2,
+			})
+		);
+	}
+	#[test]
+	fn requires_json_chunk_first() {
+		let data = glb(2, &[chunk(CHUNK_TYPE_BIN, &[0; 4])]);
+		assert_eq!(parse(&data), Err(GlbError::MissingJsonChunk));
+	}
+}
@@ -0,0 +1,253 @@
+// File: src/gltf/json.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Serde mirror of the parts of the glTF 2.0 schema the renderer consumes.
+// Field names follow the spec, unknown properties are ignored.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Document {
+	pub asset: Asset,
+	#[serde(default)]
+	pub buffers: Vec<Buffer>,
+	#[serde(default)]
+	pub buffer_views: Vec<BufferView>,
+	#[serde(default)]
+	pub accessors: Vec<Accessor>,
+	#[serde(default)]
+	pub meshes: Vec<Mesh>,
+	#[serde(default)]
+	pub materials: Vec<Material>,
+	#[serde(default)]
+	pub textures: Vec<Texture>,
+	#[serde(default)]
+	pub images: Vec<Image>,
+	#[serde(default)]
+	pub samplers: Vec<Sampler>,
+	#[serde(default)]
+	pub nodes: Vec<Node>,
+	#[serde(default)]
+	pub extensions: Extensions,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Extensions {
+	#[serde(rename = "KHR_lights_punctual")]
+	pub lights_punctual: Option<LightsPunctual>,
+}
+#[derive(Clone, Debug, Deserialize)]
+pub struct LightsPunctual {
+	pub lights: Vec<PunctualLight>,
+}
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PunctualLight {
+	pub name: Option<String>,
+	#[serde(rename = "type")]
+	pub kind: String,
+	#[serde(default = "default_light_color")]
+	pub color: [f32; 3],
+	#[serde(default = "default_intensity")]
+	pub intensity: f32,
+	pub range: Option<f32>,
+	pub spot: Option<Spot>,
+}
+fn default_light_color() -> [f32; 3] {
+	[1.0, 1.0, 1.0]
+}
+fn default_intensity() -> f32 {
+	1.0
+}
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Spot {
+	#[serde(default)]
+	pub inner_cone_angle: f32,
+	#[serde(default = "default_outer_cone_angle")]
+	pub outer_cone_angle: f32,
+}
+impl Default for Spot {
+	fn default() -> Self {
+		Self {
+			inner_cone_angle: 0.0,
+			outer_cone_angle: default_outer_cone_angle(),
+		}
+	}
+}
+fn default_outer_cone_angle() -> f32 {
+	std::f32::consts::FRAC_PI_4
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Node {
+	pub name: Option<String>,
+	#[serde(default)]
+	pub children: Vec<usize>,
+	pub matrix: Option<[f32; 16]>,
+	pub translation: Option<[f32; 3]>,
+	pub rotation: Option<[f32; 4]>,
+	pub scale: Option<[f32; 3]>,
+	pub mesh: Option<usize>,
+	#[serde(default)]
+	pub extensions: NodeExtensions,
+}
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct NodeExtensions {
+	#[serde(rename = "KHR_lights_punctual")]
+	pub light: Option<NodeLight>,
+}
+#[derive(Clone, Debug, Deserialize)]
+pub struct NodeLight {
+	pub light: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Asset {
+	pub version: String,
+	pub min_version: Option<String>,
+	pub generator: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Buffer {
+	pub uri: Option<String>,
+	pub byte_length: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferView {
+	pub buffer: usize,
+	#[serde(default)]
+	pub byte_offset: usize,
+	pub byte_length: usize,
+	pub byte_stride: Option<usize>,
+	pub target: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Accessor {
+	pub buffer_view: Option<usize>,
+	#[serde(default)]
+	pub byte_offset: usize,
+	pub component_type: u32,
+	#[serde(default)]
+	pub normalized: bool,
+	pub count: usize,
+	#[serde(rename = "type")]
+	pub kind: String,
+	pub min: Option<Vec<f32>>,
+	pub max: Option<Vec<f32>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mesh {
+	pub name: Option<String>,
+	pub primitives: Vec<Primitive>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Primitive {
+	pub attributes: BTreeMap<String, usize>,
+	pub indices: Option<usize>,
+	pub material: Option<usize>,
+	pub mode: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Material {
+	pub name: Option<String>,
+	pub pbr_metallic_roughness: Option<PbrMetallicRoughness>,
+	pub normal_texture: Option<TextureInfo>,
+	pub occlusion_texture: Option<TextureInfo>,
+	pub emissive_texture: Option<TextureInfo>,
+	#[serde(default)]
+	pub emissive_factor: [f32; 3],
+	#[serde(default)]
+	pub alpha_mode: AlphaMode,
+	#[serde(default = "default_alpha_cutoff")]
+	pub alpha_cutoff: f32,
+	#[serde(default)]
+	pub double_sided: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum AlphaMode {
+	#[default]
+	Opaque,
+	Mask,
+	Blend,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PbrMetallicRoughness {
+	#[serde(default = "default_base_color_factor")]
+	pub base_color_factor: [f32; 4],
+	pub base_color_texture: Option<TextureInfo>,
+	#[serde(default = "default_factor")]
+	pub metallic_factor: f32,
+	#[serde(default = "default_factor")]
+	pub roughness_factor: f32,
+	pub metallic_roughness_texture: Option<TextureInfo>,
+}
+
+// Normal textures add `scale` and occlusion textures `strength`, read as
+// one since no texture has both.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureInfo {
+	pub index: usize,
+	#[serde(default)]
+	pub tex_coord: u32,
+	#[serde(default = "default_factor", alias = "strength")]
+	pub scale: f32,
+}
+fn default_base_color_factor() -> [f32; 4] {
+	[1.0, 1.0, 1.0, 1.0]
+}
+fn default_factor() -> f32 {
+	1.0
+}
+fn default_alpha_cutoff() -> f32 {
+	0.5
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Texture {
+	pub sampler: Option<usize>,
+	pub source: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+	pub uri: Option<String>,
+	pub mime_type: Option<String>,
+	pub buffer_view: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sampler {
+	pub mag_filter: Option<i32>,
+	pub min_filter: Option<i32>,
+	pub wrap_s: Option<i32>,
+	pub wrap_t: Option<i32>,
+}
@@ -4,3 +4,1297 @@
 // License: AGPLv3
 //
 // Description:
+// Native glTF 2.0 parsing. A document is parsed from its JSON, checked
+// against the binary buffers it references and then translated into the
+// same ModelConfig the rest of the resource loading already understands.
+
+pub mod data_uri;
+pub mod glb;
+pub mod json;
+
+use std::{
+	collections::HashMap,
+	fmt,
+};
+
+use cgmath::{
+	Matrix4,
+	One,
+	Point3,
+	Quaternion,
+	Transform,
+	Vector3,
+};
+use wasm_bindgen::JsValue;
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+	config::scene_config::{
+		AlphaModeConfig,
+		BufferConfig,
+		BufferViewConfig,
+		LightConfig,
+		MaterialConfig,
+		MaterialTextureConfig,
+		MeshConfig,
+		ModelConfig,
+		Texture as TextureConfig,
+	},
+	model::texture::{
+		Sampler,
+		TextureFormat,
+	},
+};
+
+use self::glb::GlbError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GltfError {
+	Glb(GlbError),
+	Json(String),
+	UnsupportedVersion(String),
+	IndexOutOfRange {
+		kind: &'static str,
+		index: usize,
+		referenced_by: String,
+	},
+	MissingBufferUri(usize),
+	InvalidDataUri(usize),
+	UnsupportedImage(usize),
+	BufferTooShort {
+		buffer: usize,
+		byte_length: usize,
+		actual: usize,
+	},
+	BufferViewOutOfRange {
+		buffer_view: usize,
+		end: usize,
+		buffer_length: usize,
+	},
+	InvalidByteStride {
+		buffer_view: usize,
+		byte_stride: usize,
+	},
+	BufferViewOverflow(usize),
+	AccessorWithoutBufferView(usize),
+	AccessorOverflow(usize),
+	AccessorOutOfRange {
+		accessor: usize,
+		end: usize,
+		buffer_view_length: usize,
+	},
+	MisalignedAccessor {
+		accessor: usize,
+		byte_offset: usize,
+	},
+	StrideSmallerThanElement {
+		accessor: usize,
+		byte_stride: usize,
+		element_size: usize,
+	},
+	UnknownComponentType {
+		accessor: usize,
+		component_type: u32,
+	},
+	UnknownAccessorType {
+		accessor: usize,
+		kind: String,
+	},
+	MissingPosition {
+		mesh: usize,
+		primitive: usize,
+	},
+	UnknownLightType {
+		light: usize,
+		kind: String,
+	},
+	// Textures sourced only through extensions such as KHR_texture_basisu.
+	TextureWithoutSource(usize),
+}
+impl fmt::Display for GltfError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			GltfError::Glb(err) => write!(f, "{}", err),
+			GltfError::Json(err) => write!(f, "Invalid glTF json: {}", err),
+			GltfError::UnsupportedVersion(version) => {
+				write!(f, "Unsupported glTF version {}", version)
+			}
+			GltfError::IndexOutOfRange {
+				kind,
+				index,
+				referenced_by,
+			} => write!(
+				f,
+				"{} references {} {} which does not exist",
+				referenced_by, kind, index
+			),
+			GltfError::MissingBufferUri(buffer) => {
+				write!(f, "Buffer {} has no uri", buffer)
+			}
+			GltfError::InvalidDataUri(buffer) => {
+				write!(f, "Buffer {} has a malformed data uri", buffer)
+			}
+			GltfError::UnsupportedImage(image) => {
+				write!(f, "Image {} has no uri", image)
+			}
+			GltfError::BufferTooShort {
+				buffer,
+				byte_length,
+				actual,
+			} => write!(
+				f,
+				"Buffer {} declares {} bytes but only {} were loaded",
+				buffer, byte_length, actual
+			),
+			GltfError::BufferViewOutOfRange {
+				buffer_view,
+				end,
+				buffer_length,
+			} => write!(
+				f,
+				"Buffer view {} ends at byte {} past the end of its buffer ({} bytes)",
+				buffer_view, end, buffer_length
+			),
+			GltfError::InvalidByteStride {
+				buffer_view,
+				byte_stride,
+			} => write!(
+				f,
+				"Buffer view {} has invalid byte stride {}",
+				buffer_view, byte_stride
+			),
+			GltfError::BufferViewOverflow(buffer_view) => write!(
+				f,
+				"Buffer view {} ends past the addressable range",
+				buffer_view
+			),
+			GltfError::AccessorWithoutBufferView(accessor) => write!(
+				f,
+				"Accessor {} has no buffer view which is not supported",
+				accessor
+			),
+			GltfError::AccessorOverflow(accessor) => write!(
+				f,
+				"Accessor {} ends past the addressable range",
+				accessor
+			),
+			GltfError::AccessorOutOfRange {
+				accessor,
+				end,
+				buffer_view_length,
+			} => write!(
+				f,
+				"Accessor {} ends at byte {} past the end of its buffer view ({} bytes)",
+				accessor, end, buffer_view_length
+			),
+			GltfError::MisalignedAccessor {
+				accessor,
+				byte_offset,
+			} => write!(
+				f,
+				"Accessor {} byte offset {} is not aligned to its component size",
+				accessor, byte_offset
+			),
+			GltfError::StrideSmallerThanElement {
+				accessor,
+				byte_stride,
+				element_size,
+			} => write!(
+				f,
+				"Accessor {} elements are {} bytes but the buffer view stride is {}",
+				accessor, element_size, byte_stride
+			),
+			GltfError::UnknownComponentType {
+				accessor,
+				component_type,
+			} => write!(
+				f,
+				"Accessor {} has unknown component type {}",
+				accessor, component_type
+			),
+			GltfError::UnknownAccessorType { accessor, kind } => {
+				write!(f, "Accessor {} has unknown type {}", accessor, kind)
+			}
+			GltfError::MissingPosition { mesh, primitive } => write!(
+				f,
+				"Primitive {} of mesh {} has no POSITION attribute",
+				primitive, mesh
+			),
+			GltfError::UnknownLightType { light, kind } => {
+				write!(f, "Light {} has unknown type {}", light, kind)
+			}
+			GltfError::TextureWithoutSource(texture) => write!(
+				f,
+				"Texture {} has no source image which is not supported",
+				texture
+			),
+		}
+	}
+}
+impl From<GlbError> for GltfError {
+	fn from(err: GlbError) -> GltfError {
+		GltfError::Glb(err)
+	}
+}
+impl From<GltfError> for JsValue {
+	fn from(err: GltfError) -> JsValue {
+		JsValue::from_str(&err.to_string())
+	}
+}
+
+pub fn component_size(component_type: u32) -> Option<usize> {
+	match component_type {
+		WebGl2RenderingContext::BYTE
+		| WebGl2RenderingContext::UNSIGNED_BYTE => Some(1),
+		WebGl2RenderingContext::SHORT
+		| WebGl2RenderingContext::UNSIGNED_SHORT => Some(2),
+		WebGl2RenderingContext::UNSIGNED_INT
+		| WebGl2RenderingContext::FLOAT => Some(4),
+		_ => None,
+	}
+}
+pub fn components_per_element(kind: &str) -> Option<usize> {
+	match kind {
+		"SCALAR" => Some(1),
+		"VEC2" => Some(2),
+		"VEC3" => Some(3),
+		"VEC4" | "MAT2" => Some(4),
+		"MAT3" => Some(9),
+		"MAT4" => Some(16),
+		_ => None,
+	}
+}
+
+/// The directory part of a uri including the trailing slash, used to
+/// resolve the relative uris of buffers and images.
+pub fn base_path(uri: &str) -> &str {
+	match uri.rfind('/') {
+		Some(i) => &uri[..=i],
+		None => "",
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Gltf {
+	pub document: json::Document,
+}
+impl Gltf {
+	pub fn from_slice(data: &[u8]) -> Result<Self, GltfError> {
+		let document: json::Document = serde_json::from_slice(data)
+			.map_err(|err| GltfError::Json(err.to_string()))?;
+		if !document.asset.version.starts_with("2.") {
+			return Err(GltfError::UnsupportedVersion(document.asset.version));
+		}
+		Ok(Self { document })
+	}
+	/// Parses either a .gltf JSON document or a .glb container. For a .glb
+	/// the contents of its BIN chunk are returned alongside the document.
+	pub fn from_bytes(
+		data: &[u8],
+	) -> Result<(Self, Option<Vec<u8>>), GltfError> {
+		if glb::is_glb(data) {
+			let glb = glb::parse(data)?;
+			let gltf = Self::from_slice(glb.json)?;
+			Ok((gltf, glb.bin.map(|bin| bin.to_vec())))
+		} else {
+			Ok((Self::from_slice(data)?, None))
+		}
+	}
+	/// Data for a buffer that has no uri. Only the first buffer of a .glb
+	/// may omit its uri, it then refers to the BIN chunk.
+	pub fn embedded_buffer(
+		&self,
+		index: usize,
+		bin: &mut Option<Vec<u8>>,
+	) -> Result<Vec<u8>, GltfError> {
+		match bin.take() {
+			Some(bin) if index == 0 => Ok(bin),
+			_ => Err(GltfError::MissingBufferUri(index)),
+		}
+	}
+	/// The bytes covered by a buffer view, used for images stored inside a
+	/// buffer instead of behind a uri.
+	pub fn buffer_view_data<'a, B: AsRef<[u8]>>(
+		&self,
+		index: usize,
+		buffers: &'a [B],
+	) -> Result<&'a [u8], GltfError> {
+		self.validate_buffer_view(index, buffers)?;
+		let view = self.buffer_view(index, || String::from("Image"))?;
+		let buffer = buffers[view.buffer].as_ref();
+		Ok(&buffer[view.byte_offset..view.byte_offset + view.byte_length])
+	}
+
+	/// Checks every buffer, buffer view and accessor against the loaded
+	/// buffer data so that later slicing can not go out of bounds.
+	pub fn validate<B: AsRef<[u8]>>(
+		&self,
+		buffers: &[B],
+	) -> Result<(), GltfError> {
+		for (i, buffer) in self.document.buffers.iter().enumerate() {
+			let data =
+				buffers.get(i).ok_or_else(|| GltfError::IndexOutOfRange {
+					kind: "buffer",
+					index: i,
+					referenced_by: String::from("Loaded buffer data"),
+				})?;
+			let actual = data.as_ref().len();
+			if actual < buffer.byte_length {
+				return Err(GltfError::BufferTooShort {
+					buffer: i,
+					byte_length: buffer.byte_length,
+					actual,
+				});
+			}
+		}
+		for i in 0..self.document.buffer_views.len() {
+			self.validate_buffer_view(i, buffers)?;
+		}
+		for i in 0..self.document.accessors.len() {
+			self.validate_accessor(i, buffers)?;
+		}
+		for (m, mesh) in self.document.meshes.iter().enumerate() {
+			for (p, primitive) in mesh.primitives.iter().enumerate() {
+				let referenced_by = || format!("Primitive {} of mesh {}", p, m);
+				for accessor in primitive
+					.attributes
+					.values()
+					.chain(primitive.indices.iter())
+				{
+					self.accessor(*accessor, referenced_by)?;
+				}
+				if let Some(material) = primitive.material {
+					if material >= self.document.materials.len() {
+						return Err(GltfError::IndexOutOfRange {
+							kind: "material",
+							index: material,
+							referenced_by: referenced_by(),
+						});
+					}
+				}
+			}
+		}
+		for (i, texture) in self.document.textures.iter().enumerate() {
+			let referenced_by = || format!("Texture {}", i);
+			if let Some(source) = texture.source {
+				if source >= self.document.images.len() {
+					return Err(GltfError::IndexOutOfRange {
+						kind: "image",
+						index: source,
+						referenced_by: referenced_by(),
+					});
+				}
+			}
+			if let Some(sampler) = texture.sampler {
+				if sampler >= self.document.samplers.len() {
+					return Err(GltfError::IndexOutOfRange {
+						kind: "sampler",
+						index: sampler,
+						referenced_by: referenced_by(),
+					});
+				}
+			}
+		}
+		Ok(())
+	}
+	pub fn validate_buffer_view<B: AsRef<[u8]>>(
+		&self,
+		index: usize,
+		buffers: &[B],
+	) -> Result<(), GltfError> {
+		let view = self.buffer_view(index, || String::from("Validation"))?;
+		let buffer_length = buffers
+			.get(view.buffer)
+			.ok_or_else(|| GltfError::IndexOutOfRange {
+				kind: "buffer",
+				index: view.buffer,
+				referenced_by: format!("Buffer view {}", index),
+			})?
+			.as_ref()
+			.len();
+		let end = view
+			.byte_offset
+			.checked_add(view.byte_length)
+			.ok_or(GltfError::BufferViewOverflow(index))?;
+		if end > buffer_length {
+			return Err(GltfError::BufferViewOutOfRange {
+				buffer_view: index,
+				end,
+				buffer_length,
+			});
+		}
+		if let Some(byte_stride) = view.byte_stride {
+			if !(4..=252).contains(&byte_stride) || byte_stride % 4 != 0 {
+				return Err(GltfError::InvalidByteStride {
+					buffer_view: index,
+					byte_stride,
+				});
+			}
+		}
+		Ok(())
+	}
+	pub fn validate_accessor<B: AsRef<[u8]>>(
+		&self,
+		index: usize,
+		buffers: &[B],
+	) -> Result<(), GltfError> {
+		let accessor = self.accessor(index, || String::from("Validation"))?;
+		let view_index = accessor
+			.buffer_view
+			.ok_or(GltfError::AccessorWithoutBufferView(index))?;
+		let view =
+			self.buffer_view(view_index, || format!("Accessor {}", index))?;
+		self.validate_buffer_view(view_index, buffers)?;
+
+		let component_size = component_size(accessor.component_type).ok_or(
+			GltfError::UnknownComponentType {
+				accessor: index,
+				component_type: accessor.component_type,
+			},
+		)?;
+		let components =
+			components_per_element(&accessor.kind).ok_or_else(|| {
+				GltfError::UnknownAccessorType {
+					accessor: index,
+					kind: accessor.kind.clone(),
+				}
+			})?;
+		let element_size = component_size * components;
+
+		let start = view
+			.byte_offset
+			.checked_add(accessor.byte_offset)
+			.ok_or(GltfError::AccessorOverflow(index))?;
+		if start % component_size != 0 {
+			return Err(GltfError::MisalignedAccessor {
+				accessor: index,
+				byte_offset: accessor.byte_offset,
+			});
+		}
+
+		let stride = match view.byte_stride {
+			Some(byte_stride) if byte_stride < element_size => {
+				return Err(GltfError::StrideSmallerThanElement {
+					accessor: index,
+					byte_stride,
+					element_size,
+				});
+			}
+			Some(byte_stride) => byte_stride,
+			None => element_size,
+		};
+		let end = if accessor.count == 0 {
+			Some(accessor.byte_offset)
+		} else {
+			stride
+				.checked_mul(accessor.count - 1)
+				.and_then(|size| size.checked_add(element_size))
+				.and_then(|size| size.checked_add(accessor.byte_offset))
+		}
+		.ok_or(GltfError::AccessorOverflow(index))?;
+		if end > view.byte_length {
+			return Err(GltfError::AccessorOutOfRange {
+				accessor: index,
+				end,
+				buffer_view_length: view.byte_length,
+			});
+		}
+		Ok(())
+	}
+
+	/// Translates the document into a ModelConfig. The buffers must be the
+	/// ones the document was validated against.
+	pub fn model_config(
+		&self,
+		id: &str,
+		buffers: Vec<Vec<u8>>,
+	) -> Result<ModelConfig, GltfError> {
+		self.validate(&buffers)?;
+
+		let mut meshes = Vec::new();
+		for (m, mesh) in self.document.meshes.iter().enumerate() {
+			for (p, primitive) in mesh.primitives.iter().enumerate() {
+				if !primitive.attributes.contains_key("POSITION") {
+					return Err(GltfError::MissingPosition {
+						mesh: m,
+						primitive: p,
+					});
+				}
+				let index_view = match primitive.indices {
+					Some(accessor) => Some(self.index_view_config(accessor)?),
+					None => None,
+				};
+				let mut buffer_views =
+					Vec::with_capacity(primitive.attributes.len());
+				for (name, accessor) in primitive.attributes.iter() {
+					buffer_views
+						.push(self.attribute_view_config(name, *accessor)?);
+				}
+				meshes.push(MeshConfig {
+					index_view,
+					buffer_views,
+					material: primitive.material.map(|m| m as u32),
+					mode: primitive
+						.mode
+						.unwrap_or(WebGl2RenderingContext::TRIANGLES),
+				});
+			}
+		}
+
+		let mut materials: Vec<_> = self
+			.document
+			.materials
+			.iter()
+			.enumerate()
+			.map(|(i, material)| {
+				let pbr = material.pbr_metallic_roughness.as_ref();
+				let texture = |info: Option<&json::TextureInfo>| {
+					info.map(|info| MaterialTextureConfig {
+						index: info.index as u32,
+						tex_coord: info.tex_coord,
+					})
+				};
+				let scale = |info: Option<&json::TextureInfo>| {
+					info.map_or(1.0, |info| info.scale)
+				};
+				MaterialConfig {
+					id: material
+						.name
+						.clone()
+						.unwrap_or_else(|| format!("{}-material-{}", id, i)),
+					base_color_factor: pbr
+						.map_or([1.0; 4], |pbr| pbr.base_color_factor),
+					base_color: texture(
+						pbr.and_then(|pbr| pbr.base_color_texture.as_ref()),
+					),
+					metallic_factor: pbr.map_or(1.0, |pbr| pbr.metallic_factor),
+					roughness_factor: pbr
+						.map_or(1.0, |pbr| pbr.roughness_factor),
+					metallic_roughness: texture(pbr.and_then(|pbr| {
+						pbr.metallic_roughness_texture.as_ref()
+					})),
+					normal: texture(material.normal_texture.as_ref()),
+					normal_scale: scale(material.normal_texture.as_ref()),
+					occlusion: texture(material.occlusion_texture.as_ref()),
+					occlusion_strength: scale(
+						material.occlusion_texture.as_ref(),
+					),
+					emissive: texture(material.emissive_texture.as_ref()),
+					emissive_factor: material.emissive_factor,
+					alpha_mode: match material.alpha_mode {
+						json::AlphaMode::Opaque => AlphaModeConfig::Opaque,
+						json::AlphaMode::Mask => AlphaModeConfig::Mask,
+						json::AlphaMode::Blend => AlphaModeConfig::Blend,
+					},
+					alpha_cutoff: material.alpha_cutoff,
+					double_sided: material.double_sided,
+				}
+			})
+			.collect();
+
+		let mut samplers = self
+			.document
+			.samplers
+			.iter()
+			.map(sampler_config)
+			.collect::<Vec<_>>();
+		let mut default_sampler = None;
+		let mut textures = Vec::with_capacity(self.document.textures.len());
+		for (i, texture) in self.document.textures.iter().enumerate() {
+			let source =
+				texture.source.ok_or(GltfError::TextureWithoutSource(i))?;
+			let sampler = match texture.sampler {
+				Some(sampler) => sampler,
+				None => *default_sampler.get_or_insert_with(|| {
+					samplers.push(sampler_config(&json::Sampler::default()));
+					samplers.len() - 1
+				}),
+			};
+			textures.push(TextureConfig {
+				source,
+				sampler,
+				format: Default::default(),
+			});
+		}
+		assign_texture_formats(&mut materials, &mut textures);
+
+		Ok(ModelConfig {
+			id: id.to_owned(),
+			buffers: buffers.into_iter().map(BufferConfig).collect(),
+			meshes,
+			materials,
+			texture_wrapper_id: format!("{}-images", id),
+			samplers,
+			textures,
+			lights: self.light_configs(id)?,
+		})
+	}
+
+	/// The KHR_lights_punctual lights the document's nodes place, in the
+	/// space of its root nodes. Lights shine down their node's -Z axis.
+	pub fn light_configs(
+		&self,
+		id: &str,
+	) -> Result<Vec<LightConfig>, GltfError> {
+		let lights = match self.document.extensions.lights_punctual {
+			Some(ref lights_punctual) => &lights_punctual.lights,
+			None => return Ok(Vec::new()),
+		};
+		let mut configs = Vec::new();
+		for (n, matrix) in self.node_matrices()? {
+			let index = match self.document.nodes[n].extensions.light {
+				Some(ref node_light) => node_light.light,
+				None => continue,
+			};
+			let light =
+				lights.get(index).ok_or(GltfError::IndexOutOfRange {
+					kind: "light",
+					index,
+					referenced_by: format!("Node {}", n),
+				})?;
+			let id = format!("{}-light-{}", id, n);
+			let position = matrix.transform_point(Point3::new(0.0, 0.0, 0.0));
+			let direction = matrix.transform_vector(-Vector3::unit_z());
+			let (color, intensity) = (light.color, light.intensity);
+			configs.push(match light.kind.as_str() {
+				"point" => LightConfig::Point {
+					id,
+					position: position.into(),
+					color,
+					intensity,
+					range: light.range,
+				},
+				"directional" => LightConfig::Directional {
+					id,
+					direction: direction.into(),
+					color,
+					intensity,
+					shadow: None,
+				},
+				"spot" => {
+					let spot = light.spot.clone().unwrap_or_default();
+					LightConfig::Spot {
+						id,
+						position: position.into(),
+						direction: direction.into(),
+						color,
+						intensity,
+						range: light.range,
+						inner_cone_angle: spot.inner_cone_angle,
+						outer_cone_angle: spot.outer_cone_angle,
+						shadow: None,
+					}
+				}
+				kind => {
+					return Err(GltfError::UnknownLightType {
+						light: index,
+						kind: kind.to_owned(),
+					})
+				}
+			});
+		}
+		Ok(configs)
+	}
+	// Every node reachable from a root with its transform to root space,
+	// parents before their children.
+	fn node_matrices(&self) -> Result<Vec<(usize, Matrix4<f32>)>, GltfError> {
+		let nodes = &self.document.nodes;
+		let mut is_child = vec![false; nodes.len()];
+		for (n, node) in nodes.iter().enumerate() {
+			for &child in node.children.iter() {
+				if child >= nodes.len() {
+					return Err(GltfError::IndexOutOfRange {
+						kind: "node",
+						index: child,
+						referenced_by: format!("Node {}", n),
+					});
+				}
+				is_child[child] = true;
+			}
+		}
+		let mut stack = (0..nodes.len())
+			.rev()
+			.filter(|n| !is_child[*n])
+			.map(|n| (n, Matrix4::one()))
+			.collect::<Vec<_>>();
+		// The spec forbids cycles and shared children, they are skipped
+		// rather than followed forever.
+		let mut visited = vec![false; nodes.len()];
+		let mut matrices = Vec::with_capacity(nodes.len());
+		while let Some((n, parent)) = stack.pop() {
+			if std::mem::replace(&mut visited[n], true) {
+				continue;
+			}
+			let matrix = parent * node_matrix(&nodes[n]);
+			stack.extend(nodes[n].children.iter().rev().map(|c| (*c, matrix)));
+			matrices.push((n, matrix));
+		}
+		Ok(matrices)
+	}
+
+	fn accessor(
+		&self,
+		index: usize,
+		referenced_by: impl Fn() -> String,
+	) -> Result<&json::Accessor, GltfError> {
+		self.document.accessors.get(index).ok_or_else(|| {
+			GltfError::IndexOutOfRange {
+				kind: "accessor",
+				index,
+				referenced_by: referenced_by(),
+			}
+		})
+	}
+	fn buffer_view(
+		&self,
+		index: usize,
+		referenced_by: impl Fn() -> String,
+	) -> Result<&json::BufferView, GltfError> {
+		self.document.buffer_views.get(index).ok_or_else(|| {
+			GltfError::IndexOutOfRange {
+				kind: "buffer view",
+				index,
+				referenced_by: referenced_by(),
+			}
+		})
+	}
+	fn index_view_config(
+		&self,
+		index: usize,
+	) -> Result<BufferViewConfig, GltfError> {
+		let accessor = self.accessor(index, || String::from("Indices"))?;
+		let view_index = accessor
+			.buffer_view
+			.ok_or(GltfError::AccessorWithoutBufferView(index))?;
+		let view =
+			self.buffer_view(view_index, || format!("Accessor {}", index))?;
+		let component_size = component_size(accessor.component_type).ok_or(
+			GltfError::UnknownComponentType {
+				accessor: index,
+				component_type: accessor.component_type,
+			},
+		)?;
+		// Index data gets its own element array buffer, so fold the accessor
+		// offset into the slice rather than the draw call.
+		Ok(BufferViewConfig {
+			id: String::from("INDEX"),
+			buffer: view.buffer,
+			length: accessor.count * component_size,
+			buffer_offset: Some(
+				(view.byte_offset + accessor.byte_offset) as i32,
+			),
+			offset: None,
+			stride: None,
+			component_size: 1,
+			component_count: accessor.count as i32,
+			component_type: accessor.component_type,
+			normalized: false,
+			min: None,
+			max: None,
+		})
+	}
+	fn attribute_view_config(
+		&self,
+		name: &str,
+		index: usize,
+	) -> Result<BufferViewConfig, GltfError> {
+		let accessor =
+			self.accessor(index, || format!("Attribute {}", name))?;
+		let view_index = accessor
+			.buffer_view
+			.ok_or(GltfError::AccessorWithoutBufferView(index))?;
+		let view =
+			self.buffer_view(view_index, || format!("Accessor {}", index))?;
+		let components =
+			components_per_element(&accessor.kind).ok_or_else(|| {
+				GltfError::UnknownAccessorType {
+					accessor: index,
+					kind: accessor.kind.clone(),
+				}
+			})?;
+		Ok(BufferViewConfig {
+			id: name.to_owned(),
+			buffer: view.buffer,
+			length: view.byte_length,
+			buffer_offset: Some(view.byte_offset as i32),
+			offset: Some(accessor.byte_offset as i32),
+			stride: view.byte_stride.map(|stride| stride as i32),
+			component_size: components as i32,
+			component_count: accessor.count as i32,
+			component_type: accessor.component_type,
+			normalized: accessor.normalized,
+			min: accessor.min.clone(),
+			max: accessor.max.clone(),
+		})
+	}
+}
+
+// `matrix` takes precedence over translation, rotation and scale.
+fn node_matrix(node: &json::Node) -> Matrix4<f32> {
+	if let Some(m) = node.matrix {
+		return Matrix4::new(
+			m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7], m[8], m[9], m[10],
+			m[11], m[12], m[13], m[14], m[15],
+		);
+	}
+	let [x, y, z, w] = node.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+	let [sx, sy, sz] = node.scale.unwrap_or([1.0, 1.0, 1.0]);
+	Matrix4::from_translation(Vector3::from(
+		node.translation.unwrap_or_default(),
+	)) * Matrix4::from(Quaternion::new(w, x, y, z))
+		* Matrix4::from_nonuniform_scale(sx, sy, sz)
+}
+
+// Wrapping repeats unless given, as the spec has it. Filtering it leaves to
+// the implementation, which here is trilinear.
+fn sampler_config(sampler: &json::Sampler) -> Sampler {
+	Sampler::new(
+		sampler
+			.mag_filter
+			.unwrap_or(WebGl2RenderingContext::LINEAR as i32),
+		sampler
+			.min_filter
+			.unwrap_or(WebGl2RenderingContext::LINEAR_MIPMAP_LINEAR as i32),
+		sampler
+			.wrap_s
+			.unwrap_or(WebGl2RenderingContext::REPEAT as i32),
+		sampler
+			.wrap_t
+			.unwrap_or(WebGl2RenderingContext::REPEAT as i32),
+	)
+}
+// Gives each texture the format of the material slots it's in. One shown
+// as a color by some materials and read as data by others is copied, with
+// those others pointed at the copy.
+fn assign_texture_formats(
+	materials: &mut [MaterialConfig],
+	textures: &mut Vec<TextureConfig>,
+) {
+	let mut assigned: Vec<Option<TextureFormat>> = vec![None; textures.len()];
+	let mut copies = HashMap::new();
+	for material in materials.iter_mut() {
+		for (slot, texture) in material.textures_mut() {
+			let texture = match texture {
+				Some(texture) => texture,
+				None => continue,
+			};
+			let index = texture.index as usize;
+			// Left for validation to report.
+			if index >= assigned.len() {
+				continue;
+			}
+			let format = MaterialConfig::slot_format(slot);
+			match assigned[index] {
+				None => {
+					assigned[index] = Some(format);
+					textures[index].format = format;
+				}
+				Some(assigned) if assigned == format => {}
+				Some(_) => {
+					let copy =
+						*copies.entry((index, format)).or_insert_with(|| {
+							textures.push(TextureConfig {
+								format,
+								..textures[index].clone()
+							});
+							textures.len() - 1
+						});
+					texture.index = copy as u32;
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::texture::ColorSpace;
+
+	// Khronos sample model "Triangle" with its buffer embedded.
+	const TRIANGLE: &str = r#"{
+		"asset": { "version": "2.0" },
+		"buffers": [{
+			"uri": "data:application/octet-stream;base64,AAABAAIAAAAAAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAAAAAACAPwAAAAA=",
+			"byteLength": 44
+		}],
+		"bufferViews": [
+			{ "buffer": 0, "byteOffset": 0, "byteLength": 6, "target": 34963 },
+			{ "buffer": 0, "byteOffset": 8, "byteLength": 36, "target": 34962 }
+		],
+		"accessors": [
+			{ "bufferView": 0, "byteOffset": 0, "componentType": 5123,
+				"count": 3, "type": "SCALAR", "max": [2], "min": [0] },
+			{ "bufferView": 1, "byteOffset": 0, "componentType": 5126,
+				"count": 3, "type": "VEC3", "max": [1, 1, 0], "min": [0, 0, 0] }
+		],
+		"meshes": [{ "primitives": [{ "attributes": { "POSITION": 1 }, "indices": 0 }] }]
+	}"#;
+
+	fn triangle() -> (Gltf, Vec<Vec<u8>>) {
+		let gltf = Gltf::from_slice(TRIANGLE.as_bytes()).unwrap();
+		let uri = gltf.document.buffers[0].uri.as_ref().unwrap();
+		let buffer = data_uri::decode(uri).unwrap();
+		(gltf, vec![buffer])
+	}
+
+	#[test]
+	fn parses_and_validates_embedded_triangle() {
+		let (gltf, buffers) = triangle();
+		assert_eq!(buffers[0].len(), 44);
+		assert_eq!(gltf.validate(&buffers), Ok(()));
+	}
+	#[test]
+	fn rejects_unsupported_version() {
+		let json = br#"{ "asset": { "version": "1.0" } }"#;
+		assert_eq!(
+			Gltf::from_slice(json).unwrap_err(),
+			GltfError::UnsupportedVersion(String::from("1.0"))
+		);
+	}
+	#[test]
+	fn detects_short_buffer() {
+		let (gltf, buffers) = triangle();
+		let short: &[u8] = &buffers[0][..40];
+		assert_eq!(
+			gltf.validate(&[short]),
+			Err(GltfError::BufferTooShort {
+				buffer: 0,
+				byte_length: 44,
+				actual: 40,
+			})
+		);
+	}
+	#[test]
+	fn detects_buffer_view_past_buffer_end() {
+		let (mut gltf, buffers) = triangle();
+		gltf.document.buffer_views[1].byte_offset = 12;
+		assert_eq!(
+			gltf.validate_buffer_view(1, &buffers),
+			Err(GltfError::BufferViewOutOfRange {
+				buffer_view: 1,
+				end: 48,
+				buffer_length: 44,
+			})
+		);
+	}
+	#[test]
+	fn detects_accessor_past_buffer_view_end() {
+		let (mut gltf, buffers) = triangle();
+		gltf.document.accessors[1].count = 4;
+		assert_eq!(
+			gltf.validate_accessor(1, &buffers),
+			Err(GltfError::AccessorOutOfRange {
+				accessor: 1,
+				end: 48,
+				buffer_view_length: 36,
+			})
+		);
+	}
+	#[test]
+	fn detects_overflowing_ranges() {
+		let (mut gltf, buffers) = triangle();
+		gltf.document.buffer_views[1].byte_length = usize::MAX;
+		assert_eq!(
+			gltf.validate_buffer_view(1, &buffers),
+			Err(GltfError::BufferViewOverflow(1))
+		);
+
+		let (mut gltf, buffers) = triangle();
+		gltf.document.accessors[1].count = usize::MAX / 4;
+		assert_eq!(
+			gltf.validate_accessor(1, &buffers),
+			Err(GltfError::AccessorOverflow(1))
+		);
+	}
+	#[test]
+	fn detects_stride_smaller_than_element() {
+		let (mut gltf, buffers) = triangle();
+		gltf.document.buffer_views[1].byte_stride = Some(8);
+		assert_eq!(
+			gltf.validate_accessor(1, &buffers),
+			Err(GltfError::StrideSmallerThanElement {
+				accessor: 1,
+				byte_stride: 8,
+				element_size: 12,
+			})
+		);
+	}
+	#[test]
+	fn loads_triangle_from_glb() {
+		let (_gltf, buffers) = triangle();
+		let json = TRIANGLE.replace(
+			r#""uri": "data:application/octet-stream;base64,AAABAAIAAAAAAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAAAAAACAPwAAAAA=","#,
+			"",
+		);
+		let mut json = json.into_bytes();
+		let padding = (4 - json.len() % 4) % 4;
+		json.extend(std::iter::repeat_n(b' ', padding));
+		let mut data = Vec::new();
+		data.extend_from_slice(&glb::MAGIC.to_le_bytes());
+		data.extend_from_slice(&glb::VERSION.to_le_bytes());
+		let length = 12 + 8 + json.len() + 8 + buffers[0].len();
+		data.extend_from_slice(&(length as u32).to_le_bytes());
+		data.extend_from_slice(&(json.len() as u32).to_le_bytes());
+		data.extend_from_slice(&glb::CHUNK_TYPE_JSON.to_le_bytes());
+		data.extend_from_slice(&json);
+		data.extend_from_slice(&(buffers[0].len() as u32).to_le_bytes());
+		data.extend_from_slice(&glb::CHUNK_TYPE_BIN.to_le_bytes());
+		data.extend_from_slice(&buffers[0]);
+
+		let (gltf, mut bin) = Gltf::from_bytes(&data).unwrap();
+		assert_eq!(gltf.document.buffers[0].uri, None);
+		let buffer = gltf.embedded_buffer(0, &mut bin).unwrap();
+		assert_eq!(buffer, buffers[0]);
+		assert_eq!(gltf.validate(&[buffer]), Ok(()));
+		assert_eq!(
+			gltf.embedded_buffer(1, &mut bin),
+			Err(GltfError::MissingBufferUri(1))
+		);
+	}
+	#[test]
+	fn reads_buffer_view_data() {
+		let (gltf, buffers) = triangle();
+		assert_eq!(
+			gltf.buffer_view_data(0, &buffers),
+			Ok(&[0u8, 0, 1, 0, 2, 0][..])
+		);
+	}
+	#[test]
+	fn builds_model_config() {
+		let (gltf, buffers) = triangle();
+		let config = gltf.model_config("Triangle", buffers).unwrap();
+		assert_eq!(config.meshes.len(), 1);
+		let mesh = &config.meshes[0];
+		let index_view = mesh.index_view.as_ref().unwrap();
+		assert_eq!(index_view.buffer_offset, Some(0));
+		assert_eq!(index_view.length, 6);
+		assert_eq!(index_view.component_count, 3);
+		let position = &mesh.buffer_views[0];
+		assert_eq!(position.id, "POSITION");
+		assert_eq!(position.buffer_offset, Some(8));
+		assert_eq!(position.component_size, 3);
+		assert_eq!(position.component_type, WebGl2RenderingContext::FLOAT);
+		assert_eq!(mesh.mode, WebGl2RenderingContext::TRIANGLES);
+		assert!(config.lights.is_empty());
+	}
+	#[test]
+	fn keeps_normalized_accessors() {
+		let (mut gltf, buffers) = triangle();
+		gltf.document.accessors[1].normalized = true;
+		let config = gltf.model_config("Triangle", buffers).unwrap();
+		assert!(config.meshes[0].buffer_views[0].normalized);
+		assert!(!config.meshes[0].index_view.as_ref().unwrap().normalized);
+	}
+	#[test]
+	fn keeps_primitive_modes() {
+		let strip =
+			TRIANGLE.replace(r#""indices": 0"#, r#""indices": 0, "mode": 5"#);
+		let gltf = Gltf::from_slice(strip.as_bytes()).unwrap();
+		let (_, buffers) = triangle();
+		let config = gltf.model_config("Strip", buffers).unwrap();
+		assert_eq!(
+			config.meshes[0].mode,
+			WebGl2RenderingContext::TRIANGLE_STRIP
+		);
+	}
+	#[test]
+	fn converts_material_factors_and_texture_sets() {
+		let (mut gltf, buffers) = triangle();
+		let materials = br#"[
+			{
+				"pbrMetallicRoughness": {
+					"baseColorFactor": [1, 0.5, 0.25, 1],
+					"metallicRoughnessTexture": { "index": 1, "texCoord": 1 },
+					"roughnessFactor": 0.5
+				},
+				"normalTexture": { "index": 0, "scale": 2 },
+				"occlusionTexture": { "index": 1, "strength": 0.25 },
+				"emissiveFactor": [1, 1, 0],
+				"alphaMode": "MASK",
+				"alphaCutoff": 0.25,
+				"doubleSided": true
+			},
+			{}
+		]"#;
+		gltf.document.materials = serde_json::from_slice(materials).unwrap();
+		let config = gltf.model_config("Triangle", buffers).unwrap();
+
+		let material = &config.materials[0];
+		assert_eq!(material.id, "Triangle-material-0");
+		assert_eq!(material.base_color_factor, [1.0, 0.5, 0.25, 1.0]);
+		assert_eq!(material.base_color, None);
+		assert_eq!(
+			(material.metallic_factor, material.roughness_factor),
+			(1.0, 0.5)
+		);
+		assert_eq!(
+			material.metallic_roughness,
+			Some(MaterialTextureConfig {
+				index: 1,
+				tex_coord: 1
+			})
+		);
+		assert_eq!(material.normal_scale, 2.0);
+		assert_eq!(material.occlusion_strength, 0.25);
+		assert_eq!(material.emissive_factor, [1.0, 1.0, 0.0]);
+		assert_eq!(material.alpha_mode, AlphaModeConfig::Mask);
+		assert_eq!(material.alpha_cutoff, 0.25);
+		assert!(material.double_sided);
+
+		// glTF's defaults.
+		let material = &config.materials[1];
+		assert_eq!(material.base_color_factor, [1.0; 4]);
+		assert_eq!(
+			(material.metallic_factor, material.roughness_factor),
+			(1.0, 1.0)
+		);
+		assert_eq!(
+			(material.normal_scale, material.occlusion_strength),
+			(1.0, 1.0)
+		);
+		assert_eq!(material.emissive_factor, [0.0; 3]);
+		assert_eq!(material.alpha_mode, AlphaModeConfig::Opaque);
+		assert_eq!(material.alpha_cutoff, 0.5);
+		assert!(!material.double_sided);
+	}
+	#[test]
+	fn undefined_sampler_fields_follow_the_spec() {
+		let (mut gltf, buffers) = triangle();
+		gltf.document.images =
+			serde_json::from_slice(br#"[{ "uri": "a.png" }]"#).unwrap();
+		gltf.document.samplers = serde_json::from_slice(
+			br#"[{ "minFilter": 9728, "wrapT": 33071 }]"#,
+		)
+		.unwrap();
+		gltf.document.textures = serde_json::from_slice(
+			br#"[{ "source": 0, "sampler": 0 }, { "source": 0 }]"#,
+		)
+		.unwrap();
+		let config = gltf.model_config("Triangle", buffers).unwrap();
+		let repeat = WebGl2RenderingContext::REPEAT as i32;
+		assert_eq!(
+			config.samplers,
+			vec![
+				Sampler::new(
+					WebGl2RenderingContext::LINEAR as i32,
+					WebGl2RenderingContext::NEAREST as i32,
+					repeat,
+					WebGl2RenderingContext::CLAMP_TO_EDGE as i32,
+				),
+				// For the texture without one.
+				Sampler::new(
+					WebGl2RenderingContext::LINEAR as i32,
+					WebGl2RenderingContext::LINEAR_MIPMAP_LINEAR as i32,
+					repeat,
+					repeat,
+				),
+			]
+		);
+		assert_eq!(config.textures[1].sampler, 1);
+	}
+	#[test]
+	fn rejects_textures_without_source() {
+		let (mut gltf, buffers) = triangle();
+		gltf.document.textures = serde_json::from_slice(
+			br#"[{ "extensions": { "KHR_texture_basisu": { "source": 0 } } }]"#,
+		)
+		.unwrap();
+		assert!(matches!(
+			gltf.model_config("Triangle", buffers),
+			Err(GltfError::TextureWithoutSource(0))
+		));
+	}
+	#[test]
+	fn textures_take_the_color_space_of_their_slots() {
+		let (mut gltf, buffers) = triangle();
+		gltf.document.images = serde_json::from_slice(
+			br#"[{ "uri": "a.png" }, { "uri": "b.png" }]"#,
+		)
+		.unwrap();
+		gltf.document.textures =
+			serde_json::from_slice(br#"[{ "source": 0 }, { "source": 1 }]"#)
+				.unwrap();
+		// The first texture is a color to one material and normals to the
+		// other.
+		let materials = br#"[
+			{
+				"pbrMetallicRoughness": { "baseColorTexture": { "index": 0 } },
+				"emissiveTexture": { "index": 0 },
+				"occlusionTexture": { "index": 1 }
+			},
+			{ "normalTexture": { "index": 0 } }
+		]"#;
+		gltf.document.materials = serde_json::from_slice(materials).unwrap();
+		let config = gltf.model_config("Triangle", buffers).unwrap();
+
+		let srgb = MaterialConfig::slot_format("base_color");
+		let linear = MaterialConfig::slot_format("normal");
+		assert_eq!(srgb.color_space, ColorSpace::Srgb);
+		assert_eq!(linear.color_space, ColorSpace::Linear);
+		let formats = config
+			.textures
+			.iter()
+			.map(|texture| (texture.source, texture.format))
+			.collect::<Vec<_>>();
+		assert_eq!(formats, vec![(0, srgb), (1, linear), (0, linear)]);
+		let index =
+			|texture: Option<MaterialTextureConfig>| texture.unwrap().index;
+		assert_eq!(index(config.materials[0].base_color), 0);
+		assert_eq!(index(config.materials[0].emissive), 0);
+		assert_eq!(index(config.materials[0].occlusion), 1);
+		assert_eq!(index(config.materials[1].normal), 2);
+	}
+	#[test]
+	fn places_punctual_lights_with_their_nodes() {
+		let json = br#"{
+			"asset": { "version": "2.0" },
+			"extensions": { "KHR_lights_punctual": { "lights": [
+				{ "type": "spot", "intensity": 20, "range": 10,
+					"spot": { "outerConeAngle": 0.5 } },
+				{ "type": "area" }
+			] } },
+			"nodes": [
+				{ "children": [1], "translation": [1, 0, 0] },
+				{ "translation": [0, 2, 0],
+					"rotation": [-0.70710677, 0, 0, 0.70710677],
+					"extensions": { "KHR_lights_punctual": { "light": 0 } } }
+			]
+		}"#;
+		let mut gltf = Gltf::from_slice(json).unwrap();
+		let lights = gltf.light_configs("lamp").unwrap();
+		match lights[..] {
+			[LightConfig::Spot {
+				ref id,
+				position,
+				direction,
+				intensity,
+				range,
+				inner_cone_angle,
+				outer_cone_angle,
+				..
+			}] => {
+				assert_eq!(id, "lamp-light-1");
+				assert_eq!(position, [1.0, 2.0, 0.0]);
+				cgmath::assert_abs_diff_eq!(
+					Vector3::from(direction),
+					-Vector3::unit_y(),
+					epsilon = 1e-6
+				);
+				assert_eq!((intensity, range), (20.0, Some(10.0)));
+				assert_eq!((inner_cone_angle, outer_cone_angle), (0.0, 0.5));
+			}
+			_ => panic!("expected one spot light, got {:?}", lights),
+		}
+
+		gltf.document.nodes[1].extensions.light =
+			Some(json::NodeLight { light: 1 });
+		assert_eq!(
+			gltf.light_configs("lamp").unwrap_err(),
+			GltfError::UnknownLightType {
+				light: 1,
+				kind: String::from("area"),
+			}
+		);
+	}
+}
@@ -0,0 +1,86 @@
+// File: src/input/keyboard.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Keys are identified by their `code`, the physical key, so movement keys
+// stay in place on non-QWERTY layouts.
+
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyboardEvent {
+	pub key: String,
+	pub code: String,
+	pub repeat: bool,
+
+	pub ctrl: bool,
+	pub shift: bool,
+	pub alt: bool,
+	pub meta: bool,
+}
+impl From<web_sys::KeyboardEvent> for KeyboardEvent {
+	fn from(other: web_sys::KeyboardEvent) -> Self {
+		Self {
+			key: other.key(),
+			code: other.code(),
+			repeat: other.repeat(),
+			ctrl: other.ctrl_key(),
+			shift: other.shift_key(),
+			alt: other.alt_key(),
+			meta: other.meta_key(),
+		}
+	}
+}
+pub trait KeyboardEventSubscriber {
+	fn notify_key_down(&mut self, _event: &KeyboardEvent) {}
+	fn notify_key_up(&mut self, _event: &KeyboardEvent) {}
+	// Codes whose browser default, e.g. scrolling, is prevented.
+	fn bound_keys(&self) -> Vec<&'static str> {
+		Vec::new()
+	}
+}
+
+// The keys currently held down.
+#[derive(Clone, Debug, Default)]
+pub struct KeyState {
+	down: HashSet<String>,
+}
+impl KeyState {
+	pub fn press(&mut self, code: &str) {
+		self.down.insert(code.to_owned());
+	}
+	pub fn release(&mut self, code: &str) {
+		self.down.remove(code);
+	}
+	// For when key up events can be missed, e.g. the page losing focus.
+	// Returns the codes that were down.
+	pub fn release_all(&mut self) -> Vec<String> {
+		self.down.drain().collect()
+	}
+	pub fn is_down(&self, code: &str) -> bool {
+		self.down.contains(code)
+	}
+	pub fn any_down(&self, codes: &[&str]) -> bool {
+		codes.iter().any(|code| self.is_down(code))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tracks_held_keys() {
+		let mut keys = KeyState::default();
+		keys.press("KeyW");
+		keys.press("ShiftLeft");
+		keys.release("KeyW");
+
+		assert!(!keys.is_down("KeyW"));
+		assert!(keys.any_down(&["ShiftLeft", "ShiftRight"]));
+		assert_eq!(keys.release_all(), vec![String::from("ShiftLeft")]);
+		assert!(!keys.is_down("ShiftLeft"));
+	}
+}
@@ -5,25 +5,37 @@
 //
 // Description:
 
+pub mod keyboard;
 pub mod mouse;
 pub mod slider;
 
+use keyboard::{
+	KeyState,
+	KeyboardEvent,
+	KeyboardEventSubscriber,
+};
 use macros::*;
 use mouse::{
 	MouseEvent,
 	MouseEventSubscriber,
+	WheelEvent,
 };
 use std::{
 	cell::RefCell,
+	collections::HashSet,
 	rc::Rc,
 };
 use wasm_bindgen::{
 	prelude::*,
 	JsCast,
 };
-use web_sys::HtmlCanvasElement;
+use web_sys::{
+	HtmlCanvasElement,
+	HtmlElement,
+};
 
 type MouseSubscriberWrapper = Rc<RefCell<dyn MouseEventSubscriber>>;
+type KeyboardSubscriberWrapper = Rc<RefCell<dyn KeyboardEventSubscriber>>;
 // type SliderEventWrapper = Rc<RefCell<dyn SliderEventSubscriber>>;
 
 #[derive(Clone)]
@@ -33,11 +45,19 @@ pub struct InputHandler {
 	mouse_move_events: Vec<MouseEvent>,
 	mouse_enter_event: Option<MouseEvent>,
 	mouse_leave_event: Option<MouseEvent>,
+	mouse_wheel_events: Vec<WheelEvent>,
 	mouse_down_event_subscribers: Vec<MouseSubscriberWrapper>,
 	mouse_up_event_subscribers: Vec<MouseSubscriberWrapper>,
 	mouse_move_event_subscribers: Vec<MouseSubscriberWrapper>,
 	mouse_enter_event_subscribers: Vec<MouseSubscriberWrapper>,
 	mouse_leave_event_subscribers: Vec<MouseSubscriberWrapper>,
+	mouse_wheel_event_subscribers: Vec<MouseSubscriberWrapper>,
+	key_down_events: Vec<KeyboardEvent>,
+	key_up_events: Vec<KeyboardEvent>,
+	key_state: KeyState,
+	key_down_event_subscribers: Vec<KeyboardSubscriberWrapper>,
+	key_up_event_subscribers: Vec<KeyboardSubscriberWrapper>,
+	bound_keys: HashSet<&'static str>,
 	// slider_events: HashMap<String, SliderEvent>,
 	// slider_event_subscribers: HashMap<String, SliderEventWrapper>,
 }
@@ -50,11 +70,19 @@ impl InputHandler {
 			mouse_move_events: Vec::new(),
 			mouse_enter_event: None,
 			mouse_leave_event: None,
+			mouse_wheel_events: Vec::new(),
 			mouse_down_event_subscribers: Vec::new(),
 			mouse_up_event_subscribers: Vec::new(),
 			mouse_move_event_subscribers: Vec::new(),
 			mouse_enter_event_subscribers: Vec::new(),
 			mouse_leave_event_subscribers: Vec::new(),
+			mouse_wheel_event_subscribers: Vec::new(),
+			key_down_events: Vec::new(),
+			key_up_events: Vec::new(),
+			key_state: KeyState::default(),
+			key_down_event_subscribers: Vec::new(),
+			key_up_event_subscribers: Vec::new(),
+			bound_keys: HashSet::new(),
 			// slider_events: HashMap::new(),
 			// slider_event_subscribers: HashMap::new(),
 		}));
@@ -93,6 +121,88 @@ impl InputHandler {
 			);
 			closure.forget();
 		}
+		{
+			// Not a create_callback! since the page must not scroll while
+			// the wheel is used over the canvas.
+			let input_handler = Rc::clone(&input_handler);
+			let closure =
+				Closure::wrap(Box::new(move |event: web_sys::WheelEvent| {
+					event.prevent_default();
+					input_handler
+						.borrow_mut()
+						.mouse_wheel_events
+						.push(WheelEvent::from(event));
+				}) as Box<dyn FnMut(_)>);
+			let _err = canvas.add_event_listener_with_callback(
+				"wheel",
+				closure.as_ref().unchecked_ref(),
+			);
+			closure.forget();
+		}
+		// The canvas only receives key events while focused, so listen on
+		// the window and leave out keys typed into the page's fields.
+		let window = web_sys::window().unwrap();
+		{
+			let input_handler = Rc::clone(&input_handler);
+			let closure =
+				Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+					if targets_text_field(&event) {
+						return;
+					}
+					let mut input_handler = input_handler.borrow_mut();
+					// Shortcuts like ctrl+D keep working.
+					if input_handler.bound_keys.contains(event.code().as_str())
+						&& !(event.ctrl_key()
+							|| event.alt_key() || event.meta_key())
+					{
+						event.prevent_default();
+					}
+					let event = KeyboardEvent::from(event);
+					input_handler.key_state.press(&event.code);
+					input_handler.key_down_events.push(event);
+				}) as Box<dyn FnMut(_)>);
+			let _err = window.add_event_listener_with_callback(
+				"keydown",
+				closure.as_ref().unchecked_ref(),
+			);
+			closure.forget();
+		}
+		{
+			// Not filtered, a key held down before a field took focus still
+			// has to be released.
+			let input_handler = Rc::clone(&input_handler);
+			let closure =
+				Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+					let event = KeyboardEvent::from(event);
+					let mut input_handler = input_handler.borrow_mut();
+					input_handler.key_state.release(&event.code);
+					input_handler.key_up_events.push(event);
+				}) as Box<dyn FnMut(_)>);
+			let _err = window.add_event_listener_with_callback(
+				"keyup",
+				closure.as_ref().unchecked_ref(),
+			);
+			closure.forget();
+		}
+		{
+			// Keys released while the page is in the background never
+			// send a key up, so one is queued for each.
+			let input_handler = Rc::clone(&input_handler);
+			let closure = Closure::wrap(Box::new(move || {
+				let mut input_handler = input_handler.borrow_mut();
+				for code in input_handler.key_state.release_all() {
+					input_handler.key_up_events.push(KeyboardEvent {
+						code,
+						..KeyboardEvent::default()
+					});
+				}
+			}) as Box<dyn FnMut()>);
+			let _err = window.add_event_listener_with_callback(
+				"blur",
+				closure.as_ref().unchecked_ref(),
+			);
+			closure.forget();
+		}
 
 		input_handler
 	}
@@ -102,6 +212,9 @@ impl InputHandler {
 		self.notify_mouse_move_subscribers();
 		self.notify_mouse_enter_subscribers();
 		self.notify_mouse_leave_subscribers();
+		self.notify_mouse_wheel_subscribers();
+		self.notify_key_down_subscribers();
+		self.notify_key_up_subscribers();
 	}
 	pub fn flush_events(&mut self) {
 		self.mouse_down_events.clear();
@@ -109,6 +222,9 @@ impl InputHandler {
 		self.mouse_move_events.clear();
 		self.mouse_enter_event = None;
 		self.mouse_leave_event = None;
+		self.mouse_wheel_events.clear();
+		self.key_down_events.clear();
+		self.key_up_events.clear();
 	}
 	pub fn flush_subscribers(&mut self) {
 		self.mouse_down_event_subscribers.clear();
@@ -116,8 +232,10 @@ impl InputHandler {
 		self.mouse_move_event_subscribers.clear();
 		self.mouse_enter_event_subscribers.clear();
 		self.mouse_leave_event_subscribers.clear();
+		self.mouse_wheel_event_subscribers.clear();
+		self.key_down_event_subscribers.clear();
+		self.key_up_event_subscribers.clear();
 	}
-
 	pub fn subscribe_for_mouse_down_event(
 		&mut self,
 		subscriber: &Rc<RefCell<dyn MouseEventSubscriber>>,
@@ -148,6 +266,25 @@ impl InputHandler {
 	) {
 		self.mouse_leave_event_subscribers.push(subscriber.clone());
 	}
+	pub fn subscribe_for_mouse_wheel_event(
+		&mut self,
+		subscriber: &Rc<RefCell<dyn MouseEventSubscriber>>,
+	) {
+		self.mouse_wheel_event_subscribers.push(subscriber.clone());
+	}
+	pub fn subscribe_for_key_down_event(
+		&mut self,
+		subscriber: &Rc<RefCell<dyn KeyboardEventSubscriber>>,
+	) {
+		self.bound_keys.extend(subscriber.borrow().bound_keys());
+		self.key_down_event_subscribers.push(subscriber.clone());
+	}
+	pub fn subscribe_for_key_up_event(
+		&mut self,
+		subscriber: &Rc<RefCell<dyn KeyboardEventSubscriber>>,
+	) {
+		self.key_up_event_subscribers.push(subscriber.clone());
+	}
 
 	fn notify_mouse_down_subscribers(&self) {
 		gen_notify_event_body_queue!(
@@ -174,4 +311,34 @@ impl InputHandler {
 			"mouse_leave_event_subscribers mouse_leave_event notify_leave"
 		);
 	}
+	fn notify_mouse_wheel_subscribers(&self) {
+		gen_notify_event_body_queue!(
+			"mouse_wheel_event_subscribers mouse_wheel_events notify_wheel"
+		);
+	}
+	fn notify_key_down_subscribers(&self) {
+		gen_notify_event_body_queue!(
+			"key_down_event_subscribers key_down_events notify_key_down"
+		);
+	}
+	fn notify_key_up_subscribers(&self) {
+		gen_notify_event_body_queue!(
+			"key_up_event_subscribers key_up_events notify_key_up"
+		);
+	}
+}
+
+fn targets_text_field(event: &web_sys::KeyboardEvent) -> bool {
+	let element = match event
+		.target()
+		.and_then(|target| target.dyn_into::<HtmlElement>().ok())
+	{
+		Some(element) => element,
+		None => return false,
+	};
+	element.is_content_editable()
+		|| matches!(
+			element.tag_name().as_str(),
+			"INPUT" | "TEXTAREA" | "SELECT"
+		)
 }
@@ -10,7 +10,7 @@ use web_sys::{
 	Element,
 };
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct MouseEvent {
 	pub x: i32,
 	pub y: i32,
@@ -27,24 +27,6 @@ pub struct MouseEvent {
 	pub alt: bool,
 	pub meta: bool,
 }
-impl Default for MouseEvent {
-	fn default() -> Self {
-		Self {
-			x: 0,
-			y: 0,
-			left_btn: false,
-			right_btn: false,
-			middle_btn: false,
-			aux_btn: false,
-			back_btn: false,
-			forward_btn: false,
-			ctrl: false,
-			shift: false,
-			alt: false,
-			meta: false,
-		}
-	}
-}
 impl From<web_sys::MouseEvent> for MouseEvent {
 	fn from(other: web_sys::MouseEvent) -> Self {
 		let buttons = other.buttons();
@@ -64,12 +46,47 @@ impl From<web_sys::MouseEvent> for MouseEvent {
 		}
 	}
 }
+// Scroll deltas are in pixels whatever unit the browser reported them in.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct WheelEvent {
+	pub x: i32,
+	pub y: i32,
+
+	pub delta_x: f64,
+	pub delta_y: f64,
+
+	pub ctrl: bool,
+	pub shift: bool,
+	pub alt: bool,
+	pub meta: bool,
+}
+impl From<web_sys::WheelEvent> for WheelEvent {
+	fn from(other: web_sys::WheelEvent) -> Self {
+		let scale = match other.delta_mode() {
+			web_sys::WheelEvent::DOM_DELTA_LINE => 16.0,
+			web_sys::WheelEvent::DOM_DELTA_PAGE => 800.0,
+			_ => 1.0,
+		};
+		Self {
+			x: other.offset_x(),
+			y: other.offset_y(),
+			delta_x: other.delta_x() * scale,
+			delta_y: other.delta_y() * scale,
+			ctrl: other.ctrl_key(),
+			shift: other.shift_key(),
+			alt: other.alt_key(),
+			meta: other.meta_key(),
+		}
+	}
+}
+
 pub trait MouseEventSubscriber {
 	fn notify_down(&mut self, _event: &MouseEvent) {}
 	fn notify_up(&mut self, _event: &MouseEvent) {}
 	fn notify_move(&mut self, _event: &MouseEvent) {}
 	fn notify_enter(&mut self, _event: &MouseEvent) {}
 	fn notify_leave(&mut self, _event: &MouseEvent) {}
+	fn notify_wheel(&mut self, _event: &WheelEvent) {}
 }
 
 pub struct MouseLocationDisplay {
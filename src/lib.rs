@@ -5,20 +5,30 @@
 //
 // Description:
 
+pub mod backend;
+pub mod bounds;
 pub mod camera;
 pub mod config;
 pub mod context;
+pub mod environment;
 pub mod gltf;
 pub mod input;
 pub mod lights;
 pub mod model;
+pub mod picking;
 pub mod program;
+pub mod renderer;
 pub mod resources;
 pub mod scene_graph;
 pub mod shader;
+pub mod shadow;
+pub mod viewport;
 pub mod warning;
 
-use std::cell::RefCell;
+use std::cell::{
+	Cell,
+	RefCell,
+};
 use std::rc::Rc;
 
 use wasm_bindgen::{
@@ -32,26 +42,26 @@ use web_sys::{
 
 use cgmath::{
 	conv::*,
-	prelude::*,
 	Matrix4,
 };
 
 use input::{
+	keyboard::KeyboardEventSubscriber,
 	mouse::MouseEventSubscriber,
 	InputHandler,
 };
 
-use program::Program;
+use camera::{
+	fly::FlyController,
+	orbit::OrbitController,
+};
+use renderer::Renderer;
 use resources::Resources;
 
-use camera::Camera;
 use context::Context;
 
 use crate::input::mouse::MouseLocationDisplay;
-use crate::input::slider::{
-	CustomSuperellipse,
-	CustomSupershape2D,
-};
+use crate::input::slider::CustomSupershape2D;
 use crate::model::Drawable;
 use crate::{
 	config::{
@@ -80,6 +90,92 @@ extern "C" {
 	fn log(s: &str);
 }
 
+thread_local! {
+	// Set once start has loaded the scene, for the exported functions below.
+	static RENDERER: RefCell<Option<Rc<RefCell<Renderer>>>> =
+		const { RefCell::new(None) };
+	static CONTEXT: RefCell<Option<Rc<Context>>> = const { RefCell::new(None) };
+	static CAMERA_MODE: Cell<CameraMode> = const { Cell::new(CameraMode::Orbit) };
+}
+
+// Which controller drives the active camera.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum CameraMode {
+	Orbit,
+	Fly,
+}
+fn with_renderer<T>(
+	f: impl FnOnce(&mut Renderer) -> Result<T, String>,
+) -> Result<T, JsValue> {
+	RENDERER.with(|renderer| match *renderer.borrow() {
+		Some(ref renderer) => Ok(f(&mut renderer.borrow_mut())?),
+		None => Err(JsValue::from_str("The renderer has not been started")),
+	})
+}
+
+#[wasm_bindgen]
+pub fn set_active_camera(index: usize) -> Result<(), JsValue> {
+	with_renderer(|renderer| renderer.set_active_camera(index))
+}
+#[wasm_bindgen]
+pub fn set_active_camera_by_id(id: &str) -> Result<(), JsValue> {
+	with_renderer(|renderer| renderer.set_active_camera_by_id(id))
+}
+#[wasm_bindgen]
+pub fn active_camera() -> Result<usize, JsValue> {
+	with_renderer(|renderer| Ok(renderer.active_camera_index()))
+}
+#[wasm_bindgen]
+pub fn camera_count() -> Result<usize, JsValue> {
+	with_renderer(|renderer| Ok(renderer.camera_count()))
+}
+
+// "orbit" or "fly".
+#[wasm_bindgen]
+pub fn set_camera_mode(mode: &str) -> Result<(), JsValue> {
+	let mode = match mode {
+		"orbit" => CameraMode::Orbit,
+		"fly" => CameraMode::Fly,
+		_ => {
+			return Err(JsValue::from_str(&format!(
+				"Unknown camera mode '{}', expected 'orbit' or 'fly'",
+				mode
+			)))
+		}
+	};
+	CAMERA_MODE.with(|current| current.set(mode));
+	Ok(())
+}
+
+#[wasm_bindgen(getter_with_clone)]
+pub struct PickedNode {
+	// The node's id from the scene config, undefined when it has none.
+	pub id: Option<String>,
+	// Which of the node's model's meshes.
+	pub mesh: usize,
+}
+
+// The scene graph node drawn at canvas position x and y in CSS pixels, like
+// mouse events use. Undefined where nothing was drawn. Needs the scene's
+// "pick" program.
+#[wasm_bindgen]
+pub fn pick(x: i32, y: i32) -> Result<Option<PickedNode>, JsValue> {
+	let size =
+		CONTEXT.with(|context| context.borrow().as_ref().map(|c| c.size()));
+	let size = size.ok_or("The renderer has not been started")?;
+	let (x, y) = match size.to_ndc(x, y) {
+		Some(ndc) => ndc,
+		None => return Ok(None),
+	};
+	with_renderer(|renderer| {
+		let picked = renderer.pick_gpu(&size.viewport, x, y)?;
+		Ok(picked.map(|picked| PickedNode {
+			id: picked.node.borrow().id().map(String::from),
+			mesh: picked.mesh,
+		}))
+	})
+}
+
 #[wasm_bindgen]
 pub async fn start(config: JsValue) -> Result<(), JsValue> {
 	init_panic_hook();
@@ -125,11 +221,64 @@ pub async fn start(config: JsValue) -> Result<(), JsValue> {
 	gl.enable(WebGl2RenderingContext::DEPTH_TEST);
 
 	let mut resources = Resources::new(Rc::clone(&gl));
-	resources.load_scene(&scene_config).await?;
-	let camera = resources.cameras.get("main").unwrap().clone();
-	let pbr_shader = resources.programs.get("pbr").unwrap().clone();
-
-	setup_program(&gl, &pbr_shader, &camera);
+	let viewport = context.size().viewport;
+	let (scene_graph, _warnings) = resources
+		.load_scene(
+			&scene_config,
+			viewport.width as u32,
+			viewport.height as u32,
+		)
+		.await?;
+	let pbr_shader = Rc::clone(resources.programs.get_by_name("pbr")?);
+	let renderer = Rc::new(RefCell::new(Renderer::new(
+		Rc::clone(&gl),
+		resources,
+		scene_graph,
+		&scene_config,
+	)?));
+	// Scenes without a "pick" program can still be picked on the CPU.
+	let pick_program = renderer
+		.borrow()
+		.resources
+		.programs
+		.get_by_name("pick")
+		.map(Rc::clone);
+	if let Ok(program) = pick_program {
+		renderer.borrow_mut().enable_gpu_picking(program)?;
+	}
+	// Without a "shadow" program lights render unshadowed.
+	let shadow_program = renderer
+		.borrow()
+		.resources
+		.programs
+		.get_by_name("shadow")
+		.map(Rc::clone);
+	if let Ok(program) = shadow_program {
+		renderer.borrow_mut().enable_shadows(program)?;
+	}
+	RENDERER.with(|r| *r.borrow_mut() = Some(Rc::clone(&renderer)));
+	CONTEXT.with(|c| *c.borrow_mut() = Some(Rc::clone(&context)));
+
+	let camera = **renderer.borrow().active_camera()?;
+	let orbit = Rc::new(RefCell::new(OrbitController::new(&camera)));
+	let fly = Rc::new(RefCell::new(FlyController::new(&camera)));
+	let mut controlled_camera = renderer.borrow().active_camera_index();
+	let mut camera_mode = CAMERA_MODE.with(Cell::get);
+	{
+		let fly_keys: Rc<RefCell<dyn KeyboardEventSubscriber>> = fly.clone();
+		let orbit: Rc<RefCell<dyn MouseEventSubscriber>> = orbit.clone();
+		let fly: Rc<RefCell<dyn MouseEventSubscriber>> = fly.clone();
+		let mut input_handler = input_handler.borrow_mut();
+		for controller in [&orbit, &fly] {
+			input_handler.subscribe_for_mouse_down_event(controller);
+			input_handler.subscribe_for_mouse_up_event(controller);
+			input_handler.subscribe_for_mouse_move_event(controller);
+			input_handler.subscribe_for_mouse_leave_event(controller);
+		}
+		input_handler.subscribe_for_mouse_wheel_event(&orbit);
+		input_handler.subscribe_for_key_down_event(&fly_keys);
+		input_handler.subscribe_for_key_up_event(&fly_keys);
+	}
 
 	// let superellipse = Rc::new(RefCell::new(CustomSuperellipse::new(
 	// 	&context.document,
@@ -144,6 +293,7 @@ pub async fn start(config: JsValue) -> Result<(), JsValue> {
 	let g = f.clone();
 
 	let mut previous_time = 0.0;
+	let mut previous_frame_time = context.now()?;
 	let mut frames = 0;
 	let fps_span = context
 		.document
@@ -152,6 +302,10 @@ pub async fn start(config: JsValue) -> Result<(), JsValue> {
 
 	*g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
 		let current_time = context.now().unwrap();
+		// Clamped so a backgrounded tab doesn't fly off when it returns.
+		let frame_seconds =
+			((current_time - previous_frame_time) / 1000.0).min(0.1) as f32;
+		previous_frame_time = current_time;
 		frames += 1;
 		if current_time > (previous_time + 1000.0) {
 			let fps = (frames * 1000) as f64 / (current_time - previous_time);
@@ -160,6 +314,14 @@ pub async fn start(config: JsValue) -> Result<(), JsValue> {
 			fps_span.set_inner_html(&fps.to_string());
 		}
 
+		if let Some(size) = context.resize() {
+			if let Err(e) =
+				renderer.borrow_mut().set_aspect(size.viewport.aspect())
+			{
+				log!("{}", e);
+			}
+		}
+
 		input_handler.borrow_mut().notify_subscribers();
 		input_handler.borrow_mut().flush_events();
 
@@ -169,6 +331,42 @@ pub async fn start(config: JsValue) -> Result<(), JsValue> {
 		);
 
 		{
+			let mut renderer = renderer.borrow_mut();
+			// Frees whatever the last frame stopped referencing.
+			renderer.resources.release_unused();
+			let mut orbit = orbit.borrow_mut();
+			let mut fly = fly.borrow_mut();
+			let mode = CAMERA_MODE.with(Cell::get);
+			if renderer.active_camera_index() != controlled_camera
+				|| mode != camera_mode
+			{
+				controlled_camera = renderer.active_camera_index();
+				camera_mode = mode;
+				if let Ok(camera) = renderer.active_camera() {
+					orbit.sync(camera);
+					fly.sync(camera);
+				}
+			}
+			let updated = renderer.update_active_camera(|camera| {
+				match camera_mode {
+					CameraMode::Orbit => orbit.update(camera),
+					CameraMode::Fly => fly.update(camera, frame_seconds),
+				};
+			});
+			if let Err(e) = updated {
+				log!("{}", e);
+			}
+			if let Err(e) = renderer.render_shadows(&context.size().viewport) {
+				log!("{}", e);
+			}
+		}
+
+		if let Err(e) = renderer.borrow().draw_scene(&pbr_shader) {
+			log!("{}", e);
+		}
+		{
+			// Not in the scene graph, it uses the camera and lights
+			// draw_scene applied.
 			// let mut s = superellipse.borrow_mut();
 			// s.update(&gl);
 			// s.mesh.borrow().draw(&gl, &pbr_shader);
@@ -200,42 +398,6 @@ pub fn mat_4_to_array(mat: &Matrix4<f32>) -> [f32; 16] {
 	unsafe { std::mem::transmute::<[[f32; 4]; 4], [f32; 16]>(array4x4(*mat)) }
 }
 
-fn setup_program(
-	gl: &WebGl2RenderingContext,
-	program: &Program,
-	camera: &Camera,
-) {
-	gl.use_program(Some(&program.program));
-	let uniform_locations = &program.uniform_locations;
-	{
-		let camera_pos_loc = uniform_locations.get("CAMERA_POS");
-		let camera_pos = camera.eye;
-		gl.uniform3f(camera_pos_loc, camera_pos.x, camera_pos.y, camera_pos.z);
-	}
-
-	let model_matrix = Matrix4::identity();
-	{
-		let projection_loc = uniform_locations.get("PROJECTION_MATRIX");
-		let view_loc = uniform_locations.get("VIEW_MATRIX");
-		let model_loc = uniform_locations.get("MODEL_MATRIX");
-
-		gl.uniform_matrix4fv_with_f32_array(
-			projection_loc,
-			false,
-			&mat_4_to_array(&camera.perspective_matrix()),
-		);
-		gl.uniform_matrix4fv_with_f32_array(
-			view_loc,
-			false,
-			&mat_4_to_array(&camera.view_matrix()),
-		);
-		gl.uniform_matrix4fv_with_f32_array(
-			model_loc,
-			false,
-			&mat_4_to_array(&model_matrix),
-		);
-	}
-}
 #[cfg(test)]
 mod tests {
 	#[test]
@@ -0,0 +1,223 @@
+// File: src/lights/manager.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Gathers the scene's lights and uploads them to the POINT_LIGHTS,
+// DIRECTION_LIGHTS and SPOT_LIGHTS uniform arrays pbr.frag and
+// terrain.frag loop over.
+
+use crate::{
+	backend::GlBackend,
+	program::Program,
+	scene_graph::SceneGraph,
+	shadow::MAX_SPOT_SHADOWS,
+};
+
+use super::{
+	DirectionLight,
+	Light,
+	PointLight,
+	SpotLight,
+};
+
+// Must match the array sizes the shaders declare.
+pub const MAX_POINT_LIGHTS: usize = 8;
+pub const MAX_DIRECTION_LIGHTS: usize = 4;
+pub const MAX_SPOT_LIGHTS: usize = 4;
+
+#[derive(Clone, Debug, Default)]
+pub struct LightManager {
+	pub point_lights: Vec<PointLight>,
+	pub direction_lights: Vec<DirectionLight>,
+	pub spot_lights: Vec<SpotLight>,
+}
+impl LightManager {
+	// Lights past the shaders' limits are left out.
+	pub fn new(lights: &[Light]) -> Self {
+		let mut manager = Self::default();
+		for light in lights.iter() {
+			match *light {
+				Light::Point(light)
+					if manager.point_lights.len() < MAX_POINT_LIGHTS =>
+				{
+					manager.point_lights.push(light)
+				}
+				Light::Directional(light)
+					if manager.direction_lights.len()
+						< MAX_DIRECTION_LIGHTS =>
+				{
+					manager.direction_lights.push(light)
+				}
+				Light::Spot(light)
+					if manager.spot_lights.len() < MAX_SPOT_LIGHTS =>
+				{
+					manager.spot_lights.push(light)
+				}
+				_ => (),
+			}
+		}
+		manager
+	}
+	// The graph's lights in world space.
+	pub fn from_scene<G: GlBackend>(scene_graph: &SceneGraph<G>) -> Self {
+		Self::new(&scene_graph.lights())
+	}
+	// Only the first directional light that casts shadows gets cascades.
+	pub fn cascaded_light(&self) -> Option<usize> {
+		self.direction_lights
+			.iter()
+			.position(|light| light.shadow.is_some())
+	}
+	// The spot lights that get shadow maps, in shadow map order.
+	pub fn shadowed_spot_lights(&self) -> Vec<usize> {
+		(0..self.spot_lights.len())
+			.filter(|i| self.spot_lights[*i].shadow.is_some())
+			.take(MAX_SPOT_SHADOWS)
+			.collect()
+	}
+
+	// `program` must be in use. Uniforms the program lacks are skipped.
+	pub fn upload<G: GlBackend>(&self, gl: &G, program: &Program<G>) {
+		let uniform_locations = &program.uniform_locations;
+		let set_vec3 = |name: String, v: [f32; 3]| {
+			gl.uniform3f(uniform_locations.get(&name), v[0], v[1], v[2]);
+		};
+		// The shaders read a range of zero as unlimited.
+		let set_float = |name: String, x: f32| {
+			gl.uniform1f(uniform_locations.get(&name), x);
+		};
+		// Which shadow map a light uses, -1 for none.
+		let set_shadow = |name: String, index: Option<usize>| {
+			let index = index.map_or(-1, |index| index as i32);
+			gl.uniform1i(uniform_locations.get(&name), index);
+		};
+
+		gl.uniform1i(
+			uniform_locations.get("POINT_LIGHT_COUNT"),
+			self.point_lights.len() as i32,
+		);
+		for (i, light) in self.point_lights.iter().enumerate() {
+			set_vec3(
+				format!("POINT_LIGHTS[{}].position", i),
+				light.position.into(),
+			);
+			set_vec3(format!("POINT_LIGHTS[{}].color", i), light.color.into());
+			set_float(
+				format!("POINT_LIGHTS[{}].range", i),
+				light.range.unwrap_or(0.0),
+			);
+		}
+
+		gl.uniform1i(
+			uniform_locations.get("DIRECTION_LIGHT_COUNT"),
+			self.direction_lights.len() as i32,
+		);
+		let cascaded = self.cascaded_light();
+		for (i, light) in self.direction_lights.iter().enumerate() {
+			set_vec3(
+				format!("DIRECTION_LIGHTS[{}].direction", i),
+				light.dir.into(),
+			);
+			set_vec3(
+				format!("DIRECTION_LIGHTS[{}].color", i),
+				light.color.into(),
+			);
+			set_shadow(
+				format!("DIRECTION_LIGHTS[{}].shadow", i),
+				Some(0).filter(|_| cascaded == Some(i)),
+			);
+		}
+
+		gl.uniform1i(
+			uniform_locations.get("SPOT_LIGHT_COUNT"),
+			self.spot_lights.len() as i32,
+		);
+		let shadowed = self.shadowed_spot_lights();
+		for (i, light) in self.spot_lights.iter().enumerate() {
+			let field = |name: &str| format!("SPOT_LIGHTS[{}].{}", i, name);
+			let (scale, offset) = light.angle_scale_offset();
+			set_vec3(field("position"), light.position.into());
+			set_vec3(field("direction"), light.dir.into());
+			set_vec3(field("color"), light.color.into());
+			set_float(field("range"), light.range.unwrap_or(0.0));
+			set_float(field("angle_scale"), scale);
+			set_float(field("angle_offset"), offset);
+			set_shadow(
+				field("shadow"),
+				shadowed.iter().position(|shadowed| *shadowed == i),
+			);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::recording::{
+		GlCall,
+		RecordingBackend,
+	};
+	use crate::config::scene_config::UniformConfig;
+	use crate::shader::Shader;
+	use cgmath::{
+		Point3,
+		Vector3,
+	};
+	use web_sys::WebGl2RenderingContext as Gl;
+
+	fn point(x: f32) -> Light {
+		Light::Point(PointLight {
+			position: Point3::new(x, 0.0, 0.0),
+			color: Vector3::new(1.0, 1.0, 1.0),
+			range: None,
+		})
+	}
+
+	#[test]
+	fn keeps_lights_within_the_shader_limits() {
+		let lights = (0..MAX_POINT_LIGHTS + 2)
+			.map(|i| point(i as f32))
+			.collect::<Vec<_>>();
+		let manager = LightManager::new(&lights);
+		assert_eq!(manager.point_lights.len(), MAX_POINT_LIGHTS);
+		assert_eq!(manager.point_lights[1].position.x, 1.0);
+		assert!(manager.direction_lights.is_empty());
+	}
+	#[test]
+	fn uploads_counts_and_each_light() {
+		let gl = RecordingBackend::new();
+		let uniforms = [
+			UniformConfig::new("POINT_LIGHT_COUNT"),
+			UniformConfig {
+				count: Some(MAX_POINT_LIGHTS),
+				fields: vec![String::from("position"), String::from("color")],
+				..UniformConfig::new("POINT_LIGHTS")
+			},
+		];
+		let vert = Shader::new(&gl, Gl::VERTEX_SHADER, "", &[], &[]).unwrap();
+		let frag =
+			Shader::new(&gl, Gl::FRAGMENT_SHADER, "", &[], &uniforms).unwrap();
+		let program = Program::new(&gl, &vert, &frag).unwrap().0;
+		assert!(program
+			.uniform_locations
+			.contains_key("POINT_LIGHTS[7].color"));
+
+		gl.clear_calls();
+		LightManager::new(&[point(2.0), point(3.0)]).upload(&gl, &program);
+		let calls = gl.calls();
+		assert!(calls.contains(&GlCall::Uniform1i(
+			Some(String::from("POINT_LIGHT_COUNT")),
+			2
+		)));
+		assert!(calls.contains(&GlCall::Uniform3f(
+			Some(String::from("POINT_LIGHTS[1].position")),
+			3.0,
+			0.0,
+			0.0
+		)));
+		// The program has no direction light uniforms.
+		assert!(calls.contains(&GlCall::Uniform1i(None, 0)));
+	}
+}
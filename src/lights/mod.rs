@@ -5,19 +5,186 @@
 //
 // Description:
 
+pub mod manager;
+
 use cgmath::{
+	InnerSpace,
+	Matrix4,
 	Point3,
+	Transform,
 	Vector3,
 };
 
+use crate::config::scene_config::{
+	LightConfig,
+	ShadowConfig,
+};
+
 #[derive(Copy, Clone, Debug)]
 pub struct DirectionLight {
 	pub dir: Vector3<f32>,
 	pub color: Vector3<f32>,
+	// Casts shadows when set.
+	pub shadow: Option<ShadowConfig>,
 }
 
 #[derive(Copy, Clone, Debug)]
 pub struct PointLight {
 	pub position: Point3<f32>,
 	pub color: Vector3<f32>,
+	// Unlimited when None.
+	pub range: Option<f32>,
+}
+
+// Full strength inside the inner cone, fading out to the outer cone. The
+// angles are radians from `dir`.
+#[derive(Copy, Clone, Debug)]
+pub struct SpotLight {
+	pub position: Point3<f32>,
+	pub dir: Vector3<f32>,
+	pub color: Vector3<f32>,
+	pub range: Option<f32>,
+	pub inner_cone_angle: f32,
+	pub outer_cone_angle: f32,
+	pub shadow: Option<ShadowConfig>,
+}
+impl SpotLight {
+	// The scale and offset that map the cosine of the angle from the
+	// direction to the fade, precomputed for the shader.
+	pub fn angle_scale_offset(&self) -> (f32, f32) {
+		let inner = self.inner_cone_angle.cos();
+		let outer = self.outer_cone_angle.cos();
+		let scale = 1.0 / (inner - outer).max(0.001);
+		(scale, -outer * scale)
+	}
+	// How much of the light reaches along `to_point` from the light's
+	// position, before distance attenuation.
+	pub fn cone_attenuation(&self, to_point: Vector3<f32>) -> f32 {
+		let (scale, offset) = self.angle_scale_offset();
+		let cos_angle = self.dir.dot(to_point.normalize());
+		let fade = (cos_angle * scale + offset).clamp(0.0, 1.0);
+		fade * fade
+	}
+}
+
+// Inverse square falloff that reaches zero at `range`, the form the glTF
+// KHR_lights_punctual extension recommends. Matches pbr.frag.
+pub fn range_attenuation(distance: f32, range: Option<f32>) -> f32 {
+	let inverse_square = 1.0 / (distance * distance).max(0.0001);
+	match range {
+		Some(range) if range > 0.0 => {
+			let ratio = distance / range;
+			(1.0 - ratio.powi(4)).clamp(0.0, 1.0) * inverse_square
+		}
+		_ => inverse_square,
+	}
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Light {
+	Point(PointLight),
+	Directional(DirectionLight),
+	Spot(SpotLight),
+}
+impl Light {
+	pub fn from_config(config: &LightConfig) -> Self {
+		match *config {
+			LightConfig::Point {
+				position,
+				color,
+				intensity,
+				range,
+				..
+			} => Light::Point(PointLight {
+				position: Point3::from(position),
+				color: Vector3::from(color) * intensity,
+				range,
+			}),
+			LightConfig::Directional {
+				direction,
+				color,
+				intensity,
+				shadow,
+				..
+			} => Light::Directional(DirectionLight {
+				dir: Vector3::from(direction).normalize(),
+				color: Vector3::from(color) * intensity,
+				shadow,
+			}),
+			LightConfig::Spot {
+				position,
+				direction,
+				color,
+				intensity,
+				range,
+				inner_cone_angle,
+				outer_cone_angle,
+				shadow,
+				..
+			} => Light::Spot(SpotLight {
+				position: Point3::from(position),
+				dir: Vector3::from(direction).normalize(),
+				color: Vector3::from(color) * intensity,
+				range,
+				inner_cone_angle,
+				outer_cone_angle,
+				shadow,
+			}),
+		}
+	}
+	// Moves the light from node space into the space `matrix` maps to.
+	pub fn transformed(&self, matrix: &Matrix4<f32>) -> Self {
+		match *self {
+			Light::Point(light) => Light::Point(PointLight {
+				position: matrix.transform_point(light.position),
+				..light
+			}),
+			Light::Directional(light) => Light::Directional(DirectionLight {
+				dir: matrix.transform_vector(light.dir).normalize(),
+				..light
+			}),
+			Light::Spot(light) => Light::Spot(SpotLight {
+				position: matrix.transform_point(light.position),
+				dir: matrix.transform_vector(light.dir).normalize(),
+				..light
+			}),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cgmath::assert_abs_diff_eq;
+
+	#[test]
+	fn range_fades_inverse_square_to_zero() {
+		assert_abs_diff_eq!(range_attenuation(2.0, None), 0.25);
+		assert_abs_diff_eq!(range_attenuation(10.0, None), 0.01);
+		// Half way out a little is lost to the window.
+		assert_abs_diff_eq!(range_attenuation(2.0, Some(4.0)), 0.25 * 0.9375);
+		assert_eq!(range_attenuation(4.0, Some(4.0)), 0.0);
+		assert_eq!(range_attenuation(5.0, Some(4.0)), 0.0);
+	}
+	#[test]
+	fn spot_cone_fades_between_its_angles() {
+		let spot = SpotLight {
+			position: Point3::new(0.0, 0.0, 0.0),
+			dir: -Vector3::unit_y(),
+			color: Vector3::new(1.0, 1.0, 1.0),
+			range: None,
+			inner_cone_angle: 0.2,
+			outer_cone_angle: 0.6,
+			shadow: None,
+		};
+		let at = |angle: f32| {
+			spot.cone_attenuation(Vector3::new(angle.sin(), -angle.cos(), 0.0))
+		};
+		assert_abs_diff_eq!(at(0.0), 1.0);
+		assert_abs_diff_eq!(at(0.2), 1.0, epsilon = 1e-5);
+		assert!(at(0.4) > 0.0 && at(0.4) < 1.0);
+		assert_abs_diff_eq!(at(0.6), 0.0, epsilon = 1e-5);
+		assert_eq!(at(1.0), 0.0);
+		assert_eq!(spot.cone_attenuation(Vector3::unit_y()), 0.0);
+	}
 }
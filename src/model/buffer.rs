@@ -5,20 +5,19 @@
 //
 // Description:
 
-use web_sys::{
-	WebGl2RenderingContext,
-	WebGlBuffer,
-};
+use web_sys::WebGl2RenderingContext;
+
+use crate::backend::GlBackend;
 
 #[derive(Clone, Debug)]
-pub struct Buffer {
-	pub handle: WebGlBuffer,
+pub struct Buffer<G: GlBackend = WebGl2RenderingContext> {
+	pub handle: G::Buffer,
 	pub buffer_type: u32,
 }
 
-impl Buffer {
+impl<G: GlBackend> Buffer<G> {
 	pub fn new(
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		buffer_type: u32,
 		data: &[u8],
 	) -> Result<Self, &'static str> {
@@ -31,17 +30,17 @@ impl Buffer {
 			buffer_type,
 		})
 	}
-	pub fn bind(&self, gl: &WebGl2RenderingContext) {
+	pub fn bind(&self, gl: &G) {
 		gl.bind_buffer(self.buffer_type, Some(&self.handle));
 	}
-	fn bind_data(gl: &WebGl2RenderingContext, buffer_type: u32, data: &[u8]) {
-		unsafe {
-			let array = js_sys::Uint8Array::view(&data);
-			gl.buffer_data_with_array_buffer_view(
-				buffer_type,
-				&array,
-				WebGl2RenderingContext::STATIC_DRAW,
-			);
-		}
+	pub fn delete(&self, gl: &G) {
+		gl.delete_buffer(Some(&self.handle));
+	}
+	fn bind_data(gl: &G, buffer_type: u32, data: &[u8]) {
+		gl.buffer_data_with_u8_array(
+			buffer_type,
+			data,
+			WebGl2RenderingContext::STATIC_DRAW,
+		);
 	}
 }
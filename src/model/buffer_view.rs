@@ -18,6 +18,7 @@ pub struct BufferView {
 	pub component_size: i32,
 	pub component_count: i32,
 	pub component_type: u32,
+	pub normalized: bool,
 }
 impl BufferView {
 	pub fn new(intermediate: &BufferViewConfig) -> Self {
@@ -33,6 +34,7 @@ impl BufferView {
 			component_size: intermediate.component_size,
 			component_count: intermediate.component_count,
 			component_type: intermediate.component_type,
+			normalized: intermediate.normalized,
 		}
 	}
 }
@@ -5,126 +5,180 @@
 //
 // Description:
 
-use std::collections::HashMap;
 use std::rc::Rc;
 
-use web_sys::{
-	WebGl2RenderingContext,
-	WebGlUniformLocation,
+use cgmath::{
+	Vector3,
+	Vector4,
 };
+use web_sys::WebGl2RenderingContext;
+
+type Gl = WebGl2RenderingContext;
 
 use super::texture::Texture;
+use crate::backend::GlBackend;
+use crate::environment::{
+	bind_environment,
+	Environment,
+};
+use crate::program::UniformLocations;
+
+// pbr.frag samples TEXCOORD_0 and TEXCOORD_1.
+pub const MAX_TEX_COORDS: usize = 2;
+
+// How a material's alpha is used, as glTF's alphaMode.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum AlphaMode {
+	// Alpha is ignored.
+	#[default]
+	Opaque,
+	// Fragments with alpha below the cutoff are discarded, the rest are
+	// opaque.
+	Mask(f32),
+	// Blended over what's behind, see SceneGraph::draw_sorted.
+	Blend,
+}
+impl AlphaMode {
+	// ALPHA_MODE in pbr.frag.
+	fn index(&self) -> i32 {
+		match self {
+			AlphaMode::Opaque => 0,
+			AlphaMode::Mask(_) => 1,
+			AlphaMode::Blend => 2,
+		}
+	}
+}
+
+// A texture and the TEXCOORD_n set it's sampled with.
+#[derive(Clone, Debug)]
+pub struct MaterialTexture<G: GlBackend = WebGl2RenderingContext> {
+	pub texture: Rc<Texture<G>>,
+	pub tex_coord: u32,
+}
 
+// glTF's metallic-roughness material. Each texture is scaled by its factor,
+// a missing texture samples as one.
 #[derive(Clone, Debug)]
-pub struct Material {
-	pub diffuse_tex: Option<Rc<Texture>>,
-	pub normal_tex: Option<Rc<Texture>>,
-	pub metallic_roughness_part: MetallicRoughnessPart,
-	pub occlusion_part: OcclusionPart,
+pub struct Material<G: GlBackend = WebGl2RenderingContext> {
+	pub base_color_factor: Vector4<f32>,
+	pub base_color_tex: Option<MaterialTexture<G>>,
+	pub metallic_factor: f32,
+	pub roughness_factor: f32,
+	// Metalness in blue, roughness in green.
+	pub metallic_roughness_tex: Option<MaterialTexture<G>>,
+	pub normal_tex: Option<MaterialTexture<G>>,
+	pub normal_scale: f32,
+	pub occlusion_tex: Option<MaterialTexture<G>>,
+	pub occlusion_strength: f32,
+	pub emissive_tex: Option<MaterialTexture<G>>,
+	pub emissive_factor: Vector3<f32>,
+	pub alpha_mode: AlphaMode,
+	// Back faces are culled unless set.
+	pub double_sided: bool,
+	// Image based lighting, shared by every material in the scene.
+	pub environment: Option<Rc<Environment<G>>>,
 }
-impl Default for Material {
+// glTF's defaults, a white rough metal.
+impl<G: GlBackend> Default for Material<G> {
 	fn default() -> Self {
 		Self {
-			diffuse_tex: None,
+			base_color_factor: Vector4::new(1.0, 1.0, 1.0, 1.0),
+			base_color_tex: None,
+			metallic_factor: 1.0,
+			roughness_factor: 1.0,
+			metallic_roughness_tex: None,
 			normal_tex: None,
-			metallic_roughness_part: MetallicRoughnessPart::default(),
-			occlusion_part: OcclusionPart::default(),
+			normal_scale: 1.0,
+			occlusion_tex: None,
+			occlusion_strength: 1.0,
+			emissive_tex: None,
+			emissive_factor: Vector3::new(0.0, 0.0, 0.0),
+			alpha_mode: AlphaMode::default(),
+			double_sided: false,
+			environment: None,
 		}
 	}
 }
-impl Material {
+impl<G: GlBackend> Material<G> {
+	pub fn is_blended(&self) -> bool {
+		self.alpha_mode == AlphaMode::Blend
+	}
+	pub fn set_face_culling(&self, gl: &G) {
+		if self.double_sided {
+			gl.disable(Gl::CULL_FACE);
+		} else {
+			gl.enable(Gl::CULL_FACE);
+		}
+	}
 	pub fn bind_to_uniforms(
 		&self,
-		gl: &WebGl2RenderingContext,
-		uniform_locations: &HashMap<String, WebGlUniformLocation>,
+		gl: &G,
+		uniform_locations: &UniformLocations<G>,
 	) {
-		let mut texture_unit = 0;
-
-		let use_diffuse_tex = if let Some(ref diffuse_tex) = self.diffuse_tex {
-			let diffuse_tex_loc = uniform_locations.get("DIFFUSE_TEX");
-			gl.uniform1i(diffuse_tex_loc, texture_unit);
-			diffuse_tex.bind(gl, texture_unit as u32);
-			texture_unit += 1;
-			true
-		} else {
-			false
-		};
-		let use_diffuse_tex_loc = uniform_locations.get("USE_DIFFUSE_TEX");
-		gl.uniform1i(use_diffuse_tex_loc, use_diffuse_tex as i32);
+		// Each texture gets the next unit, the rest stay below the
+		// environment's.
+		let textures = [
+			("BASE_COLOR", &self.base_color_tex),
+			("METALLIC_ROUGHNESS", &self.metallic_roughness_tex),
+			("NORMAL", &self.normal_tex),
+			("OCCLUSION", &self.occlusion_tex),
+			("EMISSIVE", &self.emissive_tex),
+		];
+		for (unit, (name, texture)) in textures.iter().enumerate() {
+			let unit = unit as u32;
+			let use_loc = uniform_locations.get(&format!("USE_{}_TEX", name));
+			gl.uniform1i(use_loc, texture.is_some() as i32);
+			let tex_loc = uniform_locations.get(&format!("{}_TEX", name));
+			gl.uniform1i(tex_loc, unit as i32);
+			if let Some(texture) = texture {
+				let tex_coord_loc =
+					uniform_locations.get(&format!("{}_TEX_COORD", name));
+				gl.uniform1i(tex_coord_loc, texture.tex_coord as i32);
+				texture.texture.bind(gl, unit);
+			}
+		}
 
-		let use_normal_tex = if let Some(ref normal_tex) = self.normal_tex {
-			let normal_tex_loc = uniform_locations.get("NORMAL_TEX");
-			gl.uniform1i(normal_tex_loc, texture_unit);
-			normal_tex.bind(gl, texture_unit as u32);
-			texture_unit += 1;
-			true
-		} else {
-			false
-		};
-		let use_normal_tex_loc = uniform_locations.get("USE_NORMAL_TEX");
-		gl.uniform1i(use_normal_tex_loc, use_normal_tex as i32);
+		let factor = self.base_color_factor;
+		gl.uniform4f(
+			uniform_locations.get("BASE_COLOR_FACTOR"),
+			factor.x,
+			factor.y,
+			factor.z,
+			factor.w,
+		);
+		gl.uniform1f(
+			uniform_locations.get("METALLIC_FACTOR"),
+			self.metallic_factor,
+		);
+		gl.uniform1f(
+			uniform_locations.get("ROUGHNESS_FACTOR"),
+			self.roughness_factor,
+		);
+		gl.uniform1f(uniform_locations.get("NORMAL_SCALE"), self.normal_scale);
+		gl.uniform1f(
+			uniform_locations.get("OCCLUSION_STRENGTH"),
+			self.occlusion_strength,
+		);
+		let emissive = self.emissive_factor;
+		gl.uniform3f(
+			uniform_locations.get("EMISSIVE_FACTOR"),
+			emissive.x,
+			emissive.y,
+			emissive.z,
+		);
 
-		let use_matallic_roughness_tex = match self.metallic_roughness_part {
-			MetallicRoughnessPart::Texture(ref t) => {
-				let metallic_roughness_loc =
-					uniform_locations.get("METALLIC_ROUGHNESS_TEX");
-				gl.uniform1i(metallic_roughness_loc, texture_unit);
-				t.bind(gl, texture_unit as u32);
-				texture_unit += 1;
-				true
-			}
-			MetallicRoughnessPart::Values(m, r) => {
-				let metallic_loc = uniform_locations.get("METALLIC");
-				let roughness_loc = uniform_locations.get("ROUGHNESS");
-				gl.uniform1f(metallic_loc, m);
-				gl.uniform1f(roughness_loc, r);
-				false
-			}
-		};
-		let use_metallic_roughness_tex_loc =
-			uniform_locations.get("USE_METALLIC_ROUGHNESS_TEX");
 		gl.uniform1i(
-			use_metallic_roughness_tex_loc,
-			use_matallic_roughness_tex as i32,
+			uniform_locations.get("ALPHA_MODE"),
+			self.alpha_mode.index(),
+		);
+		if let AlphaMode::Mask(cutoff) = self.alpha_mode {
+			gl.uniform1f(uniform_locations.get("ALPHA_CUTOFF"), cutoff);
+		}
+		gl.uniform1i(
+			uniform_locations.get("DOUBLE_SIDED"),
+			self.double_sided as i32,
 		);
 
-		let use_occlusion_tex = match self.occlusion_part {
-			OcclusionPart::Texture(ref t) => {
-				let occlusion_tex_loc = uniform_locations.get("OCCLUSION_TEX");
-				gl.uniform1i(occlusion_tex_loc, texture_unit);
-				t.bind(gl, texture_unit as u32);
-				// texture_unit += 1;
-				true
-			}
-			OcclusionPart::Value(ao) => {
-				let occlusion_loc = uniform_locations.get("OCCLUSION");
-				gl.uniform1f(occlusion_loc, ao);
-				false
-			}
-		};
-		let use_occlusion_tex_loc = uniform_locations.get("USE_OCCLUSION_TEX");
-		gl.uniform1i(use_occlusion_tex_loc, use_occlusion_tex as i32);
-	}
-}
-
-#[derive(Clone, Debug)]
-pub enum MetallicRoughnessPart {
-	Texture(Rc<Texture>),
-	Values(f32, f32),
-}
-impl Default for MetallicRoughnessPart {
-	fn default() -> Self {
-		MetallicRoughnessPart::Values(0.0, 0.0)
-	}
-}
-
-#[derive(Clone, Debug)]
-pub enum OcclusionPart {
-	Texture(Rc<Texture>),
-	Value(f32),
-}
-impl Default for OcclusionPart {
-	fn default() -> Self {
-		OcclusionPart::Value(0.0)
+		bind_environment(gl, uniform_locations, self.environment.as_deref());
 	}
 }
@@ -8,8 +8,8 @@
 use std::rc::Rc;
 
 use cgmath::Point3;
-use web_sys::WebGl2RenderingContext;
 
+use crate::backend::GlBackend;
 use crate::model::mesh::Mesh;
 
 use super::{
@@ -19,7 +19,7 @@ use super::{
 	MeshMode,
 };
 
-pub fn generate_cube(gl: &WebGl2RenderingContext) -> Rc<Mesh> {
+pub fn generate_cube<G: GlBackend>(gl: &G) -> Rc<Mesh<G>> {
 	let vertex_count = 8;
 	let index_count = 36;
 	let face_count = 12;
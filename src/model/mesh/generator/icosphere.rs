@@ -21,8 +21,7 @@ use cgmath::{
 	Zero,
 };
 
-use web_sys::WebGl2RenderingContext;
-
+use crate::backend::GlBackend;
 use crate::model::mesh::Mesh;
 
 use super::{
@@ -36,11 +35,11 @@ type Indices = Vec<u32>;
 type SharedIndicesKey = ([u8; 4], [u8; 4]);
 type SharedIndices = HashMap<SharedIndicesKey, usize>;
 
-pub fn generate_icosphere(
-	gl: &WebGl2RenderingContext,
+pub fn generate_icosphere<G: GlBackend>(
+	gl: &G,
 	radius: f32,
 	subdivisions: usize,
-) -> Rc<Mesh> {
+) -> Rc<Mesh<G>> {
 	let (mut vertices, mut indices, mut shared_indices) =
 		icosahedron_smooth_normals(radius);
 
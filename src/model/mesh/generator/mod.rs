@@ -28,11 +28,13 @@ use cgmath::{
 
 use web_sys::WebGl2RenderingContext;
 
+use crate::backend::GlBackend;
 use crate::model::{
 	buffer::Buffer,
 	buffer_view::BufferView,
 	material::Material,
 	mesh::{
+		geometry::Geometry,
 		AttributeBufferViews,
 		Mesh,
 	},
@@ -50,30 +52,30 @@ pub enum MeshMode {
 }
 
 pub trait MeshGeneratorT {
-	fn generate_mesh(
+	fn generate_mesh<G: GlBackend>(
 		&mut self,
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		mode: MeshMode,
-	) -> Mesh;
-	fn generate_rc_mesh(
+	) -> Mesh<G>;
+	fn generate_rc_mesh<G: GlBackend>(
 		&mut self,
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		mode: MeshMode,
-	) -> Rc<Mesh> {
+	) -> Rc<Mesh<G>> {
 		Rc::new(self.generate_mesh(gl, mode))
 	}
-	fn generate_refcell_mesh(
+	fn generate_refcell_mesh<G: GlBackend>(
 		&mut self,
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		mode: MeshMode,
-	) -> RefCell<Mesh> {
+	) -> RefCell<Mesh<G>> {
 		RefCell::new(self.generate_mesh(gl, mode))
 	}
-	fn generate_rc_refcell_mesh(
+	fn generate_rc_refcell_mesh<G: GlBackend>(
 		&mut self,
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		mode: MeshMode,
-	) -> Rc<RefCell<Mesh>> {
+	) -> Rc<RefCell<Mesh<G>>> {
 		Rc::new(self.generate_refcell_mesh(gl, mode))
 	}
 }
@@ -96,11 +98,11 @@ impl Default for MeshGenerator {
 	}
 }
 impl MeshGeneratorT for MeshGenerator {
-	fn generate_mesh(
+	fn generate_mesh<G: GlBackend>(
 		&mut self,
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		mode: MeshMode,
-	) -> Mesh {
+	) -> Mesh<G> {
 		let (buffers, index_buffer_view, attribute_buffer_views, mode) =
 			match mode {
 				MeshMode::Points => {
@@ -179,8 +181,15 @@ impl MeshGeneratorT for MeshGenerator {
 					)
 				}
 			};
-		let material = Rc::new(Material::default());
-		Mesh::new(
+		// Plain white plastic rather than glTF's default metal. Double sided
+		// as the generators don't wind their triangles consistently.
+		let material = Rc::new(Material {
+			metallic_factor: 0.0,
+			roughness_factor: 0.5,
+			double_sided: true,
+			..Material::default()
+		});
+		let mut mesh = Mesh::new(
 			gl,
 			&material,
 			&buffers,
@@ -188,7 +197,15 @@ impl MeshGeneratorT for MeshGenerator {
 			&attribute_buffer_views,
 			mode,
 		)
-		.unwrap()
+		.unwrap();
+		let geometry = Geometry::new(
+			self.vertices.iter().map(|v| v.position).collect(),
+			index_buffer_view.and(self.indices.as_deref()),
+			mode,
+		);
+		mesh.bounds = geometry.bounds();
+		mesh.geometry = Some(Rc::new(geometry));
+		mesh
 	}
 }
 impl MeshGenerator {
@@ -329,10 +346,10 @@ impl MeshGenerator {
 				vertex.tangent = Some(tangent.normalize());
 			});
 	}
-	fn create_attribute_buffer(
+	fn create_attribute_buffer<G: GlBackend>(
 		&self,
-		gl: &WebGl2RenderingContext,
-	) -> (Rc<Buffer>, AttributeBufferViews) {
+		gl: &G,
+	) -> (Rc<Buffer<G>>, AttributeBufferViews) {
 		let vertex_count = self.vertices.len();
 		let mut data_f32 = Vec::with_capacity(
 			vertex_count * self.vertices[0].stride() as usize,
@@ -369,10 +386,10 @@ impl MeshGenerator {
 		};
 		(attribute_buffer, attribute_buffer_views)
 	}
-	fn create_index_buffer(
+	fn create_index_buffer<G: GlBackend>(
 		&self,
-		gl: &WebGl2RenderingContext,
-	) -> (Rc<Buffer>, BufferView) {
+		gl: &G,
+	) -> (Rc<Buffer<G>>, BufferView) {
 		let indices = self.indices.as_ref().expect("Expected Some index data");
 
 		let ratio = mem::size_of::<u32>() / mem::size_of::<u8>();
@@ -400,6 +417,7 @@ impl MeshGenerator {
 			component_size: 1,
 			component_count: indices.len() as i32,
 			component_type: WebGl2RenderingContext::UNSIGNED_INT,
+			normalized: false,
 		};
 
 		(index_buffer, index_buffer_view)
@@ -2,8 +2,7 @@ use core::f64::consts::PI;
 
 use cgmath::Point3;
 
-use web_sys::WebGl2RenderingContext;
-
+use crate::backend::GlBackend;
 use crate::model::mesh::{
 	generator::{
 		vertex::Vertex,
@@ -72,11 +71,11 @@ impl Superellipse {
 	}
 }
 impl MeshGeneratorT for Superellipse {
-	fn generate_mesh(
+	fn generate_mesh<G: GlBackend>(
 		&mut self,
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		mode: MeshMode,
-	) -> Mesh {
+	) -> Mesh<G> {
 		assert!(mode == MeshMode::Points || mode == MeshMode::TriangleFan);
 		let points = self.points();
 		let mut mesh_generator = MeshGenerator::new(None, None, None);
@@ -7,6 +7,7 @@
 
 use std::rc::Rc;
 
+use crate::backend::GlBackend;
 use crate::context::Context;
 use crate::{
 	model::Drawable,
@@ -34,28 +35,22 @@ use web_sys::{
 	WebGl2RenderingContext,
 };
 
-impl Drawable for Terrain {
-	fn draw(&self, gl: &WebGl2RenderingContext, program: &Program) {
+impl<G: GlBackend> Drawable<G> for Terrain<G> {
+	fn draw(&self, gl: &G, program: &Program<G>) {
 		self.mesh.draw(gl, program);
 	}
 }
 
-pub struct Terrain {
+pub struct Terrain<G: GlBackend = WebGl2RenderingContext> {
 	chunk_size: usize,
 	scale: Vector3<f32>,
 
-	mesh: Rc<Mesh>,
+	mesh: Rc<Mesh<G>>,
 	// indices: Vec<u32>,
 	// vertices: Vec<Vertex>,
 }
 
 impl Terrain {
-	pub fn chunk_size(&self) -> usize {
-		self.chunk_size
-	}
-	pub fn scale(&self) -> &Vector3<f32> {
-		&self.scale
-	}
 	pub async fn generate_from_image(
 		gl: &WebGl2RenderingContext,
 		chunk_size: usize,
@@ -121,10 +116,18 @@ impl Terrain {
 			heights
 		};
 
-		Ok(Terrain::generate(&gl, chunk_size, &scale, &heights)?)
+		Ok(Terrain::generate(gl, chunk_size, scale, &heights)?)
+	}
+}
+impl<G: GlBackend> Terrain<G> {
+	pub fn chunk_size(&self) -> usize {
+		self.chunk_size
+	}
+	pub fn scale(&self) -> &Vector3<f32> {
+		&self.scale
 	}
 	pub fn generate(
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		chunk_size: usize,
 		scale: &Vector3<f32>,
 		heights: &[f32],
@@ -147,7 +150,7 @@ impl Terrain {
 		{
 			let vertices = generator.mut_ref_vertices();
 			Self::_init_vertices_with_positions(
-				vertices, chunk_size, &scale, heights,
+				vertices, chunk_size, scale, heights,
 			);
 		}
 		{
@@ -279,11 +282,13 @@ impl Terrain {
 	}
 	fn _init_indices(indices: &mut Vec<u32>, chunk_size: usize) {
 		for x in 0..chunk_size {
-			let mut lower_left = x * (chunk_size + 1);
-			let mut lower_right = lower_left + 1;
-			let mut upper_left = lower_left + chunk_size + 1;
-			let mut upper_right = upper_left + 1;
-			for _z in 0..(chunk_size) {
+			let row_start = x * (chunk_size + 1);
+			for z in 0..chunk_size {
+				let lower_left = row_start + z;
+				let lower_right = lower_left + 1;
+				let upper_left = lower_left + chunk_size + 1;
+				let upper_right = upper_left + 1;
+
 				indices.push(lower_left as u32);
 				indices.push(lower_right as u32);
 				indices.push(upper_left as u32);
@@ -291,11 +296,6 @@ impl Terrain {
 				indices.push(lower_right as u32);
 				indices.push(upper_right as u32);
 				indices.push(upper_left as u32);
-
-				lower_left = lower_right;
-				lower_right += 1;
-				upper_left = upper_right;
-				upper_right += 1;
 			}
 		}
 	}
@@ -16,8 +16,7 @@ use cgmath::{
 	Vector3,
 };
 
-use web_sys::WebGl2RenderingContext;
-
+use crate::backend::GlBackend;
 use crate::model::mesh::Mesh;
 
 use super::{
@@ -27,12 +26,12 @@ use super::{
 	MeshMode,
 };
 
-pub fn generate_uv_sphere(
-	gl: &WebGl2RenderingContext,
+pub fn generate_uv_sphere<G: GlBackend>(
+	gl: &G,
 	radius: f32,
 	vertical_subdivisions: usize,
 	horizontal_subdivisions: usize,
-) -> Rc<Mesh> {
+) -> Rc<Mesh<G>> {
 	let vertex_count =
 		(vertical_subdivisions + 1) * (horizontal_subdivisions + 1);
 	let index_count = (vertical_subdivisions - 1) * horizontal_subdivisions * 6;
@@ -99,10 +98,12 @@ pub fn generate_uv_sphere(
 	}
 
 	for i in 0..stack_count {
-		let mut k1 = i * (sector_count + 1);
-		let mut k2 = k1 + sector_count + 1;
+		let stack_start = i * (sector_count + 1);
+
+		for j in 0..sector_count {
+			let k1 = stack_start + j;
+			let k2 = k1 + sector_count + 1;
 
-		for _j in 0..sector_count {
 			if i != 0 {
 				generator.push_index_slice(&[k1, k2, k1 + 1])
 			}
@@ -110,9 +111,6 @@ pub fn generate_uv_sphere(
 			if i != (stack_count - 1) {
 				generator.push_index_slice(&[k1 + 1, k2, k2 + 1])
 			}
-
-			k1 += 1;
-			k2 += 1;
 		}
 	}
 
@@ -143,6 +143,7 @@ impl Vertex {
 			component_size,
 			component_count: vertex_count as i32,
 			component_type: WebGl2RenderingContext::FLOAT,
+			normalized: false,
 		}
 	}
 }
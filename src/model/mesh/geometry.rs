@@ -0,0 +1,200 @@
+// File: src/model/mesh/geometry.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// A copy of a mesh's positions and triangles kept on the CPU for bounds and
+// picking, the GPU buffers can't be read back.
+
+use std::convert::TryInto;
+
+use cgmath::Point3;
+use web_sys::WebGl2RenderingContext;
+
+use crate::bounds::Aabb;
+use crate::model::buffer_view::BufferView;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Geometry {
+	pub positions: Vec<Point3<f32>>,
+	// Indices into positions, wound as drawn.
+	pub triangles: Vec<[u32; 3]>,
+}
+impl Geometry {
+	// Lists the triangles `mode` draws from `indices`, or from the
+	// positions in order without them. Modes that draw no triangles, points
+	// and lines, leave the list empty.
+	pub fn new(
+		positions: Vec<Point3<f32>>,
+		indices: Option<&[u32]>,
+		mode: u32,
+	) -> Self {
+		let sequential;
+		let indices = match indices {
+			Some(indices) => indices,
+			None => {
+				sequential = (0..positions.len() as u32).collect::<Vec<_>>();
+				&sequential
+			}
+		};
+		let triangles = match mode {
+			WebGl2RenderingContext::TRIANGLES => indices
+				.chunks_exact(3)
+				.map(|t| [t[0], t[1], t[2]])
+				.collect(),
+			WebGl2RenderingContext::TRIANGLE_FAN => indices
+				.windows(2)
+				.skip(1)
+				.map(|t| [indices[0], t[0], t[1]])
+				.collect(),
+			// Every other triangle is flipped to keep the winding.
+			WebGl2RenderingContext::TRIANGLE_STRIP => indices
+				.windows(3)
+				.enumerate()
+				.map(|(i, t)| {
+					if i % 2 == 0 {
+						[t[0], t[1], t[2]]
+					} else {
+						[t[1], t[0], t[2]]
+					}
+				})
+				.collect(),
+			_ => Vec::new(),
+		};
+		let len = positions.len() as u32;
+		let triangles = triangles
+			.into_iter()
+			.filter(|t: &[u32; 3]| t.iter().all(|i| *i < len))
+			.collect();
+		Self {
+			positions,
+			triangles,
+		}
+	}
+	pub fn bounds(&self) -> Option<Aabb> {
+		Aabb::from_points(self.positions.iter().copied())
+	}
+	pub fn triangle(&self, index: usize) -> [Point3<f32>; 3] {
+		let [a, b, c] = self.triangles[index];
+		[
+			self.positions[a as usize],
+			self.positions[b as usize],
+			self.positions[c as usize],
+		]
+	}
+}
+
+// Reads the positions `view` describes out of the buffer they were
+// uploaded from. Only three component float positions are understood.
+pub fn read_positions(
+	data: &[u8],
+	view: &BufferView,
+) -> Option<Vec<Point3<f32>>> {
+	if view.component_type != WebGl2RenderingContext::FLOAT
+		|| view.component_size != 3
+	{
+		return None;
+	}
+	let stride = match view.stride {
+		0 => 3 * 4,
+		stride => stride as usize,
+	};
+	let start = view.combined_offset as usize;
+	let read = |offset: usize| {
+		let bytes = data.get(offset..offset + 4)?.try_into().ok()?;
+		Some(f32::from_le_bytes(bytes))
+	};
+	(0..view.component_count as usize)
+		.map(|i| {
+			let offset = start + i * stride;
+			Some(Point3::new(
+				read(offset)?,
+				read(offset + 4)?,
+				read(offset + 8)?,
+			))
+		})
+		.collect()
+}
+// Reads `count` tightly packed unsigned indices of `component_type`.
+pub fn read_indices(
+	data: &[u8],
+	component_type: u32,
+	count: usize,
+) -> Option<Vec<u32>> {
+	let size = match component_type {
+		WebGl2RenderingContext::UNSIGNED_BYTE => 1,
+		WebGl2RenderingContext::UNSIGNED_SHORT => 2,
+		WebGl2RenderingContext::UNSIGNED_INT => 4,
+		_ => return None,
+	};
+	let data = data.get(..count * size)?;
+	Some(
+		data.chunks_exact(size)
+			.map(|bytes| match *bytes {
+				[a] => a as u32,
+				[a, b] => u16::from_le_bytes([a, b]) as u32,
+				[a, b, c, d] => u32::from_le_bytes([a, b, c, d]),
+				_ => unreachable!(),
+			})
+			.collect(),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn points(count: usize) -> Vec<Point3<f32>> {
+		(0..count)
+			.map(|i| Point3::new(i as f32, 0.0, 0.0))
+			.collect::<Vec<_>>()
+	}
+
+	#[test]
+	fn lists_the_triangles_each_mode_draws() {
+		let fan = Geometry::new(
+			points(5),
+			None,
+			WebGl2RenderingContext::TRIANGLE_FAN,
+		);
+		assert_eq!(fan.triangles, vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+
+		let strip = Geometry::new(
+			points(5),
+			None,
+			WebGl2RenderingContext::TRIANGLE_STRIP,
+		);
+		assert_eq!(strip.triangles, vec![[0, 1, 2], [2, 1, 3], [2, 3, 4]]);
+
+		// Out of range indices are dropped rather than panicking later.
+		let indexed = Geometry::new(
+			points(3),
+			Some(&[2, 1, 0, 0, 1, 7, 1]),
+			WebGl2RenderingContext::TRIANGLES,
+		);
+		assert_eq!(indexed.triangles, vec![[2, 1, 0]]);
+		assert_eq!(indexed.triangle(0)[0], Point3::new(2.0, 0.0, 0.0));
+
+		let points =
+			Geometry::new(points(3), None, WebGl2RenderingContext::POINTS);
+		assert!(points.triangles.is_empty());
+		assert!(points.bounds().is_some());
+	}
+	#[test]
+	fn reads_each_index_size() {
+		let shorts = [1u8, 0, 0, 1];
+		assert_eq!(
+			read_indices(&shorts, WebGl2RenderingContext::UNSIGNED_SHORT, 2),
+			Some(vec![1, 256])
+		);
+		assert_eq!(
+			read_indices(&shorts, WebGl2RenderingContext::UNSIGNED_BYTE, 3),
+			Some(vec![1, 0, 0])
+		);
+		assert_eq!(
+			read_indices(&shorts, WebGl2RenderingContext::UNSIGNED_INT, 2),
+			None
+		);
+	}
+}
@@ -6,21 +6,22 @@
 // Description:
 
 pub mod generator;
+pub mod geometry;
 
 use std::{
 	convert::TryFrom,
 	rc::Rc,
 };
 
-use web_sys::{
-	WebGl2RenderingContext,
-	WebGlVertexArrayObject,
-};
+use web_sys::WebGl2RenderingContext;
 
+use self::geometry::Geometry;
 use super::buffer::Buffer;
 use super::buffer_view::BufferView;
 use super::material::Material;
 use super::Drawable;
+use crate::backend::GlBackend;
+use crate::bounds::Aabb;
 use crate::program::attribute_locations;
 use crate::{
 	config::scene_config::BufferViewConfig,
@@ -58,7 +59,8 @@ impl TryFrom<&[BufferViewConfig]> for AttributeBufferViews {
 				"NORMAL" => normal = Some(BufferView::new(view)),
 				"TANGENT" => tangent = Some(BufferView::new(view)),
 				"BITANGENT" => bitangent = Some(BufferView::new(view)),
-				"COLOR" => color = Some(BufferView::new(view)),
+				// glTF names the first set COLOR_0.
+				"COLOR" | "COLOR_0" => color = Some(BufferView::new(view)),
 				"TEXCOORD_0" => texcoord_0 = Some(BufferView::new(view)),
 				"TEXCOORD_1" => texcoord_1 = Some(BufferView::new(view)),
 				"TEXCOORD_2" => texcoord_2 = Some(BufferView::new(view)),
@@ -84,19 +86,23 @@ impl TryFrom<&[BufferViewConfig]> for AttributeBufferViews {
 }
 
 #[derive(Clone, Debug)]
-pub struct Mesh {
-	vao: WebGlVertexArrayObject,
-	pub material: Rc<Material>,
-	pub buffers: Vec<Rc<Buffer>>,
+pub struct Mesh<G: GlBackend = WebGl2RenderingContext> {
+	vao: G::VertexArray,
+	pub material: Rc<Material<G>>,
+	pub buffers: Vec<Rc<Buffer<G>>>,
 	pub index_view: Option<BufferView>,
 	pub attribute_buffer_views: AttributeBufferViews,
 	pub mode: u32,
+	// In model space. Meshes without bounds are never culled.
+	pub bounds: Option<Aabb>,
+	// Meshes without geometry can't be picked.
+	pub geometry: Option<Rc<Geometry>>,
 }
-impl Mesh {
+impl<G: GlBackend> Mesh<G> {
 	pub fn new(
-		gl: &WebGl2RenderingContext,
-		material: &Rc<Material>,
-		buffers: &[Rc<Buffer>],
+		gl: &G,
+		material: &Rc<Material<G>>,
+		buffers: &[Rc<Buffer<G>>],
 		index_view: &Option<BufferView>,
 		attribute_buffer_views: &AttributeBufferViews,
 		mode: u32,
@@ -109,19 +115,25 @@ impl Mesh {
 			index_view: *index_view,
 			attribute_buffer_views: *attribute_buffer_views,
 			mode,
+			bounds: None,
+			geometry: None,
 		})
 	}
-	pub fn clean_up(&mut self, gl: &WebGl2RenderingContext) {
+	// Deletes the vertex array and any buffers no one else holds. Shared
+	// buffers are left for their other owners to release.
+	pub fn clean_up(&self, gl: &G) {
 		gl.delete_vertex_array(Some(&self.vao));
 		for buffer in self.buffers.iter() {
-			gl.delete_buffer(Some(&buffer.handle));
+			if Rc::strong_count(buffer) == 1 {
+				buffer.delete(gl);
+			}
 		}
 	}
 	fn _create_vao(
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		attribute_buffer_views: &AttributeBufferViews,
-		buffers: &[Rc<Buffer>],
-	) -> Result<WebGlVertexArrayObject, &'static str> {
+		buffers: &[Rc<Buffer<G>>],
+	) -> Result<G::VertexArray, &'static str> {
 		let vao = gl
 			.create_vertex_array()
 			.ok_or("Failed to create vertex array object")?;
@@ -186,10 +198,10 @@ impl Mesh {
 		Ok(vao)
 	}
 	fn _bind_view(
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		attribute_location: u32,
 		view: &Option<BufferView>,
-		buffers: &[Rc<Buffer>],
+		buffers: &[Rc<Buffer<G>>],
 	) {
 		if let Some(view) = view {
 			buffers[view.buffer].bind(gl);
@@ -197,7 +209,7 @@ impl Mesh {
 				attribute_location,
 				view.component_size,
 				view.component_type,
-				false,
+				view.normalized,
 				view.stride,
 				view.combined_offset,
 			);
@@ -205,8 +217,9 @@ impl Mesh {
 		}
 	}
 }
-impl Drawable for Mesh {
-	fn draw(&self, gl: &WebGl2RenderingContext, program: &Program) {
+impl<G: GlBackend> Drawable<G> for Mesh<G> {
+	fn draw(&self, gl: &G, program: &Program<G>) {
+		self.material.set_face_culling(gl);
 		self.material
 			.bind_to_uniforms(gl, &program.uniform_locations);
 
@@ -221,9 +234,10 @@ impl Drawable for Mesh {
 				0, // index_view.combined_offset,
 			);
 		} else {
+			// The view's offset is already in the attribute pointers.
 			gl.draw_arrays(
 				self.mode,
-				self.attribute_buffer_views.position.buffer_offset,
+				0,
 				self.attribute_buffer_views.position.component_count,
 			);
 		}
@@ -14,26 +14,67 @@ pub mod texture;
 use std::rc::Rc;
 use web_sys::WebGl2RenderingContext;
 
-use self::mesh::Mesh;
+use self::{
+	buffer::Buffer,
+	material::Material,
+	mesh::Mesh,
+	texture::Texture,
+};
+use crate::backend::GlBackend;
+use crate::bounds::Aabb;
+use crate::lights::Light;
 use crate::program::Program;
+use crate::resources::handle::Handle;
 
-pub trait Drawable {
-	fn draw(&self, gl: &WebGl2RenderingContext, program: &Program);
+pub trait Drawable<G: GlBackend = WebGl2RenderingContext> {
+	fn draw(&self, gl: &G, program: &Program<G>);
 }
 
 #[derive(Clone, Debug)]
-pub struct Model {
-	pub meshes: Vec<Rc<Mesh>>,
+pub struct Model<G: GlBackend = WebGl2RenderingContext> {
+	pub meshes: Vec<Rc<Mesh<G>>>,
+	// In model space, e.g. a glTF's punctual lights. A scene node showing
+	// the model gets them as children.
+	pub lights: Vec<Light>,
+	// What loading the model added to Resources, removed along with it.
+	pub resources: ModelResources<G>,
 }
-impl Model {
-	pub fn new(meshes: &[Rc<Mesh>]) -> Self {
+impl<G: GlBackend> Model<G> {
+	pub fn new(meshes: &[Rc<Mesh<G>>]) -> Self {
 		Self {
 			meshes: meshes.to_owned(),
+			lights: Vec::new(),
+			resources: ModelResources::default(),
+		}
+	}
+	// The bounds of every mesh, or none if any mesh has none.
+	pub fn bounds(&self) -> Option<Aabb> {
+		let mut meshes = self.meshes.iter();
+		let first = meshes.next()?.bounds?;
+		meshes.try_fold(first, |bounds, mesh| Some(bounds.union(&mesh.bounds?)))
+	}
+}
+#[derive(Clone, Debug)]
+pub struct ModelResources<G: GlBackend = WebGl2RenderingContext> {
+	pub textures: Vec<Handle<Texture<G>>>,
+	pub materials: Vec<Handle<Material<G>>>,
+	pub buffers: Vec<Handle<Buffer<G>>>,
+	pub meshes: Vec<Handle<Mesh<G>>>,
+}
+// By hand so G doesn't have to be Default.
+impl<G: GlBackend> Default for ModelResources<G> {
+	fn default() -> Self {
+		Self {
+			textures: Vec::new(),
+			materials: Vec::new(),
+			buffers: Vec::new(),
+			meshes: Vec::new(),
 		}
 	}
 }
-impl Drawable for Model {
-	fn draw(&self, gl: &WebGl2RenderingContext, program: &Program) {
+
+impl<G: GlBackend> Drawable<G> for Model<G> {
+	fn draw(&self, gl: &G, program: &Program<G>) {
 		self.meshes.iter().for_each(|mesh| mesh.draw(gl, program));
 	}
 }
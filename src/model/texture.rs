@@ -11,15 +11,34 @@ use serde::Deserialize;
 use web_sys::{
 	HtmlImageElement,
 	WebGl2RenderingContext,
-	WebGlTexture,
 };
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+use crate::backend::GlBackend;
+
+type Gl = WebGl2RenderingContext;
+
+// The extension's enums, which WebGl2RenderingContext doesn't have.
+const TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FF;
+// Past this sharper distant textures aren't worth the extra samples.
+pub const MAX_ANISOTROPY: f32 = 16.0;
+
+// The most anisotropic filtering GL supports, None without the extension.
+pub fn max_anisotropy<G: GlBackend>(gl: &G) -> Option<f32> {
+	if !gl.get_extension("EXT_texture_filter_anisotropic") {
+		return None;
+	}
+	gl.get_parameter_f32(MAX_TEXTURE_MAX_ANISOTROPY_EXT)
+		.map(|max| max.min(MAX_ANISOTROPY))
+		.filter(|max| *max > 1.0)
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
 pub struct Sampler {
-	mag_filter: i32,
-	min_filter: i32,
-	wrap_s: i32,
-	wrap_t: i32,
+	pub mag_filter: i32,
+	pub min_filter: i32,
+	pub wrap_s: i32,
+	pub wrap_t: i32,
 }
 impl Default for Sampler {
 	fn default() -> Self {
@@ -32,87 +51,324 @@ impl Default for Sampler {
 	}
 }
 
+impl Sampler {
+	pub fn new(
+		mag_filter: i32,
+		min_filter: i32,
+		wrap_s: i32,
+		wrap_t: i32,
+	) -> Self {
+		Self {
+			mag_filter,
+			min_filter,
+			wrap_s,
+			wrap_t,
+		}
+	}
+	// Whether the min filter reads mipmaps, which textures sampled with it
+	// need generated.
+	pub fn uses_mipmaps(&self) -> bool {
+		matches!(
+			self.min_filter as u32,
+			Gl::NEAREST_MIPMAP_NEAREST
+				| Gl::LINEAR_MIPMAP_NEAREST
+				| Gl::NEAREST_MIPMAP_LINEAR
+				| Gl::LINEAR_MIPMAP_LINEAR
+		)
+	}
+}
+
+// A WebGL2 sampler object, shared by every texture sampled the same way.
 #[derive(Clone, Debug)]
-pub struct Texture {
-	handle: WebGlTexture,
-	sampler: Rc<Sampler>,
+pub struct SamplerObject<G: GlBackend = WebGl2RenderingContext> {
+	handle: G::Sampler,
+	sampler: Sampler,
+}
+impl<G: GlBackend> SamplerObject<G> {
+	// Trilinear filtering is made anisotropic up to `max_anisotropy`, see
+	// max_anisotropy. Other filters are kept as they are.
+	pub fn new(
+		gl: &G,
+		sampler: &Sampler,
+		max_anisotropy: Option<f32>,
+	) -> Result<Self, &'static str> {
+		let handle = gl.create_sampler().ok_or("Failed to create a sampler")?;
+		for (pname, param) in [
+			(Gl::TEXTURE_MAG_FILTER, sampler.mag_filter),
+			(Gl::TEXTURE_MIN_FILTER, sampler.min_filter),
+			(Gl::TEXTURE_WRAP_S, sampler.wrap_s),
+			(Gl::TEXTURE_WRAP_T, sampler.wrap_t),
+		] {
+			gl.sampler_parameteri(&handle, pname, param);
+		}
+		let trilinear = sampler.min_filter == Gl::LINEAR_MIPMAP_LINEAR as i32
+			&& sampler.mag_filter == Gl::LINEAR as i32;
+		if let (true, Some(max_anisotropy)) = (trilinear, max_anisotropy) {
+			gl.sampler_parameterf(
+				&handle,
+				TEXTURE_MAX_ANISOTROPY_EXT,
+				max_anisotropy,
+			);
+		}
+		Ok(Self {
+			handle,
+			sampler: *sampler,
+		})
+	}
+	pub fn bind(&self, gl: &G, texture_unit: u32) {
+		gl.bind_sampler(texture_unit, Some(&self.handle));
+	}
+	pub fn delete(&self, gl: &G) {
+		gl.delete_sampler(Some(&self.handle));
+	}
+	pub fn sampler(&self) -> &Sampler {
+		&self.sampler
+	}
+}
+
+// How a texture's values are encoded. Colors authored for display, as
+// glTF's base color and emissive images are, are sRGB and decoded to linear
+// when sampled. Data such as normals stays linear.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+	Srgb,
+	#[default]
+	Linear,
+}
+
+// The channels a texture keeps.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Channels {
+	R,
+	Rg,
+	Rgb,
+	#[default]
+	Rgba,
+}
+impl Channels {
+	pub fn count(&self) -> usize {
+		match self {
+			Channels::R => 1,
+			Channels::Rg => 2,
+			Channels::Rgb => 3,
+			Channels::Rgba => 4,
+		}
+	}
+}
+
+// How each channel of the pixels given is stored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ComponentType {
+	UnsignedByte,
+	Float,
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Eq, Hash)]
+pub struct TextureFormat {
+	#[serde(default)]
+	pub channels: Channels,
+	#[serde(default)]
+	pub color_space: ColorSpace,
+}
+
+// The arguments to upload a texture with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GlFormat {
+	pub internal_format: i32,
+	pub format: u32,
+	pub data_type: u32,
+	// Color renderable, which generating mipmaps needs.
+	pub mipmappable: bool,
+}
+
+impl TextureFormat {
+	pub fn new(channels: Channels, color_space: ColorSpace) -> Self {
+		Self {
+			channels,
+			color_space,
+		}
+	}
+	// Bytes are kept at eight bits, floats at sixteen so they can be
+	// filtered without an extension. sRGB is only for bytes in color
+	// channels. Of the formats here only SRGB8 and the floats can't be
+	// rendered to.
+	pub fn gl_format(
+		&self,
+		component_type: ComponentType,
+	) -> Result<GlFormat, &'static str> {
+		let format = match self.channels {
+			Channels::R => Gl::RED,
+			Channels::Rg => Gl::RG,
+			Channels::Rgb => Gl::RGB,
+			Channels::Rgba => Gl::RGBA,
+		};
+		let internal_format = match (component_type, self.color_space) {
+			(ComponentType::UnsignedByte, ColorSpace::Srgb) => {
+				match self.channels {
+					Channels::Rgb => Gl::SRGB8,
+					Channels::Rgba => Gl::SRGB8_ALPHA8,
+					_ => return Err("sRGB textures need RGB or RGBA channels"),
+				}
+			}
+			(ComponentType::UnsignedByte, ColorSpace::Linear) => {
+				match self.channels {
+					Channels::R => Gl::R8,
+					Channels::Rg => Gl::RG8,
+					Channels::Rgb => Gl::RGB8,
+					Channels::Rgba => Gl::RGBA8,
+				}
+			}
+			(ComponentType::Float, ColorSpace::Srgb) => {
+				return Err("sRGB textures need byte channels")
+			}
+			(ComponentType::Float, ColorSpace::Linear) => match self.channels {
+				Channels::R => Gl::R16F,
+				Channels::Rg => Gl::RG16F,
+				Channels::Rgb => Gl::RGB16F,
+				Channels::Rgba => Gl::RGBA16F,
+			},
+		};
+		let (data_type, mipmappable) = match component_type {
+			ComponentType::UnsignedByte => {
+				(Gl::UNSIGNED_BYTE, internal_format != Gl::SRGB8)
+			}
+			ComponentType::Float => (Gl::FLOAT, false),
+		};
+		Ok(GlFormat {
+			internal_format: internal_format as i32,
+			format,
+			data_type,
+			mipmappable,
+		})
+	}
+}
+
+// A texture's pixels. Raw pixels are in rows from the top as an image's
+// are, tightly packed with the channels of the TextureFormat they're
+// uploaded with.
+#[derive(Copy, Clone, Debug)]
+pub enum TextureData<'a> {
+	Image(&'a HtmlImageElement),
+	U8 {
+		width: u32,
+		height: u32,
+		pixels: &'a [u8],
+	},
+	F32 {
+		width: u32,
+		height: u32,
+		pixels: &'a [f32],
+	},
+}
+impl TextureData<'_> {
+	pub fn size(&self) -> (u32, u32) {
+		match *self {
+			TextureData::Image(image) => (image.width(), image.height()),
+			TextureData::U8 { width, height, .. }
+			| TextureData::F32 { width, height, .. } => (width, height),
+		}
+	}
+	pub fn component_type(&self) -> ComponentType {
+		match self {
+			TextureData::Image(_) | TextureData::U8 { .. } => {
+				ComponentType::UnsignedByte
+			}
+			TextureData::F32 { .. } => ComponentType::Float,
+		}
+	}
+	// The number of components given, None for images.
+	fn len(&self) -> Option<usize> {
+		match self {
+			TextureData::Image(_) => None,
+			TextureData::U8 { pixels, .. } => Some(pixels.len()),
+			TextureData::F32 { pixels, .. } => Some(pixels.len()),
+		}
+	}
 }
-impl Texture {
-	pub fn bind(&self, gl: &WebGl2RenderingContext, texture_unit: u32) {
+
+#[derive(Clone, Debug)]
+pub struct Texture<G: GlBackend = WebGl2RenderingContext> {
+	handle: G::Texture,
+	sampler: Rc<SamplerObject<G>>,
+}
+impl<G: GlBackend> Texture<G> {
+	// Binds the texture's sampler along with it, which overrides the
+	// texture's own parameters.
+	pub fn bind(&self, gl: &G, texture_unit: u32) {
 		gl.active_texture(WebGl2RenderingContext::TEXTURE0 + texture_unit);
 		gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&self.handle));
+		self.sampler.bind(gl, texture_unit);
 	}
 	pub fn new(
-		gl: &WebGl2RenderingContext,
-		image_element: &HtmlImageElement,
-		texture_unit: u32,
-		sampler: &Rc<Sampler>,
+		gl: &G,
+		data: TextureData,
+		format: TextureFormat,
+		sampler: &Rc<SamplerObject<G>>,
 	) -> Result<Self, &'static str> {
-		let handle = gl.create_texture().ok_or("Failed to create a texture")?;
+		let gl_format = format.gl_format(data.component_type())?;
+		let (width, height) = data.size();
+		let expected =
+			width as usize * height as usize * format.channels.count();
+		if data.len().is_some_and(|len| len != expected) {
+			return Err("The pixels don't fill the texture's width and height");
+		}
+		// WebGL2 has mipmaps for any size, they're made when the sampler
+		// reads them.
+		let use_mipmap = sampler.sampler().uses_mipmaps();
+		if use_mipmap && !gl_format.mipmappable {
+			return Err("The sampler needs mipmaps the format can't generate");
+		}
+		let mip_level = 0;
 
-		gl.active_texture(WebGl2RenderingContext::TEXTURE0 + texture_unit);
+		let handle = gl.create_texture().ok_or("Failed to create a texture")?;
+		gl.active_texture(WebGl2RenderingContext::TEXTURE0);
 		gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&handle));
 
-		let (mip_level, use_mipmap, min_filter, fmt) = {
-			let width = image_element.width();
-			let height = image_element.height();
-			let is_power_of_2 =
-				Self::is_power_of_2(width) && Self::is_power_of_2(height);
-
-			// let min_filter = sampler.min_filter as u32;
-			// let has_mipmap_min_filter = min_filter == WebGl2RenderingContext::LINEAR_MIPMAP_LINEAR
-			// 	|| min_filter == WebGl2RenderingContext::LINEAR_MIPMAP_NEAREST
-			// 	|| min_filter == WebGl2RenderingContext::NEAREST_MIPMAP_LINEAR
-			// 	|| min_filter == WebGl2RenderingContext::NEAREST_MIPMAP_NEAREST;
-
-			let use_mipmap = is_power_of_2; // && has_mipmap_min_filter;
-			let min_filter = if use_mipmap {
-				WebGl2RenderingContext::LINEAR_MIPMAP_LINEAR as i32
-			} else {
-				sampler.min_filter
-			};
-
-			let fmt = if texture_unit == 0 {
-				WebGl2RenderingContext::RGBA
-			} else {
-				WebGl2RenderingContext::RGB
-			};
-			(0, use_mipmap, min_filter, fmt)
+		let target = WebGl2RenderingContext::TEXTURE_2D;
+		let uploaded = match data {
+			TextureData::Image(image) => gl
+				.tex_image_2d_with_html_image_element(
+					target,
+					mip_level,
+					gl_format.internal_format,
+					gl_format.format,
+					gl_format.data_type,
+					image,
+				),
+			TextureData::U8 { pixels, .. } => {
+				// Rows of one or three bytes a pixel needn't be four byte
+				// aligned.
+				gl.pixel_storei(WebGl2RenderingContext::UNPACK_ALIGNMENT, 1);
+				let uploaded = gl.tex_image_2d_with_opt_u8_array(
+					target,
+					mip_level,
+					gl_format.internal_format,
+					width as i32,
+					height as i32,
+					gl_format.format,
+					gl_format.data_type,
+					Some(pixels),
+				);
+				gl.pixel_storei(WebGl2RenderingContext::UNPACK_ALIGNMENT, 4);
+				uploaded
+			}
+			TextureData::F32 { pixels, .. } => gl.tex_image_2d_with_f32_array(
+				target,
+				mip_level,
+				gl_format.internal_format,
+				width as i32,
+				height as i32,
+				gl_format.format,
+				gl_format.data_type,
+				pixels,
+			),
 		};
-
-		gl.tex_parameteri(
-			WebGl2RenderingContext::TEXTURE_2D,
-			WebGl2RenderingContext::TEXTURE_WRAP_S,
-			sampler.wrap_s,
-		);
-		gl.tex_parameteri(
-			WebGl2RenderingContext::TEXTURE_2D,
-			WebGl2RenderingContext::TEXTURE_WRAP_T,
-			sampler.wrap_t,
-		);
-		gl.tex_parameteri(
-			WebGl2RenderingContext::TEXTURE_2D,
-			WebGl2RenderingContext::TEXTURE_MIN_FILTER,
-			min_filter,
-		);
-		gl.tex_parameteri(
-			WebGl2RenderingContext::TEXTURE_2D,
-			WebGl2RenderingContext::TEXTURE_MAG_FILTER,
-			sampler.mag_filter,
-		);
-
-		let internal_format = fmt as i32;
-		let src_format = fmt;
-		let src_type = WebGl2RenderingContext::UNSIGNED_BYTE;
-		gl.tex_image_2d_with_u32_and_u32_and_html_image_element(
-			WebGl2RenderingContext::TEXTURE_2D,
-			mip_level,
-			internal_format,
-			src_format,
-			src_type,
-			image_element,
-		)
-		.map_err(|_e| "failed to create glTexture from image")?;
+		if let Err(err) = uploaded {
+			gl.delete_texture(Some(&handle));
+			return Err(err);
+		}
 
 		if use_mipmap {
 			gl.generate_mipmap(WebGl2RenderingContext::TEXTURE_2D);
@@ -123,10 +379,231 @@ impl Texture {
 			sampler: Rc::clone(sampler),
 		})
 	}
-	pub fn is_power_of_2(num: u32) -> bool {
-		matches!(
-			num,
-			2 | 4 | 8 | 16 | 32 | 64 | 128 | 256 | 512 | 1024 | 2048 | 4096
+	pub fn delete(&self, gl: &G) {
+		gl.delete_texture(Some(&self.handle));
+	}
+	pub fn sampler(&self) -> &Rc<SamplerObject<G>> {
+		&self.sampler
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::recording::{
+		GlCall,
+		RecordingBackend,
+	};
+
+	#[test]
+	fn formats_follow_channels_and_color_space() {
+		let format = |channels, color_space| {
+			TextureFormat::new(channels, color_space)
+				.gl_format(ComponentType::UnsignedByte)
+		};
+		let srgb = format(Channels::Rgba, ColorSpace::Srgb).unwrap();
+		assert_eq!(srgb.internal_format, Gl::SRGB8_ALPHA8 as i32);
+		assert_eq!(srgb.format, Gl::RGBA);
+		assert_eq!(srgb.data_type, Gl::UNSIGNED_BYTE);
+		assert!(srgb.mipmappable);
+		// SRGB8 can't be rendered to for mipmaps.
+		let srgb = format(Channels::Rgb, ColorSpace::Srgb).unwrap();
+		assert_eq!(srgb.internal_format, Gl::SRGB8 as i32);
+		assert!(!srgb.mipmappable);
+		assert!(format(Channels::R, ColorSpace::Srgb).is_err());
+		assert!(format(Channels::Rg, ColorSpace::Srgb).is_err());
+
+		for (channels, internal_format, gl_format) in [
+			(Channels::R, Gl::R8, Gl::RED),
+			(Channels::Rg, Gl::RG8, Gl::RG),
+			(Channels::Rgb, Gl::RGB8, Gl::RGB),
+			(Channels::Rgba, Gl::RGBA8, Gl::RGBA),
+		] {
+			let linear = format(channels, ColorSpace::Linear).unwrap();
+			assert_eq!(linear.internal_format, internal_format as i32);
+			assert_eq!(linear.format, gl_format);
+			assert!(linear.mipmappable);
+		}
+		assert_eq!(
+			TextureFormat::default(),
+			TextureFormat::new(Channels::Rgba, ColorSpace::Linear)
+		);
+	}
+	#[test]
+	fn float_formats_are_linear_and_half_precision() {
+		let float = TextureFormat::new(Channels::R, ColorSpace::Linear)
+			.gl_format(ComponentType::Float)
+			.unwrap();
+		assert_eq!(float.internal_format, Gl::R16F as i32);
+		assert_eq!((float.format, float.data_type), (Gl::RED, Gl::FLOAT));
+		assert!(!float.mipmappable);
+		assert!(TextureFormat::new(Channels::Rgba, ColorSpace::Srgb)
+			.gl_format(ComponentType::Float)
+			.is_err());
+	}
+	fn sampler_object(sampler: Sampler) -> Rc<SamplerObject<RecordingBackend>> {
+		let gl = RecordingBackend::new();
+		Rc::new(SamplerObject::new(&gl, &sampler, None).unwrap())
+	}
+	fn trilinear() -> Sampler {
+		Sampler::new(
+			Gl::LINEAR as i32,
+			Gl::LINEAR_MIPMAP_LINEAR as i32,
+			Gl::REPEAT as i32,
+			Gl::REPEAT as i32,
 		)
 	}
+
+	#[test]
+	fn uploads_raw_pixels() {
+		let gl = RecordingBackend::new();
+		let sampler = sampler_object(Sampler::default());
+		let format = TextureFormat::new(Channels::Rgb, ColorSpace::Linear);
+		let pixels = [255; 2 * 2 * 3];
+		let data = TextureData::U8 {
+			width: 2,
+			height: 2,
+			pixels: &pixels,
+		};
+		Texture::new(&gl, data, format, &sampler).unwrap();
+		let calls = gl.calls();
+		let upload = calls
+			.iter()
+			.position(|call| matches!(call, GlCall::TexImage2DPixels { .. }))
+			.unwrap();
+		assert_eq!(
+			calls[upload - 1],
+			GlCall::PixelStorei(Gl::UNPACK_ALIGNMENT, 1)
+		);
+		assert_eq!(
+			calls[upload],
+			GlCall::TexImage2DPixels {
+				target: Gl::TEXTURE_2D,
+				level: 0,
+				internal_format: Gl::RGB8 as i32,
+				width: 2,
+				height: 2,
+				format: Gl::RGB,
+				type_: Gl::UNSIGNED_BYTE,
+				length: Some(12),
+			}
+		);
+		assert_eq!(
+			calls[upload + 1],
+			GlCall::PixelStorei(Gl::UNPACK_ALIGNMENT, 4)
+		);
+		// The sampler is the texture's parameters.
+		assert!(!calls
+			.iter()
+			.any(|call| matches!(call, GlCall::TexParameteri(..))));
+
+		// Floats are uploaded as they are, without mipmaps.
+		let gl = RecordingBackend::new();
+		let format = TextureFormat::new(Channels::R, ColorSpace::Linear);
+		let pixels = [0.5; 4 * 4];
+		let data = TextureData::F32 {
+			width: 4,
+			height: 4,
+			pixels: &pixels,
+		};
+		Texture::new(&gl, data, format, &sampler).unwrap();
+		assert_eq!(
+			gl.count(|call| matches!(
+				call,
+				GlCall::TexImage2DPixels { internal_format, .. }
+					if *internal_format == Gl::R16F as i32
+			)),
+			1
+		);
+		assert_eq!(
+			gl.count(|call| matches!(call, GlCall::GenerateMipmap(_))),
+			0
+		);
+
+		// Too few pixels for the size.
+		let gl = RecordingBackend::new();
+		let data = TextureData::F32 {
+			width: 4,
+			height: 3,
+			pixels: &pixels,
+		};
+		assert!(Texture::new(&gl, data, format, &sampler).is_err());
+		assert!(gl.calls().is_empty());
+	}
+	#[test]
+	fn mipmaps_follow_the_min_filter() {
+		let format = TextureFormat::default();
+		let pixels = [0; 3 * 5 * 4];
+		let data = TextureData::U8 {
+			width: 3,
+			height: 5,
+			pixels: &pixels,
+		};
+		let mipmaps = |sampler: Sampler| {
+			let gl = RecordingBackend::new();
+			Texture::new(&gl, data, format, &sampler_object(sampler)).unwrap();
+			gl.count(|call| matches!(call, GlCall::GenerateMipmap(_)))
+		};
+		// Whatever the size.
+		assert_eq!(mipmaps(trilinear()), 1);
+		let nearest_mipmap = Sampler {
+			min_filter: Gl::NEAREST_MIPMAP_NEAREST as i32,
+			..Sampler::default()
+		};
+		assert_eq!(mipmaps(nearest_mipmap), 1);
+		assert_eq!(mipmaps(Sampler::default()), 0);
+
+		// Floats can't have them generated.
+		let pixels = [0.0; 4];
+		let data = TextureData::F32 {
+			width: 2,
+			height: 2,
+			pixels: &pixels,
+		};
+		let format = TextureFormat::new(Channels::R, ColorSpace::Linear);
+		let gl = RecordingBackend::new();
+		assert!(
+			Texture::new(&gl, data, format, &sampler_object(trilinear()))
+				.is_err()
+		);
+	}
+	#[test]
+	fn samplers_are_set_up_once_and_anisotropic_when_trilinear() {
+		let gl = RecordingBackend::new();
+		assert_eq!(max_anisotropy(&gl), None);
+		gl.add_extension(
+			"EXT_texture_filter_anisotropic",
+			&[(MAX_TEXTURE_MAX_ANISOTROPY_EXT, 32.0)],
+		);
+		let max = max_anisotropy(&gl);
+		assert_eq!(max, Some(MAX_ANISOTROPY));
+
+		let sampler = SamplerObject::new(&gl, &trilinear(), max).unwrap();
+		let handle = sampler.handle;
+		let calls = gl.calls();
+		assert_eq!(calls[0], GlCall::CreateSampler(handle));
+		assert!(calls.contains(&GlCall::SamplerParameteri(
+			handle,
+			Gl::TEXTURE_MIN_FILTER,
+			Gl::LINEAR_MIPMAP_LINEAR as i32
+		)));
+		assert!(calls.contains(&GlCall::SamplerParameteri(
+			handle,
+			Gl::TEXTURE_WRAP_T,
+			Gl::REPEAT as i32
+		)));
+		assert!(calls.contains(&GlCall::SamplerParameterf(
+			handle,
+			TEXTURE_MAX_ANISOTROPY_EXT,
+			MAX_ANISOTROPY
+		)));
+
+		// Other filters are left as asked for.
+		gl.clear_calls();
+		SamplerObject::new(&gl, &Sampler::default(), max).unwrap();
+		assert_eq!(
+			gl.count(|call| matches!(call, GlCall::SamplerParameterf(..))),
+			0
+		);
+	}
 }
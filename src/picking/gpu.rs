@@ -0,0 +1,294 @@
+// File: src/picking/gpu.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Picks by drawing every node's id into a one pixel integer framebuffer
+// and reading it back. The projection is narrowed to the pixel under the
+// cursor, so the cost doesn't depend on triangle counts like ray casting.
+
+use std::rc::Rc;
+
+use cgmath::{
+	Matrix4,
+	Vector3,
+};
+use rctree::Node;
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+	backend::GlBackend,
+	bounds::Frustum,
+	camera::Camera,
+	mat_4_to_array,
+	model::Drawable,
+	program::Program,
+	scene_graph::{
+		NodeData,
+		NodeTypeData,
+		SceneGraph,
+	},
+	viewport::Viewport,
+};
+
+type Gl = WebGl2RenderingContext;
+
+// Ids keep the mesh index in the low bits and the node's depth first index
+// plus one above them, so zero is left for nothing drawn.
+const MESH_BITS: u32 = 8;
+pub const MAX_MESHES: usize = 1 << MESH_BITS;
+pub const MAX_NODES: usize = (1 << (32 - MESH_BITS)) - 1;
+
+// None if either index doesn't fit.
+pub fn encode_id(node: usize, mesh: usize) -> Option<u32> {
+	if node >= MAX_NODES || mesh >= MAX_MESHES {
+		return None;
+	}
+	Some(((node as u32 + 1) << MESH_BITS) | mesh as u32)
+}
+// The node and mesh index, None for the cleared background.
+pub fn decode_id(id: u32) -> Option<(usize, usize)> {
+	match id >> MESH_BITS {
+		0 => None,
+		node => Some(((node - 1) as usize, (id as usize) & (MAX_MESHES - 1))),
+	}
+}
+
+// Maps the pixel at normalized device coordinates x and y of a viewport
+// `width` by `height` pixels to the whole of clip space. Applied after the
+// projection.
+pub fn pick_matrix(x: f32, y: f32, width: i32, height: i32) -> Matrix4<f32> {
+	Matrix4::from_nonuniform_scale(
+		width.max(1) as f32,
+		height.max(1) as f32,
+		1.0,
+	) * Matrix4::from_translation(Vector3::new(-x, -y, 0.0))
+}
+
+#[derive(Debug)]
+pub struct Picked<G: GlBackend = WebGl2RenderingContext> {
+	pub node: Node<NodeData<G>>,
+	// Which of the node's model's meshes, zero for a mesh node.
+	pub mesh: usize,
+	// The node's position in a depth first walk of the graph.
+	pub node_index: usize,
+}
+
+// `program` draws with general.vert and writes PICK_ID, see pick.frag.
+#[derive(Debug)]
+pub struct PickPass<G: GlBackend = WebGl2RenderingContext> {
+	framebuffer: G::Framebuffer,
+	id_buffer: G::Renderbuffer,
+	depth_buffer: G::Renderbuffer,
+	program: Rc<Program<G>>,
+}
+impl<G: GlBackend> PickPass<G> {
+	pub fn new(gl: &G, program: Rc<Program<G>>) -> Result<Self, &'static str> {
+		let framebuffer = gl
+			.create_framebuffer()
+			.ok_or("Failed to create pick framebuffer")?;
+		let id_buffer = gl
+			.create_renderbuffer()
+			.ok_or("Failed to create pick renderbuffer")?;
+		let depth_buffer = gl
+			.create_renderbuffer()
+			.ok_or("Failed to create pick renderbuffer")?;
+
+		gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&framebuffer));
+		for (renderbuffer, internal_format, attachment) in [
+			(&id_buffer, Gl::R32UI, Gl::COLOR_ATTACHMENT0),
+			(&depth_buffer, Gl::DEPTH_COMPONENT24, Gl::DEPTH_ATTACHMENT),
+		] {
+			gl.bind_renderbuffer(Gl::RENDERBUFFER, Some(renderbuffer));
+			gl.renderbuffer_storage(Gl::RENDERBUFFER, internal_format, 1, 1);
+			gl.framebuffer_renderbuffer(
+				Gl::FRAMEBUFFER,
+				attachment,
+				Gl::RENDERBUFFER,
+				Some(renderbuffer),
+			);
+		}
+		gl.bind_renderbuffer(Gl::RENDERBUFFER, None);
+		let status = gl.check_framebuffer_status(Gl::FRAMEBUFFER);
+		gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+
+		let pass = Self {
+			framebuffer,
+			id_buffer,
+			depth_buffer,
+			program,
+		};
+		if status != Gl::FRAMEBUFFER_COMPLETE {
+			pass.delete(gl);
+			return Err("Pick framebuffer is incomplete");
+		}
+		Ok(pass)
+	}
+	pub fn delete(&self, gl: &G) {
+		gl.delete_framebuffer(Some(&self.framebuffer));
+		gl.delete_renderbuffer(Some(&self.id_buffer));
+		gl.delete_renderbuffer(Some(&self.depth_buffer));
+	}
+
+	// What `camera` sees at normalized device coordinates x and y of
+	// `viewport`. Drawing goes back to the canvas and `viewport` after.
+	pub fn pick(
+		&self,
+		gl: &G,
+		scene_graph: &SceneGraph<G>,
+		camera: &Camera,
+		viewport: &Viewport,
+		x: f32,
+		y: f32,
+	) -> Result<Option<Picked<G>>, &'static str> {
+		let projection = pick_matrix(x, y, viewport.width, viewport.height)
+			* camera.projection_matrix();
+		let view = camera.view_matrix();
+		// Only what's under the pixel is left inside.
+		let frustum = Frustum::from_matrix(&(projection * view));
+
+		let program = self.program.as_ref();
+		let uniform_locations = &program.uniform_locations;
+		gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&self.framebuffer));
+		gl.viewport(0, 0, 1, 1);
+		gl.clear_bufferuiv_with_u32_array(Gl::COLOR, 0, &[0; 4]);
+		gl.clear(Gl::DEPTH_BUFFER_BIT);
+
+		gl.use_program(Some(&program.program));
+		gl.uniform_matrix4fv_with_f32_array(
+			uniform_locations.get("PROJECTION_MATRIX"),
+			false,
+			&mat_4_to_array(&projection),
+		);
+		gl.uniform_matrix4fv_with_f32_array(
+			uniform_locations.get("VIEW_MATRIX"),
+			false,
+			&mat_4_to_array(&view),
+		);
+		for (index, node) in scene_graph.root.descendants().enumerate() {
+			let node = node.borrow();
+			let visible = node
+				.world_bounds()
+				.is_none_or(|bounds| frustum.intersects(&bounds));
+			let meshes = match node.ref_node_type_data() {
+				NodeTypeData::Mesh(mesh) if visible => vec![mesh],
+				NodeTypeData::Model(model) if visible => {
+					model.meshes.iter().collect()
+				}
+				_ => continue,
+			};
+			gl.uniform_matrix4fv_with_f32_array(
+				uniform_locations.get("MODEL_MATRIX"),
+				false,
+				&mat_4_to_array(&node.ref_transform().world_matrix()),
+			);
+			for (i, mesh) in meshes.into_iter().enumerate() {
+				let id = match encode_id(index, i) {
+					Some(id) => id,
+					None => continue,
+				};
+				gl.uniform1ui(uniform_locations.get("PICK_ID"), id);
+				mesh.draw(gl, program);
+			}
+		}
+
+		// RGBA_INTEGER is the format reads from unsigned integer buffers
+		// are guaranteed to support.
+		let mut pixel = [0; 4];
+		let read = gl.read_pixels_with_u32_array(
+			0,
+			0,
+			1,
+			1,
+			Gl::RGBA_INTEGER,
+			Gl::UNSIGNED_INT,
+			&mut pixel,
+		);
+		gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+		gl.viewport(viewport.x, viewport.y, viewport.width, viewport.height);
+		read?;
+
+		Ok(decode_id(pixel[0]).and_then(|(node_index, mesh)| {
+			let node = scene_graph.root.descendants().nth(node_index)?;
+			Some(Picked {
+				node,
+				mesh,
+				node_index,
+			})
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::recording::{
+		GlCall,
+		RecordingBackend,
+	};
+	use crate::shader::Shader;
+	use cgmath::{
+		assert_abs_diff_eq,
+		Vector4,
+	};
+
+	#[test]
+	fn ids_round_trip_and_leave_zero_empty() {
+		assert_eq!(decode_id(0), None);
+		assert_eq!(encode_id(0, 0), Some(1 << MESH_BITS));
+		for (node, mesh) in [(0, 0), (3, 7), (MAX_NODES - 1, MAX_MESHES - 1)] {
+			let id = encode_id(node, mesh).unwrap();
+			assert_ne!(id, 0);
+			assert_eq!(decode_id(id), Some((node, mesh)));
+		}
+		assert_eq!(encode_id(MAX_NODES, 0), None);
+		assert_eq!(encode_id(0, MAX_MESHES), None);
+	}
+	#[test]
+	fn pick_matrix_centers_the_picked_pixel() {
+		let matrix = pick_matrix(0.5, -0.25, 800, 600);
+		let picked = matrix * Vector4::new(0.5, -0.25, 0.3, 1.0);
+		assert_abs_diff_eq!(picked, Vector4::new(0.0, 0.0, 0.3, 1.0));
+		// The neighbouring pixel's center lands outside clip space.
+		let beside = matrix * Vector4::new(0.5 + 2.0 / 800.0, -0.25, 0.0, 1.0);
+		assert_abs_diff_eq!(beside.x, 2.0, epsilon = 1e-4);
+	}
+	#[test]
+	fn picking_an_empty_scene_restores_the_canvas() {
+		let gl = RecordingBackend::new();
+		let vert = Shader::new(&gl, Gl::VERTEX_SHADER, "", &[], &[]).unwrap();
+		let frag = Shader::new(&gl, Gl::FRAGMENT_SHADER, "", &[], &[]).unwrap();
+		let program = Rc::new(Program::new(&gl, &vert, &frag).unwrap().0);
+		let pass = PickPass::new(&gl, program).unwrap();
+		assert!(gl.calls().contains(&GlCall::RenderbufferStorage {
+			internal_format: Gl::R32UI,
+			width: 1,
+			height: 1,
+		}));
+
+		gl.clear_calls();
+		let viewport = Viewport {
+			x: 0,
+			y: 10,
+			width: 200,
+			height: 100,
+		};
+		let camera = Camera::test_camera(200, 100);
+		let graph = SceneGraph::default();
+		let picked = pass.pick(&gl, &graph, &camera, &viewport, 0.0, 0.0);
+		assert!(picked.unwrap().is_none());
+
+		let calls = gl.calls();
+		assert_eq!(calls.last(), Some(&GlCall::Viewport(0, 10, 200, 100)));
+		assert!(calls.contains(&GlCall::BindFramebuffer(Gl::FRAMEBUFFER, None)));
+		assert!(calls.contains(&GlCall::ReadPixels {
+			x: 0,
+			y: 0,
+			width: 1,
+			height: 1,
+			format: Gl::RGBA_INTEGER,
+			type_: Gl::UNSIGNED_INT,
+		}));
+	}
+}
@@ -0,0 +1,172 @@
+// File: src/picking/mod.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Rays and what they hit, for selecting things under the cursor. The gpu
+// module picks by drawing instead, which scales to dense meshes.
+
+pub mod gpu;
+
+use cgmath::{
+	InnerSpace,
+	Matrix4,
+	Point3,
+	Vector3,
+};
+use rctree::Node;
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+	backend::GlBackend,
+	bounds::Aabb,
+	scene_graph::NodeData,
+};
+
+// Points along the ray are `origin + direction * t` for t >= 0.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+	pub origin: Point3<f32>,
+	pub direction: Vector3<f32>,
+}
+impl Ray {
+	// `direction` is normalized so t is a distance.
+	pub fn new(origin: Point3<f32>, direction: Vector3<f32>) -> Self {
+		Self {
+			origin,
+			direction: direction.normalize(),
+		}
+	}
+	pub fn at(&self, t: f32) -> Point3<f32> {
+		self.origin + self.direction * t
+	}
+	// The direction is left unnormalized so t means the same along the
+	// transformed ray as along this one.
+	pub fn transformed(&self, matrix: &Matrix4<f32>) -> Self {
+		Self {
+			origin: Point3::from_homogeneous(
+				matrix * self.origin.to_homogeneous(),
+			),
+			direction: (matrix * self.direction.extend(0.0)).truncate(),
+		}
+	}
+
+	// The t the ray enters the box at, zero if it starts inside.
+	pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+		let mut near = 0.0f32;
+		let mut far = f32::INFINITY;
+		for axis in 0..3 {
+			let origin = self.origin[axis];
+			let direction = self.direction[axis];
+			let (min, max) = (aabb.min[axis], aabb.max[axis]);
+			if direction == 0.0 {
+				if origin < min || origin > max {
+					return None;
+				}
+				continue;
+			}
+			let a = (min - origin) / direction;
+			let b = (max - origin) / direction;
+			near = near.max(a.min(b));
+			far = far.min(a.max(b));
+			if near > far {
+				return None;
+			}
+		}
+		Some(near)
+	}
+	// Möller-Trumbore. Both sides of the triangle are hit.
+	pub fn intersect_triangle(
+		&self,
+		triangle: &[Point3<f32>; 3],
+	) -> Option<f32> {
+		let [a, b, c] = *triangle;
+		let edge_1 = b - a;
+		let edge_2 = c - a;
+		let p = self.direction.cross(edge_2);
+		let determinant = edge_1.dot(p);
+		if determinant.abs() < f32::EPSILON {
+			return None;
+		}
+		let inverse = 1.0 / determinant;
+		let to_origin = self.origin - a;
+		let u = to_origin.dot(p) * inverse;
+		if !(0.0..=1.0).contains(&u) {
+			return None;
+		}
+		let q = to_origin.cross(edge_1);
+		let v = self.direction.dot(q) * inverse;
+		if v < 0.0 || u + v > 1.0 {
+			return None;
+		}
+		let t = edge_2.dot(q) * inverse;
+		if t >= 0.0 {
+			Some(t)
+		} else {
+			None
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Hit<G: GlBackend = WebGl2RenderingContext> {
+	pub node: Node<NodeData<G>>,
+	// Which of the node's model's meshes, zero for a mesh node.
+	pub mesh: usize,
+	// Index into the mesh geometry's triangles.
+	pub triangle: usize,
+	// Along the world space ray.
+	pub distance: f32,
+	pub point: Point3<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cgmath::assert_abs_diff_eq;
+
+	#[test]
+	fn ray_enters_boxes_it_points_at() {
+		let aabb = Aabb::new(
+			Point3::new(-1.0, -1.0, -1.0),
+			Point3::new(1.0, 1.0, 1.0),
+		);
+		let toward = Ray::new(Point3::new(0.5, 0.0, 5.0), -Vector3::unit_z());
+		assert_eq!(toward.intersect_aabb(&aabb), Some(4.0));
+
+		let inside = Ray::new(Point3::new(0.0, 0.0, 0.0), Vector3::unit_x());
+		assert_eq!(inside.intersect_aabb(&aabb), Some(0.0));
+
+		let away = Ray::new(Point3::new(0.0, 0.0, 5.0), Vector3::unit_z());
+		assert_eq!(away.intersect_aabb(&aabb), None);
+		let beside = Ray::new(Point3::new(2.0, 0.0, 5.0), -Vector3::unit_z());
+		assert_eq!(beside.intersect_aabb(&aabb), None);
+	}
+	#[test]
+	fn ray_hits_triangles_from_either_side() {
+		let triangle = [
+			Point3::new(0.0, 0.0, 0.0),
+			Point3::new(1.0, 0.0, 0.0),
+			Point3::new(0.0, 1.0, 0.0),
+		];
+		let front = Ray::new(Point3::new(0.25, 0.25, 2.0), -Vector3::unit_z());
+		assert_abs_diff_eq!(front.intersect_triangle(&triangle).unwrap(), 2.0);
+		let back = Ray::new(Point3::new(0.25, 0.25, -3.0), Vector3::unit_z());
+		assert_abs_diff_eq!(back.intersect_triangle(&triangle).unwrap(), 3.0);
+
+		let outside =
+			Ray::new(Point3::new(0.75, 0.75, 2.0), -Vector3::unit_z());
+		assert_eq!(outside.intersect_triangle(&triangle), None);
+		let parallel = Ray::new(Point3::new(0.0, 0.0, 1.0), Vector3::unit_x());
+		assert_eq!(parallel.intersect_triangle(&triangle), None);
+	}
+	#[test]
+	fn transformed_rays_keep_their_distances() {
+		let ray = Ray::new(Point3::new(0.0, 0.0, 10.0), -Vector3::unit_z());
+		let to_model = Matrix4::from_scale(0.5);
+		let local = ray.transformed(&to_model);
+		assert_abs_diff_eq!(local.at(4.0), Point3::new(0.0, 0.0, 3.0));
+		assert_abs_diff_eq!(ray.at(4.0), Point3::new(0.0, 0.0, 6.0));
+	}
+}
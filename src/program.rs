@@ -7,13 +7,9 @@
 
 use std::collections::HashMap;
 
-use web_sys::{
-	WebGl2RenderingContext,
-	WebGlProgram,
-	WebGlShader,
-	WebGlUniformLocation,
-};
+use web_sys::WebGl2RenderingContext;
 
+use crate::backend::GlBackend;
 use crate::config::scene_config::{
 	AttributeConfig,
 	UniformConfig,
@@ -33,17 +29,20 @@ pub mod attribute_locations {
 	pub const TEXCORD_3_LOCATION: u32 = 8;
 }
 
+pub type UniformLocations<G> =
+	HashMap<String, <G as GlBackend>::UniformLocation>;
+
 #[derive(Clone, Debug)]
-pub struct Program {
-	pub program: WebGlProgram,
+pub struct Program<G: GlBackend = WebGl2RenderingContext> {
+	pub program: G::Program,
 	pub attribute_locations: HashMap<String, u32>,
-	pub uniform_locations: HashMap<String, WebGlUniformLocation>,
+	pub uniform_locations: UniformLocations<G>,
 }
-impl Program {
+impl<G: GlBackend> Program<G> {
 	pub fn new(
-		gl: &WebGl2RenderingContext,
-		vert: &Shader,
-		frag: &Shader,
+		gl: &G,
+		vert: &Shader<G>,
+		frag: &Shader<G>,
 	) -> Result<(Self, Vec<ShaderWarning>), String> {
 		let program = Self::link_program(gl, &vert.handle, &frag.handle)?;
 
@@ -73,12 +72,15 @@ impl Program {
 			warnings,
 		))
 	}
+	pub fn delete(&self, gl: &G) {
+		gl.delete_program(Some(&self.program));
+	}
 
 	fn link_program(
-		gl: &WebGl2RenderingContext,
-		vert_shader: &WebGlShader,
-		frag_shader: &WebGlShader,
-	) -> Result<WebGlProgram, String> {
+		gl: &G,
+		vert_shader: &G::Shader,
+		frag_shader: &G::Shader,
+	) -> Result<G::Program, String> {
 		let program = gl
 			.create_program()
 			.ok_or("Unable to create shader object")?;
@@ -87,39 +89,31 @@ impl Program {
 		gl.attach_shader(&program, frag_shader);
 		gl.link_program(&program);
 
-		if gl
-			.get_program_parameter(
-				&program,
-				WebGl2RenderingContext::LINK_STATUS,
-			)
-			.as_bool()
-			.unwrap_or(false)
-		{
+		if gl.get_program_parameter_bool(
+			&program,
+			WebGl2RenderingContext::LINK_STATUS,
+		) {
 			gl.validate_program(&program);
-			if gl
-				.get_program_parameter(
-					&program,
-					WebGl2RenderingContext::VALIDATE_STATUS,
-				)
-				.as_bool()
-				.unwrap_or(false)
-			{
+			if gl.get_program_parameter_bool(
+				&program,
+				WebGl2RenderingContext::VALIDATE_STATUS,
+			) {
 				Ok(program)
 			} else {
-				Err(Self::get_logs(gl, &program, &vert_shader, &frag_shader))
+				Err(Self::get_logs(gl, &program, vert_shader, frag_shader))
 			}
 		} else {
-			Err(Self::get_logs(gl, &program, &vert_shader, &frag_shader))
+			Err(Self::get_logs(gl, &program, vert_shader, frag_shader))
 		}
 	}
 	fn get_logs(
-		gl: &WebGl2RenderingContext,
-		program: &WebGlProgram,
-		vert_shader: &WebGlShader,
-		frag_shader: &WebGlShader,
+		gl: &G,
+		program: &G::Program,
+		vert_shader: &G::Shader,
+		frag_shader: &G::Shader,
 	) -> String {
 		let program_log = gl
-			.get_program_info_log(&program)
+			.get_program_info_log(program)
 			.unwrap_or_else(|| String::from("Unable to get program logs"));
 		let vert_log =
 			gl.get_shader_info_log(vert_shader).unwrap_or_else(|| {
@@ -139,8 +133,8 @@ impl Program {
 		logs
 	}
 	fn attribute_locations(
-		gl: &WebGl2RenderingContext,
-		program: &WebGlProgram,
+		gl: &G,
+		program: &G::Program,
 		attributes: &[AttributeConfig],
 	) -> Result<(HashMap<String, u32>, Vec<ShaderWarning>), &'static str> {
 		let mut locations = HashMap::new();
@@ -159,28 +153,22 @@ impl Program {
 		Ok((locations, warnings))
 	}
 	fn uniform_locations(
-		gl: &WebGl2RenderingContext,
-		program: &WebGlProgram,
+		gl: &G,
+		program: &G::Program,
 		uniforms: &[UniformConfig],
-	) -> Result<
-		(HashMap<String, WebGlUniformLocation>, Vec<ShaderWarning>),
-		&'static str,
-	> {
+	) -> Result<(UniformLocations<G>, Vec<ShaderWarning>), &'static str> {
 		let mut locations = HashMap::new();
 		let mut warnings = Vec::new();
 		locations.reserve(uniforms.len());
-		for uniform in uniforms.iter() {
-			let location =
-				match gl.get_uniform_location(program, uniform.name.as_str()) {
-					Some(location) => location,
-					None => {
-						warnings.push(ShaderWarning::UniformNotFound(
-							uniform.name.clone(),
-						));
-						continue;
-					}
-				};
-			locations.insert(uniform.name.clone(), location);
+		for name in uniforms.iter().flat_map(UniformConfig::names) {
+			let location = match gl.get_uniform_location(program, &name) {
+				Some(location) => location,
+				None => {
+					warnings.push(ShaderWarning::UniformNotFound(name));
+					continue;
+				}
+			};
+			locations.insert(name, location);
 		}
 		Ok((locations, warnings))
 	}
@@ -0,0 +1,393 @@
+// File: src/renderer.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Owns the loaded resources and scene graph along with the scene's cameras,
+// one of which is active. Camera uniforms are uploaded every frame so
+// switching cameras takes effect on the next draw.
+
+use std::rc::Rc;
+
+use cgmath::{
+	Matrix4,
+	One,
+};
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+	backend::GlBackend,
+	camera::Camera,
+	config::scene_config::SceneConfig,
+	lights::manager::LightManager,
+	mat_4_to_array,
+	picking::{
+		gpu::{
+			PickPass,
+			Picked,
+		},
+		Hit,
+	},
+	program::Program,
+	resources::{
+		handle::Handle,
+		Resources,
+	},
+	scene_graph::SceneGraph,
+	shadow::pass::{
+		disable_shadows,
+		ShadowPass,
+	},
+	viewport::Viewport,
+};
+
+#[derive(Debug)]
+pub struct Renderer<G: GlBackend = WebGl2RenderingContext> {
+	gl: Rc<G>,
+	pub resources: Resources<G>,
+	pub scene_graph: SceneGraph<G>,
+
+	// In the order the scene config lists them.
+	cameras: Vec<Handle<Camera>>,
+	active_camera: usize,
+
+	// The scene graph's lights, see refresh_lights.
+	lights: LightManager,
+
+	// Set by enable_gpu_picking.
+	pick_pass: Option<PickPass<G>>,
+	// Set by enable_shadows.
+	shadow_pass: Option<ShadowPass<G>>,
+}
+impl<G: GlBackend> Renderer<G> {
+	// Starts from the config's `current_camera`. The config's cameras must
+	// already be loaded into `resources`.
+	pub fn new(
+		gl: Rc<G>,
+		resources: Resources<G>,
+		scene_graph: SceneGraph<G>,
+		config: &SceneConfig,
+	) -> Result<Self, String> {
+		let cameras = config
+			.cameras
+			.iter()
+			.map(|camera| resources.cameras.find(&camera.id))
+			.collect::<Result<Vec<_>, _>>()?;
+		let lights = LightManager::from_scene(&scene_graph);
+		let mut renderer = Self {
+			gl,
+			resources,
+			scene_graph,
+			cameras,
+			active_camera: 0,
+			lights,
+			pick_pass: None,
+			shadow_pass: None,
+		};
+		renderer.set_active_camera(config.current_camera)?;
+		Ok(renderer)
+	}
+
+	pub fn camera_count(&self) -> usize {
+		self.cameras.len()
+	}
+	pub fn active_camera_index(&self) -> usize {
+		self.active_camera
+	}
+	pub fn active_camera(&self) -> Result<&Rc<Camera>, String> {
+		let handle = self
+			.cameras
+			.get(self.active_camera)
+			.ok_or("The scene has no cameras")?;
+		Ok(self.resources.cameras.get(*handle)?)
+	}
+	pub fn set_active_camera(&mut self, index: usize) -> Result<(), String> {
+		if index >= self.cameras.len() {
+			return Err(format!(
+				"Camera index {} is out of range, there are {} cameras",
+				index,
+				self.cameras.len()
+			));
+		}
+		self.active_camera = index;
+		Ok(())
+	}
+	pub fn set_active_camera_by_id(&mut self, id: &str) -> Result<(), String> {
+		let handle = self.resources.cameras.find(id)?;
+		let index = self
+			.cameras
+			.iter()
+			.position(|camera| *camera == handle)
+			.ok_or_else(|| {
+				format!("Camera '{}' is not part of the scene", id)
+			})?;
+		self.set_active_camera(index)
+	}
+
+	// Gives every camera the aspect ratio of the viewport drawn to, e.g.
+	// after the canvas was resized.
+	pub fn set_aspect(&mut self, aspect: f32) -> Result<(), String> {
+		let cameras = self
+			.resources
+			.cameras
+			.iter()
+			.filter(|(_, camera)| camera.aspect != aspect)
+			.map(|(handle, camera)| (handle, **camera))
+			.collect::<Vec<_>>();
+		for (handle, mut camera) in cameras {
+			camera.aspect = aspect;
+			self.resources.cameras.replace(handle, Rc::new(camera))?;
+		}
+		Ok(())
+	}
+
+	// Edits a copy of the active camera and stores it in place of the
+	// original, so the camera keeps its handle.
+	pub fn update_active_camera(
+		&mut self,
+		update: impl FnOnce(&mut Camera),
+	) -> Result<(), String> {
+		let mut camera = **self.active_camera()?;
+		update(&mut camera);
+		let handle = self.cameras[self.active_camera];
+		self.resources.cameras.replace(handle, Rc::new(camera))?;
+		Ok(())
+	}
+
+	// Binds `program` and uploads the active camera's matrices and position.
+	// MODEL_MATRIX is reset to identity for drawables outside the graph.
+	pub fn apply_camera(&self, program: &Program<G>) -> Result<(), String> {
+		let camera = self.active_camera()?;
+		let gl = self.gl.as_ref();
+		let uniform_locations = &program.uniform_locations;
+
+		gl.use_program(Some(&program.program));
+		gl.uniform_matrix4fv_with_f32_array(
+			uniform_locations.get("PROJECTION_MATRIX"),
+			false,
+			&mat_4_to_array(&camera.projection_matrix()),
+		);
+		gl.uniform_matrix4fv_with_f32_array(
+			uniform_locations.get("VIEW_MATRIX"),
+			false,
+			&mat_4_to_array(&camera.view_matrix()),
+		);
+		gl.uniform_matrix4fv_with_f32_array(
+			uniform_locations.get("MODEL_MATRIX"),
+			false,
+			&mat_4_to_array(&Matrix4::one()),
+		);
+		let eye = camera.eye;
+		gl.uniform3f(uniform_locations.get("CAMERA_POS"), eye.x, eye.y, eye.z);
+		Ok(())
+	}
+	// Collects the scene graph's lights again, after it was changed. The
+	// shadow maps are remade for the new lights.
+	pub fn refresh_lights(&mut self) -> Result<(), String> {
+		self.lights = LightManager::from_scene(&self.scene_graph);
+		match self.shadow_pass.as_ref() {
+			Some(pass) => self.enable_shadows(Rc::clone(pass.program())),
+			None => Ok(()),
+		}
+	}
+	pub fn lights(&self) -> &LightManager {
+		&self.lights
+	}
+	// Binds `program` and uploads the scene's lights along with the shadow
+	// maps from the last render_shadows.
+	pub fn apply_lights(&self, program: &Program<G>) {
+		let gl = self.gl.as_ref();
+		gl.use_program(Some(&program.program));
+		self.lights.upload(gl, program);
+		match self.shadow_pass {
+			Some(ref pass) => pass.upload(gl, program, &self.lights),
+			None => disable_shadows(gl, program),
+		}
+	}
+	// Gives the lights with shadow configs shadow maps drawn with
+	// `program`, see ShadowPass.
+	pub fn enable_shadows(
+		&mut self,
+		program: Rc<Program<G>>,
+	) -> Result<(), String> {
+		let gl = self.gl.as_ref();
+		let pass = ShadowPass::new(gl, program, &self.lights)?;
+		if let Some(previous) = self.shadow_pass.replace(pass) {
+			previous.delete(gl);
+		}
+		Ok(())
+	}
+	// Renders the shadow maps for the active camera's view. Drawing goes
+	// back to the canvas and `viewport` after.
+	pub fn render_shadows(
+		&mut self,
+		viewport: &Viewport,
+	) -> Result<(), String> {
+		let camera = Rc::clone(self.active_camera()?);
+		if let Some(ref mut pass) = self.shadow_pass {
+			pass.render(
+				self.gl.as_ref(),
+				&self.scene_graph,
+				&self.lights,
+				&camera,
+				viewport,
+			);
+		}
+		Ok(())
+	}
+	// What's under normalized device coordinates x and y as seen by the
+	// active camera.
+	pub fn pick(&self, x: f32, y: f32) -> Result<Option<Hit<G>>, String> {
+		let ray = self.active_camera()?.ray(x, y);
+		Ok(self.scene_graph.pick(&ray))
+	}
+	// Lets pick_gpu draw ids with `program`, see PickPass.
+	pub fn enable_gpu_picking(
+		&mut self,
+		program: Rc<Program<G>>,
+	) -> Result<(), String> {
+		let pass = PickPass::new(self.gl.as_ref(), program)?;
+		if let Some(previous) = self.pick_pass.replace(pass) {
+			previous.delete(self.gl.as_ref());
+		}
+		Ok(())
+	}
+	// Like pick but reads the node under normalized device coordinates x
+	// and y of `viewport` back from the GPU. Picks whole meshes, without
+	// the triangle or point.
+	pub fn pick_gpu(
+		&self,
+		viewport: &Viewport,
+		x: f32,
+		y: f32,
+	) -> Result<Option<Picked<G>>, String> {
+		let pass = self
+			.pick_pass
+			.as_ref()
+			.ok_or("GPU picking has not been enabled")?;
+		let camera = self.active_camera()?;
+		Ok(pass.pick(
+			self.gl.as_ref(),
+			&self.scene_graph,
+			camera,
+			viewport,
+			x,
+			y,
+		)?)
+	}
+	// Draws the meshes the active camera can see, blended ones last, and
+	// returns how many were drawn. Shadows are from the last render_shadows.
+	pub fn draw_scene(&self, program: &Program<G>) -> Result<usize, String> {
+		self.apply_camera(program)?;
+		self.apply_lights(program);
+		let camera = self.active_camera()?;
+		Ok(self
+			.scene_graph
+			.draw_sorted(self.gl.as_ref(), program, camera))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::recording::{
+		GlCall,
+		RecordingBackend,
+	};
+	use crate::config::scene_config::UniformConfig;
+	use crate::resources::traits::NewResourceT;
+
+	fn renderer() -> (Rc<RecordingBackend>, Renderer<RecordingBackend>) {
+		let config: SceneConfig = serde_json::from_str(
+			r#"{
+				"current_camera": 1,
+				"cameras": [
+					{
+						"id": "front", "fov_y": 45, "z_near": 0.1, "z_far": 100,
+						"eye": [0, 0, 5], "center": [0, 0, 0], "up": [0, 1, 0]
+					},
+					{
+						"id": "side", "fov_y": 45, "z_near": 0.1, "z_far": 100,
+						"eye": [5, 0, 0], "center": [0, 0, 0], "up": [0, 1, 0]
+					}
+				],
+				"shaders": [],
+				"programs": []
+			}"#,
+		)
+		.unwrap();
+		let gl = Rc::new(RecordingBackend::new());
+		let mut resources = Resources::new(Rc::clone(&gl));
+		resources.load_cameras(&config.cameras, 800, 600);
+		let scene_graph = resources.build_scene(&config).unwrap();
+		let renderer =
+			Renderer::new(Rc::clone(&gl), resources, scene_graph, &config)
+				.unwrap();
+		(gl, renderer)
+	}
+	fn program(
+		renderer: &mut Renderer<RecordingBackend>,
+	) -> Rc<Program<RecordingBackend>> {
+		let uniforms = ["VIEW_MATRIX", "CAMERA_POS"]
+			.iter()
+			.map(|name| UniformConfig::new(name))
+			.collect::<Vec<_>>();
+		let resources = &mut renderer.resources;
+		let vertex = resources
+			.new_shader("vert", "vertex", "", &[], &uniforms)
+			.unwrap();
+		let fragment = resources
+			.new_shader("frag", "fragment", "", &[], &[])
+			.unwrap();
+		let (program, _) =
+			resources.new_program("program", vertex, fragment).unwrap();
+		Rc::clone(resources.programs.get(program).unwrap())
+	}
+	fn camera_positions(gl: &RecordingBackend) -> Vec<(f32, f32, f32)> {
+		gl.calls()
+			.into_iter()
+			.filter_map(|call| match call {
+				GlCall::Uniform3f(Some(ref name), x, y, z)
+					if name == "CAMERA_POS" =>
+				{
+					Some((x, y, z))
+				}
+				_ => None,
+			})
+			.collect()
+	}
+
+	#[test]
+	fn starts_from_current_camera() {
+		let (_gl, renderer) = renderer();
+		assert_eq!(renderer.active_camera_index(), 1);
+		assert_eq!(renderer.active_camera().unwrap().eye.x, 5.0);
+	}
+	#[test]
+	fn switching_cameras_changes_the_next_upload() {
+		let (gl, mut renderer) = renderer();
+		let program = program(&mut renderer);
+
+		renderer.apply_camera(&program).unwrap();
+		renderer.set_active_camera_by_id("front").unwrap();
+		renderer.apply_camera(&program).unwrap();
+
+		assert_eq!(
+			camera_positions(&gl),
+			vec![(5.0, 0.0, 0.0), (0.0, 0.0, 5.0)]
+		);
+		assert!(renderer.set_active_camera(2).is_err());
+		assert!(renderer.set_active_camera_by_id("missing").is_err());
+		assert_eq!(renderer.active_camera_index(), 0);
+	}
+	#[test]
+	fn resizing_updates_every_camera() {
+		let (_gl, mut renderer) = renderer();
+		renderer.set_aspect(2.0).unwrap();
+		for (_, camera) in renderer.resources.cameras.iter() {
+			assert_eq!(camera.aspect, 2.0);
+		}
+		assert_eq!(renderer.active_camera().unwrap().eye.x, 5.0);
+	}
+}
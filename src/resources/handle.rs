@@ -0,0 +1,312 @@
+// File: src/resources/handle.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Generational handles into typed resource pools. A slot's generation is
+// bumped whenever its resource is removed, so a handle kept past removal
+// is reported as stale instead of aliasing whatever reuses the slot.
+
+use std::{
+	collections::HashMap,
+	fmt,
+	hash::{
+		Hash,
+		Hasher,
+	},
+	marker::PhantomData,
+	rc::Rc,
+};
+
+use wasm_bindgen::JsValue;
+
+pub struct Handle<T> {
+	index: u32,
+	generation: u32,
+	resource: PhantomData<fn() -> T>,
+}
+impl<T> Handle<T> {
+	fn new(index: u32, generation: u32) -> Self {
+		Self {
+			index,
+			generation,
+			resource: PhantomData,
+		}
+	}
+	pub fn index(&self) -> u32 {
+		self.index
+	}
+	pub fn generation(&self) -> u32 {
+		self.generation
+	}
+}
+// Implemented by hand so handles are Copy and comparable whatever T is.
+impl<T> Copy for Handle<T> {}
+impl<T> Clone for Handle<T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+impl<T> PartialEq for Handle<T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.index == other.index && self.generation == other.generation
+	}
+}
+impl<T> Eq for Handle<T> {}
+impl<T> Hash for Handle<T> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.index.hash(state);
+		self.generation.hash(state);
+	}
+}
+impl<T> fmt::Debug for Handle<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Handle({}v{})", self.index, self.generation)
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum HandleError {
+	Stale {
+		kind: &'static str,
+		index: u32,
+		generation: u32,
+	},
+	OutOfRange {
+		kind: &'static str,
+		index: u32,
+	},
+	UnknownName {
+		kind: &'static str,
+		name: String,
+	},
+}
+impl fmt::Display for HandleError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			HandleError::Stale {
+				kind,
+				index,
+				generation,
+			} => write!(
+				f,
+				"{} handle {}v{} is stale, the {} was removed",
+				kind, index, generation, kind
+			),
+			HandleError::OutOfRange { kind, index } => write!(
+				f,
+				"{} handle {} does not belong to this pool",
+				kind, index
+			),
+			HandleError::UnknownName { kind, name } => {
+				write!(f, "no {} named '{}'", kind, name)
+			}
+		}
+	}
+}
+impl From<HandleError> for String {
+	fn from(error: HandleError) -> Self {
+		error.to_string()
+	}
+}
+impl From<HandleError> for JsValue {
+	fn from(error: HandleError) -> Self {
+		JsValue::from_str(&error.to_string())
+	}
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+	generation: u32,
+	value: Option<Rc<T>>,
+}
+
+#[derive(Debug)]
+pub struct Pool<T> {
+	kind: &'static str,
+	slots: Vec<Slot<T>>,
+	free: Vec<u32>,
+	names: HashMap<String, Handle<T>>,
+}
+impl<T> Pool<T> {
+	pub fn new(kind: &'static str) -> Self {
+		Self {
+			kind,
+			slots: Vec::new(),
+			free: Vec::new(),
+			names: HashMap::new(),
+		}
+	}
+	pub fn insert(&mut self, value: Rc<T>) -> Handle<T> {
+		match self.free.pop() {
+			Some(index) => {
+				let slot = &mut self.slots[index as usize];
+				slot.value = Some(value);
+				Handle::new(index, slot.generation)
+			}
+			None => {
+				let index = self.slots.len() as u32;
+				self.slots.push(Slot {
+					generation: 0,
+					value: Some(value),
+				});
+				Handle::new(index, 0)
+			}
+		}
+	}
+	// Inserts a value that can also be found by name. A value already
+	// using the name is removed from the pool and returned.
+	pub fn insert_named(
+		&mut self,
+		name: &str,
+		value: Rc<T>,
+	) -> (Handle<T>, Option<Rc<T>>) {
+		let replaced = match self.names.get(name) {
+			Some(handle) => self.remove(*handle).ok(),
+			None => None,
+		};
+		let handle = self.insert(value);
+		self.names.insert(name.to_owned(), handle);
+		(handle, replaced)
+	}
+	pub fn get(&self, handle: Handle<T>) -> Result<&Rc<T>, HandleError> {
+		let slot = self.slot(handle)?;
+		slot.value.as_ref().ok_or(HandleError::Stale {
+			kind: self.kind,
+			index: handle.index,
+			generation: handle.generation,
+		})
+	}
+	pub fn get_all(
+		&self,
+		handles: &[Handle<T>],
+	) -> Result<Vec<Rc<T>>, HandleError> {
+		handles
+			.iter()
+			.map(|handle| self.get(*handle).map(Rc::clone))
+			.collect()
+	}
+	// Swaps the value behind a live handle, returning the old value. The
+	// handle, and any name for it, stays valid.
+	pub fn replace(
+		&mut self,
+		handle: Handle<T>,
+		value: Rc<T>,
+	) -> Result<Rc<T>, HandleError> {
+		self.get(handle)?;
+		let slot = &mut self.slots[handle.index as usize];
+		Ok(slot.value.replace(value).unwrap())
+	}
+	pub fn contains(&self, handle: Handle<T>) -> bool {
+		self.get(handle).is_ok()
+	}
+	pub fn remove(&mut self, handle: Handle<T>) -> Result<Rc<T>, HandleError> {
+		self.get(handle)?;
+		let slot = &mut self.slots[handle.index as usize];
+		slot.generation = slot.generation.wrapping_add(1);
+		self.free.push(handle.index);
+		self.names.retain(|_, named| *named != handle);
+		Ok(slot.value.take().unwrap())
+	}
+	pub fn find(&self, name: &str) -> Result<Handle<T>, HandleError> {
+		self.names
+			.get(name)
+			.copied()
+			.ok_or_else(|| HandleError::UnknownName {
+				kind: self.kind,
+				name: name.to_owned(),
+			})
+	}
+	pub fn get_by_name(&self, name: &str) -> Result<&Rc<T>, HandleError> {
+		self.get(self.find(name)?)
+	}
+	pub fn len(&self) -> usize {
+		self.slots.len() - self.free.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+	pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &Rc<T>)> {
+		self.slots.iter().enumerate().filter_map(|(i, slot)| {
+			slot.value
+				.as_ref()
+				.map(|value| (Handle::new(i as u32, slot.generation), value))
+		})
+	}
+	fn slot(&self, handle: Handle<T>) -> Result<&Slot<T>, HandleError> {
+		let slot = self.slots.get(handle.index as usize).ok_or(
+			HandleError::OutOfRange {
+				kind: self.kind,
+				index: handle.index,
+			},
+		)?;
+		if slot.generation != handle.generation {
+			return Err(HandleError::Stale {
+				kind: self.kind,
+				index: handle.index,
+				generation: handle.generation,
+			});
+		}
+		Ok(slot)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn removed_handle_is_stale_after_slot_reuse() {
+		let mut pool = Pool::new("string");
+		let first = pool.insert(Rc::new(String::from("first")));
+		pool.remove(first).unwrap();
+		let second = pool.insert(Rc::new(String::from("second")));
+
+		assert_eq!(first.index(), second.index());
+		assert_eq!(
+			pool.get(first),
+			Err(HandleError::Stale {
+				kind: "string",
+				index: 0,
+				generation: 0,
+			})
+		);
+		assert_eq!(pool.get(second).unwrap().as_str(), "second");
+		assert!(pool.remove(first).is_err());
+		assert_eq!(pool.len(), 1);
+	}
+	#[test]
+	fn names_resolve_and_replacing_returns_old_value() {
+		let mut pool = Pool::new("string");
+		let (a, replaced) =
+			pool.insert_named("main", Rc::new(String::from("a")));
+		assert!(replaced.is_none());
+		assert_eq!(pool.find("main"), Ok(a));
+
+		let (b, replaced) =
+			pool.insert_named("main", Rc::new(String::from("b")));
+		assert_eq!(replaced.unwrap().as_str(), "a");
+		assert!(!pool.contains(a));
+		assert_eq!(pool.get_by_name("main").unwrap().as_str(), "b");
+
+		pool.remove(b).unwrap();
+		assert_eq!(
+			pool.find("main"),
+			Err(HandleError::UnknownName {
+				kind: "string",
+				name: String::from("main"),
+			})
+		);
+	}
+	#[test]
+	fn foreign_handle_is_out_of_range() {
+		let mut other = Pool::new("string");
+		let pool: Pool<String> = Pool::new("string");
+		let handle = other.insert(Rc::new(String::new()));
+		assert!(matches!(
+			pool.get(handle),
+			Err(HandleError::OutOfRange { .. })
+		));
+	}
+}
@@ -30,7 +30,6 @@ use web_sys::HtmlImageElement;
 ///  img.src = src;
 /// })
 /// ```
-
 pub struct ImageFuture {
 	image: Option<HtmlImageElement>,
 	load_failed: Rc<RefCell<bool>>,
@@ -79,7 +78,8 @@ impl Future for ImageFuture {
 				let on_error_closure = Closure::wrap(Box::new(move || {
 					*failed_flag.borrow_mut() = true;
 					waker.wake_by_ref();
-				}) as Box<dyn FnMut()>);
+				})
+					as Box<dyn FnMut()>);
 				image.set_onerror(Some(
 					on_error_closure.as_ref().unchecked_ref(),
 				));
@@ -5,18 +5,17 @@
 //
 // Description:
 
+pub mod handle;
 pub mod image_future;
 pub mod traits;
 
-use std::{
-	collections::HashMap,
-	convert::TryFrom,
-};
+use std::convert::TryFrom;
 use std::{
 	fmt::Debug,
 	rc::Rc,
 };
 
+use futures::future::join_all;
 use wasm_bindgen::{
 	JsCast,
 	JsValue,
@@ -24,8 +23,11 @@ use wasm_bindgen::{
 use wasm_bindgen_futures::JsFuture;
 
 use web_sys::{
+	Blob,
+	BlobPropertyBag,
 	Document,
 	HtmlImageElement,
+	Url,
 	WebGl2RenderingContext,
 };
 use web_sys::{
@@ -35,18 +37,37 @@ use web_sys::{
 	Response,
 };
 
+use crate::backend::GlBackend;
+use crate::bounds::Aabb;
 use crate::camera::Camera;
 use crate::config::scene_config::{
+	AlphaModeConfig,
 	AttributeConfig,
+	BufferConfig,
 	CameraConfig,
+	EnvironmentConfig,
 	SceneConfig,
 	UniformConfig,
 };
+use crate::environment::{
+	hdr::HdrImage,
+	Environment,
+	EnvironmentMaps,
+};
+use crate::gltf::{
+	self,
+	data_uri,
+	Gltf,
+	GltfError,
+};
+use crate::lights::Light;
 use crate::{
 	config::scene_config::{
 		BufferViewConfig,
 		MaterialConfig,
+		MaterialTextureConfig,
 		ModelConfig,
+		PrimitiveConfig,
 		ProgramConfig,
 		ShaderConfig,
 	},
@@ -54,141 +75,211 @@ use crate::{
 		buffer::Buffer,
 		buffer_view::BufferView,
 		material::{
+			AlphaMode,
 			Material,
-			MetallicRoughnessPart,
-			OcclusionPart,
+			MaterialTexture,
 		},
 		mesh::{
+			generator::{
+				cube::generate_cube,
+				uv_sphere::generate_uv_sphere,
+			},
+			geometry::{
+				self,
+				Geometry,
+			},
 			AttributeBufferViews,
 			Mesh,
 		},
 		texture::{
+			self,
 			Sampler,
+			SamplerObject,
 			Texture,
+			TextureData,
+			TextureFormat,
 		},
 		Model,
+		ModelResources,
 	},
 };
 
 use crate::program::Program;
+use crate::scene_graph::SceneGraph;
 use crate::shader::Shader;
 use crate::warning::*;
 
 use self::{
+	handle::{
+		Handle,
+		HandleError,
+		Pool,
+	},
 	image_future::ImageFuture,
 	traits::{
 		AddResourceT,
+		GetResourceT,
 		NewResourceT,
+		RemoveResourceT,
 	},
 };
 
+// GL backed resources removed from Resources while still referenced
+// elsewhere, e.g. a buffer a mesh still draws from.
+#[derive(Debug)]
+enum PendingRelease<G: GlBackend> {
+	Shader(Rc<Shader<G>>),
+	Program(Rc<Program<G>>),
+	Texture(Rc<Texture<G>>),
+	Sampler(Rc<SamplerObject<G>>),
+	Buffer(Rc<Buffer<G>>),
+	Mesh(Rc<Mesh<G>>),
+	Environment(Rc<Environment<G>>),
+}
+impl<G: GlBackend> PendingRelease<G> {
+	fn is_unused(&self) -> bool {
+		let strong_count = match self {
+			PendingRelease::Shader(shader) => Rc::strong_count(shader),
+			PendingRelease::Program(program) => Rc::strong_count(program),
+			PendingRelease::Texture(texture) => Rc::strong_count(texture),
+			PendingRelease::Sampler(sampler) => Rc::strong_count(sampler),
+			PendingRelease::Buffer(buffer) => Rc::strong_count(buffer),
+			PendingRelease::Mesh(mesh) => Rc::strong_count(mesh),
+			PendingRelease::Environment(environment) => {
+				Rc::strong_count(environment)
+			}
+		};
+		strong_count == 1
+	}
+	fn release(&self, gl: &G) {
+		match self {
+			PendingRelease::Shader(shader) => shader.delete(gl),
+			PendingRelease::Program(program) => program.delete(gl),
+			PendingRelease::Texture(texture) => texture.delete(gl),
+			PendingRelease::Sampler(sampler) => sampler.delete(gl),
+			PendingRelease::Buffer(buffer) => buffer.delete(gl),
+			PendingRelease::Mesh(mesh) => mesh.clean_up(gl),
+			PendingRelease::Environment(environment) => environment.delete(gl),
+		}
+	}
+}
+
 #[derive(Debug)]
-pub struct Resources {
-	gl: Rc<WebGl2RenderingContext>,
-
-	pub cameras: HashMap<String, Rc<Camera>>,
-
-	pub strings: HashMap<String, Rc<String>>,
-	pub shaders: HashMap<String, Rc<Shader>>,
-	pub programs: HashMap<String, Rc<Program>>,
-
-	pub textures: HashMap<String, Rc<Texture>>,
-	next_sampler_id: u32,
-	pub samplers: HashMap<u32, Rc<Sampler>>,
-	next_material_id: u32,
-	pub materials: HashMap<u32, Rc<Material>>,
-
-	next_buffer_id: u32,
-	pub buffers: HashMap<u32, Rc<Buffer>>,
-	next_mesh_id: u32,
-	pub meshes: HashMap<u32, Rc<Mesh>>,
-	pub models: HashMap<String, Rc<Model>>,
+pub struct Resources<G: GlBackend = WebGl2RenderingContext> {
+	gl: Rc<G>,
+
+	pub cameras: Pool<Camera>,
+
+	pub strings: Pool<String>,
+	pub shaders: Pool<Shader<G>>,
+	pub programs: Pool<Program<G>>,
+
+	pub textures: Pool<Texture<G>>,
+	pub samplers: Pool<SamplerObject<G>>,
+	pub materials: Pool<Material<G>>,
+
+	pub buffers: Pool<Buffer<G>>,
+	pub meshes: Pool<Mesh<G>>,
+	pub models: Pool<Model<G>>,
+
+	// Given to every material added after it's loaded.
+	pub environment: Option<Rc<Environment<G>>>,
+	// Trilinear samplers are made anisotropic up to this, None without the
+	// extension.
+	max_anisotropy: Option<f32>,
+
+	pending_release: Vec<PendingRelease<G>>,
 }
 
-impl AddResourceT for Resources {
-	fn add_camera(&mut self, id: &str, camera: &Camera) -> Option<&Rc<Camera>> {
-		self.cameras
-			.insert(id.to_owned(), Rc::new(camera.to_owned()));
-		self.cameras.get(id)
+impl<G: GlBackend> AddResourceT<G> for Resources<G> {
+	fn add_camera(&mut self, id: &str, camera: &Camera) -> Handle<Camera> {
+		self.cameras.insert_named(id, Rc::new(camera.to_owned())).0
 	}
 
-	fn add_string(&mut self, id: &str, string: &str) -> Option<&Rc<String>> {
-		self.strings
-			.insert(id.to_owned(), Rc::new(string.to_owned()));
-		self.strings.get(id)
+	fn add_string(&mut self, id: &str, string: &str) -> Handle<String> {
+		self.strings.insert_named(id, Rc::new(string.to_owned())).0
 	}
-	fn add_shader(&mut self, id: &str, shader: &Shader) -> Option<&Rc<Shader>> {
-		self.shaders
-			.insert(id.to_owned(), Rc::new(shader.to_owned()));
-		self.shaders.get(id)
+	fn add_shader(
+		&mut self,
+		id: &str,
+		shader: &Shader<G>,
+	) -> Handle<Shader<G>> {
+		let (handle, replaced) =
+			self.shaders.insert_named(id, Rc::new(shader.to_owned()));
+		if let Some(shader) = replaced {
+			self.release(PendingRelease::Shader(shader));
+		}
+		handle
 	}
 	fn add_program(
 		&mut self,
 		id: &str,
-		program: &Program,
-	) -> Option<&Rc<Program>> {
-		self.programs
-			.insert(id.to_owned(), Rc::new(program.to_owned()));
-		self.programs.get(id)
+		program: &Program<G>,
+	) -> Handle<Program<G>> {
+		let (handle, replaced) =
+			self.programs.insert_named(id, Rc::new(program.to_owned()));
+		if let Some(program) = replaced {
+			self.release(PendingRelease::Program(program));
+		}
+		handle
 	}
 
 	fn add_texture(
 		&mut self,
 		id: &str,
-		texture: &Texture,
-	) -> Option<&Rc<Texture>> {
-		self.textures
-			.insert(id.to_owned(), Rc::new(texture.to_owned()));
-		self.textures.get(id)
+		texture: &Texture<G>,
+	) -> Handle<Texture<G>> {
+		let (handle, replaced) =
+			self.textures.insert_named(id, Rc::new(texture.to_owned()));
+		if let Some(texture) = replaced {
+			self.release(PendingRelease::Texture(texture));
+		}
+		handle
 	}
 	fn add_sampler(
 		&mut self,
-		sampler: &Sampler,
-	) -> Option<(u32, &Rc<Sampler>)> {
-		let id = self.next_sampler_id;
-		self.samplers.insert(id, Rc::new(sampler.to_owned()));
-		self.next_sampler_id += 1;
-		self.samplers.get(&id).map(|s| (id, s))
-	}
-	fn add_material(
-		&mut self,
-		material: &Material,
-	) -> Option<(u32, &Rc<Material>)> {
-		let id = self.next_material_id;
-		self.materials.insert(id, Rc::new(material.to_owned()));
-		self.next_material_id += 1;
-		self.materials.get(&id).map(|m| (id, m))
-	}
-
-	fn add_buffer(&mut self, buffer: &Buffer) -> Option<(u32, &Rc<Buffer>)> {
-		let id = self.next_buffer_id;
-		self.buffers.insert(id, Rc::new(buffer.to_owned()));
-		self.next_buffer_id += 1;
-		self.buffers.get(&id).map(|b| (id, b))
-	}
-	fn add_mesh(&mut self, mesh: &Mesh) -> Option<(u32, &Rc<Mesh>)> {
-		let id = self.next_mesh_id;
-		self.meshes.insert(id, Rc::new(mesh.to_owned()));
-		self.next_mesh_id += 1;
-		self.meshes.get(&id).map(|m| (id, m))
-	}
-	fn add_model(&mut self, id: &str, model: &Model) -> Option<&Rc<Model>> {
-		self.models.insert(id.to_owned(), Rc::new(model.to_owned()));
-		self.models.get(id)
+		sampler: &SamplerObject<G>,
+	) -> Handle<SamplerObject<G>> {
+		self.samplers.insert(Rc::new(sampler.to_owned()))
+	}
+	fn add_material(&mut self, material: &Material<G>) -> Handle<Material<G>> {
+		let material = Material {
+			environment: material
+				.environment
+				.clone()
+				.or_else(|| self.environment.clone()),
+			..material.to_owned()
+		};
+		self.materials.insert(Rc::new(material))
+	}
+
+	fn add_buffer(&mut self, buffer: &Buffer<G>) -> Handle<Buffer<G>> {
+		self.buffers.insert(Rc::new(buffer.to_owned()))
+	}
+	fn add_mesh(&mut self, mesh: &Mesh<G>) -> Handle<Mesh<G>> {
+		self.meshes.insert(Rc::new(mesh.to_owned()))
+	}
+	fn add_model(&mut self, id: &str, model: &Model<G>) -> Handle<Model<G>> {
+		let (handle, replaced) =
+			self.models.insert_named(id, Rc::new(model.to_owned()));
+		if let Some(model) = replaced {
+			self.release_model(model);
+		}
+		handle
 	}
 }
 
-impl NewResourceT for Resources {
+impl<G: GlBackend> NewResourceT<G> for Resources<G> {
 	fn new_camera(
 		&mut self,
 		config: &CameraConfig,
 		width: u32,
 		height: u32,
-	) -> Option<&Rc<Camera>> {
+	) -> Handle<Camera> {
 		self.add_camera(&config.id, &Camera::new(config, width, height))
 	}
 
-	fn new_string(&mut self, id: &str, string: &str) -> Option<&Rc<String>> {
+	fn new_string(&mut self, id: &str, string: &str) -> Handle<String> {
 		self.add_string(id, string)
 	}
 	fn new_shader(
@@ -198,144 +289,349 @@ impl NewResourceT for Resources {
 		source: &str,
 		attributes: &[AttributeConfig],
 		uniforms: &[UniformConfig],
-	) -> Result<&Rc<Shader>, &'static str> {
+	) -> Result<Handle<Shader<G>>, &'static str> {
 		let shader_type = match shader_type {
 			"vertex" => WebGl2RenderingContext::VERTEX_SHADER,
 			"fragment" => WebGl2RenderingContext::FRAGMENT_SHADER,
 			_ => return Err("Shader type is not 'vertex' or 'fragment'"),
 		};
 
-		self.add_shader(
-			id,
-			&Shader::new(&self.gl, shader_type, source, attributes, uniforms)?,
-		)
-		.ok_or("Failed to insert shader")
+		let shader = Shader::new(
+			self.gl.as_ref(),
+			shader_type,
+			source,
+			attributes,
+			uniforms,
+		)?;
+		Ok(self.add_shader(id, &shader))
 	}
 	fn new_program(
 		&mut self,
 		id: &str,
-		vertex: &Shader,
-		fragment: &Shader,
-	) -> Result<(&Rc<Program>, Vec<ShaderWarning>), String> {
-		let (program, warnings) = Program::new(&self.gl, vertex, fragment)?;
-
-		self.add_program(id, &program)
-			.ok_or_else(|| String::from("Failed to insert program"))
-			.map(|p| (p, warnings))
+		vertex: Handle<Shader<G>>,
+		fragment: Handle<Shader<G>>,
+	) -> Result<(Handle<Program<G>>, Vec<ShaderWarning>), String> {
+		let (program, warnings) = Program::new(
+			self.gl.as_ref(),
+			self.shaders.get(vertex)?,
+			self.shaders.get(fragment)?,
+		)?;
+		Ok((self.add_program(id, &program), warnings))
 	}
 	fn new_program_from_shader_ids(
 		&mut self,
 		id: &str,
 		vertex_id: &str,
 		fragment_id: &str,
-	) -> Result<(&Rc<Program>, Vec<ShaderWarning>), String> {
-		let (program, warnings) = {
-			let vertex = self.shaders.get(vertex_id).ok_or_else(|| {
-				String::from("Resources is missing vertex shader")
-			})?;
-			let fragment = self.shaders.get(fragment_id).ok_or_else(|| {
-				String::from("Resources is missing fragment shader")
-			})?;
-			Program::new(&self.gl, vertex, fragment)?
-		};
-		self.add_program(id, &program)
-			.ok_or_else(|| String::from("Failed to insert program"))
-			.map(|p| (p, warnings))
+	) -> Result<(Handle<Program<G>>, Vec<ShaderWarning>), String> {
+		let vertex = self.shaders.find(vertex_id)?;
+		let fragment = self.shaders.find(fragment_id)?;
+		self.new_program(id, vertex, fragment)
 	}
 
+	fn new_sampler(
+		&mut self,
+		sampler: &Sampler,
+	) -> Result<Handle<SamplerObject<G>>, String> {
+		let shared = self
+			.samplers
+			.iter()
+			.find(|(_, object)| object.sampler() == sampler)
+			.map(|(handle, _)| handle);
+		if let Some(handle) = shared {
+			return Ok(handle);
+		}
+		let sampler =
+			SamplerObject::new(self.gl.as_ref(), sampler, self.max_anisotropy)?;
+		Ok(self.add_sampler(&sampler))
+	}
 	fn new_texture(
 		&mut self,
 		id: &str,
-		image_element: &HtmlImageElement,
-		texture_unit: u32,
-		sampler: &Rc<Sampler>,
-	) -> Result<&Rc<Texture>, &'static str> {
-		let texture =
-			Texture::new(&self.gl, image_element, texture_unit, sampler)?;
-		self.add_texture(id, &texture)
-			.ok_or("Failed to add new texture")
+		data: TextureData,
+		format: TextureFormat,
+		sampler: Handle<SamplerObject<G>>,
+	) -> Result<Handle<Texture<G>>, String> {
+		let texture = Texture::new(
+			self.gl.as_ref(),
+			data,
+			format,
+			self.samplers.get(sampler)?,
+		)?;
+		Ok(self.add_texture(id, &texture))
 	}
 	fn new_material(
 		&mut self,
 		material: &MaterialConfig,
-		textures: &[Rc<Texture>],
-	) -> Result<(u32, &Rc<Material>), &'static str> {
-		let diffuse_tex = material
-			.diffuse
-			.map(|i| textures.get(i as usize).cloned())
-			.flatten();
-
-		let normal_tex = material
-			.normal
-			.map(|i| textures.get(i as usize).cloned())
-			.flatten();
-
-		let metallic_roughness_part = material
-			.metallic_roughness
-			.map(|i| {
-				textures
-					.get(i as usize)
-					.map(|t| MetallicRoughnessPart::Texture(Rc::clone(t)))
-			})
-			.flatten()
-			.unwrap_or_else(MetallicRoughnessPart::default);
-
-		let occlusion_part = material
-			.occlusion
-			.map(|i| {
-				textures
-					.get(i as usize)
-					.map(|t| OcclusionPart::Texture(Rc::clone(t)))
+		textures: &[Handle<Texture<G>>],
+	) -> Result<Handle<Material<G>>, String> {
+		let textures = self.textures.get_all(textures)?;
+		let texture = |config: Option<MaterialTextureConfig>| {
+			config.and_then(|config| {
+				textures.get(config.index as usize).map(|texture| {
+					MaterialTexture {
+						texture: Rc::clone(texture),
+						tex_coord: config.tex_coord,
+					}
+				})
 			})
-			.flatten()
-			.unwrap_or_else(OcclusionPart::default);
+		};
 
 		let mat = Material {
-			diffuse_tex,
-			normal_tex,
-			metallic_roughness_part,
-			occlusion_part,
+			base_color_factor: material.base_color_factor.into(),
+			base_color_tex: texture(material.base_color),
+			metallic_factor: material.metallic_factor,
+			roughness_factor: material.roughness_factor,
+			metallic_roughness_tex: texture(material.metallic_roughness),
+			normal_tex: texture(material.normal),
+			normal_scale: material.normal_scale,
+			occlusion_tex: texture(material.occlusion),
+			occlusion_strength: material.occlusion_strength,
+			emissive_tex: texture(material.emissive),
+			emissive_factor: material.emissive_factor.into(),
+			alpha_mode: match material.alpha_mode {
+				AlphaModeConfig::Opaque => AlphaMode::Opaque,
+				AlphaModeConfig::Mask => AlphaMode::Mask(material.alpha_cutoff),
+				AlphaModeConfig::Blend => AlphaMode::Blend,
+			},
+			double_sided: material.double_sided,
+			environment: None,
 		};
 
-		self.add_material(&mat).ok_or("Failed to add material")
+		Ok(self.add_material(&mat))
 	}
 
 	fn new_buffer(
 		&mut self,
 		buffer_type: u32,
 		data: &[u8],
-	) -> Result<(u32, &Rc<Buffer>), &'static str> {
-		let buffer = Buffer::new(&self.gl, buffer_type, data)?;
-		self.add_buffer(&buffer).ok_or("Failed to add buffer.")
+	) -> Result<Handle<Buffer<G>>, &'static str> {
+		let buffer = Buffer::new(self.gl.as_ref(), buffer_type, data)?;
+		Ok(self.add_buffer(&buffer))
 	}
 	fn new_mesh(
 		&mut self,
-		material: &Rc<Material>,
-		buffers: &[Rc<Buffer>],
+		material: Handle<Material<G>>,
+		buffers: &[Handle<Buffer<G>>],
 		index_view: &Option<BufferViewConfig>,
 		buffer_views: &[BufferViewConfig],
 		mode: u32,
-	) -> Result<(u32, &Rc<Mesh>), &'static str> {
+		geometry: Option<Geometry>,
+	) -> Result<Handle<Mesh<G>>, String> {
+		let material = self.materials.get(material)?;
+		let buffers = self.buffers.get_all(buffers)?;
 		let index_view = index_view.clone().map(|ref i| BufferView::new(i));
 		let attribute_buffer_views =
 			AttributeBufferViews::try_from(buffer_views)?;
-		let mesh = Mesh::new(
-			&self.gl,
+		let mut mesh = Mesh::new(
+			self.gl.as_ref(),
 			material,
-			buffers,
+			&buffers,
 			&index_view,
 			&attribute_buffer_views,
 			mode,
 		)?;
-		self.add_mesh(&mesh).ok_or("Failed to add mesh.")
+		// Accessor bounds save a pass over the positions when given.
+		mesh.bounds = buffer_views
+			.iter()
+			.find(|view| view.id == "POSITION")
+			.and_then(|view| match (&view.min, &view.max) {
+				(Some(min), Some(max)) => Aabb::from_min_max(min, max),
+				_ => None,
+			})
+			.or_else(|| geometry.as_ref().and_then(Geometry::bounds));
+		mesh.geometry = geometry.map(Rc::new);
+		Ok(self.add_mesh(&mesh))
 	}
 	fn new_model(
 		&mut self,
 		id: &str,
-		meshes: &[Rc<Mesh>],
-	) -> Result<&Rc<Model>, &'static str> {
-		let model = Model::new(meshes);
-		self.add_model(id, &model).ok_or("Failed to add model")
+		meshes: &[Handle<Mesh<G>>],
+	) -> Result<Handle<Model<G>>, String> {
+		let model = Model::new(&self.meshes.get_all(meshes)?);
+		Ok(self.add_model(id, &model))
+	}
+	fn new_primitive(
+		&mut self,
+		config: &PrimitiveConfig,
+	) -> Result<Handle<Model<G>>, String> {
+		let mesh = match *config {
+			PrimitiveConfig::Cube { .. } => generate_cube(self.gl.as_ref()),
+			PrimitiveConfig::UvSphere {
+				radius,
+				vertical_subdivisions,
+				horizontal_subdivisions,
+				..
+			} => {
+				if vertical_subdivisions < 2 || horizontal_subdivisions < 3 {
+					return Err(format!(
+						"Primitive '{}' needs at least 2 vertical and 3 \
+						 horizontal subdivisions",
+						config.id()
+					));
+				}
+				generate_uv_sphere(
+					self.gl.as_ref(),
+					radius,
+					vertical_subdivisions,
+					horizontal_subdivisions,
+				)
+			}
+		};
+		// Through add_material so the generated material is lit by the
+		// environment too.
+		let mut mesh = mesh.as_ref().clone();
+		let material = self.add_material(&mesh.material);
+		mesh.material = Rc::clone(self.materials.get(material)?);
+		let mesh = self.add_mesh(&mesh);
+		let mesh = Rc::clone(self.meshes.get(mesh)?);
+		Ok(self.add_model(config.id(), &Model::new(&[mesh])))
+	}
+}
+
+impl<G: GlBackend> GetResourceT<G> for Resources<G> {
+	fn get_camera(
+		&self,
+		handle: Handle<Camera>,
+	) -> Result<&Rc<Camera>, HandleError> {
+		self.cameras.get(handle)
+	}
+	fn get_string(
+		&self,
+		handle: Handle<String>,
+	) -> Result<&Rc<String>, HandleError> {
+		self.strings.get(handle)
+	}
+	fn get_shader(
+		&self,
+		handle: Handle<Shader<G>>,
+	) -> Result<&Rc<Shader<G>>, HandleError> {
+		self.shaders.get(handle)
+	}
+	fn get_program(
+		&self,
+		handle: Handle<Program<G>>,
+	) -> Result<&Rc<Program<G>>, HandleError> {
+		self.programs.get(handle)
+	}
+
+	fn get_texture(
+		&self,
+		handle: Handle<Texture<G>>,
+	) -> Result<&Rc<Texture<G>>, HandleError> {
+		self.textures.get(handle)
+	}
+	fn get_sampler(
+		&self,
+		handle: Handle<SamplerObject<G>>,
+	) -> Result<&Rc<SamplerObject<G>>, HandleError> {
+		self.samplers.get(handle)
+	}
+	fn get_material(
+		&self,
+		handle: Handle<Material<G>>,
+	) -> Result<&Rc<Material<G>>, HandleError> {
+		self.materials.get(handle)
+	}
+
+	fn get_buffer(
+		&self,
+		handle: Handle<Buffer<G>>,
+	) -> Result<&Rc<Buffer<G>>, HandleError> {
+		self.buffers.get(handle)
+	}
+	fn get_mesh(
+		&self,
+		handle: Handle<Mesh<G>>,
+	) -> Result<&Rc<Mesh<G>>, HandleError> {
+		self.meshes.get(handle)
+	}
+	fn get_model(
+		&self,
+		handle: Handle<Model<G>>,
+	) -> Result<&Rc<Model<G>>, HandleError> {
+		self.models.get(handle)
+	}
+}
+
+impl<G: GlBackend> RemoveResourceT<G> for Resources<G> {
+	fn remove_camera(
+		&mut self,
+		handle: Handle<Camera>,
+	) -> Result<(), HandleError> {
+		self.cameras.remove(handle).map(drop)
+	}
+	fn remove_string(
+		&mut self,
+		handle: Handle<String>,
+	) -> Result<(), HandleError> {
+		self.strings.remove(handle).map(drop)
+	}
+	fn remove_shader(
+		&mut self,
+		handle: Handle<Shader<G>>,
+	) -> Result<(), HandleError> {
+		let shader = self.shaders.remove(handle)?;
+		self.release(PendingRelease::Shader(shader));
+		Ok(())
+	}
+	fn remove_program(
+		&mut self,
+		handle: Handle<Program<G>>,
+	) -> Result<(), HandleError> {
+		let program = self.programs.remove(handle)?;
+		self.release(PendingRelease::Program(program));
+		Ok(())
+	}
+
+	fn remove_texture(
+		&mut self,
+		handle: Handle<Texture<G>>,
+	) -> Result<(), HandleError> {
+		let texture = self.textures.remove(handle)?;
+		self.release(PendingRelease::Texture(texture));
+		Ok(())
+	}
+	fn remove_sampler(
+		&mut self,
+		handle: Handle<SamplerObject<G>>,
+	) -> Result<(), HandleError> {
+		let sampler = self.samplers.remove(handle)?;
+		self.release(PendingRelease::Sampler(sampler));
+		Ok(())
+	}
+	fn remove_material(
+		&mut self,
+		handle: Handle<Material<G>>,
+	) -> Result<(), HandleError> {
+		self.materials.remove(handle)?;
+		self.release_unused();
+		Ok(())
+	}
+
+	fn remove_buffer(
+		&mut self,
+		handle: Handle<Buffer<G>>,
+	) -> Result<(), HandleError> {
+		let buffer = self.buffers.remove(handle)?;
+		self.release(PendingRelease::Buffer(buffer));
+		Ok(())
+	}
+	fn remove_mesh(
+		&mut self,
+		handle: Handle<Mesh<G>>,
+	) -> Result<(), HandleError> {
+		let mesh = self.meshes.remove(handle)?;
+		self.release(PendingRelease::Mesh(mesh));
+		Ok(())
+	}
+	fn remove_model(
+		&mut self,
+		handle: Handle<Model<G>>,
+	) -> Result<(), HandleError> {
+		let model = self.models.remove(handle)?;
+		self.release_model(model);
+		Ok(())
 	}
 }
 
@@ -373,13 +669,13 @@ pub async fn fetch_json(
 	mode: RequestMode,
 ) -> Result<JsValue, JsValue> {
 	let opts = {
-		let mut temp = RequestInit::new();
-		temp.method("GET");
-		temp.mode(mode);
+		let temp = RequestInit::new();
+		temp.set_method("GET");
+		temp.set_mode(mode);
 		temp
 	};
 
-	let request = Request::new_with_str_and_init(&path, &opts)?;
+	let request = Request::new_with_str_and_init(path, &opts)?;
 	request.headers().set("Accept", "application/json")?;
 	let window = web_sys::window().unwrap();
 	let response_value =
@@ -395,26 +691,175 @@ pub async fn fetch_json(
 	Ok(json)
 }
 
-impl Resources {
-	pub fn new(gl: Rc<WebGl2RenderingContext>) -> Self {
+/// Copies a loaded mesh's positions and triangles out of its buffers.
+fn mesh_geometry(
+	views: &[BufferViewConfig],
+	index_view: &Option<BufferViewConfig>,
+	buffers: &[BufferConfig],
+	mode: u32,
+) -> Option<Geometry> {
+	let position = views.iter().find(|view| view.id == "POSITION")?;
+	let positions = geometry::read_positions(
+		&buffers.get(position.buffer)?.0,
+		&BufferView::new(position),
+	)?;
+	let indices = match *index_view {
+		Some(ref view) => {
+			let offset = view.buffer_offset.unwrap_or(0) as usize;
+			Some(geometry::read_indices(
+				buffers.get(view.buffer)?.0.get(offset..)?,
+				view.component_type,
+				view.component_count as usize,
+			)?)
+		}
+		None => None,
+	};
+	Some(Geometry::new(positions, indices.as_deref(), mode))
+}
+
+/// Wraps bytes in a blob so an image element can load them.
+fn object_url(data: &[u8], mime_type: &str) -> Result<String, JsValue> {
+	let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(data));
+	let options = BlobPropertyBag::new();
+	options.set_type(mime_type);
+	let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+	Url::create_object_url_with_blob(&blob)
+}
+
+/// Object URLs that get revoked when this goes out of scope, so an early
+/// return can't leak the blobs behind them.
+#[derive(Default)]
+struct ObjectUrls(Vec<String>);
+impl Drop for ObjectUrls {
+	fn drop(&mut self) {
+		for url in self.0.iter() {
+			// Nothing left to do for a URL the browser won't revoke.
+			let _ = Url::revoke_object_url(url);
+		}
+	}
+}
+
+/// The image elements inside the element with id `wrapper_id`, in order.
+fn texture_wrapper_images(
+	wrapper_id: &str,
+) -> Result<Vec<HtmlImageElement>, String> {
+	let wrapper = web_sys::window()
+		.and_then(|window| window.document())
+		.and_then(|document| document.get_element_by_id(wrapper_id))
+		.ok_or_else(|| {
+			format!("No element with id '{}' to take images from", wrapper_id)
+		})?;
+	let children = wrapper.children();
+	(0..children.length())
+		.filter_map(|i| children.item(i))
+		.map(|child| {
+			child.dyn_into::<HtmlImageElement>().map_err(|_| {
+				format!(
+					"Element '{}' holds something other than images",
+					wrapper_id
+				)
+			})
+		})
+		.collect()
+}
+
+pub async fn fetch_bytes(
+	path: &str,
+	mode: RequestMode,
+) -> Result<Vec<u8>, JsValue> {
+	let opts = {
+		let temp = RequestInit::new();
+		temp.set_method("GET");
+		temp.set_mode(mode);
+		temp
+	};
+
+	let request = Request::new_with_str_and_init(path, &opts)?;
+	let window = web_sys::window().unwrap();
+	let response_value =
+		JsFuture::from(window.fetch_with_request(&request)).await?;
+
+	let response: Response = response_value.dyn_into().unwrap();
+	if !response.ok() {
+		return Err(JsValue::from_str("Failed to fetch bytes"));
+	}
+
+	let array_buffer = JsFuture::from(response.array_buffer()?).await?;
+
+	Ok(js_sys::Uint8Array::new(&array_buffer).to_vec())
+}
+
+impl<G: GlBackend> Resources<G> {
+	pub fn new(gl: Rc<G>) -> Self {
 		Self {
+			max_anisotropy: texture::max_anisotropy(gl.as_ref()),
 			gl,
-			cameras: HashMap::new(),
-			strings: HashMap::new(),
-			shaders: HashMap::new(),
-			programs: HashMap::new(),
-			textures: HashMap::new(),
-			next_sampler_id: 0,
-			samplers: HashMap::new(),
-			next_buffer_id: 0,
-			buffers: HashMap::new(),
-			next_material_id: 0,
-			materials: HashMap::new(),
-			next_mesh_id: 0,
-			meshes: HashMap::new(),
-			models: HashMap::new(),
+			cameras: Pool::new("camera"),
+			strings: Pool::new("string"),
+			shaders: Pool::new("shader"),
+			programs: Pool::new("program"),
+			textures: Pool::new("texture"),
+			samplers: Pool::new("sampler"),
+			materials: Pool::new("material"),
+			buffers: Pool::new("buffer"),
+			meshes: Pool::new("mesh"),
+			models: Pool::new("model"),
+			environment: None,
+			pending_release: Vec::new(),
 		}
 	}
+	// Deletes the GL objects of removed resources that are no longer
+	// referenced and returns how many were released. Releasing a mesh can
+	// free its buffers, so sweep until nothing changes.
+	pub fn release_unused(&mut self) -> usize {
+		let gl = self.gl.as_ref();
+		let mut released = 0;
+		loop {
+			let pending = self.pending_release.len();
+			self.pending_release.retain(|resource| {
+				if resource.is_unused() {
+					resource.release(gl);
+					false
+				} else {
+					true
+				}
+			});
+			let count = pending - self.pending_release.len();
+			if count == 0 {
+				return released;
+			}
+			released += count;
+		}
+	}
+	fn release(&mut self, resource: PendingRelease<G>) {
+		self.pending_release.push(resource);
+		self.release_unused();
+	}
+	// Removes what `model` was loaded with. Handles another load already
+	// replaced, like its named textures, are stale and skipped.
+	fn release_model(&mut self, model: Rc<Model<G>>) {
+		let owned = &model.resources;
+		for handle in owned.meshes.iter() {
+			if let Ok(mesh) = self.meshes.remove(*handle) {
+				self.pending_release.push(PendingRelease::Mesh(mesh));
+			}
+		}
+		for handle in owned.buffers.iter() {
+			if let Ok(buffer) = self.buffers.remove(*handle) {
+				self.pending_release.push(PendingRelease::Buffer(buffer));
+			}
+		}
+		for handle in owned.materials.iter() {
+			let _ = self.materials.remove(*handle);
+		}
+		for handle in owned.textures.iter() {
+			if let Ok(texture) = self.textures.remove(*handle) {
+				self.pending_release.push(PendingRelease::Texture(texture));
+			}
+		}
+		drop(model);
+		self.release_unused();
+	}
 	pub async fn load_texts(
 		&mut self,
 		sources: &[&str],
@@ -422,11 +867,13 @@ impl Resources {
 		let mut futures = Vec::with_capacity(sources.len());
 		let mut texts = Vec::with_capacity(sources.len());
 		for &source in sources.iter() {
-			if !self.strings.contains_key(source) {
+			if let Ok(text) = self.strings.get_by_name(source) {
+				texts.push(Rc::clone(text));
+			} else {
 				let opts = {
-					let mut temp = RequestInit::new();
-					temp.method("GET");
-					temp.mode(RequestMode::SameOrigin);
+					let temp = RequestInit::new();
+					temp.set_method("GET");
+					temp.set_mode(RequestMode::SameOrigin);
 					temp
 				};
 				let uri = format!("assets/{}", source);
@@ -436,8 +883,6 @@ impl Resources {
 				let response_value =
 					JsFuture::from(window.fetch_with_request(&request));
 				futures.push((source, response_value));
-			} else {
-				texts.push(self.strings.get(source).unwrap().clone());
 			}
 		}
 		for (source, future) in futures.into_iter() {
@@ -446,14 +891,15 @@ impl Resources {
 			let response: Response = response_value.dyn_into().unwrap();
 			let text =
 				JsFuture::from(response.text()?).await?.as_string().unwrap();
-			texts.push(Rc::clone(self.new_string(source, &text).unwrap()));
+			let handle = self.new_string(source, &text);
+			texts.push(Rc::clone(self.strings.get(handle).unwrap()));
 		}
 		Ok(texts)
 	}
 	pub async fn load_shaders(
 		&mut self,
 		configs: &[ShaderConfig],
-	) -> Result<Vec<Rc<Shader>>, &'static str> {
+	) -> Result<Vec<Handle<Shader<G>>>, &'static str> {
 		let sources = configs
 			.iter()
 			.map(|config| config.path.as_str())
@@ -466,20 +912,21 @@ impl Resources {
 
 		let mut shaders = Vec::with_capacity(configs.len());
 		for (i, config) in configs.iter().enumerate() {
-			shaders.push(Rc::clone(self.new_shader(
+			shaders.push(self.new_shader(
 				&config.id,
 				&config.kind,
 				&shader_sources[i],
 				&config.attributes,
 				&config.uniforms,
-			)?));
+			)?);
 		}
 		Ok(shaders)
 	}
+	#[allow(clippy::type_complexity)]
 	pub fn load_programs(
 		&mut self,
 		configs: &[ProgramConfig],
-	) -> Result<(Vec<Rc<Program>>, Vec<ShaderWarning>), String> {
+	) -> Result<(Vec<Handle<Program<G>>>, Vec<ShaderWarning>), String> {
 		let mut programs = Vec::with_capacity(configs.len());
 		let mut warnings = Vec::new();
 		for config in configs.iter() {
@@ -490,105 +937,209 @@ impl Resources {
 					&config.fragment_id,
 				)?;
 			warnings.append(&mut program_warnings);
-			programs.push(Rc::clone(program));
+			programs.push(program);
 		}
 		Ok((programs, warnings))
 	}
 	pub fn load_models(
 		&mut self,
 		models: &[ModelConfig],
-	) -> Result<Vec<Rc<Model>>, &'static str> {
+	) -> Result<Vec<Handle<Model<G>>>, String> {
 		let mut ret = Vec::with_capacity(models.len());
 		for model_data in models.iter() {
-			let mut samplers = Vec::with_capacity(model_data.samplers.len());
-			for sampler in model_data.samplers.iter() {
-				let (_sampler_id, sampler) = self.add_sampler(sampler).unwrap();
-				samplers.push(Rc::clone(sampler));
-			}
-			let collection_id = format!("{}-images", model_data.id);
-			let collection = web_sys::window()
-				.unwrap()
-				.document()
-				.unwrap()
-				.get_element_by_id(&collection_id)
-				.unwrap()
-				.children();
-			let texture_count = collection.length();
-			let mut image_elements = Vec::new();
-			for i in 0..texture_count {
-				let image_element = collection
-					.item(i)
-					.unwrap()
-					.dyn_into::<HtmlImageElement>()
-					.unwrap();
-				image_elements.push(image_element);
-			}
+			let image_elements = if model_data.textures.is_empty() {
+				Vec::new()
+			} else {
+				texture_wrapper_images(&model_data.texture_wrapper_id)?
+			};
+			let images = image_elements
+				.iter()
+				.map(TextureData::Image)
+				.collect::<Vec<_>>();
+			ret.push(self.load_model(model_data, &images)?);
+		}
+		Ok(ret)
+	}
+	/// Fetches a .gltf or .glb file relative to the assets directory along
+	/// with the buffers and images it references and builds a model from it.
+	pub async fn load_gltf(
+		&mut self,
+		id: &str,
+		uri: &str,
+	) -> Result<Handle<Model<G>>, JsValue> {
+		let path = format!("assets/{}", uri);
+		let base_path = gltf::base_path(&path);
+		let data = fetch_bytes(&path, RequestMode::SameOrigin).await?;
+		let (gltf, mut bin) = Gltf::from_bytes(&data)?;
 
-			let mut textures = Vec::with_capacity(texture_count as usize);
-			for texture in model_data.textures.iter() {
-				let image_element = &image_elements[texture.source];
-				let texture_id = image_element.id();
-				let sampler = &samplers[texture.sampler];
-				let texture =
-					self.new_texture(&texture_id, image_element, 0, sampler)?;
-				textures.push(Rc::clone(texture));
-			}
+		let mut buffers = Vec::with_capacity(gltf.document.buffers.len());
+		for (i, buffer) in gltf.document.buffers.iter().enumerate() {
+			let data = match buffer.uri {
+				Some(ref uri) if data_uri::is_data_uri(uri) => {
+					data_uri::decode(uri).ok_or(GltfError::InvalidDataUri(i))?
+				}
+				Some(ref uri) => {
+					let buffer_path = format!("{}{}", base_path, uri);
+					fetch_bytes(&buffer_path, RequestMode::SameOrigin).await?
+				}
+				None => gltf.embedded_buffer(i, &mut bin)?,
+			};
+			buffers.push(data);
+		}
+		gltf.validate(&buffers)?;
 
-			let mut materials = Vec::with_capacity(model_data.materials.len());
-			for material in model_data.materials.iter() {
-				let (_material_id, material) =
-					self.new_material(material, &textures)?;
-				materials.push(Rc::clone(material));
-			}
+		let mut object_urls = ObjectUrls::default();
+		let mut image_futures = Vec::with_capacity(gltf.document.images.len());
+		for (i, image) in gltf.document.images.iter().enumerate() {
+			let source = match (&image.uri, image.buffer_view) {
+				(Some(uri), _) if data_uri::is_data_uri(uri) => uri.clone(),
+				(Some(uri), _) => format!("{}{}", base_path, uri),
+				(None, Some(view)) => {
+					let data = gltf.buffer_view_data(view, &buffers)?;
+					let mime_type = image.mime_type.as_deref().unwrap_or("");
+					let url = object_url(data, mime_type)?;
+					object_urls.0.push(url.clone());
+					url
+				}
+				(None, None) => {
+					return Err(GltfError::UnsupportedImage(i).into())
+				}
+			};
+			image_futures.push(ImageFuture::new(&source));
+		}
+		let images = join_all(image_futures).await;
+		drop(object_urls);
+		let mut image_elements = Vec::with_capacity(images.len());
+		for image in images.into_iter() {
+			let image = image
+				.map_err(|_e| JsValue::from_str("Failed to fetch image"))?;
+			image_elements.push(image);
+		}
+		let images = image_elements
+			.iter()
+			.map(TextureData::Image)
+			.collect::<Vec<_>>();
 
-			let mut buffers = Vec::with_capacity(model_data.buffers.len() + 1);
-			for buffer_data in model_data.buffers.iter() {
-				let (_buffer_id, buffer) = self.new_buffer(
-					WebGl2RenderingContext::ARRAY_BUFFER,
-					&buffer_data.0,
-				)?;
-				buffers.push(Rc::clone(buffer));
-			}
+		let model_data = gltf.model_config(id, buffers)?;
+		Ok(self.load_model(&model_data, &images)?)
+	}
+	/// Builds a model from its config, with `images` the pixels of the
+	/// sources its textures reference. Images can be elements or pixels
+	/// decoded or generated without the DOM.
+	pub fn load_model(
+		&mut self,
+		model_data: &ModelConfig,
+		images: &[TextureData],
+	) -> Result<Handle<Model<G>>, String> {
+		model_data.validate()?;
+		let samplers = model_data
+			.samplers
+			.iter()
+			.map(|sampler| self.new_sampler(sampler))
+			.collect::<Result<Vec<_>, _>>()?;
 
-			let mut meshes = Vec::with_capacity(model_data.meshes.len());
-			for mesh in model_data.meshes.iter() {
-				// Clean this up
-				let index_view = match mesh.index_view {
-					Some(ref i) => {
-						let offset = i.buffer_offset.unwrap_or(0) as usize;
-						let index_buffer_size = offset + i.length;
-						let slice = &model_data.buffers[i.buffer].0
-							[offset..index_buffer_size];
-						let (_index_buffer_id, index_buffer) = self
-							.new_buffer(
-								WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
-								slice,
-							)?;
-						buffers.push(Rc::clone(index_buffer));
-						let mut view = i.clone();
-						view.buffer = buffers.len() - 1;
-						Some(view)
-					}
-					None => None,
-				};
-				let material = if let Some(material_index) = mesh.material {
-					materials[material_index as usize].clone()
-				} else {
-					Rc::new(Material::default())
-				};
-				let (_mesh_id, mesh) = self.new_mesh(
-					&material,
-					&buffers,
-					&index_view,
-					&mesh.buffer_views,
-					WebGl2RenderingContext::TRIANGLES,
-				)?;
-				meshes.push(Rc::clone(mesh));
-			}
-			let model = self.new_model(&model_data.id, &meshes)?;
-			ret.push(Rc::clone(model));
+		let mut textures = Vec::with_capacity(model_data.textures.len());
+		for (i, texture) in model_data.textures.iter().enumerate() {
+			let image = images.get(texture.source).ok_or_else(|| {
+				format!(
+					"Texture {} of model '{}' references missing image {}",
+					i, model_data.id, texture.source
+				)
+			})?;
+			let texture_id = format!("{}-texture-{}", model_data.id, i);
+			let sampler = samplers[texture.sampler];
+			textures.push(self.new_texture(
+				&texture_id,
+				*image,
+				texture.format,
+				sampler,
+			)?);
 		}
-		Ok(ret)
+
+		let mut materials = Vec::with_capacity(model_data.materials.len());
+		for material in model_data.materials.iter() {
+			materials.push(self.new_material(material, &textures)?);
+		}
+
+		let mut buffers = Vec::with_capacity(model_data.buffers.len() + 1);
+		for buffer_data in model_data.buffers.iter() {
+			buffers.push(self.new_buffer(
+				WebGl2RenderingContext::ARRAY_BUFFER,
+				&buffer_data.0,
+			)?);
+		}
+
+		let mut meshes = Vec::with_capacity(model_data.meshes.len());
+		for mesh in model_data.meshes.iter() {
+			// Clean this up
+			let index_view = match mesh.index_view {
+				Some(ref i) => {
+					let offset = i.buffer_offset.unwrap_or(0) as usize;
+					let index_buffer_size = offset + i.length;
+					let slice = &model_data.buffers[i.buffer].0
+						[offset..index_buffer_size];
+					buffers.push(self.new_buffer(
+						WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
+						slice,
+					)?);
+					let mut view = i.clone();
+					view.buffer = buffers.len() - 1;
+					Some(view)
+				}
+				None => None,
+			};
+			let material = if let Some(material_index) = mesh.material {
+				materials[material_index as usize]
+			} else {
+				let material = self.add_material(&Material::default());
+				materials.push(material);
+				material
+			};
+			let geometry = mesh_geometry(
+				&mesh.buffer_views,
+				&mesh.index_view,
+				&model_data.buffers,
+				mesh.mode,
+			);
+			meshes.push(self.new_mesh(
+				material,
+				&buffers,
+				&index_view,
+				&mesh.buffer_views,
+				mesh.mode,
+				geometry,
+			)?);
+		}
+		let mut model = Model::new(&self.meshes.get_all(&meshes)?);
+		model.lights =
+			model_data.lights.iter().map(Light::from_config).collect();
+		model.resources = ModelResources {
+			textures,
+			materials,
+			buffers,
+			meshes,
+		};
+		Ok(self.add_model(&model_data.id, &model))
+	}
+	/// Fetches an equirectangular .hdr relative to the assets directory and
+	/// prefilters it into the environment later materials are lit by.
+	pub async fn load_environment(
+		&mut self,
+		config: &EnvironmentConfig,
+	) -> Result<(), String> {
+		let path = format!("assets/{}", config.uri);
+		let data = fetch_bytes(&path, RequestMode::SameOrigin)
+			.await
+			.map_err(|_| format!("Failed to fetch environment '{}'", path))?;
+		let image = HdrImage::parse(&data)
+			.map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+		let maps = EnvironmentMaps::new(&image, config.size as usize);
+		let environment =
+			Environment::new(self.gl.as_ref(), &maps, config.intensity)?;
+		if let Some(old) = self.environment.replace(Rc::new(environment)) {
+			self.release(PendingRelease::Environment(old));
+		}
+		Ok(())
 	}
 	pub fn load_cameras(
 		&mut self,
@@ -600,17 +1151,44 @@ impl Resources {
 			self.new_camera(config, width, height);
 		}
 	}
+	// `width` and `height` give the cameras their initial aspect ratio.
 	pub async fn load_scene(
 		&mut self,
 		config: &SceneConfig,
-	) -> Result<Vec<ShaderWarning>, &'static str> {
+		width: u32,
+		height: u32,
+	) -> Result<(SceneGraph<G>, Vec<ShaderWarning>), String> {
+		config.validate()?;
 		self.load_shaders(&config.shaders).await?;
-		let warnings = match self.load_programs(&config.programs) {
-			Ok((_, warnings)) => warnings,
-			Err(_e) => return Err("Failed to build shader programs"),
-		};
-		self.load_cameras(&config.cameras, 800, 600);
-		Ok(warnings)
+		let (_, warnings) = self
+			.load_programs(&config.programs)
+			.map_err(|e| format!("Failed to build shader programs: {}", e))?;
+		self.load_cameras(&config.cameras, width, height);
+		if let Some(ref environment) = config.environment {
+			self.load_environment(environment).await?;
+		}
+		for model in config.models.iter() {
+			self.load_gltf(&model.id, &model.uri).await.map_err(|e| {
+				format!(
+					"Failed to load model '{}': {}",
+					model.id,
+					e.as_string().unwrap_or_else(|| format!("{:?}", e))
+				)
+			})?;
+		}
+		let scene_graph = self.build_scene(config)?;
+		Ok((scene_graph, warnings))
+	}
+	// The part of load_scene that needs no fetching: generates the
+	// primitives and builds the node hierarchy over the loaded models.
+	pub fn build_scene(
+		&mut self,
+		config: &SceneConfig,
+	) -> Result<SceneGraph<G>, String> {
+		for primitive in config.primitives.iter() {
+			self.new_primitive(primitive)?;
+		}
+		SceneGraph::from_config(&config.nodes, &config.lights, &self.models)
 	}
 }
 
@@ -635,3 +1213,605 @@ impl Resources {
 
 // 	Ok(text)
 // }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::recording::{
+		GlCall,
+		RecordingBackend,
+	};
+	use crate::bounds::Frustum;
+	use crate::model::Drawable;
+	use crate::picking::Ray;
+	use crate::program::attribute_locations;
+
+	fn resources() -> (Rc<RecordingBackend>, Resources<RecordingBackend>) {
+		let gl = Rc::new(RecordingBackend::new());
+		(Rc::clone(&gl), Resources::new(gl))
+	}
+
+	#[test]
+	fn removing_unshared_buffer_deletes_it() {
+		let (gl, mut resources) = resources();
+		let buffer = resources
+			.new_buffer(WebGl2RenderingContext::ARRAY_BUFFER, &[0; 36])
+			.unwrap();
+		let handle = resources.get_buffer(buffer).unwrap().handle;
+
+		assert!(resources.remove_buffer(buffer).is_ok());
+		assert!(resources.get_buffer(buffer).is_err());
+		assert!(resources.remove_buffer(buffer).is_err());
+		assert!(gl.calls().contains(&GlCall::DeleteBuffer(Some(handle))));
+	}
+	#[test]
+	fn shared_buffer_is_deleted_with_its_last_mesh() {
+		let (gl, mut resources) = resources();
+		let buffer = resources
+			.new_buffer(WebGl2RenderingContext::ARRAY_BUFFER, &[0; 36])
+			.unwrap();
+		let handle = resources.get_buffer(buffer).unwrap().handle;
+		let material = resources.add_material(&Material::default());
+		let mesh = resources
+			.new_mesh(
+				material,
+				&[buffer],
+				&None,
+				&[BufferViewConfig::test_positions(3)],
+				WebGl2RenderingContext::TRIANGLES,
+				None,
+			)
+			.unwrap();
+
+		resources.remove_buffer(buffer).unwrap();
+		assert_eq!(gl.count(|c| matches!(c, GlCall::DeleteBuffer(_))), 0);
+
+		resources.remove_mesh(mesh).unwrap();
+		assert_eq!(gl.count(|c| matches!(c, GlCall::DeleteVertexArray(_))), 1);
+		assert_eq!(gl.count(|c| *c == GlCall::DeleteBuffer(Some(handle))), 1);
+	}
+	#[test]
+	fn held_program_waits_for_release_sweep() {
+		let (gl, mut resources) = resources();
+		let vertex = resources
+			.new_shader("vert", "vertex", "", &[], &[])
+			.unwrap();
+		let fragment = resources
+			.new_shader("frag", "fragment", "", &[], &[])
+			.unwrap();
+		let (handle, _) =
+			resources.new_program("program", vertex, fragment).unwrap();
+		let program = Rc::clone(resources.get_program(handle).unwrap());
+
+		resources.remove_program(handle).unwrap();
+		assert_eq!(resources.release_unused(), 0);
+		drop(program);
+		assert_eq!(resources.release_unused(), 1);
+		assert_eq!(gl.count(|c| matches!(c, GlCall::DeleteProgram(_))), 1);
+	}
+	#[test]
+	fn stale_mesh_handle_is_an_error_not_an_alias() {
+		let (_gl, mut resources) = resources();
+		let buffer = resources
+			.new_buffer(WebGl2RenderingContext::ARRAY_BUFFER, &[0; 36])
+			.unwrap();
+		resources.remove_buffer(buffer).unwrap();
+		let reused = resources
+			.new_buffer(WebGl2RenderingContext::ARRAY_BUFFER, &[0; 36])
+			.unwrap();
+		assert_eq!(buffer.index(), reused.index());
+
+		let material = resources.add_material(&Material::default());
+		let result = resources.new_mesh(
+			material,
+			&[buffer],
+			&None,
+			&[BufferViewConfig::test_positions(3)],
+			WebGl2RenderingContext::TRIANGLES,
+			None,
+		);
+		assert_eq!(
+			result.unwrap_err(),
+			"buffer handle 0v0 is stale, the buffer was removed"
+		);
+	}
+	#[test]
+	fn replacing_a_named_shader_releases_the_old_one() {
+		let (gl, mut resources) = resources();
+		let old = resources
+			.new_shader("vert", "vertex", "", &[], &[])
+			.unwrap();
+		let new = resources
+			.new_shader("vert", "vertex", "", &[], &[])
+			.unwrap();
+
+		assert!(resources.get_shader(old).is_err());
+		assert_eq!(resources.shaders.find("vert"), Ok(new));
+		assert_eq!(gl.count(|c| matches!(c, GlCall::DeleteShader(_))), 1);
+	}
+	#[test]
+	fn models_keep_their_primitive_mode() {
+		let (_gl, mut resources) = resources();
+		// A unit quad as a strip of two triangles.
+		let positions: Vec<u8> = [
+			0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0,
+		]
+		.iter()
+		.flat_map(|x| x.to_le_bytes())
+		.collect();
+		let config: ModelConfig = serde_json::from_value(serde_json::json!({
+			"id": "strip",
+			"buffers": [positions],
+			"meshes": [{
+				"index_view": null,
+				"buffer_views": [{
+					"id": "POSITION", "buffer": 0, "length": 48,
+					"buffer_offset": null, "offset": null, "stride": null,
+					"component_size": 3, "component_count": 4,
+					"component_type": WebGl2RenderingContext::FLOAT
+				}],
+				"material": null,
+				"mode": WebGl2RenderingContext::TRIANGLE_STRIP
+			}],
+			"materials": [],
+			"texture_wrapper_id": "",
+			"samplers": [],
+			"textures": []
+		}))
+		.unwrap();
+		let model = resources.load_model(&config, &[]).unwrap();
+		let mesh = &resources.get_model(model).unwrap().meshes[0];
+		assert_eq!(mesh.mode, WebGl2RenderingContext::TRIANGLE_STRIP);
+		let geometry = mesh.geometry.as_ref().unwrap();
+		assert_eq!(geometry.triangles, vec![[0, 1, 2], [2, 1, 3]]);
+	}
+	#[test]
+	fn gltf_attributes_bind_normalized_colors_and_draw_from_zero() {
+		let (gl, mut resources) = resources();
+		// Twelve bytes of something else, a triangle and its byte colors.
+		let mut data = vec![0; 48];
+		data.extend_from_slice(&[255; 12]);
+		let config: ModelConfig = serde_json::from_value(serde_json::json!({
+			"id": "triangle",
+			"buffers": [data],
+			"meshes": [{
+				"index_view": null,
+				"buffer_views": [{
+					"id": "POSITION", "buffer": 0, "length": 36,
+					"buffer_offset": 12, "offset": null, "stride": null,
+					"component_size": 3, "component_count": 3,
+					"component_type": WebGl2RenderingContext::FLOAT
+				}, {
+					"id": "COLOR_0", "buffer": 0, "length": 12,
+					"buffer_offset": 48, "offset": null, "stride": null,
+					"component_size": 4, "component_count": 3,
+					"component_type": WebGl2RenderingContext::UNSIGNED_BYTE,
+					"normalized": true
+				}],
+				"material": null
+			}],
+			"materials": [],
+			"texture_wrapper_id": "",
+			"samplers": [],
+			"textures": []
+		}))
+		.unwrap();
+		let model = resources.load_model(&config, &[]).unwrap();
+		let (vertex, fragment) = (
+			resources
+				.new_shader("vert", "vertex", "", &[], &[])
+				.unwrap(),
+			resources
+				.new_shader("frag", "fragment", "", &[], &[])
+				.unwrap(),
+		);
+		let (program, _) =
+			resources.new_program("program", vertex, fragment).unwrap();
+		let program = resources.programs.get(program).unwrap();
+		resources
+			.get_model(model)
+			.unwrap()
+			.draw(gl.as_ref(), program);
+
+		let calls = gl.calls();
+		assert!(calls.contains(&GlCall::VertexAttribPointer {
+			index: attribute_locations::COLOR_LOCATION,
+			size: 4,
+			type_: WebGl2RenderingContext::UNSIGNED_BYTE,
+			normalized: true,
+			stride: 0,
+			offset: 48,
+		}));
+		assert!(calls.contains(&GlCall::DrawArrays {
+			mode: WebGl2RenderingContext::TRIANGLES,
+			first: 0,
+			count: 3,
+		}));
+	}
+	#[test]
+	fn removing_a_model_deletes_its_gl_objects() {
+		let (gl, mut resources) = resources();
+		// One triangle, its three u16 indices after the positions.
+		let mut data = vec![0; 36];
+		data.extend_from_slice(&[0, 0, 1, 0, 2, 0]);
+		let config: ModelConfig = serde_json::from_value(serde_json::json!({
+			"id": "triangle",
+			"buffers": [data],
+			"meshes": [{
+				"index_view": {
+					"id": "INDEX", "buffer": 0, "length": 6,
+					"buffer_offset": 36, "offset": null, "stride": null,
+					"component_size": 1, "component_count": 3,
+					"component_type": WebGl2RenderingContext::UNSIGNED_SHORT
+				},
+				"buffer_views": [{
+					"id": "POSITION", "buffer": 0, "length": 36,
+					"buffer_offset": null, "offset": null, "stride": null,
+					"component_size": 3, "component_count": 3,
+					"component_type": WebGl2RenderingContext::FLOAT
+				}],
+				"material": null
+			}],
+			"materials": [],
+			"texture_wrapper_id": "",
+			"samplers": [],
+			"textures": []
+		}))
+		.unwrap();
+		let deletes = |gl: &RecordingBackend| {
+			(
+				gl.count(|c| matches!(c, GlCall::DeleteBuffer(_))),
+				gl.count(|c| matches!(c, GlCall::DeleteVertexArray(_))),
+			)
+		};
+
+		// Loading under the same id replaces the first copy.
+		resources.load_model(&config, &[]).unwrap();
+		let model = resources.load_model(&config, &[]).unwrap();
+		assert_eq!(deletes(&gl), (2, 1));
+		assert_eq!(resources.buffers.len(), 2);
+		assert_eq!(resources.meshes.len(), 1);
+
+		resources.remove_model(model).unwrap();
+		assert_eq!(deletes(&gl), (4, 2));
+		assert!(resources.buffers.is_empty());
+		assert!(resources.meshes.is_empty());
+		assert!(resources.materials.is_empty());
+	}
+	#[test]
+	fn models_load_textures_from_raw_pixels() {
+		let (gl, mut resources) = resources();
+		let config: ModelConfig = serde_json::from_str(
+			r#"{
+				"id": "m",
+				"buffers": [],
+				"meshes": [],
+				"materials": [{
+					"id": "m-material",
+					"base_color": { "index": 0, "tex_coord": 0 },
+					"occlusion": { "index": 1, "tex_coord": 0 }
+				}],
+				"texture_wrapper_id": "missing",
+				"samplers": [{
+					"mag_filter": 9729, "min_filter": 9729,
+					"wrap_s": 10497, "wrap_t": 10497
+				}],
+				"textures": [
+					{
+						"source": 0, "sampler": 0,
+						"format": { "channels": "rgba", "color_space": "srgb" }
+					},
+					{ "source": 1, "sampler": 0, "format": { "channels": "r" } }
+				]
+			}"#,
+		)
+		.unwrap();
+		let color = [255; 4 * 4 * 4];
+		let occlusion = [1.0; 4 * 4];
+		let images = [
+			TextureData::U8 {
+				width: 4,
+				height: 4,
+				pixels: &color,
+			},
+			TextureData::F32 {
+				width: 4,
+				height: 4,
+				pixels: &occlusion,
+			},
+		];
+		resources.load_model(&config, &images).unwrap();
+		let internal_formats = gl
+			.calls()
+			.into_iter()
+			.filter_map(|call| match call {
+				GlCall::TexImage2DPixels {
+					internal_format, ..
+				} => Some(internal_format as u32),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			internal_formats,
+			vec![
+				WebGl2RenderingContext::SRGB8_ALPHA8,
+				WebGl2RenderingContext::R16F
+			]
+		);
+		assert!(resources.textures.find("m-texture-1").is_ok());
+		// Both textures share one sampler object, as do later equal samplers.
+		let sampler = resources.new_sampler(&config.samplers[0]).unwrap();
+		assert_eq!(resources.samplers.len(), 1);
+		assert_eq!(
+			gl.count(|call| matches!(call, GlCall::CreateSampler(_))),
+			1
+		);
+		assert!(resources.remove_sampler(sampler).is_ok());
+		assert_eq!(
+			gl.count(|call| matches!(call, GlCall::DeleteSampler(_))),
+			0
+		);
+
+		// A source past the images given.
+		let error = resources.load_model(&config, &images[..1]).unwrap_err();
+		assert_eq!(error, "Texture 1 of model 'm' references missing image 1");
+	}
+	#[test]
+	fn materials_and_primitives_are_lit_by_the_environment() {
+		let (gl, mut resources) = resources();
+		let image = HdrImage {
+			width: 16,
+			height: 8,
+			pixels: vec![[1.0; 3]; 16 * 8],
+		};
+		let maps = EnvironmentMaps::new(&image, 4);
+		let environment = Environment::new(gl.as_ref(), &maps, 1.0).unwrap();
+		let environment = Rc::new(environment);
+		resources.environment = Some(Rc::clone(&environment));
+
+		let material = resources.add_material(&Material::default());
+		let material = resources.get_material(material).unwrap();
+		assert!(material
+			.environment
+			.as_ref()
+			.is_some_and(|e| Rc::ptr_eq(e, &environment)));
+
+		let config = PrimitiveConfig::Cube {
+			id: String::from("box"),
+		};
+		let model = resources.new_primitive(&config).unwrap();
+		let mesh = &resources.get_model(model).unwrap().meshes[0];
+		assert!(mesh.material.environment.is_some());
+	}
+	#[test]
+	fn scene_config_builds_graph_with_models_and_lights() {
+		let config: SceneConfig = serde_json::from_str(
+			r#"{
+				"current_camera": 0,
+				"cameras": [],
+				"shaders": [],
+				"programs": [],
+				"primitives": [
+					{ "kind": "cube", "id": "box" },
+					{ "kind": "uv_sphere", "id": "ball", "radius": 0.5 }
+				],
+				"lights": [
+					{ "kind": "point", "id": "lamp", "position": [0, 1, 0] },
+					{ "kind": "directional", "id": "sun", "direction": [0, -2, 0] }
+				],
+				"nodes": [
+					{
+						"id": "parent",
+						"translation": [2, 0, 0],
+						"model": "box",
+						"children": [
+							{ "translation": [0, 3, 0], "light": "lamp" },
+							{ "scale": [2, 2, 2], "model": "ball" }
+						]
+					}
+				]
+			}"#,
+		)
+		.unwrap();
+		let (_gl, mut resources) = resources();
+		let graph = resources.build_scene(&config).unwrap();
+
+		assert_eq!(resources.models.len(), 2);
+		let kinds = graph
+			.root
+			.descendants()
+			.map(|node| {
+				let data = node.borrow();
+				let data = data.ref_node_type_data();
+				(data.is_model(), data.is_light())
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			kinds,
+			vec![
+				(false, false),
+				(true, false),
+				(false, true),
+				(true, false),
+				(false, true),
+			]
+		);
+		let ids = graph
+			.root
+			.descendants()
+			.map(|node| node.borrow().id().map(String::from))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			ids,
+			vec![None, Some(String::from("parent")), None, None, None]
+		);
+
+		let lights = graph.lights();
+		match lights[0] {
+			Light::Point(light) => {
+				assert_eq!(light.position, cgmath::Point3::new(2.0, 4.0, 0.0))
+			}
+			_ => panic!("expected the lamp first"),
+		}
+		match lights[1] {
+			Light::Directional(light) => {
+				assert_eq!(light.dir, cgmath::Vector3::new(0.0, -1.0, 0.0))
+			}
+			_ => panic!("expected the unplaced sun under the root"),
+		}
+	}
+	#[test]
+	fn nodes_outside_the_frustum_are_not_drawn() {
+		let config: SceneConfig = serde_json::from_str(
+			r#"{
+				"current_camera": 0,
+				"cameras": [],
+				"shaders": [],
+				"programs": [],
+				"primitives": [{ "kind": "cube", "id": "box" }],
+				"nodes": [
+					{ "model": "box" },
+					{ "translation": [0, 0, 50], "model": "box" },
+					{
+						"translation": [-30, 0, 0],
+						"children": [{ "translation": [28, 0, 0], "model": "box" }]
+					}
+				]
+			}"#,
+		)
+		.unwrap();
+		let (gl, mut resources) = resources();
+		let graph = resources.build_scene(&config).unwrap();
+		let (vertex, fragment) = (
+			resources
+				.new_shader("vert", "vertex", "", &[], &[])
+				.unwrap(),
+			resources
+				.new_shader("frag", "fragment", "", &[], &[])
+				.unwrap(),
+		);
+		let (program, _) =
+			resources.new_program("program", vertex, fragment).unwrap();
+		let program = resources.programs.get(program).unwrap();
+
+		let view = cgmath::Matrix4::look_at_rh(
+			cgmath::Point3::new(0.0, 0.0, 10.0),
+			cgmath::Point3::new(0.0, 0.0, 0.0),
+			cgmath::Vector3::unit_y(),
+		);
+		let projection =
+			cgmath::perspective(cgmath::Deg(45.0), 1.0, 0.1, 100.0);
+		let frustum = Frustum::from_matrix(&(projection * view));
+
+		let drawn = graph.draw_visible(gl.as_ref(), program, &frustum);
+		assert_eq!(drawn, 2);
+		assert_eq!(
+			gl.count(|call| matches!(call, GlCall::DrawElements { .. })),
+			2
+		);
+	}
+	#[test]
+	fn picking_finds_the_closest_triangle() {
+		let config: SceneConfig = serde_json::from_str(
+			r#"{
+				"current_camera": 0,
+				"cameras": [],
+				"shaders": [],
+				"programs": [],
+				"primitives": [{ "kind": "cube", "id": "box" }],
+				"nodes": [
+					{ "translation": [0, 0, -5], "model": "box" },
+					{ "scale": [2, 2, 2], "model": "box" }
+				]
+			}"#,
+		)
+		.unwrap();
+		let (_gl, mut resources) = resources();
+		let graph = resources.build_scene(&config).unwrap();
+
+		let toward = Ray::new(
+			cgmath::Point3::new(0.2, -0.4, 10.0),
+			-cgmath::Vector3::unit_z(),
+		);
+		let hit = graph.pick(&toward).unwrap();
+		assert_eq!((hit.mesh, hit.triangle), (0, 4));
+		assert!((hit.distance - 9.0).abs() < 1e-5);
+		assert!(hit.node.borrow().world_bounds().unwrap().max.z > 0.9);
+
+		let away = Ray::new(
+			cgmath::Point3::new(0.0, 3.0, 10.0),
+			-cgmath::Vector3::unit_z(),
+		);
+		assert!(graph.pick(&away).is_none());
+	}
+	#[test]
+	fn line_models_load_but_are_not_picked() {
+		let (_gl, mut resources) = resources();
+		// The outline of a unit square at z = 0.
+		let positions: Vec<u8> = [
+			0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0,
+		]
+		.iter()
+		.flat_map(|x| x.to_le_bytes())
+		.collect();
+		let model: ModelConfig = serde_json::from_value(serde_json::json!({
+			"id": "outline",
+			"buffers": [positions],
+			"meshes": [{
+				"index_view": null,
+				"buffer_views": [{
+					"id": "POSITION", "buffer": 0, "length": 48,
+					"buffer_offset": null, "offset": null, "stride": null,
+					"component_size": 3, "component_count": 4,
+					"component_type": WebGl2RenderingContext::FLOAT
+				}],
+				"material": null,
+				"mode": WebGl2RenderingContext::LINE_LOOP
+			}],
+			"materials": [],
+			"texture_wrapper_id": "",
+			"samplers": [],
+			"textures": []
+		}))
+		.unwrap();
+		let handle = resources.load_model(&model, &[]).unwrap();
+		let mesh = &resources.get_model(handle).unwrap().meshes[0];
+		assert_eq!(mesh.mode, WebGl2RenderingContext::LINE_LOOP);
+		assert!(mesh.geometry.as_ref().unwrap().triangles.is_empty());
+
+		let config: SceneConfig = serde_json::from_str(
+			r#"{
+				"current_camera": 0,
+				"cameras": [],
+				"shaders": [],
+				"programs": [],
+				"nodes": [{ "model": "outline" }]
+			}"#,
+		)
+		.unwrap();
+		let graph = resources.build_scene(&config).unwrap();
+		let ray = Ray::new(
+			cgmath::Point3::new(0.5, 0.5, 10.0),
+			-cgmath::Vector3::unit_z(),
+		);
+		assert!(graph.pick(&ray).is_none());
+	}
+	#[test]
+	fn scene_node_with_unknown_model_is_an_error() {
+		let config: SceneConfig = serde_json::from_str(
+			r#"{
+				"current_camera": 0,
+				"cameras": [],
+				"shaders": [],
+				"programs": [],
+				"nodes": [{ "model": "missing" }]
+			}"#,
+		)
+		.unwrap();
+		let (_gl, mut resources) = resources();
+		assert_eq!(
+			resources.build_scene(&config).unwrap_err(),
+			"no model named 'missing'"
+		);
+	}
+}
@@ -5,24 +5,32 @@
 //
 // Description:
 
-use web_sys::HtmlImageElement;
+use web_sys::WebGl2RenderingContext;
 
 use crate::{
+	backend::GlBackend,
 	camera::Camera,
 	config::scene_config::{
 		AttributeConfig,
 		BufferViewConfig,
 		CameraConfig,
 		MaterialConfig,
+		PrimitiveConfig,
 		UniformConfig,
 	},
 	model::{
 		buffer::Buffer,
 		material::Material,
-		mesh::Mesh,
+		mesh::{
+			geometry::Geometry,
+			Mesh,
+		},
 		texture::{
 			Sampler,
+			SamplerObject,
 			Texture,
+			TextureData,
+			TextureFormat,
 		},
 		Model,
 	},
@@ -33,42 +41,49 @@ use crate::{
 	warning::ShaderWarning,
 };
 
+use super::handle::{
+	Handle,
+	HandleError,
+};
+
 use std::rc::Rc;
 
-pub trait AddResourceT {
-	fn add_camera(&mut self, id: &str, camera: &Camera) -> Option<&Rc<Camera>>;
-	fn add_string(&mut self, id: &str, string: &str) -> Option<&Rc<String>>;
-	fn add_shader(&mut self, id: &str, shader: &Shader) -> Option<&Rc<Shader>>;
+type HandleResult<T> = Result<T, HandleError>;
+
+pub trait AddResourceT<G: GlBackend = WebGl2RenderingContext> {
+	fn add_camera(&mut self, id: &str, camera: &Camera) -> Handle<Camera>;
+	fn add_string(&mut self, id: &str, string: &str) -> Handle<String>;
+	fn add_shader(&mut self, id: &str, shader: &Shader<G>)
+		-> Handle<Shader<G>>;
 	fn add_program(
 		&mut self,
 		id: &str,
-		program: &Program,
-	) -> Option<&Rc<Program>>;
+		program: &Program<G>,
+	) -> Handle<Program<G>>;
 
 	fn add_texture(
 		&mut self,
 		id: &str,
-		texture: &Texture,
-	) -> Option<&Rc<Texture>>;
-	fn add_sampler(&mut self, sampler: &Sampler)
-		-> Option<(u32, &Rc<Sampler>)>;
-	fn add_material(
-		&mut self,
-		material: &Material,
-	) -> Option<(u32, &Rc<Material>)>;
+		texture: &Texture<G>,
+	) -> Handle<Texture<G>>;
+	fn add_sampler(
+		&mut self,
+		sampler: &SamplerObject<G>,
+	) -> Handle<SamplerObject<G>>;
+	fn add_material(&mut self, material: &Material<G>) -> Handle<Material<G>>;
 
-	fn add_buffer(&mut self, buffer: &Buffer) -> Option<(u32, &Rc<Buffer>)>;
-	fn add_mesh(&mut self, mesh: &Mesh) -> Option<(u32, &Rc<Mesh>)>;
-	fn add_model(&mut self, id: &str, model: &Model) -> Option<&Rc<Model>>;
+	fn add_buffer(&mut self, buffer: &Buffer<G>) -> Handle<Buffer<G>>;
+	fn add_mesh(&mut self, mesh: &Mesh<G>) -> Handle<Mesh<G>>;
+	fn add_model(&mut self, id: &str, model: &Model<G>) -> Handle<Model<G>>;
 }
-pub trait NewResourceT {
+pub trait NewResourceT<G: GlBackend = WebGl2RenderingContext> {
 	fn new_camera(
 		&mut self,
 		config: &CameraConfig,
 		width: u32,
 		height: u32,
-	) -> Option<&Rc<Camera>>;
-	fn new_string(&mut self, id: &str, string: &str) -> Option<&Rc<String>>;
+	) -> Handle<Camera>;
+	fn new_string(&mut self, id: &str, string: &str) -> Handle<String>;
 	fn new_shader(
 		&mut self,
 		id: &str,
@@ -76,64 +91,129 @@ pub trait NewResourceT {
 		shader_source: &str,
 		attributes: &[AttributeConfig],
 		uniforms: &[UniformConfig],
-	) -> Result<&Rc<Shader>, &'static str>;
+	) -> Result<Handle<Shader<G>>, &'static str>;
 	fn new_program(
 		&mut self,
 		id: &str,
-		vertex: &Shader,
-		fragment: &Shader,
-	) -> Result<(&Rc<Program>, Vec<ShaderWarning>), String>;
+		vertex: Handle<Shader<G>>,
+		fragment: Handle<Shader<G>>,
+	) -> Result<(Handle<Program<G>>, Vec<ShaderWarning>), String>;
 	fn new_program_from_shader_ids(
 		&mut self,
 		id: &str,
 		vertex_id: &str,
 		fragment_id: &str,
-	) -> Result<(&Rc<Program>, Vec<ShaderWarning>), String>;
+	) -> Result<(Handle<Program<G>>, Vec<ShaderWarning>), String>;
 
+	// Shares the sampler object of an equal sampler when there is one.
+	fn new_sampler(
+		&mut self,
+		sampler: &Sampler,
+	) -> Result<Handle<SamplerObject<G>>, String>;
 	fn new_texture(
 		&mut self,
 		id: &str,
-		image_element: &HtmlImageElement,
-		texture_unit: u32,
-		sampler: &Rc<Sampler>,
-	) -> Result<&Rc<Texture>, &'static str>;
+		data: TextureData,
+		format: TextureFormat,
+		sampler: Handle<SamplerObject<G>>,
+	) -> Result<Handle<Texture<G>>, String>;
 	fn new_material(
 		&mut self,
 		material: &MaterialConfig,
-		textures: &[Rc<Texture>],
-	) -> Result<(u32, &Rc<Material>), &'static str>;
+		textures: &[Handle<Texture<G>>],
+	) -> Result<Handle<Material<G>>, String>;
 
 	fn new_buffer(
 		&mut self,
 		buffer_type: u32,
 		data: &[u8],
-	) -> Result<(u32, &Rc<Buffer>), &'static str>;
+	) -> Result<Handle<Buffer<G>>, &'static str>;
 	fn new_mesh(
 		&mut self,
-		material: &Rc<Material>,
-		buffers: &[Rc<Buffer>],
+		material: Handle<Material<G>>,
+		buffers: &[Handle<Buffer<G>>],
 		index_view: &Option<BufferViewConfig>,
 		buffer_views: &[BufferViewConfig],
 		mode: u32,
-	) -> Result<(u32, &Rc<Mesh>), &'static str>;
+		geometry: Option<Geometry>,
+	) -> Result<Handle<Mesh<G>>, String>;
 	fn new_model(
 		&mut self,
 		id: &str,
-		meshes: &[Rc<Mesh>],
-	) -> Result<&Rc<Model>, &'static str>;
+		meshes: &[Handle<Mesh<G>>],
+	) -> Result<Handle<Model<G>>, String>;
+	fn new_primitive(
+		&mut self,
+		config: &PrimitiveConfig,
+	) -> Result<Handle<Model<G>>, String>;
 }
 pub trait LoadResourceT {
 	// fn load_config();
 	// fn load_model();
 	// fn load_mesh();
 }
-pub trait GetResourceT {
-	fn get_text(id: &str) -> Option<String>;
-	fn get_shader(id: &str) -> Option<Shader>;
-	fn get_program(id: &str) -> Option<Program>;
+// Lookups fail with a HandleError when the handle's resource was removed,
+// even if its slot has since been reused.
+pub trait GetResourceT<G: GlBackend = WebGl2RenderingContext> {
+	fn get_camera(&self, handle: Handle<Camera>) -> HandleResult<&Rc<Camera>>;
+	fn get_string(&self, handle: Handle<String>) -> HandleResult<&Rc<String>>;
+	fn get_shader(
+		&self,
+		handle: Handle<Shader<G>>,
+	) -> HandleResult<&Rc<Shader<G>>>;
+	fn get_program(
+		&self,
+		handle: Handle<Program<G>>,
+	) -> HandleResult<&Rc<Program<G>>>;
+
+	fn get_texture(
+		&self,
+		handle: Handle<Texture<G>>,
+	) -> HandleResult<&Rc<Texture<G>>>;
+	fn get_sampler(
+		&self,
+		handle: Handle<SamplerObject<G>>,
+	) -> HandleResult<&Rc<SamplerObject<G>>>;
+	fn get_material(
+		&self,
+		handle: Handle<Material<G>>,
+	) -> HandleResult<&Rc<Material<G>>>;
+
+	fn get_buffer(
+		&self,
+		handle: Handle<Buffer<G>>,
+	) -> HandleResult<&Rc<Buffer<G>>>;
+	fn get_mesh(&self, handle: Handle<Mesh<G>>) -> HandleResult<&Rc<Mesh<G>>>;
+	fn get_model(
+		&self,
+		handle: Handle<Model<G>>,
+	) -> HandleResult<&Rc<Model<G>>>;
 }
-pub trait RemoveResourceT {
-	fn remove_text(id: &str);
-	fn remove_shader(id: &str);
-	fn remove_program(id: &str);
+// GL objects are deleted once the last reference to them is gone, until
+// then they wait for a later release sweep.
+pub trait RemoveResourceT<G: GlBackend = WebGl2RenderingContext> {
+	fn remove_camera(&mut self, handle: Handle<Camera>) -> HandleResult<()>;
+	fn remove_string(&mut self, handle: Handle<String>) -> HandleResult<()>;
+	fn remove_shader(&mut self, handle: Handle<Shader<G>>) -> HandleResult<()>;
+	fn remove_program(
+		&mut self,
+		handle: Handle<Program<G>>,
+	) -> HandleResult<()>;
+
+	fn remove_texture(
+		&mut self,
+		handle: Handle<Texture<G>>,
+	) -> HandleResult<()>;
+	fn remove_sampler(
+		&mut self,
+		handle: Handle<SamplerObject<G>>,
+	) -> HandleResult<()>;
+	fn remove_material(
+		&mut self,
+		handle: Handle<Material<G>>,
+	) -> HandleResult<()>;
+
+	fn remove_buffer(&mut self, handle: Handle<Buffer<G>>) -> HandleResult<()>;
+	fn remove_mesh(&mut self, handle: Handle<Mesh<G>>) -> HandleResult<()>;
+	fn remove_model(&mut self, handle: Handle<Model<G>>) -> HandleResult<()>;
 }
@@ -5,67 +5,401 @@
 //
 // Description:
 
-use std::rc::Rc;
+use std::{
+	collections::{
+		HashMap,
+		HashSet,
+	},
+	rc::Rc,
+};
 
 use rctree::Node;
 
 use cgmath::{
+	EuclideanSpace,
+	InnerSpace,
 	Matrix4,
 	One,
+	Point3,
+	Quaternion,
+	SquareMatrix,
+	Vector3,
 };
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
+	backend::GlBackend,
+	bounds::{
+		Aabb,
+		Frustum,
+	},
+	camera::Camera,
+	config::scene_config::{
+		LightConfig,
+		NodeConfig,
+	},
+	lights::Light,
 	model::{
 		mesh::Mesh,
 		Drawable,
+		Model,
+	},
+	picking::{
+		Hit,
+		Ray,
 	},
 	program::Program,
+	resources::handle::Pool,
 };
 
+type Gl = WebGl2RenderingContext;
+
 #[derive(Debug)]
-pub struct SceneGraph {
-	pub root: Node<NodeData>,
+pub struct SceneGraph<G: GlBackend = WebGl2RenderingContext> {
+	pub root: Node<NodeData<G>>,
 }
-impl Default for SceneGraph {
+impl<G: GlBackend> Default for SceneGraph<G> {
 	fn default() -> Self {
 		Self {
 			root: Node::new(NodeData::default()),
 		}
 	}
 }
-impl Drawable for SceneGraph {
-	fn draw(&self, gl: &WebGl2RenderingContext, program: &Program) {
-		draw_node(&self.root, gl, program);
+impl<G: GlBackend> SceneGraph<G> {
+	// Builds the node hierarchy of a scene config, looking models up by
+	// name. Lights no node references are attached to the root.
+	pub fn from_config(
+		nodes: &[NodeConfig],
+		lights: &[LightConfig],
+		models: &Pool<Model<G>>,
+	) -> Result<Self, String> {
+		let mut scene_lights = HashMap::with_capacity(lights.len());
+		for light in lights.iter() {
+			let previous =
+				scene_lights.insert(light.id(), Light::from_config(light));
+			if previous.is_some() {
+				return Err(format!("Duplicate light id '{}'", light.id()));
+			}
+		}
+
+		let mut builder = GraphBuilder {
+			models,
+			lights: &scene_lights,
+			placed_lights: HashSet::new(),
+		};
+		let mut graph = Self::default();
+		for node in nodes.iter() {
+			graph.root.append(builder.build_node(node)?);
+		}
+		for light in lights.iter() {
+			if !builder.placed_lights.contains(light.id()) {
+				graph.root.append(Node::new(NodeData::new(
+					Transform::default(),
+					NodeTypeData::Light(scene_lights[light.id()]),
+				)));
+			}
+		}
+		set_local_matrix(&mut graph.root, &Matrix4::one());
+		Ok(graph)
+	}
+	// Every light in the graph moved into world space.
+	pub fn lights(&self) -> Vec<Light> {
+		self.root
+			.descendants()
+			.filter_map(|node| {
+				let node = node.borrow();
+				match node.node_type_data {
+					NodeTypeData::Light(ref light) => {
+						Some(light.transformed(&node.transform.world_matrix))
+					}
+					_ => None,
+				}
+			})
+			.collect()
+	}
+}
+impl<G: GlBackend> SceneGraph<G> {
+	// The closest triangle `ray` hits. Only nodes whose bounds the ray
+	// passes through closer than the best hit so far are tested.
+	pub fn pick(&self, ray: &Ray) -> Option<Hit<G>> {
+		let mut closest: Option<Hit<G>> = None;
+		for node in self.root.descendants() {
+			let data = node.borrow();
+			let limit =
+				closest.as_ref().map_or(f32::INFINITY, |hit| hit.distance);
+			match data.world_bounds.and_then(|b| ray.intersect_aabb(&b)) {
+				Some(t) if t <= limit => (),
+				_ => continue,
+			}
+			let meshes = match data.node_type_data {
+				NodeTypeData::Mesh(ref mesh) => vec![mesh],
+				NodeTypeData::Model(ref model) => model.meshes.iter().collect(),
+				_ => continue,
+			};
+			let local = match data.transform.world_matrix.invert() {
+				Some(to_model) => ray.transformed(&to_model),
+				None => continue,
+			};
+			for (i, mesh) in meshes.into_iter().enumerate() {
+				let geometry = match mesh.geometry {
+					Some(ref geometry) => geometry,
+					None => continue,
+				};
+				for triangle in 0..geometry.triangles.len() {
+					let distance = match local
+						.intersect_triangle(&geometry.triangle(triangle))
+					{
+						Some(distance) => distance,
+						None => continue,
+					};
+					if closest
+						.as_ref()
+						.is_none_or(|hit| distance < hit.distance)
+					{
+						closest = Some(Hit {
+							node: node.clone(),
+							mesh: i,
+							triangle,
+							distance,
+							point: ray.at(distance),
+						});
+					}
+				}
+			}
+		}
+		closest
+	}
+}
+struct GraphBuilder<'a, G: GlBackend> {
+	models: &'a Pool<Model<G>>,
+	lights: &'a HashMap<&'a str, Light>,
+	placed_lights: HashSet<&'a str>,
+}
+impl<'a, G: GlBackend> GraphBuilder<'a, G> {
+	fn build_node(
+		&mut self,
+		config: &'a NodeConfig,
+	) -> Result<Node<NodeData<G>>, String> {
+		let light = match config.light {
+			Some(ref id) => {
+				let light = self.lights.get(id.as_str()).ok_or_else(|| {
+					format!("Node references unknown light '{}'", id)
+				})?;
+				self.placed_lights.insert(id.as_str());
+				Some(*light)
+			}
+			None => None,
+		};
+		let model = match config.model {
+			Some(ref id) => Some(Rc::clone(self.models.get_by_name(id)?)),
+			None => None,
+		};
+
+		let transform = Transform::new(&local_matrix(config));
+		let mut node = match (model, light) {
+			(Some(model), light) => {
+				let model_lights = model.lights.clone();
+				let mut node = Node::new(NodeData::new(
+					transform,
+					NodeTypeData::Model(model),
+				));
+				for light in model_lights {
+					node.append(Node::new(NodeData::new(
+						Transform::default(),
+						NodeTypeData::Light(light),
+					)));
+				}
+				// A node holds one kind of data, so a light sharing a node
+				// with a model becomes its child.
+				if let Some(light) = light {
+					node.append(Node::new(NodeData::new(
+						Transform::default(),
+						NodeTypeData::Light(light),
+					)));
+				}
+				node
+			}
+			(None, Some(light)) => {
+				Node::new(NodeData::new(transform, NodeTypeData::Light(light)))
+			}
+			(None, None) => {
+				Node::new(NodeData::new(transform, NodeTypeData::Transform))
+			}
+		};
+		node.borrow_mut().id = config.id.clone();
+		for child in config.children.iter() {
+			node.append(self.build_node(child)?);
+		}
+		Ok(node)
+	}
+}
+fn local_matrix(config: &NodeConfig) -> Matrix4<f32> {
+	match config.matrix {
+		Some(m) => Matrix4::new(
+			m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7], m[8], m[9], m[10],
+			m[11], m[12], m[13], m[14], m[15],
+		),
+		None => {
+			let [x, y, z, w] = config.rotation;
+			let [sx, sy, sz] = config.scale;
+			Matrix4::from_translation(Vector3::from(config.translation))
+				* Matrix4::from(Quaternion::new(w, x, y, z))
+				* Matrix4::from_nonuniform_scale(sx, sy, sz)
+		}
 	}
 }
 
-pub fn draw_node(
-	node: &Node<NodeData>,
-	gl: &WebGl2RenderingContext,
-	program: &Program,
+impl<G: GlBackend> SceneGraph<G> {
+	// Draws the nodes whose bounds reach into `frustum`, nodes without
+	// bounds are always drawn. Returns how many nodes were drawn.
+	pub fn draw_visible(
+		&self,
+		gl: &G,
+		program: &Program<G>,
+		frustum: &Frustum,
+	) -> usize {
+		draw_node(&self.root, gl, program, Some(frustum))
+	}
+	// The meshes of the nodes whose bounds reach into `frustum` with their
+	// world matrices, in the order draw_visible draws them.
+	pub fn visible_meshes(
+		&self,
+		frustum: &Frustum,
+	) -> Vec<(Rc<Mesh<G>>, Matrix4<f32>)> {
+		let mut meshes = Vec::new();
+		for node in self.root.descendants() {
+			let node = node.borrow();
+			let visible = node
+				.world_bounds
+				.is_none_or(|bounds| frustum.intersects(&bounds));
+			let world_matrix = node.transform.world_matrix;
+			match node.node_type_data {
+				NodeTypeData::Mesh(ref mesh) if visible => {
+					meshes.push((Rc::clone(mesh), world_matrix))
+				}
+				NodeTypeData::Model(ref model) if visible => meshes.extend(
+					model
+						.meshes
+						.iter()
+						.map(|mesh| (Rc::clone(mesh), world_matrix)),
+				),
+				_ => (),
+			}
+		}
+		meshes
+	}
+	// Draws what `camera` sees, opaque and masked meshes first and then
+	// blended ones back to front with depth writes off, so each blends
+	// over everything behind it. Returns how many meshes were drawn.
+	pub fn draw_sorted(
+		&self,
+		gl: &G,
+		program: &Program<G>,
+		camera: &Camera,
+	) -> usize {
+		let (blended, opaque): (Vec<_>, Vec<_>) = self
+			.visible_meshes(&camera.frustum())
+			.into_iter()
+			.partition(|(mesh, _)| mesh.material.is_blended());
+		for (mesh, world_matrix) in opaque.iter() {
+			draw_mesh(gl, program, mesh, world_matrix);
+		}
+		if blended.is_empty() {
+			return opaque.len();
+		}
+
+		// Sorted by the distance along the view direction to each mesh's
+		// center, or its origin without bounds.
+		let forward = camera.forward();
+		let mut blended = blended
+			.into_iter()
+			.map(|(mesh, world_matrix)| {
+				let center = match mesh.bounds {
+					Some(bounds) => bounds.transformed(&world_matrix).center(),
+					None => Point3::from_vec(world_matrix.w.truncate()),
+				};
+				((center - camera.eye).dot(forward), mesh, world_matrix)
+			})
+			.collect::<Vec<_>>();
+		blended.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+		gl.enable(Gl::BLEND);
+		// The page composites the canvas by its alpha, which has to add up
+		// coverage like "over" does instead of being scaled by itself.
+		gl.blend_func_separate(
+			Gl::SRC_ALPHA,
+			Gl::ONE_MINUS_SRC_ALPHA,
+			Gl::ONE,
+			Gl::ONE_MINUS_SRC_ALPHA,
+		);
+		gl.depth_mask(false);
+		for (_, mesh, world_matrix) in blended.iter() {
+			draw_mesh(gl, program, mesh, world_matrix);
+		}
+		gl.depth_mask(true);
+		gl.disable(Gl::BLEND);
+		opaque.len() + blended.len()
+	}
+}
+impl<G: GlBackend> Drawable<G> for SceneGraph<G> {
+	fn draw(&self, gl: &G, program: &Program<G>) {
+		draw_node(&self.root, gl, program, None);
+	}
+}
+
+fn draw_mesh<G: GlBackend>(
+	gl: &G,
+	program: &Program<G>,
+	mesh: &Mesh<G>,
+	world_matrix: &Matrix4<f32>,
 ) {
+	gl.uniform_matrix4fv_with_f32_array(
+		program.uniform_locations.get("MODEL_MATRIX"),
+		false,
+		&crate::mat_4_to_array(world_matrix),
+	);
+	mesh.draw(gl, program);
+}
+
+pub fn draw_node<G: GlBackend>(
+	node: &Node<NodeData<G>>,
+	gl: &G,
+	program: &Program<G>,
+	frustum: Option<&Frustum>,
+) -> usize {
+	let mut drawn = 0;
 	{
 		let node = node.borrow();
-		if let NodeTypeData::Mesh(ref mesh) = node.node_type_data {
+		let drawable: Option<&dyn Drawable<G>> = match node.node_type_data {
+			NodeTypeData::Mesh(ref mesh) => Some(mesh.as_ref()),
+			NodeTypeData::Model(ref model) => Some(model.as_ref()),
+			_ => None,
+		};
+		let visible = match (frustum, node.world_bounds) {
+			(Some(frustum), Some(ref bounds)) => frustum.intersects(bounds),
+			_ => true,
+		};
+		if let Some(drawable) = drawable.filter(|_| visible) {
 			let model_loc = program.uniform_locations.get("MODEL_MATRIX");
 			gl.uniform_matrix4fv_with_f32_array(
 				model_loc,
 				false,
 				&crate::mat_4_to_array(&node.transform.world_matrix),
 			);
-			mesh.draw(gl, program);
+			drawable.draw(gl, program);
+			drawn += 1;
 
 			// crate::log!("{:?}", mesh);
 		}
 	}
 	for ref child in node.children() {
-		draw_node(child, gl, program);
+		drawn += draw_node(child, gl, program, frustum);
 	}
+	drawn
 }
 
-pub fn set_local_matrix(
-	node: &mut Node<NodeData>,
+pub fn set_local_matrix<G: GlBackend>(
+	node: &mut Node<NodeData<G>>,
 	local_matrix: &Matrix4<f32>,
 ) {
 	node.borrow_mut().transform.local_matrix = *local_matrix;
@@ -76,19 +410,19 @@ pub fn set_local_matrix(
 		}
 		None => *local_matrix,
 	};
-	node.borrow_mut().transform.world_matrix = world_matrix;
+	node.borrow_mut().set_world_matrix(&world_matrix);
 
 	for ref mut child in node.children() {
 		update_world_matrix(child, &world_matrix);
 	}
 }
-fn update_world_matrix(
-	node: &mut Node<NodeData>,
+fn update_world_matrix<G: GlBackend>(
+	node: &mut Node<NodeData<G>>,
 	parent_world_matrix: &Matrix4<f32>,
 ) {
 	let world_matrix =
 		parent_world_matrix * node.borrow_mut().transform.local_matrix;
-	node.borrow_mut().transform.world_matrix = world_matrix;
+	node.borrow_mut().set_world_matrix(&world_matrix);
 
 	for ref mut child in node.children() {
 		update_world_matrix(child, &world_matrix);
@@ -96,28 +430,58 @@ fn update_world_matrix(
 }
 
 #[derive(Debug, Clone)]
-pub struct NodeData {
+pub struct NodeData<G: GlBackend = WebGl2RenderingContext> {
+	// From the node's config, nodes a model brings along have none.
+	id: Option<String>,
 	transform: Transform,
-	node_type_data: NodeTypeData,
+	node_type_data: NodeTypeData<G>,
+	// The node's mesh or model bounds in world space, updated along with
+	// the world matrix.
+	world_bounds: Option<Aabb>,
 }
-impl Default for NodeData {
+impl<G: GlBackend> Default for NodeData<G> {
 	fn default() -> Self {
 		Self {
+			id: None,
 			transform: Transform::default(),
 			node_type_data: NodeTypeData::default(),
+			world_bounds: None,
 		}
 	}
 }
-impl NodeData {
-	pub fn new(transform: Transform, node_type_data: NodeTypeData) -> Self {
-		Self {
+impl<G: GlBackend> NodeData<G> {
+	pub fn new(transform: Transform, node_type_data: NodeTypeData<G>) -> Self {
+		let mut node = Self {
+			id: None,
 			transform,
 			node_type_data,
-		}
+			world_bounds: None,
+		};
+		node.set_world_matrix(&transform.world_matrix);
+		node
+	}
+	fn set_world_matrix(&mut self, world_matrix: &Matrix4<f32>) {
+		self.transform.world_matrix = *world_matrix;
+		let bounds = match self.node_type_data {
+			NodeTypeData::Mesh(ref mesh) => mesh.bounds,
+			NodeTypeData::Model(ref model) => model.bounds(),
+			_ => None,
+		};
+		self.world_bounds =
+			bounds.map(|bounds| bounds.transformed(world_matrix));
+	}
+	pub fn id(&self) -> Option<&str> {
+		self.id.as_deref()
+	}
+	pub fn world_bounds(&self) -> Option<Aabb> {
+		self.world_bounds
 	}
 	pub fn ref_transform(&self) -> &Transform {
 		&self.transform
 	}
+	pub fn ref_node_type_data(&self) -> &NodeTypeData<G> {
+		&self.node_type_data
+	}
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -144,25 +508,146 @@ impl Transform {
 		self.local_matrix
 	}
 	pub fn world_matrix(&self) -> Matrix4<f32> {
-		self.local_matrix
+		self.world_matrix
 	}
 }
 
-#[derive(Debug, Clone)]
-pub enum NodeTypeData {
+#[derive(Debug, Clone, Default)]
+pub enum NodeTypeData<G: GlBackend = WebGl2RenderingContext> {
+	#[default]
 	Transform,
-	Mesh(Rc<Mesh>),
+	Mesh(Rc<Mesh<G>>),
+	Model(Rc<Model<G>>),
+	Light(Light),
 }
-impl NodeTypeData {
+impl<G: GlBackend> NodeTypeData<G> {
 	pub fn is_transform(&self) -> bool {
 		matches!(self, Self::Transform)
 	}
 	pub fn is_mesh(&self) -> bool {
 		matches!(self, Self::Mesh(_))
 	}
+	pub fn is_model(&self) -> bool {
+		matches!(self, Self::Model(_))
+	}
+	pub fn is_light(&self) -> bool {
+		matches!(self, Self::Light(_))
+	}
 }
-impl Default for NodeTypeData {
-	fn default() -> Self {
-		Self::Transform
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::recording::{
+		GlCall,
+		RecordingBackend,
+	};
+	use crate::config::scene_config::{
+		BufferViewConfig,
+		UniformConfig,
+	};
+	use crate::model::material::{
+		AlphaMode,
+		Material,
+	};
+	use crate::resources::{
+		traits::{
+			AddResourceT,
+			GetResourceT,
+			NewResourceT,
+		},
+		Resources,
+	};
+
+	#[test]
+	fn world_matrix_includes_the_parents() {
+		let local_matrix = Matrix4::from_scale(2.0);
+		let mut parent = Node::new(NodeData::<RecordingBackend>::default());
+		let child = Node::new(NodeData::new(
+			Transform::new(&local_matrix),
+			NodeTypeData::default(),
+		));
+		parent.append(child.clone());
+		let parent_matrix =
+			Matrix4::from_translation(Vector3::new(1.0, 2.0, 3.0));
+		set_local_matrix(&mut parent, &parent_matrix);
+
+		let transform = *child.borrow().ref_transform();
+		assert_eq!(transform.local_matrix(), local_matrix);
+		assert_eq!(transform.world_matrix(), parent_matrix * local_matrix);
+	}
+	#[test]
+	fn blended_meshes_draw_last_back_to_front() {
+		let gl = Rc::new(RecordingBackend::new());
+		let mut resources = Resources::new(Rc::clone(&gl));
+		let buffer = resources.new_buffer(Gl::ARRAY_BUFFER, &[0; 36]).unwrap();
+		let opaque = resources.add_material(&Material::default());
+		let blended = resources.add_material(&Material {
+			alpha_mode: AlphaMode::Blend,
+			double_sided: true,
+			..Material::default()
+		});
+		let mut graph = SceneGraph::default();
+		for (z, material) in [(0.0, blended), (-2.0, opaque), (-5.0, blended)] {
+			let mesh = resources
+				.new_mesh(
+					material,
+					&[buffer],
+					&None,
+					&[BufferViewConfig::test_positions(3)],
+					Gl::TRIANGLES,
+					None,
+				)
+				.unwrap();
+			let mesh = Rc::clone(resources.get_mesh(mesh).unwrap());
+			let matrix = Matrix4::from_translation(Vector3::new(0.0, 0.0, z));
+			graph.root.append(Node::new(NodeData::new(
+				Transform::new(&matrix),
+				NodeTypeData::Mesh(mesh),
+			)));
+		}
+		let uniforms = [UniformConfig::new("MODEL_MATRIX")];
+		let vertex = resources
+			.new_shader("vert", "vertex", "", &[], &uniforms)
+			.unwrap();
+		let fragment = resources
+			.new_shader("frag", "fragment", "", &[], &[])
+			.unwrap();
+		let (program, _) =
+			resources.new_program("program", vertex, fragment).unwrap();
+		let program = resources.programs.get(program).unwrap();
+		let camera = Camera::test_camera(100, 100);
+
+		gl.clear_calls();
+		assert_eq!(graph.draw_sorted(gl.as_ref(), program, &camera), 3);
+		let calls = gl.calls();
+		let depths = calls
+			.iter()
+			.filter_map(|call| match call {
+				GlCall::UniformMatrix4fv(_, matrix) => Some(matrix[14]),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(depths, vec![-2.0, -5.0, 0.0]);
+
+		// Blending and the depth mask wrap the blended meshes, which leave
+		// culling off.
+		let position = |wanted: GlCall| {
+			calls.iter().position(|call| *call == wanted).unwrap()
+		};
+		let first_blended = position(GlCall::Disable(Gl::CULL_FACE));
+		assert!(position(GlCall::Enable(Gl::CULL_FACE)) < first_blended);
+		assert!(position(GlCall::Enable(Gl::BLEND)) < first_blended);
+		assert!(
+			position(GlCall::BlendFuncSeparate(
+				Gl::SRC_ALPHA,
+				Gl::ONE_MINUS_SRC_ALPHA,
+				Gl::ONE,
+				Gl::ONE_MINUS_SRC_ALPHA,
+			)) < first_blended
+		);
+		assert!(position(GlCall::DepthMask(false)) < first_blended);
+		assert_eq!(calls[calls.len() - 2], GlCall::DepthMask(true));
+		assert_eq!(calls[calls.len() - 1], GlCall::Disable(Gl::BLEND));
 	}
 }
@@ -5,26 +5,24 @@
 //
 // Description:
 
-use web_sys::{
-	WebGl2RenderingContext,
-	WebGlShader,
-};
+use web_sys::WebGl2RenderingContext;
 
+use crate::backend::GlBackend;
 use crate::config::scene_config::{
 	AttributeConfig,
 	UniformConfig,
 };
 
 #[derive(Clone, Debug)]
-pub struct Shader {
+pub struct Shader<G: GlBackend = WebGl2RenderingContext> {
 	pub shader_type: u32,
-	pub handle: WebGlShader,
+	pub handle: G::Shader,
 	pub attributes: Vec<AttributeConfig>,
 	pub uniforms: Vec<UniformConfig>,
 }
-impl Shader {
+impl<G: GlBackend> Shader<G> {
 	pub fn new(
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		shader_type: u32,
 		source: &str,
 		attributes: &[AttributeConfig],
@@ -37,11 +35,14 @@ impl Shader {
 			uniforms: uniforms.to_owned(),
 		})
 	}
+	pub fn delete(&self, gl: &G) {
+		gl.delete_shader(Some(&self.handle));
+	}
 	fn compile_shader(
-		gl: &WebGl2RenderingContext,
+		gl: &G,
 		shader_type: u32,
 		source: &str,
-	) -> Result<WebGlShader, &'static str> {
+	) -> Result<G::Shader, &'static str> {
 		let shader = gl
 			.create_shader(shader_type)
 			.ok_or("Unable to create shader object")?;
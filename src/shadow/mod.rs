@@ -0,0 +1,268 @@
+// File: src/shadow/mod.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Where shadow maps are rendered from. Directional lights cover the
+// camera's view with cascades, each fitted around a slice of the view
+// frustum, and spot lights render one perspective map over their cone.
+
+pub mod pass;
+
+use cgmath::{
+	EuclideanSpace,
+	Matrix,
+	Matrix4,
+	MetricSpace,
+	Point3,
+	Rad,
+	Transform,
+	Vector3,
+	Zero,
+};
+
+use crate::{
+	camera::Camera,
+	config::scene_config::ShadowConfig,
+	lights::{
+		DirectionLight,
+		SpotLight,
+	},
+};
+
+// Must match the array sizes pbr.frag declares.
+pub const MAX_CASCADES: usize = 4;
+pub const MAX_SPOT_SHADOWS: usize = 2;
+pub const MAX_SHADOW_RESOLUTION: u32 = 4096;
+// Cascades take the units from here, spot lights the ones after them.
+pub const SHADOW_TEXTURE_UNIT: u32 = 8;
+// The near plane of spot light maps.
+const SPOT_SHADOW_NEAR: f32 = 0.05;
+
+// The view depth each of `count` cascades reaches between `near` and
+// `far`. `lambda` blends evenly spaced splits (0) with logarithmic ones
+// (1), which give the cascades near the camera more of the detail.
+pub fn cascade_splits(
+	near: f32,
+	far: f32,
+	count: usize,
+	lambda: f32,
+) -> Vec<f32> {
+	(1..=count)
+		.map(|i| {
+			let p = i as f32 / count as f32;
+			let logarithmic = near * (far / near).powf(p);
+			let even = near + (far - near) * p;
+			lambda * logarithmic + (1.0 - lambda) * even
+		})
+		.collect()
+}
+
+// The world space corners of the part of `camera`'s view between view
+// depths `near` and `far`.
+pub fn frustum_corners(
+	camera: &Camera,
+	near: f32,
+	far: f32,
+) -> [Point3<f32>; 8] {
+	let slice = Camera {
+		znear: near,
+		zfar: far,
+		..*camera
+	};
+	let mut corners = [Point3::origin(); 8];
+	for (i, corner) in corners.iter_mut().enumerate() {
+		let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+		*corner = slice.unproject(Point3::new(sign(1), sign(2), sign(4)));
+	}
+	corners
+}
+
+fn up_for(dir: Vector3<f32>) -> Vector3<f32> {
+	if dir.y.abs() > 0.99 {
+		Vector3::unit_z()
+	} else {
+		Vector3::unit_y()
+	}
+}
+
+// An orthographic projection along `dir` that covers `corners`, for a
+// map `resolution` texels wide. Casters up to two of its radii further
+// toward the light still land in the map.
+pub fn directional_light_matrix(
+	dir: Vector3<f32>,
+	corners: &[Point3<f32>; 8],
+	resolution: u32,
+) -> Matrix4<f32> {
+	let center = corners
+		.iter()
+		.fold(Vector3::zero(), |sum, corner| sum + corner.to_vec())
+		/ corners.len() as f32;
+	let center = Point3::from_vec(center);
+	let radius = corners
+		.iter()
+		.map(|corner| corner.distance(center))
+		.fold(0.0, f32::max);
+	// Rounded so the map's scale doesn't change as the camera turns.
+	let radius = (radius * 16.0).ceil() / 16.0;
+
+	// Snapping the center to whole texels keeps shadow edges from
+	// shimmering as the camera moves.
+	let up = up_for(dir);
+	let rotation = Matrix4::look_to_rh(Point3::origin(), dir, up);
+	let texel = 2.0 * radius / resolution.max(1) as f32;
+	let snap = |x: f32| (x / texel).floor() * texel;
+	let rotated = rotation.transform_point(center);
+	let snapped = Point3::new(snap(rotated.x), snap(rotated.y), rotated.z);
+	// A rotation's inverse is its transpose.
+	let center = rotation.transpose().transform_point(snapped);
+
+	let view = Matrix4::look_to_rh(center, dir, up);
+	let projection =
+		cgmath::ortho(-radius, radius, -radius, radius, -3.0 * radius, radius);
+	projection * view
+}
+
+// A perspective projection over the light's cone out to `far`.
+pub fn spot_light_matrix(light: &SpotLight, far: f32) -> Matrix4<f32> {
+	let fovy = (2.0 * light.outer_cone_angle).min(std::f32::consts::PI - 0.01);
+	let view =
+		Matrix4::look_to_rh(light.position, light.dir, up_for(light.dir));
+	cgmath::perspective(Rad(fovy), 1.0, SPOT_SHADOW_NEAR, far) * view
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cascade {
+	// The view depth the cascade reaches.
+	pub far: f32,
+	// World space to the cascade's clip space.
+	pub matrix: Matrix4<f32>,
+}
+
+// The cascades covering `camera`'s view up to the shadow distance.
+pub fn cascades(
+	camera: &Camera,
+	light: &DirectionLight,
+	shadow: &ShadowConfig,
+) -> Vec<Cascade> {
+	let near = camera.znear;
+	let far = camera.zfar.min(shadow.distance).max(near);
+	let count = shadow.cascades.clamp(1, MAX_CASCADES);
+	let mut start = near;
+	cascade_splits(near, far, count, shadow.split_lambda)
+		.into_iter()
+		.map(|end| {
+			let corners = frustum_corners(camera, start, end);
+			start = end;
+			Cascade {
+				far: end,
+				matrix: directional_light_matrix(
+					light.dir,
+					&corners,
+					shadow.resolution,
+				),
+			}
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::scene_config::{
+		CameraConfig,
+		ProjectionConfig,
+	};
+	use cgmath::{
+		assert_abs_diff_eq,
+		InnerSpace,
+		Vector4,
+	};
+
+	fn camera() -> Camera {
+		Camera::new(
+			&CameraConfig {
+				id: String::from("main"),
+				projection: ProjectionConfig::Perspective,
+				fov_y: 60.0,
+				z_near: 0.1,
+				z_far: 200.0,
+				eye: [0.0, 2.0, 10.0],
+				center: [0.0, 0.0, 0.0],
+				up: [0.0, 1.0, 0.0],
+			},
+			160,
+			90,
+		)
+	}
+	fn clip(matrix: &Matrix4<f32>, point: Point3<f32>) -> Vector4<f32> {
+		let clip = matrix * point.to_homogeneous();
+		clip / clip.w
+	}
+	fn inside(v: Vector4<f32>) -> bool {
+		[v.x, v.y, v.z].iter().all(|x| x.abs() <= 1.0 + 1e-4)
+	}
+
+	#[test]
+	fn splits_blend_even_and_logarithmic() {
+		let even = cascade_splits(1.0, 100.0, 4, 0.0);
+		assert_eq!(even, vec![25.75, 50.5, 75.25, 100.0]);
+		let logarithmic = cascade_splits(1.0, 100.0, 2, 1.0);
+		assert_abs_diff_eq!(logarithmic[0], 10.0, epsilon = 1e-4);
+		assert_abs_diff_eq!(logarithmic[1], 100.0, epsilon = 1e-4);
+		let blended = cascade_splits(1.0, 100.0, 2, 0.5);
+		assert_abs_diff_eq!(blended[0], (10.0 + 50.5) / 2.0, epsilon = 1e-4);
+	}
+	#[test]
+	fn cascades_cover_their_slice_of_the_view() {
+		let camera = camera();
+		let light = DirectionLight {
+			dir: Vector3::new(-1.0, -2.0, -0.5).normalize(),
+			color: Vector3::new(1.0, 1.0, 1.0),
+			shadow: None,
+		};
+		let shadow = ShadowConfig {
+			cascades: 3,
+			distance: 40.0,
+			..ShadowConfig::default()
+		};
+		let cascades = cascades(&camera, &light, &shadow);
+		assert_eq!(cascades.len(), 3);
+		assert_abs_diff_eq!(cascades[2].far, 40.0, epsilon = 1e-4);
+		let mut near = camera.znear;
+		for cascade in cascades.iter() {
+			for corner in frustum_corners(&camera, near, cascade.far).iter() {
+				assert!(inside(clip(&cascade.matrix, *corner)));
+			}
+			near = cascade.far;
+		}
+		// Casters between the light and the slice are kept.
+		let corners = frustum_corners(&camera, camera.znear, cascades[0].far);
+		let caster = corners[0] - light.dir * 0.5;
+		assert!(inside(clip(&cascades[0].matrix, caster)));
+	}
+	#[test]
+	fn spot_map_looks_down_the_cone() {
+		let light = SpotLight {
+			position: Point3::new(0.0, 5.0, 0.0),
+			dir: -Vector3::unit_y(),
+			color: Vector3::new(1.0, 1.0, 1.0),
+			range: Some(10.0),
+			inner_cone_angle: 0.0,
+			outer_cone_angle: 0.5,
+			shadow: None,
+		};
+		let matrix = spot_light_matrix(&light, 10.0);
+		let below = clip(&matrix, Point3::new(0.0, 0.0, 0.0));
+		assert_abs_diff_eq!(below.x, 0.0, epsilon = 1e-5);
+		assert_abs_diff_eq!(below.y, 0.0, epsilon = 1e-5);
+		assert!(inside(below));
+		// Just inside and outside the cone's edge, 5 units down.
+		let edge = 5.0 * 0.5f32.tan();
+		assert!(inside(clip(&matrix, Point3::new(edge * 0.99, 0.0, 0.0))));
+		assert!(!inside(clip(&matrix, Point3::new(edge * 1.01, 0.0, 0.0))));
+		// Past the far plane.
+		assert!(!inside(clip(&matrix, Point3::new(0.0, -6.0, 0.0))));
+	}
+}
@@ -0,0 +1,436 @@
+// File: src/shadow/pass.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Renders the scene's depth from each shadow casting light into depth
+// textures and hands them to pbr.frag, which compares against them with
+// percentage closer filtering.
+
+use std::rc::Rc;
+
+use cgmath::{
+	Matrix4,
+	SquareMatrix,
+};
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+	backend::GlBackend,
+	bounds::Frustum,
+	camera::Camera,
+	lights::manager::LightManager,
+	mat_4_to_array,
+	program::Program,
+	scene_graph::SceneGraph,
+	viewport::Viewport,
+};
+
+use super::{
+	cascades,
+	spot_light_matrix,
+	Cascade,
+	MAX_CASCADES,
+	MAX_SPOT_SHADOWS,
+	SHADOW_TEXTURE_UNIT,
+};
+
+type Gl = WebGl2RenderingContext;
+
+// A square depth texture and the framebuffer that renders into it.
+// Sampled through a sampler2DShadow, so reads compare against it.
+#[derive(Debug)]
+pub struct ShadowMap<G: GlBackend = WebGl2RenderingContext> {
+	framebuffer: G::Framebuffer,
+	pub texture: G::Texture,
+	pub resolution: u32,
+}
+impl<G: GlBackend> ShadowMap<G> {
+	pub fn new(gl: &G, resolution: u32) -> Result<Self, &'static str> {
+		let texture = gl
+			.create_texture()
+			.ok_or("Failed to create shadow map texture")?;
+		let framebuffer = match gl.create_framebuffer() {
+			Some(framebuffer) => framebuffer,
+			None => {
+				gl.delete_texture(Some(&texture));
+				return Err("Failed to create shadow map framebuffer");
+			}
+		};
+		let map = Self {
+			framebuffer,
+			texture,
+			resolution,
+		};
+
+		gl.bind_texture(Gl::TEXTURE_2D, Some(&map.texture));
+		let allocated = gl.tex_image_2d_with_opt_u8_array(
+			Gl::TEXTURE_2D,
+			0,
+			Gl::DEPTH_COMPONENT24 as i32,
+			resolution as i32,
+			resolution as i32,
+			Gl::DEPTH_COMPONENT,
+			Gl::UNSIGNED_INT,
+			None,
+		);
+		// Linear filtering of comparisons blends the four nearest results.
+		for (pname, param) in [
+			(Gl::TEXTURE_MIN_FILTER, Gl::LINEAR),
+			(Gl::TEXTURE_MAG_FILTER, Gl::LINEAR),
+			(Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE),
+			(Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE),
+			(Gl::TEXTURE_COMPARE_MODE, Gl::COMPARE_REF_TO_TEXTURE),
+			(Gl::TEXTURE_COMPARE_FUNC, Gl::LEQUAL),
+		] {
+			gl.tex_parameteri(Gl::TEXTURE_2D, pname, param as i32);
+		}
+		gl.bind_texture(Gl::TEXTURE_2D, None);
+		if let Err(e) = allocated {
+			map.delete(gl);
+			return Err(e);
+		}
+
+		gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&map.framebuffer));
+		gl.framebuffer_texture_2d(
+			Gl::FRAMEBUFFER,
+			Gl::DEPTH_ATTACHMENT,
+			Gl::TEXTURE_2D,
+			Some(&map.texture),
+			0,
+		);
+		let status = gl.check_framebuffer_status(Gl::FRAMEBUFFER);
+		gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+		if status != Gl::FRAMEBUFFER_COMPLETE {
+			map.delete(gl);
+			return Err("Shadow map framebuffer is incomplete");
+		}
+		Ok(map)
+	}
+	pub fn delete(&self, gl: &G) {
+		gl.delete_framebuffer(Some(&self.framebuffer));
+		gl.delete_texture(Some(&self.texture));
+	}
+}
+
+// Sets pbr.frag's shadow uniforms for when there are no shadow maps.
+// Every shadow sampler still needs a unit of its own, one shared with a
+// material's sampler2D would fail to draw.
+pub fn disable_shadows<G: GlBackend>(gl: &G, program: &Program<G>) {
+	let uniform_locations = &program.uniform_locations;
+	gl.uniform1i(uniform_locations.get("CASCADE_COUNT"), 0);
+	gl.uniform1i(uniform_locations.get("SPOT_SHADOW_COUNT"), 0);
+	set_sampler_units(gl, program);
+}
+fn set_sampler_units<G: GlBackend>(gl: &G, program: &Program<G>) {
+	let uniform_locations = &program.uniform_locations;
+	for i in 0..MAX_CASCADES {
+		gl.uniform1i(
+			uniform_locations.get(&format!("CASCADE_MAPS[{}]", i)),
+			cascade_unit(i) as i32,
+		);
+	}
+	for i in 0..MAX_SPOT_SHADOWS {
+		gl.uniform1i(
+			uniform_locations.get(&format!("SPOT_SHADOW_MAPS[{}]", i)),
+			spot_unit(i) as i32,
+		);
+	}
+}
+fn cascade_unit(index: usize) -> u32 {
+	SHADOW_TEXTURE_UNIT + index as u32
+}
+fn spot_unit(index: usize) -> u32 {
+	SHADOW_TEXTURE_UNIT + (MAX_CASCADES + index) as u32
+}
+
+// The shadow maps for the lights a LightManager picks, see
+// LightManager::cascaded_light and shadowed_spot_lights. `program` draws
+// depth only with general.vert, see shadow.frag.
+#[derive(Debug)]
+pub struct ShadowPass<G: GlBackend = WebGl2RenderingContext> {
+	program: Rc<Program<G>>,
+	cascade_maps: Vec<ShadowMap<G>>,
+	spot_maps: Vec<ShadowMap<G>>,
+	// From the last render.
+	cascades: Vec<Cascade>,
+	spot_matrices: Vec<Matrix4<f32>>,
+}
+impl<G: GlBackend> ShadowPass<G> {
+	pub fn new(
+		gl: &G,
+		program: Rc<Program<G>>,
+		lights: &LightManager,
+	) -> Result<Self, &'static str> {
+		let mut pass = Self {
+			program,
+			cascade_maps: Vec::new(),
+			spot_maps: Vec::new(),
+			cascades: Vec::new(),
+			spot_matrices: Vec::new(),
+		};
+		let cascaded = lights
+			.cascaded_light()
+			.and_then(|i| lights.direction_lights[i].shadow);
+		let spots = lights
+			.shadowed_spot_lights()
+			.into_iter()
+			.filter_map(|i| lights.spot_lights[i].shadow);
+		let resolutions = cascaded
+			.map(|shadow| {
+				let count = shadow.cascades.clamp(1, MAX_CASCADES);
+				vec![shadow.resolution; count]
+			})
+			.unwrap_or_default();
+		for resolution in resolutions {
+			match ShadowMap::new(gl, resolution) {
+				Ok(map) => pass.cascade_maps.push(map),
+				Err(e) => {
+					pass.delete(gl);
+					return Err(e);
+				}
+			}
+		}
+		for shadow in spots {
+			match ShadowMap::new(gl, shadow.resolution) {
+				Ok(map) => pass.spot_maps.push(map),
+				Err(e) => {
+					pass.delete(gl);
+					return Err(e);
+				}
+			}
+		}
+		Ok(pass)
+	}
+	pub fn program(&self) -> &Rc<Program<G>> {
+		&self.program
+	}
+	pub fn delete(&self, gl: &G) {
+		for map in self.cascade_maps.iter().chain(self.spot_maps.iter()) {
+			map.delete(gl);
+		}
+	}
+
+	// Renders every map for `camera`'s view of `scene_graph`. Drawing goes
+	// back to the canvas and `viewport` after. `lights` must be the
+	// manager the pass was made for.
+	pub fn render(
+		&mut self,
+		gl: &G,
+		scene_graph: &SceneGraph<G>,
+		lights: &LightManager,
+		camera: &Camera,
+		viewport: &Viewport,
+	) {
+		self.cascades = lights
+			.cascaded_light()
+			.map(|i| &lights.direction_lights[i])
+			.and_then(|light| Some(cascades(camera, light, &light.shadow?)))
+			.unwrap_or_default();
+		self.spot_matrices = lights
+			.shadowed_spot_lights()
+			.into_iter()
+			.filter_map(|i| {
+				let light = &lights.spot_lights[i];
+				let shadow = light.shadow?;
+				let far = light.range.unwrap_or(shadow.distance);
+				Some(spot_light_matrix(light, far))
+			})
+			.collect();
+
+		gl.use_program(Some(&self.program.program));
+		gl.uniform_matrix4fv_with_f32_array(
+			self.program.uniform_locations.get("VIEW_MATRIX"),
+			false,
+			&mat_4_to_array(&Matrix4::identity()),
+		);
+		let cascade_matrices =
+			self.cascades.iter().map(|cascade| &cascade.matrix);
+		let maps = self
+			.cascade_maps
+			.iter()
+			.zip(cascade_matrices)
+			.chain(self.spot_maps.iter().zip(self.spot_matrices.iter()));
+		for (map, matrix) in maps {
+			self.render_map(gl, scene_graph, map, matrix);
+		}
+		gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+		gl.viewport(viewport.x, viewport.y, viewport.width, viewport.height);
+	}
+	fn render_map(
+		&self,
+		gl: &G,
+		scene_graph: &SceneGraph<G>,
+		map: &ShadowMap<G>,
+		matrix: &Matrix4<f32>,
+	) {
+		let resolution = map.resolution as i32;
+		gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&map.framebuffer));
+		gl.viewport(0, 0, resolution, resolution);
+		gl.clear(Gl::DEPTH_BUFFER_BIT);
+		gl.uniform_matrix4fv_with_f32_array(
+			self.program.uniform_locations.get("PROJECTION_MATRIX"),
+			false,
+			&mat_4_to_array(matrix),
+		);
+		scene_graph.draw_visible(
+			gl,
+			&self.program,
+			&Frustum::from_matrix(matrix),
+		);
+	}
+
+	// `program` must be in use. Binds the maps to their texture units and
+	// uploads what pbr.frag needs to sample them.
+	pub fn upload(&self, gl: &G, program: &Program<G>, lights: &LightManager) {
+		let uniform_locations = &program.uniform_locations;
+		set_sampler_units(gl, program);
+
+		let cascade_bias = lights
+			.cascaded_light()
+			.and_then(|i| lights.direction_lights[i].shadow)
+			.map_or(0.0, |shadow| shadow.bias);
+		gl.uniform1i(
+			uniform_locations.get("CASCADE_COUNT"),
+			self.cascades.len().min(self.cascade_maps.len()) as i32,
+		);
+		gl.uniform1f(uniform_locations.get("CASCADE_BIAS"), cascade_bias);
+		for (i, (cascade, map)) in self
+			.cascades
+			.iter()
+			.zip(self.cascade_maps.iter())
+			.enumerate()
+		{
+			gl.uniform1f(
+				uniform_locations.get(&format!("CASCADES[{}].far", i)),
+				cascade.far,
+			);
+			gl.uniform_matrix4fv_with_f32_array(
+				uniform_locations.get(&format!("CASCADES[{}].matrix", i)),
+				false,
+				&mat_4_to_array(&cascade.matrix),
+			);
+			gl.active_texture(Gl::TEXTURE0 + cascade_unit(i));
+			gl.bind_texture(Gl::TEXTURE_2D, Some(&map.texture));
+		}
+
+		let biases = lights
+			.shadowed_spot_lights()
+			.into_iter()
+			.filter_map(|i| lights.spot_lights[i].shadow)
+			.map(|shadow| shadow.bias);
+		let spots = self
+			.spot_matrices
+			.iter()
+			.zip(self.spot_maps.iter())
+			.zip(biases)
+			.enumerate();
+		gl.uniform1i(
+			uniform_locations.get("SPOT_SHADOW_COUNT"),
+			self.spot_matrices.len().min(self.spot_maps.len()) as i32,
+		);
+		for (i, ((matrix, map), bias)) in spots {
+			let field = |name: &str| format!("SPOT_SHADOWS[{}].{}", i, name);
+			gl.uniform_matrix4fv_with_f32_array(
+				uniform_locations.get(&field("matrix")),
+				false,
+				&mat_4_to_array(matrix),
+			);
+			gl.uniform1f(uniform_locations.get(&field("bias")), bias);
+			gl.active_texture(Gl::TEXTURE0 + spot_unit(i));
+			gl.bind_texture(Gl::TEXTURE_2D, Some(&map.texture));
+		}
+		gl.active_texture(Gl::TEXTURE0);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::recording::{
+		GlCall,
+		RecordingBackend,
+	};
+	use crate::config::scene_config::{
+		ShadowConfig,
+		UniformConfig,
+	};
+	use crate::lights::{
+		DirectionLight,
+		Light,
+	};
+	use crate::shader::Shader;
+	use cgmath::Vector3;
+
+	#[test]
+	fn renders_a_map_per_cascade_and_restores_the_canvas() {
+		let gl = RecordingBackend::new();
+		let vert = Shader::new(&gl, Gl::VERTEX_SHADER, "", &[], &[]).unwrap();
+		let frag = Shader::new(&gl, Gl::FRAGMENT_SHADER, "", &[], &[]).unwrap();
+		let program = Rc::new(Program::new(&gl, &vert, &frag).unwrap().0);
+		let shadow = ShadowConfig {
+			resolution: 512,
+			cascades: 2,
+			..ShadowConfig::default()
+		};
+		let lights = LightManager::new(&[Light::Directional(DirectionLight {
+			dir: -Vector3::unit_y(),
+			color: Vector3::new(1.0, 1.0, 1.0),
+			shadow: Some(shadow),
+		})]);
+		let mut pass = ShadowPass::new(&gl, program, &lights).unwrap();
+		let allocated = GlCall::TexImage2DPixels {
+			target: Gl::TEXTURE_2D,
+			level: 0,
+			internal_format: Gl::DEPTH_COMPONENT24 as i32,
+			width: 512,
+			height: 512,
+			format: Gl::DEPTH_COMPONENT,
+			type_: Gl::UNSIGNED_INT,
+			length: None,
+		};
+		assert_eq!(gl.count(|call| *call == allocated), 2);
+
+		gl.clear_calls();
+		let camera = Camera::test_camera(200, 100);
+		let viewport = Viewport {
+			x: 0,
+			y: 0,
+			width: 200,
+			height: 100,
+		};
+		let graph = SceneGraph::default();
+		pass.render(&gl, &graph, &lights, &camera, &viewport);
+		assert_eq!(
+			gl.count(|call| *call == GlCall::Viewport(0, 0, 512, 512)),
+			2
+		);
+		assert_eq!(gl.calls().last(), Some(&GlCall::Viewport(0, 0, 200, 100)));
+
+		let uniforms = [
+			UniformConfig::new("CASCADE_COUNT"),
+			UniformConfig {
+				count: Some(MAX_CASCADES),
+				..UniformConfig::new("CASCADE_MAPS")
+			},
+		];
+		let frag =
+			Shader::new(&gl, Gl::FRAGMENT_SHADER, "", &[], &uniforms).unwrap();
+		let pbr = Program::new(&gl, &vert, &frag).unwrap().0;
+		gl.clear_calls();
+		pass.upload(&gl, &pbr, &lights);
+		let calls = gl.calls();
+		assert!(calls.contains(&GlCall::Uniform1i(
+			Some(String::from("CASCADE_COUNT")),
+			2
+		)));
+		assert!(calls.contains(&GlCall::Uniform1i(
+			Some(String::from("CASCADE_MAPS[1]")),
+			SHADOW_TEXTURE_UNIT as i32 + 1
+		)));
+		assert!(calls.contains(&GlCall::ActiveTexture(
+			Gl::TEXTURE0 + SHADOW_TEXTURE_UNIT + 1
+		)));
+	}
+}
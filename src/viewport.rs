@@ -0,0 +1,157 @@
+// File: src/viewport.rs
+// Author: Jacob Guenther
+// Date created: Oct 2026
+// License: AGPLv3
+//
+// Description:
+// Works out the canvas's backing size and the part of it drawn to from its
+// size on the page. Kept free of the DOM so it can be tested natively.
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Viewport {
+	pub x: i32,
+	pub y: i32,
+	pub width: i32,
+	pub height: i32,
+}
+impl Viewport {
+	pub fn aspect(&self) -> f32 {
+		self.width as f32 / self.height.max(1) as f32
+	}
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CanvasSize {
+	// Pixels in the drawing buffer.
+	pub width: u32,
+	pub height: u32,
+	// Where to draw within it.
+	pub viewport: Viewport,
+	// Device pixels per CSS pixel.
+	pub pixel_ratio: f32,
+}
+impl CanvasSize {
+	// `css_width` and `css_height` are the canvas's client size, scaled by
+	// `pixel_ratio` (the window's devicePixelRatio) to get device pixels.
+	// With `fixed_aspect` the viewport is the largest centered rectangle of
+	// that aspect, leaving bars on two sides.
+	pub fn new(
+		css_width: i32,
+		css_height: i32,
+		pixel_ratio: f64,
+		fixed_aspect: Option<f32>,
+	) -> Self {
+		let pixel_ratio = if pixel_ratio > 0.0 { pixel_ratio } else { 1.0 };
+		let scale = |css: i32| ((css.max(1) as f64) * pixel_ratio).round();
+		let width = scale(css_width) as i32;
+		let height = scale(css_height) as i32;
+
+		let viewport = match fixed_aspect.filter(|aspect| *aspect > 0.0) {
+			Some(aspect) if (width as f32 / height as f32) > aspect => {
+				let fit = ((height as f32 * aspect).round() as i32).max(1);
+				Viewport {
+					x: (width - fit) / 2,
+					y: 0,
+					width: fit,
+					height,
+				}
+			}
+			Some(aspect) => {
+				let fit = ((width as f32 / aspect).round() as i32).max(1);
+				Viewport {
+					x: 0,
+					y: (height - fit) / 2,
+					width,
+					height: fit,
+				}
+			}
+			None => Viewport {
+				x: 0,
+				y: 0,
+				width,
+				height,
+			},
+		};
+		Self {
+			width: width as u32,
+			height: height as u32,
+			viewport,
+			pixel_ratio: pixel_ratio as f32,
+		}
+	}
+	// Converts a position on the canvas in CSS pixels, from the top left
+	// like mouse events, to normalized device coordinates. None if it's
+	// outside the viewport, e.g. on a letterbox bar.
+	pub fn to_ndc(&self, x: i32, y: i32) -> Option<(f32, f32)> {
+		let viewport = self.viewport;
+		let x = x as f32 * self.pixel_ratio - viewport.x as f32;
+		// The viewport is placed from the bottom of the canvas.
+		let y = self.height as f32 - y as f32 * self.pixel_ratio;
+		let y = y - viewport.y as f32;
+		let ndc = (
+			x / viewport.width as f32 * 2.0 - 1.0,
+			y / viewport.height as f32 * 2.0 - 1.0,
+		);
+		let inside = |v: f32| (-1.0..=1.0).contains(&v);
+		if inside(ndc.0) && inside(ndc.1) {
+			Some(ndc)
+		} else {
+			None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fills_the_canvas_at_the_pixel_ratio() {
+		let size = CanvasSize::new(480, 320, 2.0, None);
+		assert_eq!((size.width, size.height), (960, 640));
+		assert_eq!(
+			size.viewport,
+			Viewport {
+				x: 0,
+				y: 0,
+				width: 960,
+				height: 640,
+			}
+		);
+		assert_eq!(size.viewport.aspect(), 1.5);
+
+		let collapsed = CanvasSize::new(0, 0, 0.0, None);
+		assert_eq!((collapsed.width, collapsed.height), (1, 1));
+	}
+	#[test]
+	fn letterboxes_to_a_fixed_aspect() {
+		let wide = CanvasSize::new(1000, 400, 1.0, Some(2.0));
+		assert_eq!(
+			wide.viewport,
+			Viewport {
+				x: 100,
+				y: 0,
+				width: 800,
+				height: 400,
+			}
+		);
+		let tall = CanvasSize::new(400, 1000, 1.0, Some(2.0));
+		assert_eq!(
+			tall.viewport,
+			Viewport {
+				x: 0,
+				y: 400,
+				width: 400,
+				height: 200,
+			}
+		);
+		assert_eq!(tall.viewport.aspect(), 2.0);
+	}
+	#[test]
+	fn converts_css_positions_to_ndc() {
+		let size = CanvasSize::new(400, 1000, 2.0, Some(2.0));
+		assert_eq!(size.to_ndc(0, 400), Some((-1.0, 1.0)));
+		assert_eq!(size.to_ndc(300, 550), Some((0.5, -0.5)));
+		assert_eq!(size.to_ndc(200, 100), None);
+	}
+}